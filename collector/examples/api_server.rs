@@ -78,60 +78,51 @@ async fn main() -> anyhow::Result<()> {
             interval.tick().await;
 
             // Collect metrics
-            match collector.collect_all() {
-                Ok(metrics) => {
-                    println!("[Background] Collected metrics at {}", metrics.timestamp);
-
-                    // Update current metrics
-                    {
-                        let mut current = metrics_clone.write().await;
-                        *current = Some(metrics.clone());
-                    }
+            let metrics = collector.collect_all().await;
+            println!("[Background] Collected metrics at {}", metrics.timestamp);
 
-                    // Store in database
-                    if let Err(e) = repo_clone.store_metrics(&metrics).await {
-                        eprintln!("[Background] Failed to store metrics: {}", e);
-                    } else {
-                        println!("[Background] Metrics stored in database");
-                    }
+            // Update current metrics
+            {
+                let mut current = metrics_clone.write().await;
+                *current = Some(metrics.clone());
+            }
 
-                    // Simulate anomaly detection
-                    if metrics.cpu.global_usage > 70.0 {
-                        let anomaly = Anomaly {
-                            id: uuid::Uuid::new_v4().to_string(),
-                            timestamp: chrono::Utc::now(),
-                            severity: AnomalySeverity::Warning,
-                            category: AnomalyCategory::Cpu,
-                            message: format!(
-                                "High CPU usage detected: {:.2}%",
-                                metrics.cpu.global_usage
-                            ),
-                            metrics: serde_json::json!({
-                                "cpu_usage": metrics.cpu.global_usage,
-                                "load_avg": metrics.cpu.load_avg_1,
-                            }),
-                        };
-
-                        println!("[Background] Anomaly detected: {}", anomaly.message);
-
-                        // Add to recent anomalies
-                        {
-                            let mut recent = anomalies_clone.write().await;
-                            recent.push(anomaly.clone());
-                            // Keep only last 100 anomalies
-                            if recent.len() > 100 {
-                                recent.remove(0);
-                            }
-                        }
-
-                        // Store in database
-                        if let Err(e) = repo_clone.store_anomaly(&anomaly).await {
-                            eprintln!("[Background] Failed to store anomaly: {}", e);
-                        }
+            // Store in database
+            if let Err(e) = repo_clone.store_metrics(&metrics).await {
+                eprintln!("[Background] Failed to store metrics: {}", e);
+            } else {
+                println!("[Background] Metrics stored in database");
+            }
+
+            // Simulate anomaly detection
+            if metrics.cpu.global_usage > 70.0 {
+                let anomaly = Anomaly {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    timestamp: chrono::Utc::now(),
+                    severity: AnomalySeverity::Warning,
+                    category: AnomalyCategory::Cpu,
+                    message: format!("High CPU usage detected: {:.2}%", metrics.cpu.global_usage),
+                    metrics: serde_json::json!({
+                        "cpu_usage": metrics.cpu.global_usage,
+                        "load_avg": metrics.cpu.load_avg_1,
+                    }),
+                };
+
+                println!("[Background] Anomaly detected: {}", anomaly.message);
+
+                // Add to recent anomalies
+                {
+                    let mut recent = anomalies_clone.write().await;
+                    recent.push(anomaly.clone());
+                    // Keep only last 100 anomalies
+                    if recent.len() > 100 {
+                        recent.remove(0);
                     }
                 }
-                Err(e) => {
-                    eprintln!("[Background] Failed to collect metrics: {}", e);
+
+                // Store in database
+                if let Err(e) = repo_clone.store_anomaly(&anomaly).await {
+                    eprintln!("[Background] Failed to store anomaly: {}", e);
                 }
             }
         }
@@ -155,7 +146,15 @@ async fn main() -> anyhow::Result<()> {
         config.host, config.port
     );
     println!(
-        "  GET  http://{}:{}/api/v1/anomalies?start=&end=&severity=&limit=",
+        "  GET  http://{}:{}/api/v1/series?metric=&start=&end=&step=&agg=",
+        config.host, config.port
+    );
+    println!(
+        "  GET  http://{}:{}/api/v1/anomalies?start=&end=&severity=&category=&q=&limit=&cursor=&sort=",
+        config.host, config.port
+    );
+    println!(
+        "  GET  http://{}:{}/api/v1/anomalies/stats?start=&end=&bucket=",
         config.host, config.port
     );
     println!(
@@ -113,8 +113,11 @@ fn create_sample_metrics(
             tx_bytes: 500_000,
             rx_packets: 1000,
             tx_packets: 500,
+            interfaces: vec![],
         },
         gpu: None,
+        processes: vec![],
+        zombies: Default::default(),
     }
 }
 
@@ -47,8 +47,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 tx_bytes: 500_000,
                 rx_packets: 1000,
                 tx_packets: 500,
+                interfaces: vec![],
             },
             gpu: None,
+            processes: vec![],
+            zombies: Default::default(),
         };
         repo.store_metrics(&metrics).await?;
 
@@ -2,18 +2,27 @@
 ///
 /// This module provides HTTP REST API endpoints and WebSocket support
 /// for real-time metrics streaming.
+mod prometheus;
 mod rest;
 mod routes;
 mod websocket;
 
-pub use rest::{start_server, start_server_with_shutdown, ServerConfig};
+pub use rest::{start_server, start_server_with_shutdown, start_server_with_state, ServerConfig};
+pub use websocket::LiveEvent;
+
+pub(crate) use routes::parse_duration;
 
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, RwLock};
 
-use crate::storage::MetricsRepository;
+use crate::detector::{RuleMutes, RulesReloader};
+use crate::storage::{MaintenanceTask, MetricsRepository};
+use crate::telemetry::CollectorTelemetry;
 use shared::types::{Anomaly, SystemMetrics};
 
+/// Events buffered per WebSocket client before it starts skipping
+pub const LIVE_EVENT_CAPACITY: usize = 256;
+
 /// Shared application state passed to all HTTP handlers
 #[derive(Clone)]
 pub struct AppState {
@@ -25,6 +34,21 @@ pub struct AppState {
 
     /// Database repository for historical data
     pub repository: Arc<MetricsRepository>,
+
+    /// Detection rules reloader (None if reloading is not available)
+    pub rules_reloader: Option<Arc<RulesReloader>>,
+
+    /// Collector self-metrics exported on /metrics
+    pub telemetry: Arc<CollectorTelemetry>,
+
+    /// Database maintenance task (None if maintenance is not scheduled)
+    pub maintenance: Option<Arc<MaintenanceTask>>,
+
+    /// Samples, anomalies and incidents pushed to WebSocket clients
+    pub events: broadcast::Sender<LiveEvent>,
+
+    /// Muted rules, shared with the collection loop
+    pub mutes: Arc<RuleMutes>,
 }
 
 impl AppState {
@@ -43,6 +67,41 @@ impl AppState {
             current_metrics,
             recent_anomalies,
             repository,
+            rules_reloader: None,
+            telemetry: Arc::new(CollectorTelemetry::new()),
+            maintenance: None,
+            events: broadcast::channel(LIVE_EVENT_CAPACITY).0,
+            mutes: Arc::new(RuleMutes::default()),
         }
     }
+
+    /// Enables the rules reload endpoints
+    pub fn with_rules_reloader(mut self, reloader: Arc<RulesReloader>) -> Self {
+        self.rules_reloader = Some(reloader);
+        self
+    }
+
+    /// Shares the collection loop's self-metrics with the /metrics endpoint
+    pub fn with_telemetry(mut self, telemetry: Arc<CollectorTelemetry>) -> Self {
+        self.telemetry = telemetry;
+        self
+    }
+
+    /// Enables the maintenance endpoints
+    pub fn with_maintenance(mut self, maintenance: Arc<MaintenanceTask>) -> Self {
+        self.maintenance = Some(maintenance);
+        self
+    }
+
+    /// Streams events published by the collection loop to WebSocket clients
+    pub fn with_events(mut self, events: broadcast::Sender<LiveEvent>) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Applies mutes set through the API to the collection loop's anomalies
+    pub fn with_mutes(mut self, mutes: Arc<RuleMutes>) -> Self {
+        self.mutes = mutes;
+        self
+    }
 }
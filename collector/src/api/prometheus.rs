@@ -0,0 +1,586 @@
+/// Prometheus text exposition of the latest metrics
+///
+/// Renders `SystemMetrics` and the collector self-metrics in the Prometheus
+/// text format (version 0.0.4). Metric names are prefixed with
+/// `system_monitor_` and follow the base-unit conventions (bytes, seconds).
+use shared::types::{CollectorStatus, DiskMetrics, NetworkInterface, SystemMetrics};
+use std::fmt::Write;
+
+use crate::telemetry::{Histogram, TelemetrySnapshot, LATENCY_BUCKETS};
+
+/// Content type of the text exposition format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+const PREFIX: &str = "system_monitor_";
+const BYTES_PER_MB: f64 = 1024.0 * 1024.0;
+
+/// Metric name, help text and value accessor of a per-element gauge family
+type Family<T> = (&'static str, &'static str, fn(&T) -> f64);
+
+/// Builds an exposition document one metric family at a time
+struct Exposition {
+    out: String,
+}
+
+impl Exposition {
+    fn new() -> Self {
+        Self { out: String::new() }
+    }
+
+    /// Writes the HELP and TYPE lines of a metric family
+    fn family(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.out, "# HELP {}{} {}", PREFIX, name, help);
+        let _ = writeln!(self.out, "# TYPE {}{} {}", PREFIX, name, kind);
+    }
+
+    /// Writes one sample line
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) {
+        let _ = write!(self.out, "{}{}", PREFIX, name);
+        if !labels.is_empty() {
+            let rendered: Vec<String> = labels
+                .iter()
+                .map(|(k, v)| format!("{}=\"{}\"", k, escape_label(v)))
+                .collect();
+            let _ = write!(self.out, "{{{}}}", rendered.join(","));
+        }
+        let _ = writeln!(self.out, " {}", format_value(value));
+    }
+
+    /// Writes a family with a single unlabelled sample
+    fn gauge(&mut self, name: &str, help: &str, value: f64) {
+        self.family(name, "gauge", help);
+        self.sample(name, &[], value);
+    }
+
+    /// Writes a histogram family from non-cumulative bucket counts
+    fn histogram(&mut self, name: &str, help: &str, histogram: &Histogram) {
+        self.family(name, "histogram", help);
+        let mut cumulative = 0;
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(&histogram.buckets) {
+            cumulative += count;
+            let le = format_value(*bound);
+            self.sample(
+                &format!("{}_bucket", name),
+                &[("le", &le)],
+                cumulative as f64,
+            );
+        }
+        self.sample(
+            &format!("{}_bucket", name),
+            &[("le", "+Inf")],
+            histogram.count as f64,
+        );
+        self.sample(&format!("{}_sum", name), &[], histogram.sum);
+        self.sample(&format!("{}_count", name), &[], histogram.count as f64);
+    }
+}
+
+/// Escapes a label value as required by the text format
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Renders the exposition document
+///
+/// # Arguments
+/// * `metrics` - Latest collected metrics, or `None` before the first collection
+/// * `telemetry` - Collector self-metrics and anomaly counters
+pub fn render(metrics: Option<&SystemMetrics>, telemetry: &TelemetrySnapshot) -> String {
+    let mut exp = Exposition::new();
+
+    if let Some(metrics) = metrics {
+        render_system(&mut exp, metrics);
+    }
+
+    exp.family(
+        "anomalies_total",
+        "counter",
+        "Anomalies raised since startup by category and severity.",
+    );
+    for ((category, severity), count) in &telemetry.anomalies_total {
+        exp.sample(
+            "anomalies_total",
+            &[("category", category), ("severity", severity)],
+            *count as f64,
+        );
+    }
+
+    exp.family(
+        "collections_total",
+        "counter",
+        "Metric collections since startup.",
+    );
+    exp.sample("collections_total", &[], telemetry.collections_total as f64);
+    exp.family(
+        "collection_errors_total",
+        "counter",
+        "Metric collections in which a collector failed or timed out.",
+    );
+    exp.sample(
+        "collection_errors_total",
+        &[],
+        telemetry.collection_errors_total as f64,
+    );
+    exp.histogram(
+        "collection_duration_seconds",
+        "Time spent collecting one metrics sample.",
+        &telemetry.collection_duration,
+    );
+    exp.family(
+        "db_write_errors_total",
+        "counter",
+        "Failed database writes of metrics samples since startup.",
+    );
+    exp.sample(
+        "db_write_errors_total",
+        &[],
+        telemetry.db_write_errors_total as f64,
+    );
+    exp.histogram(
+        "db_write_duration_seconds",
+        "Time spent storing one metrics sample in the database.",
+        &telemetry.db_write_duration,
+    );
+    render_collectors(&mut exp, &telemetry.collectors);
+
+    exp.out
+}
+
+/// Writes the run counts, errors and last run duration of each enabled
+/// collector
+fn render_collectors(exp: &mut Exposition, collectors: &[CollectorStatus]) {
+    let families: [(&str, Family<CollectorStatus>); 3] = [
+        (
+            "counter",
+            (
+                "collector_runs_total",
+                "Runs of each collector since startup.",
+                |c| c.runs_total as f64,
+            ),
+        ),
+        (
+            "counter",
+            (
+                "collector_errors_total",
+                "Failed runs of each collector since startup.",
+                |c| c.errors_total as f64,
+            ),
+        ),
+        (
+            "gauge",
+            (
+                "collector_last_duration_seconds",
+                "Time the last run of each collector took.",
+                |c| c.last_duration_ms.unwrap_or(0.0) / 1000.0,
+            ),
+        ),
+    ];
+    for (kind, (name, help, value)) in families {
+        exp.family(name, kind, help);
+        for collector in collectors.iter().filter(|c| c.enabled) {
+            exp.sample(name, &[("collector", &collector.name)], value(collector));
+        }
+    }
+}
+
+fn render_system(exp: &mut Exposition, m: &SystemMetrics) {
+    exp.gauge(
+        "last_collection_timestamp_seconds",
+        "Unix time of the latest metrics sample.",
+        m.timestamp.timestamp_millis() as f64 / 1000.0,
+    );
+
+    // CPU
+    exp.gauge(
+        "cpu_usage_percent",
+        "Global CPU usage.",
+        m.cpu.global_usage as f64,
+    );
+    exp.family("cpu_core_usage_percent", "gauge", "Per-core CPU usage.");
+    for (core, usage) in m.cpu.per_core.iter().enumerate() {
+        let core = core.to_string();
+        exp.sample("cpu_core_usage_percent", &[("core", &core)], *usage as f64);
+    }
+    exp.family("load_average", "gauge", "System load average.");
+    for (period, value) in [
+        ("1m", m.cpu.load_avg_1),
+        ("5m", m.cpu.load_avg_5),
+        ("15m", m.cpu.load_avg_15),
+    ] {
+        exp.sample("load_average", &[("period", period)], value);
+    }
+
+    // Memory
+    exp.gauge(
+        "memory_total_bytes",
+        "Total physical memory.",
+        m.memory.total as f64,
+    );
+    exp.gauge(
+        "memory_used_bytes",
+        "Used physical memory.",
+        m.memory.used as f64,
+    );
+    exp.gauge(
+        "memory_available_bytes",
+        "Available physical memory.",
+        m.memory.available as f64,
+    );
+    exp.gauge(
+        "memory_usage_percent",
+        "Physical memory usage.",
+        m.memory.usage_percent as f64,
+    );
+    exp.gauge(
+        "swap_total_bytes",
+        "Total swap.",
+        m.memory.swap_total as f64,
+    );
+    exp.gauge("swap_used_bytes", "Used swap.", m.memory.swap_used as f64);
+
+    // Temperatures
+    exp.family(
+        "temperature_celsius",
+        "gauge",
+        "Temperature sensor reading.",
+    );
+    for t in &m.temperatures {
+        exp.sample(
+            "temperature_celsius",
+            &[("sensor", &t.sensor), ("label", &t.label)],
+            t.value as f64,
+        );
+    }
+
+    // Disks
+    let disk_families: [Family<DiskMetrics>; 6] = [
+        ("disk_total_bytes", "Filesystem size.", |d| d.total as f64),
+        ("disk_used_bytes", "Filesystem space used.", |d| {
+            d.used as f64
+        }),
+        ("disk_available_bytes", "Filesystem space available.", |d| {
+            d.available as f64
+        }),
+        ("disk_usage_percent", "Filesystem usage.", |d| {
+            d.usage_percent as f64
+        }),
+        ("disk_read_bytes_per_second", "Disk read rate.", |d| {
+            d.read_mb * BYTES_PER_MB
+        }),
+        ("disk_write_bytes_per_second", "Disk write rate.", |d| {
+            d.write_mb * BYTES_PER_MB
+        }),
+    ];
+    for (name, help, value) in disk_families {
+        exp.family(name, "gauge", help);
+        for d in &m.disks {
+            exp.sample(
+                name,
+                &[("device", &d.name), ("mount_point", &d.mount_point)],
+                value(d),
+            );
+        }
+    }
+
+    // USB
+    exp.gauge(
+        "usb_devices",
+        "Connected USB devices.",
+        m.usb_devices.len() as f64,
+    );
+    exp.gauge(
+        "usb_timeouts",
+        "USB devices reporting timeouts.",
+        m.usb_devices.iter().filter(|u| u.has_timeout).count() as f64,
+    );
+
+    // Network
+    let network_families: [Family<NetworkInterface>; 8] = [
+        (
+            "network_receive_bytes_per_second",
+            "Interface receive rate.",
+            |i| i.rx_bytes_per_sec as f64,
+        ),
+        (
+            "network_transmit_bytes_per_second",
+            "Interface transmit rate.",
+            |i| i.tx_bytes_per_sec as f64,
+        ),
+        (
+            "network_receive_packets_per_second",
+            "Interface receive packet rate.",
+            |i| i.rx_packets_per_sec as f64,
+        ),
+        (
+            "network_transmit_packets_per_second",
+            "Interface transmit packet rate.",
+            |i| i.tx_packets_per_sec as f64,
+        ),
+        (
+            "network_receive_errors",
+            "Receive errors since the previous sample.",
+            |i| i.rx_errors as f64,
+        ),
+        (
+            "network_transmit_errors",
+            "Transmit errors since the previous sample.",
+            |i| i.tx_errors as f64,
+        ),
+        (
+            "network_receive_dropped",
+            "Received packets dropped since the previous sample.",
+            |i| i.rx_dropped as f64,
+        ),
+        (
+            "network_transmit_dropped",
+            "Transmitted packets dropped since the previous sample.",
+            |i| i.tx_dropped as f64,
+        ),
+    ];
+    for (name, help, value) in network_families {
+        exp.family(name, "gauge", help);
+        for i in &m.network.interfaces {
+            exp.sample(name, &[("interface", &i.name)], value(i));
+        }
+    }
+    exp.family(
+        "network_up",
+        "gauge",
+        "Whether the interface is operationally up.",
+    );
+    for i in &m.network.interfaces {
+        let up = if i.operstate == "up" { 1.0 } else { 0.0 };
+        exp.sample("network_up", &[("interface", &i.name)], up);
+    }
+    exp.family(
+        "network_speed_bytes_per_second",
+        "gauge",
+        "Negotiated link speed.",
+    );
+    for i in &m.network.interfaces {
+        if let Some(speed) = i.speed_mbps {
+            // Link speed is reported in megabits per second
+            exp.sample(
+                "network_speed_bytes_per_second",
+                &[("interface", &i.name)],
+                speed as f64 * 1_000_000.0 / 8.0,
+            );
+        }
+    }
+
+    // GPU
+    if let Some(ref gpu) = m.gpu {
+        let labels = [("gpu", gpu.name.as_str())];
+        let gpu_families = [
+            (
+                "gpu_temperature_celsius",
+                "GPU temperature.",
+                gpu.temperature as f64,
+            ),
+            (
+                "gpu_usage_percent",
+                "GPU utilisation.",
+                gpu.usage_percent as f64,
+            ),
+            (
+                "gpu_memory_usage_percent",
+                "GPU memory usage.",
+                gpu.memory_usage_percent as f64,
+            ),
+            (
+                "gpu_memory_total_bytes",
+                "GPU memory size.",
+                gpu.memory_total_mb as f64 * BYTES_PER_MB,
+            ),
+            (
+                "gpu_memory_used_bytes",
+                "GPU memory used.",
+                gpu.memory_used_mb as f64 * BYTES_PER_MB,
+            ),
+            (
+                "gpu_power_watts",
+                "GPU power draw.",
+                gpu.power_draw_watts as f64,
+            ),
+            (
+                "gpu_fan_speed_percent",
+                "GPU fan speed.",
+                gpu.fan_speed_percent as f64,
+            ),
+        ];
+        for (name, help, value) in gpu_families {
+            exp.family(name, "gauge", help);
+            exp.sample(name, &labels, value);
+        }
+    }
+
+    // Processes
+    exp.gauge(
+        "zombie_processes",
+        "Zombie processes waiting to be reaped.",
+        m.zombies.total as f64,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telemetry::CollectorTelemetry;
+    use chrono::Utc;
+    use shared::types::*;
+    use std::time::Duration;
+
+    fn create_test_metrics() -> SystemMetrics {
+        SystemMetrics {
+            timestamp: Utc::now(),
+            cpu: CpuMetrics {
+                global_usage: 42.5,
+                per_core: vec![40.0, 45.0],
+                load_avg_1: 1.5,
+                load_avg_5: 1.0,
+                load_avg_15: 0.5,
+            },
+            memory: MemoryMetrics {
+                total: 8_000_000_000,
+                used: 4_000_000_000,
+                available: 4_000_000_000,
+                usage_percent: 50.0,
+                swap_total: 0,
+                swap_used: 0,
+            },
+            temperatures: vec![Temperature {
+                sensor: "coretemp".to_string(),
+                value: 55.0,
+                label: "Core \"0\"".to_string(),
+            }],
+            disks: vec![DiskMetrics {
+                name: "sda1".to_string(),
+                mount_point: "/home".to_string(),
+                total: 1000,
+                used: 900,
+                available: 100,
+                usage_percent: 90.0,
+                read_mb: 1.0,
+                write_mb: 0.0,
+            }],
+            usb_devices: vec![],
+            network: NetworkMetrics {
+                rx_bytes: 2048,
+                tx_bytes: 0,
+                rx_packets: 2,
+                tx_packets: 0,
+                interfaces: vec![NetworkInterface {
+                    name: "eth0".to_string(),
+                    rx_bytes_per_sec: 2048,
+                    tx_bytes_per_sec: 0,
+                    rx_packets_per_sec: 2,
+                    tx_packets_per_sec: 0,
+                    rx_errors: 1,
+                    tx_errors: 0,
+                    rx_dropped: 0,
+                    tx_dropped: 0,
+                    operstate: "up".to_string(),
+                    speed_mbps: Some(1000),
+                }],
+            },
+            gpu: None,
+            processes: vec![],
+            zombies: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_render_system_metrics() {
+        let telemetry = CollectorTelemetry::new().snapshot();
+        let output = render(Some(&create_test_metrics()), &telemetry);
+
+        assert!(output.contains("# TYPE system_monitor_cpu_usage_percent gauge\n"));
+        assert!(output.contains("system_monitor_cpu_usage_percent 42.5\n"));
+        assert!(output.contains("system_monitor_cpu_core_usage_percent{core=\"1\"} 45\n"));
+        assert!(output.contains("system_monitor_load_average{period=\"15m\"} 0.5\n"));
+        assert!(output.contains(
+            "system_monitor_temperature_celsius{sensor=\"coretemp\",label=\"Core \\\"0\\\"\"} 55\n"
+        ));
+        assert!(output.contains(
+            "system_monitor_disk_usage_percent{device=\"sda1\",mount_point=\"/home\"} 90\n"
+        ));
+        assert!(output.contains(
+            "system_monitor_disk_read_bytes_per_second{device=\"sda1\",mount_point=\"/home\"} 1048576\n"
+        ));
+        assert!(output.contains(
+            "system_monitor_network_receive_bytes_per_second{interface=\"eth0\"} 2048\n"
+        ));
+        assert!(output.contains("system_monitor_network_up{interface=\"eth0\"} 1\n"));
+        assert!(output.contains(
+            "system_monitor_network_speed_bytes_per_second{interface=\"eth0\"} 125000000\n"
+        ));
+        assert!(!output.contains("system_monitor_gpu_"));
+    }
+
+    #[test]
+    fn test_render_self_metrics() {
+        let telemetry = CollectorTelemetry::new();
+        telemetry.record_collection(Duration::from_millis(20));
+        telemetry.record_db_write(Duration::from_millis(2), true);
+
+        let output = render(None, &telemetry.snapshot());
+
+        // No system metrics before the first collection
+        assert!(!output.contains("system_monitor_cpu_usage_percent"));
+        assert!(output.contains(
+            "system_monitor_anomalies_total{category=\"Process\",severity=\"Critical\"} 0\n"
+        ));
+        assert!(output.contains("system_monitor_collections_total 1\n"));
+        assert!(
+            output.contains("system_monitor_collection_duration_seconds_bucket{le=\"0.01\"} 0\n")
+        );
+        assert!(
+            output.contains("system_monitor_collection_duration_seconds_bucket{le=\"0.025\"} 1\n")
+        );
+        assert!(
+            output.contains("system_monitor_collection_duration_seconds_bucket{le=\"+Inf\"} 1\n")
+        );
+        assert!(output.contains("system_monitor_db_write_duration_seconds_count 1\n"));
+    }
+
+    #[test]
+    fn test_render_collector_metrics() {
+        let telemetry = CollectorTelemetry::new();
+        let status = |name: &str, enabled: bool| CollectorStatus {
+            name: name.to_string(),
+            enabled,
+            interval_secs: 0,
+            timeout_secs: 5.0,
+            timed_out: false,
+            last_run: None,
+            last_duration_ms: Some(250.0),
+            last_error: None,
+            last_error_at: None,
+            runs_total: 4,
+            errors_total: 1,
+        };
+        telemetry.record_collectors(vec![status("gpu", true), status("usb", false)]);
+
+        let output = render(None, &telemetry.snapshot());
+
+        assert!(output.contains("system_monitor_collector_runs_total{collector=\"gpu\"} 4\n"));
+        assert!(output.contains("system_monitor_collector_errors_total{collector=\"gpu\"} 1\n"));
+        assert!(output
+            .contains("system_monitor_collector_last_duration_seconds{collector=\"gpu\"} 0.25\n"));
+        // Disabled collectors have nothing to report
+        assert!(!output.contains("collector=\"usb\""));
+    }
+}
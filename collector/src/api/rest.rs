@@ -82,7 +82,14 @@ pub async fn start_server(
         token_clone.cancel();
     });
 
-    start_server_with_shutdown(config, current_metrics, recent_anomalies, repository, shutdown_token).await
+    start_server_with_shutdown(
+        config,
+        current_metrics,
+        recent_anomalies,
+        repository,
+        shutdown_token,
+    )
+    .await
 }
 
 /// Starts the REST API and WebSocket server with an external shutdown token
@@ -107,10 +114,28 @@ pub async fn start_server_with_shutdown(
     repository: Arc<MetricsRepository>,
     shutdown_token: CancellationToken,
 ) -> anyhow::Result<()> {
-    info!("Starting REST API server...");
-
-    // Create shared application state
     let state = AppState::new(current_metrics, recent_anomalies, repository);
+    start_server_with_state(config, state, shutdown_token).await
+}
+
+/// Starts the REST API and WebSocket server with a prebuilt application state
+///
+/// Use this when the state carries optional components such as the rules
+/// reloader, which `start_server_with_shutdown` leaves disabled.
+///
+/// # Arguments
+/// * `config` - Server configuration
+/// * `state` - Shared application state
+/// * `shutdown_token` - External cancellation token for coordinated shutdown
+///
+/// # Returns
+/// Result indicating success or error with message
+pub async fn start_server_with_state(
+    config: ServerConfig,
+    state: AppState,
+    shutdown_token: CancellationToken,
+) -> anyhow::Result<()> {
+    info!("Starting REST API server...");
 
     // Build CORS layer if enabled
     let cors_layer = if config.enable_cors {
@@ -3,17 +3,21 @@
 /// Provides HTTP handlers for metrics, anomalies, system info, and health checks.
 use axum::{
     extract::{Path, Query, State, WebSocketUpgrade},
-    http::StatusCode,
+    http::{header, StatusCode},
     response::{IntoResponse, Json, Response},
-    routing::get,
+    routing::{get, post},
     Router,
 };
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use shared::types::{AnomalyCategory, AnomalySeverity, IncidentStatus};
 use tracing::error;
 
-use super::{websocket, AppState};
+use super::{prometheus, websocket, AppState};
+use crate::detector::{MetricPath, ReloadTrigger};
+use crate::storage::series::{self, MAX_RAW_SERIES_RANGE_HOURS};
+use crate::storage::{AnomalyCursor, AnomalyFilter, Resolution, SeriesAggregate, SortOrder};
 
 /// Creates the main application router with all routes
 pub fn create_router(state: AppState) -> Router {
@@ -21,11 +25,30 @@ pub fn create_router(state: AppState) -> Router {
         // API routes
         .route("/api/v1/metrics/current", get(get_current_metrics))
         .route("/api/v1/metrics/history", get(get_metrics_history))
+        .route("/api/v1/series", get(get_series))
         .route("/api/v1/anomalies", get(get_anomalies))
+        .route("/api/v1/anomalies/stats", get(get_anomaly_stats))
         .route("/api/v1/anomalies/:id", get(get_anomaly_by_id))
+        .route("/api/v1/incidents", get(get_incidents))
+        .route("/api/v1/incidents/:id", get(get_incident_by_id))
+        .route("/api/v1/incidents/:id/ack", post(acknowledge_incident))
+        .route("/api/v1/mutes", get(get_rule_mutes))
+        .route(
+            "/api/v1/rules/:rule/mute",
+            post(mute_rule).delete(unmute_rule),
+        )
         .route("/api/v1/system/info", get(get_system_info))
+        .route("/api/v1/collectors", get(get_collectors))
+        .route(
+            "/api/v1/config/reload",
+            get(get_reload_status).post(reload_config),
+        )
         // Health check
+        .route("/api/v1/maintenance", get(get_maintenance_status))
+        .route("/api/v1/maintenance/run", post(run_maintenance))
         .route("/health", get(health_check))
+        // Prometheus scrape endpoint
+        .route("/metrics", get(prometheus_metrics))
         // WebSocket endpoint
         .route("/ws", get(websocket_handler))
         .with_state(state)
@@ -62,12 +85,19 @@ struct HistoryQuery {
     start: Option<String>,
     /// End timestamp in RFC3339 format (optional, defaults to now)
     end: Option<String>,
-    /// Maximum number of records to return (optional, defaults to 100)
+    /// Maximum number of raw records to return (optional); returns the most
+    /// recent samples regardless of the time range
     limit: Option<i64>,
+    /// Resolution: raw, 1m, 15m, 1h, or a step such as 300, 90s, 5m, 6h, 1d
+    /// (optional, chosen from the range when omitted)
+    step: Option<String>,
 }
 
-/// GET /api/v1/metrics/history?start=&end=&limit=
+/// GET /api/v1/metrics/history?start=&end=&limit=&step=
 /// Returns historical metrics within a time range
+///
+/// Raw samples are returned as full `SystemMetrics`; rolled-up resolutions
+/// return min/avg/max/p95 series per metric path instead.
 async fn get_metrics_history(
     State(state): State<AppState>,
     Query(query): Query<HistoryQuery>,
@@ -94,6 +124,35 @@ async fn get_metrics_history(
         ));
     }
 
+    let resolution = match query.step.as_deref() {
+        Some(step) => parse_step(step)?,
+        None if query.limit.is_some() => Resolution::Raw,
+        None => Resolution::for_range(end - start),
+    };
+
+    if resolution != Resolution::Raw {
+        let series = state
+            .repository
+            .get_rollups(resolution, start, end, &[])
+            .await
+            .map_err(|e| {
+                error!("Failed to fetch rollups: {}", e);
+                AppError::DatabaseError(e.to_string())
+            })?;
+
+        return Ok(Json(json!({
+            "status": "success",
+            "data": {
+                "resolution": resolution,
+                "step_secs": resolution.step().map(|s| s.num_seconds()),
+                "series": series,
+                "count": series.values().map(Vec::len).max().unwrap_or(0),
+                "start": start.to_rfc3339(),
+                "end": end.to_rfc3339(),
+            },
+        })));
+    }
+
     // Fetch metrics from database
     let metrics = if let Some(limit) = query.limit {
         // If limit is specified, get recent metrics
@@ -120,6 +179,7 @@ async fn get_metrics_history(
     Ok(Json(json!({
         "status": "success",
         "data": {
+            "resolution": Resolution::Raw,
             "metrics": metrics,
             "count": metrics.len(),
             "start": start.to_rfc3339(),
@@ -128,6 +188,185 @@ async fn get_metrics_history(
     })))
 }
 
+/// Most metric paths accepted by one series request
+const MAX_SERIES_METRICS: usize = 20;
+
+/// Most points per series a request can produce
+const MAX_SERIES_POINTS: i64 = 10_000;
+
+/// GET /api/v1/series?metric=&metric=&start=&end=&step=&agg=
+/// Returns compact `[timestamp, value]` series for one or more metric paths
+///
+/// `metric` may be repeated and accepts any metric path resolving to a single
+/// value, e.g. `cpu.per_core[3]` or `disks[/home].usage_percent`. `step` is a
+/// tier name or a duration (chosen from the range when omitted; `raw` returns
+/// every sample) and `agg` is min, avg, max or p95 (default avg). Timestamps
+/// are Unix milliseconds.
+async fn get_series(
+    State(state): State<AppState>,
+    Query(params): Query<Vec<(String, String)>>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let param = |name: &str| {
+        params
+            .iter()
+            .rev()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+
+    let paths = params
+        .iter()
+        .filter(|(key, _)| key == "metric")
+        .map(|(_, value)| {
+            let path = MetricPath::parse(value).map_err(|e| AppError::BadRequest(e.to_string()))?;
+            if path.selects_many() {
+                return Err(AppError::BadRequest(format!(
+                    "Metric path '{}' selects several values; use a key or index selector or an aggregate such as max(...)",
+                    value
+                )));
+            }
+            Ok(path)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if paths.is_empty() {
+        return Err(AppError::BadRequest(
+            "At least one metric parameter is required".to_string(),
+        ));
+    }
+    if paths.len() > MAX_SERIES_METRICS {
+        return Err(AppError::BadRequest(format!(
+            "At most {} metrics can be requested at once",
+            MAX_SERIES_METRICS
+        )));
+    }
+
+    let end = match param("end") {
+        Some(end_str) => DateTime::parse_from_rfc3339(end_str)
+            .map_err(|e| AppError::BadRequest(format!("Invalid end timestamp: {}", e)))?
+            .with_timezone(&Utc),
+        None => Utc::now(),
+    };
+    let start = match param("start") {
+        Some(start_str) => DateTime::parse_from_rfc3339(start_str)
+            .map_err(|e| AppError::BadRequest(format!("Invalid start timestamp: {}", e)))?
+            .with_timezone(&Utc),
+        None => end - Duration::hours(1),
+    };
+    if start > end {
+        return Err(AppError::BadRequest(
+            "Start timestamp must be before end timestamp".to_string(),
+        ));
+    }
+
+    let step = match param("step") {
+        Some(step) => parse_series_step(step)?,
+        None => Resolution::for_range(end - start).step(),
+    };
+    let aggregate = match param("agg") {
+        Some(agg) => SeriesAggregate::parse(agg).ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "Invalid agg '{}': expected min, avg, max or p95",
+                agg
+            ))
+        })?,
+        None => SeriesAggregate::default(),
+    };
+
+    if let Some(step) = step {
+        if (end - start).num_seconds() / step.num_seconds() > MAX_SERIES_POINTS {
+            return Err(AppError::BadRequest(format!(
+                "Range and step would produce more than {} points per series",
+                MAX_SERIES_POINTS
+            )));
+        }
+    }
+    if series::needs_raw_samples(&paths, step)
+        && end - start > Duration::hours(MAX_RAW_SERIES_RANGE_HOURS)
+    {
+        return Err(AppError::BadRequest(format!(
+            "Raw samples and metrics outside the rollup tiers can only be queried over {} hours or less",
+            MAX_RAW_SERIES_RANGE_HOURS
+        )));
+    }
+
+    let series = series::load_series(&state.repository, &paths, start, end, step, aggregate)
+        .await
+        .map_err(|e| {
+            error!("Failed to load series: {}", e);
+            AppError::DatabaseError(e.to_string())
+        })?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "series": series,
+            "step_secs": step.map(|s| s.num_seconds()),
+            "agg": aggregate,
+            "start": start.to_rfc3339(),
+            "end": end.to_rfc3339(),
+        },
+    })))
+}
+
+/// Parses a series `step`: `raw` (None), a tier name or a duration
+fn parse_series_step(step: &str) -> Result<Option<Duration>, AppError> {
+    if let Some(resolution) = Resolution::parse(step) {
+        return Ok(resolution.step());
+    }
+
+    parse_duration(step)
+        .filter(|duration| duration.num_seconds() > 0)
+        .map(Some)
+        .ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "Invalid step '{}': expected raw or a duration such as 30s, 5m or 1h",
+                step
+            ))
+        })
+}
+
+/// Parses a history `step`: a tier name or a duration with an optional
+/// s/m/h/d unit (seconds when omitted)
+fn parse_step(step: &str) -> Result<Resolution, AppError> {
+    if let Some(resolution) = Resolution::parse(step) {
+        return Ok(resolution);
+    }
+
+    parse_duration(step)
+        .map(Resolution::for_step)
+        .ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "Invalid step '{}': expected raw, 1m, 15m, 1h or a duration such as 300 or 5m",
+                step
+            ))
+        })
+}
+
+/// Parses a duration such as `300`, `5m`, `1h` or `7d` (seconds when no unit is given)
+pub(crate) fn parse_duration(value: &str) -> Option<Duration> {
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => value.split_at(i),
+        None => (value, "s"),
+    };
+    let number: i64 = number.parse().ok()?;
+    match unit {
+        "s" => Duration::try_seconds(number),
+        "m" => Duration::try_minutes(number),
+        "h" => Duration::try_hours(number),
+        "d" => Duration::try_days(number),
+        _ => None,
+    }
+}
+
+/// Largest page of anomalies a client can request
+const MAX_ANOMALIES_PAGE: i64 = 1000;
+
+/// Most incidents a client can request at once
+const MAX_INCIDENTS_PAGE: i64 = 1000;
+
+/// Most time buckets a stats request can produce
+const MAX_STATS_BUCKETS: i64 = 10_000;
+
 /// Query parameters for anomalies endpoint
 #[derive(Debug, Deserialize)]
 struct AnomaliesQuery {
@@ -135,102 +374,567 @@ struct AnomaliesQuery {
     start: Option<String>,
     /// End timestamp in RFC3339 format (optional, defaults to now)
     end: Option<String>,
-    /// Filter by severity: info, warning, critical (optional)
+    /// Filter by severity: comma-separated info, warning, critical (optional)
     severity: Option<String>,
+    /// Filter by category: comma-separated cpu, memory, disk, ... (optional)
+    category: Option<String>,
+    /// Case-insensitive message substring (optional)
+    q: Option<String>,
     /// Maximum number of records to return (optional, defaults to 100)
     limit: Option<i64>,
+    /// `next_cursor` of the previous page (optional)
+    cursor: Option<String>,
+    /// Sort by timestamp: desc (default) or asc
+    sort: Option<String>,
 }
 
-/// GET /api/v1/anomalies?start=&end=&severity=&limit=
-/// Returns anomalies list with optional filtering
+/// GET /api/v1/anomalies?start=&end=&severity=&category=&q=&limit=&cursor=&sort=
+/// Returns one page of anomalies matching the filters
 async fn get_anomalies(
     State(state): State<AppState>,
     Query(query): Query<AnomaliesQuery>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    // Parse timestamps or use defaults
-    let end = match query.end {
-        Some(end_str) => DateTime::parse_from_rfc3339(&end_str)
+    let filter = parse_anomaly_filter(
+        query.start.as_deref(),
+        query.end.as_deref(),
+        query.severity.as_deref(),
+        query.category.as_deref(),
+        query.q,
+    )?;
+
+    let sort = match query.sort.as_deref() {
+        Some(value) => SortOrder::parse(value).ok_or_else(|| {
+            AppError::BadRequest(format!("Invalid sort '{}': expected asc or desc", value))
+        })?,
+        None => SortOrder::default(),
+    };
+
+    let cursor = match query.cursor.as_deref() {
+        Some(token) => Some(
+            AnomalyCursor::decode(token)
+                .ok_or_else(|| AppError::BadRequest("Invalid cursor".to_string()))?,
+        ),
+        None => None,
+    };
+
+    let limit = query.limit.unwrap_or(100).clamp(1, MAX_ANOMALIES_PAGE);
+
+    let page = state
+        .repository
+        .search_anomalies(&filter, sort, cursor.as_ref(), limit)
+        .await
+        .map_err(|e| {
+            error!("Failed to search anomalies: {}", e);
+            AppError::DatabaseError(e.to_string())
+        })?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "count": page.anomalies.len(),
+            "anomalies": page.anomalies,
+            "next_cursor": page.next_cursor,
+            "sort": sort,
+            "start": filter.start.map(|t| t.to_rfc3339()),
+            "end": filter.end.map(|t| t.to_rfc3339()),
+        },
+    })))
+}
+
+/// Query parameters for anomaly stats endpoint
+#[derive(Debug, Deserialize)]
+struct AnomalyStatsQuery {
+    /// Start timestamp in RFC3339 format (optional, defaults to 24 hours ago)
+    start: Option<String>,
+    /// End timestamp in RFC3339 format (optional, defaults to now)
+    end: Option<String>,
+    /// Filter by severity (optional, as for the anomalies list)
+    severity: Option<String>,
+    /// Filter by category (optional, as for the anomalies list)
+    category: Option<String>,
+    /// Case-insensitive message substring (optional)
+    q: Option<String>,
+    /// Time bucket width such as 5m, 1h or 1d (optional, defaults to 1h)
+    bucket: Option<String>,
+}
+
+/// GET /api/v1/anomalies/stats?start=&end=&severity=&category=&q=&bucket=
+/// Returns anomaly counts by severity, category and time bucket
+async fn get_anomaly_stats(
+    State(state): State<AppState>,
+    Query(query): Query<AnomalyStatsQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let filter = parse_anomaly_filter(
+        query.start.as_deref(),
+        query.end.as_deref(),
+        query.severity.as_deref(),
+        query.category.as_deref(),
+        query.q,
+    )?;
+
+    let bucket_param = query.bucket.as_deref().unwrap_or("1h");
+    let bucket = parse_duration(bucket_param)
+        .filter(|d| d.num_seconds() > 0)
+        .ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "Invalid bucket '{}': expected a duration such as 300, 5m or 1h",
+                bucket_param
+            ))
+        })?;
+
+    if let (Some(start), Some(end)) = (filter.start, filter.end) {
+        if (end - start).num_seconds() / bucket.num_seconds() > MAX_STATS_BUCKETS {
+            return Err(AppError::BadRequest(format!(
+                "Bucket '{}' is too small for this range (at most {} buckets)",
+                bucket_param, MAX_STATS_BUCKETS
+            )));
+        }
+    }
+
+    let stats = state
+        .repository
+        .anomaly_stats(&filter, bucket)
+        .await
+        .map_err(|e| {
+            error!("Failed to count anomalies: {}", e);
+            AppError::DatabaseError(e.to_string())
+        })?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "total": stats.total,
+            "by_severity": stats.by_severity,
+            "by_category": stats.by_category,
+            "buckets": stats.buckets,
+            "bucket_secs": bucket.num_seconds(),
+            "start": filter.start.map(|t| t.to_rfc3339()),
+            "end": filter.end.map(|t| t.to_rfc3339()),
+        },
+    })))
+}
+
+/// Builds an anomaly filter from query parameters
+///
+/// The time range defaults to the 24 hours before `end` (or now).
+fn parse_anomaly_filter(
+    start: Option<&str>,
+    end: Option<&str>,
+    severity: Option<&str>,
+    category: Option<&str>,
+    text: Option<String>,
+) -> Result<AnomalyFilter, AppError> {
+    let end = match end {
+        Some(end_str) => DateTime::parse_from_rfc3339(end_str)
             .map_err(|e| AppError::BadRequest(format!("Invalid end timestamp: {}", e)))?
             .with_timezone(&Utc),
         None => Utc::now(),
     };
 
-    let start = match query.start {
-        Some(start_str) => DateTime::parse_from_rfc3339(&start_str)
+    let start = match start {
+        Some(start_str) => DateTime::parse_from_rfc3339(start_str)
             .map_err(|e| AppError::BadRequest(format!("Invalid start timestamp: {}", e)))?
             .with_timezone(&Utc),
         None => end - Duration::hours(24),
     };
 
-    // Validate time range
     if start > end {
         return Err(AppError::BadRequest(
             "Start timestamp must be before end timestamp".to_string(),
         ));
     }
 
-    // Fetch anomalies from database
-    let mut anomalies = if let Some(limit) = query.limit {
-        state
-            .repository
-            .get_recent_anomalies(limit)
-            .await
-            .map_err(|e| {
-                error!("Failed to fetch recent anomalies: {}", e);
-                AppError::DatabaseError(e.to_string())
-            })?
-    } else {
-        state
-            .repository
-            .get_anomalies_range(start, end)
+    let severities = severity
+        .map(|list| parse_list(list, parse_severity_param))
+        .transpose()?
+        .unwrap_or_default();
+    let categories = category
+        .map(|list| parse_list(list, parse_category_param))
+        .transpose()?
+        .unwrap_or_default();
+
+    Ok(AnomalyFilter {
+        severities,
+        categories,
+        start: Some(start),
+        end: Some(end),
+        text: text.filter(|t| !t.is_empty()),
+    })
+}
+
+/// Parses a comma-separated query parameter, ignoring empty items
+fn parse_list<T>(list: &str, parse: fn(&str) -> Result<T, AppError>) -> Result<Vec<T>, AppError> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(parse)
+        .collect()
+}
+
+fn parse_severity_param(value: &str) -> Result<AnomalySeverity, AppError> {
+    match value.to_lowercase().as_str() {
+        "info" => Ok(AnomalySeverity::Info),
+        "warning" => Ok(AnomalySeverity::Warning),
+        "critical" => Ok(AnomalySeverity::Critical),
+        _ => Err(AppError::BadRequest(format!(
+            "Invalid severity '{}': expected info, warning or critical",
+            value
+        ))),
+    }
+}
+
+fn parse_category_param(value: &str) -> Result<AnomalyCategory, AppError> {
+    match value.to_lowercase().as_str() {
+        "cpu" => Ok(AnomalyCategory::Cpu),
+        "memory" => Ok(AnomalyCategory::Memory),
+        "temperature" => Ok(AnomalyCategory::Temperature),
+        "disk" => Ok(AnomalyCategory::Disk),
+        "usb" => Ok(AnomalyCategory::Usb),
+        "network" => Ok(AnomalyCategory::Network),
+        "gpu" => Ok(AnomalyCategory::Gpu),
+        "process" => Ok(AnomalyCategory::Process),
+        "system" => Ok(AnomalyCategory::System),
+        _ => Err(AppError::BadRequest(format!(
+            "Invalid category '{}': expected cpu, memory, temperature, disk, usb, network, gpu, process or system",
+            value
+        ))),
+    }
+}
+
+/// GET /api/v1/anomalies/:id
+/// Get specific anomaly by ID, with the metrics sample closest to when it was raised
+async fn get_anomaly_by_id(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let stored = state.repository.get_anomaly_by_id(&id).await.map_err(|e| {
+        error!("Failed to fetch anomaly {}: {}", id, e);
+        AppError::DatabaseError(e.to_string())
+    })?;
+
+    // Anomalies whose write failed are still in the in-memory buffer
+    let anomaly = match stored {
+        Some(anomaly) => anomaly,
+        None => state
+            .recent_anomalies
+            .read()
             .await
-            .map_err(|e| {
-                error!("Failed to fetch anomalies range: {}", e);
-                AppError::DatabaseError(e.to_string())
-            })?
+            .iter()
+            .find(|a| a.id == id)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("Anomaly with id {} not found", id)))?,
     };
 
-    // Filter by severity if specified
-    if let Some(severity_str) = query.severity {
-        let severity_lower = severity_str.to_lowercase();
-        anomalies.retain(|a| {
-            let a_severity = format!("{:?}", a.severity).to_lowercase();
-            a_severity == severity_lower
-        });
-    }
+    let snapshot = state
+        .repository
+        .get_metrics_nearest(anomaly.timestamp)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch metrics near anomaly {}: {}", id, e);
+            AppError::DatabaseError(e.to_string())
+        })?;
+
+    let mut data = json!(anomaly);
+    data["snapshot"] = json!(snapshot);
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": data,
+    })))
+}
+
+/// Query parameters for incidents endpoint
+#[derive(Debug, Deserialize)]
+struct IncidentsQuery {
+    /// Filter by status: open, acknowledged, resolved (optional)
+    status: Option<String>,
+    /// Maximum number of records to return (optional, defaults to 100)
+    limit: Option<i64>,
+}
+
+/// GET /api/v1/incidents?status=&limit=
+/// Returns incidents, most recently seen first
+async fn get_incidents(
+    State(state): State<AppState>,
+    Query(query): Query<IncidentsQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let status = match query.status.as_deref().map(str::to_lowercase).as_deref() {
+        None => None,
+        Some("open") => Some(IncidentStatus::Open),
+        Some("acknowledged") => Some(IncidentStatus::Acknowledged),
+        Some("resolved") => Some(IncidentStatus::Resolved),
+        Some(other) => {
+            return Err(AppError::BadRequest(format!(
+                "Invalid status '{}': expected open, acknowledged or resolved",
+                other
+            )))
+        }
+    };
+
+    let limit = query.limit.unwrap_or(100).clamp(1, MAX_INCIDENTS_PAGE);
+    let incidents = state
+        .repository
+        .get_incidents(status, limit)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch incidents: {}", e);
+            AppError::DatabaseError(e.to_string())
+        })?;
 
     Ok(Json(json!({
         "status": "success",
         "data": {
-            "anomalies": anomalies,
-            "count": anomalies.len(),
-            "start": start.to_rfc3339(),
-            "end": end.to_rfc3339(),
+            "incidents": incidents,
+            "count": incidents.len(),
         },
     })))
 }
 
-/// GET /api/v1/anomalies/:id
-/// Get specific anomaly by ID
-async fn get_anomaly_by_id(
+/// GET /api/v1/incidents/:id
+/// Get specific incident by ID
+async fn get_incident_by_id(
     State(state): State<AppState>,
     Path(id): Path<String>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    // Check in-memory recent anomalies first
-    let recent = state.recent_anomalies.read().await;
-    if let Some(anomaly) = recent.iter().find(|a| a.id == id) {
-        return Ok(Json(json!({
+    let incident = state.repository.get_incident(&id).await.map_err(|e| {
+        error!("Failed to fetch incident {}: {}", id, e);
+        AppError::DatabaseError(e.to_string())
+    })?;
+
+    match incident {
+        Some(incident) => Ok(Json(json!({
             "status": "success",
-            "data": anomaly,
-        })));
+            "data": incident,
+        }))),
+        None => Err(AppError::NotFound(format!(
+            "Incident with id {} not found",
+            id
+        ))),
     }
+}
 
-    // If not found in memory, we could query the database by parsing the ID
-    // For now, return not found
-    Err(AppError::NotFound(format!(
-        "Anomaly with id {} not found",
-        id
-    )))
+/// POST /api/v1/incidents/:id/ack
+/// Acknowledges an open incident; acknowledged and resolved incidents are returned unchanged
+async fn acknowledge_incident(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let incident = state
+        .repository
+        .acknowledge_incident(&id)
+        .await
+        .map_err(|e| {
+            error!("Failed to acknowledge incident {}: {}", id, e);
+            AppError::DatabaseError(e.to_string())
+        })?;
+
+    match incident {
+        Some(incident) => Ok(Json(json!({
+            "status": "success",
+            "data": incident,
+        }))),
+        None => Err(AppError::NotFound(format!(
+            "Incident with id {} not found",
+            id
+        ))),
+    }
+}
+
+/// How long a rule stays muted when no duration is given
+const DEFAULT_MUTE_HOURS: i64 = 1;
+
+/// Longest mute a client can set
+const MAX_MUTE_DAYS: i64 = 30;
+
+/// Query parameters for the mute endpoint
+#[derive(Debug, Deserialize)]
+struct MuteQuery {
+    /// How long to mute for, e.g. 30m, 2h or 1d (optional, defaults to 1h)
+    duration: Option<String>,
+}
+
+/// GET /api/v1/mutes
+/// Returns the muted rules, soonest to expire first
+async fn get_rule_mutes(
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let mutes = state
+        .repository
+        .get_rule_mutes(Utc::now())
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch rule mutes: {}", e);
+            AppError::DatabaseError(e.to_string())
+        })?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "mutes": mutes,
+            "count": mutes.len(),
+        },
+    })))
+}
+
+/// POST /api/v1/rules/:rule/mute?duration=
+/// Stops storing, streaming and notifying the rule's anomalies for a while.
+/// They do not open or reopen incidents; an incident already open stays open
+/// while the condition holds and resolves once it clears.
+async fn mute_rule(
+    State(state): State<AppState>,
+    Path(rule): Path<String>,
+    Query(query): Query<MuteQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let duration = match query.duration.as_deref() {
+        None => Duration::hours(DEFAULT_MUTE_HOURS),
+        Some(value) => parse_duration(value)
+            .filter(|d| d.num_seconds() > 0 && *d <= Duration::days(MAX_MUTE_DAYS))
+            .ok_or_else(|| {
+                AppError::BadRequest(format!(
+                    "Invalid duration '{}': expected a duration up to {}d such as 30m or 2h",
+                    value, MAX_MUTE_DAYS
+                ))
+            })?,
+    };
+
+    let mute = state
+        .repository
+        .mute_rule(&rule, Utc::now() + duration)
+        .await
+        .map_err(|e| {
+            error!("Failed to mute rule {}: {}", rule, e);
+            AppError::DatabaseError(e.to_string())
+        })?;
+    state.mutes.mute(&mute.rule, mute.muted_until);
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": mute,
+    })))
+}
+
+/// DELETE /api/v1/rules/:rule/mute
+/// Lifts a rule's mute
+async fn unmute_rule(
+    State(state): State<AppState>,
+    Path(rule): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let removed = state.repository.unmute_rule(&rule).await.map_err(|e| {
+        error!("Failed to unmute rule {}: {}", rule, e);
+        AppError::DatabaseError(e.to_string())
+    })?;
+    state.mutes.unmute(&rule);
+
+    if removed {
+        Ok(Json(json!({
+            "status": "success",
+            "data": { "rule": rule },
+        })))
+    } else {
+        Err(AppError::NotFound(format!("Rule {} is not muted", rule)))
+    }
+}
+
+/// POST /api/v1/config/reload
+/// Re-reads the detection rules file and swaps the active rules
+async fn reload_config(
+    State(state): State<AppState>,
+) -> Result<(StatusCode, Json<serde_json::Value>), AppError> {
+    let reloader = state
+        .rules_reloader
+        .as_ref()
+        .ok_or_else(|| AppError::NotFound("Rules reloading is not enabled".to_string()))?;
+
+    let status = reloader.reload(ReloadTrigger::Api).await;
+
+    if status.success {
+        Ok((
+            StatusCode::OK,
+            Json(json!({
+                "status": "success",
+                "data": status,
+            })),
+        ))
+    } else {
+        Ok((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({
+                "status": "error",
+                "message": status.error.clone().unwrap_or_default(),
+                "data": status,
+            })),
+        ))
+    }
+}
+
+/// GET /api/v1/config/reload
+/// Returns the outcome of the last rules reload attempt
+async fn get_reload_status(
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let reloader = state
+        .rules_reloader
+        .as_ref()
+        .ok_or_else(|| AppError::NotFound("Rules reloading is not enabled".to_string()))?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "rules_file": reloader.path().map(|p| p.display().to_string()),
+            "active_rules": reloader.current().len(),
+            "last_reload": reloader.last_status().await,
+        },
+    })))
+}
+
+/// GET /api/v1/maintenance
+/// Returns the retention settings and the report of the last maintenance run
+async fn get_maintenance_status(
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let maintenance = state
+        .maintenance
+        .as_ref()
+        .ok_or_else(|| AppError::NotFound("Database maintenance is not enabled".to_string()))?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "config": maintenance.config(),
+            "last_run": maintenance.last_report().await,
+        },
+    })))
+}
+
+/// POST /api/v1/maintenance/run
+/// Runs retention, vacuuming and the size limit now and returns the report
+async fn run_maintenance(
+    State(state): State<AppState>,
+) -> Result<(StatusCode, Json<serde_json::Value>), AppError> {
+    let maintenance = state
+        .maintenance
+        .as_ref()
+        .ok_or_else(|| AppError::NotFound("Database maintenance is not enabled".to_string()))?;
+
+    let report = maintenance.run_once(Utc::now()).await;
+
+    match report.error {
+        None => Ok((
+            StatusCode::OK,
+            Json(json!({
+                "status": "success",
+                "data": report,
+            })),
+        )),
+        Some(ref error) => Ok((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": error,
+                "data": report,
+            })),
+        )),
+    }
 }
 
 /// Response structure for system info
@@ -265,6 +969,20 @@ async fn get_system_info() -> Result<Json<serde_json::Value>, AppError> {
     })))
 }
 
+/// GET /api/v1/collectors
+/// Returns each collector's schedule, last run duration and last error
+async fn get_collectors(State(state): State<AppState>) -> Json<serde_json::Value> {
+    let collectors = state.telemetry.collectors();
+
+    Json(json!({
+        "status": "success",
+        "data": {
+            "collectors": collectors,
+            "count": collectors.len(),
+        },
+    }))
+}
+
 /// GET /health
 /// Health check endpoint
 async fn health_check(State(state): State<AppState>) -> Json<serde_json::Value> {
@@ -277,6 +995,15 @@ async fn health_check(State(state): State<AppState>) -> Json<serde_json::Value>
     }))
 }
 
+/// GET /metrics
+/// Prometheus text exposition of the latest metrics and collector self-metrics
+async fn prometheus_metrics(State(state): State<AppState>) -> Response {
+    let metrics = state.current_metrics.read().await;
+    let body = prometheus::render(metrics.as_ref(), &state.telemetry.snapshot());
+
+    ([(header::CONTENT_TYPE, prometheus::CONTENT_TYPE)], body).into_response()
+}
+
 /// GET /ws
 /// WebSocket upgrade endpoint
 async fn websocket_handler(ws: WebSocketUpgrade, State(state): State<AppState>) -> Response {
@@ -314,3 +1041,80 @@ impl IntoResponse for AppError {
         (status, body).into_response()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_step() {
+        assert_eq!(parse_step("raw").unwrap(), Resolution::Raw);
+        assert_eq!(parse_step("15m").unwrap(), Resolution::FifteenMinutes);
+        assert_eq!(parse_step("30").unwrap(), Resolution::Raw);
+        assert_eq!(parse_step("300").unwrap(), Resolution::Minute);
+        assert_eq!(parse_step("2h").unwrap(), Resolution::Hour);
+        assert_eq!(parse_step("1d").unwrap(), Resolution::Hour);
+        assert!(parse_step("5w").is_err());
+        assert!(parse_step("fast").is_err());
+    }
+
+    #[test]
+    fn test_parse_series_step() {
+        assert_eq!(parse_series_step("raw").unwrap(), None);
+        assert_eq!(
+            parse_series_step("15m").unwrap(),
+            Some(Duration::minutes(15))
+        );
+        assert_eq!(
+            parse_series_step("90").unwrap(),
+            Some(Duration::seconds(90))
+        );
+        assert!(parse_series_step("0").is_err());
+        assert!(parse_series_step("5w").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("300"), Some(Duration::seconds(300)));
+        assert_eq!(parse_duration("5m"), Some(Duration::minutes(5)));
+        assert_eq!(parse_duration("7d"), Some(Duration::days(7)));
+        assert_eq!(parse_duration("99999999999999d"), None);
+        assert_eq!(parse_duration("99999999999999999999"), None);
+        assert!(parse_step("99999999999999d").is_err());
+    }
+
+    #[test]
+    fn test_parse_anomaly_filter() {
+        let filter = parse_anomaly_filter(
+            Some("2024-01-29T00:00:00Z"),
+            Some("2024-01-29T12:00:00Z"),
+            Some("Warning, critical"),
+            Some("disk,"),
+            Some(String::new()),
+        )
+        .unwrap();
+        assert_eq!(
+            filter.severities,
+            vec![AnomalySeverity::Warning, AnomalySeverity::Critical]
+        );
+        assert_eq!(filter.categories, vec![AnomalyCategory::Disk]);
+        assert_eq!(filter.text, None);
+
+        let filter = parse_anomaly_filter(None, None, None, None, None).unwrap();
+        assert_eq!(
+            filter.end.unwrap() - filter.start.unwrap(),
+            Duration::hours(24)
+        );
+
+        assert!(parse_anomaly_filter(None, None, Some("urgent"), None, None).is_err());
+        assert!(parse_anomaly_filter(None, None, None, Some("fan"), None).is_err());
+        assert!(parse_anomaly_filter(
+            Some("2024-01-29T12:00:00Z"),
+            Some("2024-01-29T00:00:00Z"),
+            None,
+            None,
+            None
+        )
+        .is_err());
+    }
+}
@@ -1,76 +1,356 @@
 /// WebSocket handler for real-time metrics streaming
 ///
-/// Provides WebSocket connection handling for streaming system metrics
-/// to connected clients in real-time.
+/// Clients choose what they receive with small JSON commands:
+///
+/// - `{"action": "subscribe", "topics": ["metrics", "anomalies"]}`
+/// - `{"action": "unsubscribe", "topics": ["series:cpu.global_usage"]}`
+/// - `{"action": "set_interval", "interval_ms": 5000}`
+/// - `{"action": "set_fields", "fields": ["cpu", "memory"]}`
+///
+/// Topics are `metrics`, `anomalies`, `incidents` and `series:<metric path>`.
+/// Metrics and series values are sent at most once per client interval with
+/// the latest sample; anomalies and incidents are pushed as soon as the
+/// collection loop publishes them on the [`LiveEvent`] channel. New clients
+/// start subscribed to `metrics` and `anomalies` every 2 seconds.
 use axum::extract::ws::{Message, WebSocket};
-use serde_json::json;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use shared::types::{Anomaly, Incident, SystemMetrics};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
 use tokio::time::{interval, Duration};
-use tracing::{debug, error, info, warn};
+use tracing::{debug, info, warn};
 
 use super::AppState;
+use crate::detector::{snapshot, MetricPath};
+
+/// Default interval between metrics messages
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Shortest interval a client can request
+const MIN_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Longest interval a client can request
+const MAX_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Most series topics per client
+const MAX_SERIES_TOPICS: usize = 20;
+
+/// Top-level `SystemMetrics` fields accepted by `set_fields`
+const METRICS_FIELDS: &[&str] = &[
+    "cpu",
+    "memory",
+    "temperatures",
+    "disks",
+    "usb_devices",
+    "network",
+    "gpu",
+    "processes",
+    "zombies",
+];
+
+/// Event published by the collection loop to connected clients
+#[derive(Debug, Clone)]
+pub enum LiveEvent {
+    /// A freshly collected sample
+    Metrics(Arc<SystemMetrics>),
+    /// A new or escalated anomaly
+    Anomaly(Anomaly),
+    /// An incident that was opened, updated or resolved
+    Incident(Incident),
+}
+
+/// Command sent by a client
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum ClientCommand {
+    Subscribe {
+        topics: Vec<String>,
+    },
+    Unsubscribe {
+        topics: Vec<String>,
+    },
+    SetInterval {
+        interval_ms: u64,
+    },
+    /// Top-level metrics fields to send; empty sends every field
+    SetFields {
+        fields: Vec<String>,
+    },
+}
+
+/// What one client receives
+#[derive(Debug, Clone)]
+struct Subscription {
+    metrics: bool,
+    anomalies: bool,
+    incidents: bool,
+    series: Vec<MetricPath>,
+    interval: Duration,
+    /// Top-level metrics fields; None sends every field
+    fields: Option<Vec<String>>,
+}
+
+impl Default for Subscription {
+    fn default() -> Self {
+        Self {
+            metrics: true,
+            anomalies: true,
+            incidents: false,
+            series: Vec::new(),
+            interval: DEFAULT_INTERVAL,
+            fields: None,
+        }
+    }
+}
+
+impl Subscription {
+    /// Applies a command, leaving the subscription unchanged on error
+    fn apply(&mut self, command: &ClientCommand) -> Result<(), String> {
+        let mut next = self.clone();
+        match command {
+            ClientCommand::Subscribe { topics } => {
+                for topic in topics {
+                    next.set_topic(topic, true)?;
+                }
+                if next.series.len() > MAX_SERIES_TOPICS {
+                    return Err(format!(
+                        "At most {} series topics can be subscribed",
+                        MAX_SERIES_TOPICS
+                    ));
+                }
+            }
+            ClientCommand::Unsubscribe { topics } => {
+                for topic in topics {
+                    next.set_topic(topic, false)?;
+                }
+            }
+            ClientCommand::SetInterval { interval_ms } => {
+                let interval = Duration::from_millis(*interval_ms);
+                if !(MIN_INTERVAL..=MAX_INTERVAL).contains(&interval) {
+                    return Err(format!(
+                        "interval_ms must be between {} and {}",
+                        MIN_INTERVAL.as_millis(),
+                        MAX_INTERVAL.as_millis()
+                    ));
+                }
+                next.interval = interval;
+            }
+            ClientCommand::SetFields { fields } => {
+                if let Some(unknown) = fields
+                    .iter()
+                    .find(|field| !METRICS_FIELDS.contains(&field.as_str()))
+                {
+                    return Err(format!("Unknown metrics field '{}'", unknown));
+                }
+                next.fields = (!fields.is_empty()).then(|| fields.clone());
+            }
+        }
+        *self = next;
+        Ok(())
+    }
+
+    fn set_topic(&mut self, topic: &str, enabled: bool) -> Result<(), String> {
+        match topic {
+            "metrics" => self.metrics = enabled,
+            "anomalies" => self.anomalies = enabled,
+            "incidents" => self.incidents = enabled,
+            _ => {
+                let Some(raw) = topic.strip_prefix("series:") else {
+                    return Err(format!("Unknown topic '{}'", topic));
+                };
+                let path = MetricPath::parse(raw).map_err(|e| e.to_string())?;
+                if path.selects_many() {
+                    return Err(format!(
+                        "Metric path '{}' selects several values; use a key or index selector or an aggregate",
+                        raw
+                    ));
+                }
+                self.series
+                    .retain(|existing| existing.as_str() != path.as_str());
+                if enabled {
+                    self.series.push(path);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Describes the subscription, sent after connecting and after each command
+    fn describe(&self) -> Value {
+        let mut topics: Vec<String> = [
+            (self.metrics, "metrics"),
+            (self.anomalies, "anomalies"),
+            (self.incidents, "incidents"),
+        ]
+        .iter()
+        .filter(|(enabled, _)| *enabled)
+        .map(|(_, name)| name.to_string())
+        .collect();
+        topics.extend(self.series.iter().map(|path| format!("series:{}", path)));
+
+        json!({
+            "topics": topics,
+            "interval_ms": self.interval.as_millis() as u64,
+            "fields": self.fields,
+        })
+    }
+
+    /// Builds the messages sent for a sample at the client interval
+    fn sample_messages(&self, metrics: &SystemMetrics) -> Vec<Value> {
+        let mut messages = Vec::new();
+        let timestamp = metrics.timestamp.to_rfc3339();
+
+        if self.metrics {
+            let data = match &self.fields {
+                Some(fields) => select_fields(metrics, fields),
+                None => serde_json::to_value(metrics).unwrap_or(Value::Null),
+            };
+            messages.push(json!({
+                "type": "metrics",
+                "data": data,
+                "timestamp": timestamp,
+            }));
+        }
+
+        if !self.series.is_empty() {
+            let tree = snapshot(metrics);
+            let values: Map<String, Value> = self
+                .series
+                .iter()
+                .map(|path| {
+                    let value = path.resolve(&tree).first().map(|sample| sample.value);
+                    (path.as_str().to_string(), json!(value))
+                })
+                .collect();
+            messages.push(json!({
+                "type": "series",
+                "data": values,
+                "timestamp": timestamp,
+            }));
+        }
+
+        messages
+    }
+
+    /// Builds the message pushed for an anomaly or incident, if subscribed
+    fn event_message(&self, event: &LiveEvent) -> Option<Value> {
+        match event {
+            LiveEvent::Anomaly(anomaly) if self.anomalies => Some(json!({
+                "type": "anomaly",
+                "data": anomaly,
+                "timestamp": anomaly.timestamp.to_rfc3339(),
+            })),
+            LiveEvent::Incident(incident) if self.incidents => Some(json!({
+                "type": "incident",
+                "data": incident,
+                "timestamp": incident.last_seen.to_rfc3339(),
+            })),
+            _ => None,
+        }
+    }
+}
+
+/// Copies the timestamp and the selected top-level fields of a sample
+fn select_fields(metrics: &SystemMetrics, fields: &[String]) -> Value {
+    let Value::Object(mut all) = serde_json::to_value(metrics).unwrap_or(Value::Null) else {
+        return Value::Null;
+    };
+    let mut selected = Map::new();
+    for name in std::iter::once("timestamp").chain(fields.iter().map(String::as_str)) {
+        if let Some(value) = all.remove(name) {
+            selected.insert(name.to_string(), value);
+        }
+    }
+    Value::Object(selected)
+}
+
+/// Sends a JSON message; false if the client is gone
+async fn send_json(socket: &mut WebSocket, message: &Value) -> bool {
+    match socket.send(Message::Text(message.to_string())).await {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("Failed to send WebSocket message: {}", e);
+            false
+        }
+    }
+}
 
 /// Handles a WebSocket connection
 ///
-/// This function is called when a WebSocket connection is successfully upgraded.
-/// It sends the current system metrics to the client every 2 seconds until
-/// the connection is closed or an error occurs.
+/// Streams events from the state's [`LiveEvent`] channel according to the
+/// client's subscription until the connection is closed or an error occurs.
 ///
 /// # Arguments
 /// * `socket` - The WebSocket connection
-/// * `state` - Shared application state containing current metrics
+/// * `state` - Shared application state holding the event channel
 pub async fn handle_socket(mut socket: WebSocket, state: AppState) {
     info!("New WebSocket connection established");
 
-    // Create a ticker that fires every 2 seconds
-    let mut ticker = interval(Duration::from_secs(2));
+    let mut events = state.events.subscribe();
+    let mut subscription = Subscription::default();
+    let mut ticker = interval(subscription.interval);
+
+    // Start from the latest sample so the first tick has something to send
+    let mut latest = state.current_metrics.read().await.clone().map(Arc::new);
+    let mut latest_sent = false;
+
+    let connected = json!({
+        "type": "connected",
+        "data": subscription.describe(),
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+    });
+    if !send_json(&mut socket, &connected).await {
+        return;
+    }
+    if latest.is_none() {
+        debug!("No metrics available for WebSocket client");
+        let info = json!({
+            "type": "info",
+            "message": "No metrics available yet",
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+        });
+        if !send_json(&mut socket, &info).await {
+            return;
+        }
+    }
 
     loop {
         tokio::select! {
-            // Send metrics every 2 seconds
+            // Send the latest sample once per client interval
             _ = ticker.tick() => {
-                // Read current metrics
-                let metrics = {
-                    let metrics_guard = state.current_metrics.read().await;
-                    metrics_guard.clone()
+                let Some(metrics) = latest.as_ref().filter(|_| !latest_sent) else {
+                    continue;
                 };
+                latest_sent = true;
+                for message in subscription.sample_messages(metrics) {
+                    if !send_json(&mut socket, &message).await {
+                        return;
+                    }
+                }
+            }
 
-                // Prepare message
-                let message = match metrics {
-                    Some(m) => {
-                        // Serialize metrics to JSON
-                        match serde_json::to_string(&json!({
-                            "type": "metrics",
-                            "data": m,
-                            "timestamp": chrono::Utc::now().to_rfc3339(),
-                        })) {
-                            Ok(json_str) => Message::Text(json_str),
-                            Err(e) => {
-                                error!("Failed to serialize metrics: {}", e);
-                                continue;
-                            }
-                        }
+            // Events published by the collection loop
+            event = events.recv() => {
+                match event {
+                    Ok(LiveEvent::Metrics(metrics)) => {
+                        latest = Some(metrics);
+                        latest_sent = false;
                     }
-                    None => {
-                        // No metrics available yet
-                        debug!("No metrics available for WebSocket client");
-                        match serde_json::to_string(&json!({
-                            "type": "info",
-                            "message": "No metrics available yet",
-                            "timestamp": chrono::Utc::now().to_rfc3339(),
-                        })) {
-                            Ok(json_str) => Message::Text(json_str),
-                            Err(e) => {
-                                error!("Failed to serialize info message: {}", e);
-                                continue;
+                    Ok(event) => {
+                        if let Some(message) = subscription.event_message(&event) {
+                            if !send_json(&mut socket, &message).await {
+                                return;
                             }
                         }
                     }
-                };
-
-                // Send message to client
-                if let Err(e) = socket.send(message).await {
-                    warn!("Failed to send WebSocket message: {}", e);
-                    break;
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("WebSocket client fell behind, skipped {} events", skipped);
+                    }
+                    Err(RecvError::Closed) => {
+                        info!("Event channel closed, closing WebSocket connection");
+                        break;
+                    }
                 }
             }
 
@@ -89,8 +369,28 @@ pub async fn handle_socket(mut socket: WebSocket, state: AppState) {
                         }
                     }
                     Some(Ok(Message::Text(text))) => {
-                        // Log any text messages from client (could be used for commands)
-                        debug!("Received text message from client: {}", text);
+                        let interval_before = subscription.interval;
+                        let reply = match serde_json::from_str::<ClientCommand>(&text)
+                            .map_err(|e| format!("Invalid command: {}", e))
+                            .and_then(|command| subscription.apply(&command))
+                        {
+                            Ok(()) => json!({
+                                "type": "subscribed",
+                                "data": subscription.describe(),
+                                "timestamp": chrono::Utc::now().to_rfc3339(),
+                            }),
+                            Err(message) => json!({
+                                "type": "error",
+                                "data": message,
+                                "timestamp": chrono::Utc::now().to_rfc3339(),
+                            }),
+                        };
+                        if subscription.interval != interval_before {
+                            ticker = interval(subscription.interval);
+                        }
+                        if !send_json(&mut socket, &reply).await {
+                            break;
+                        }
                     }
                     Some(Ok(Message::Binary(_))) => {
                         debug!("Received binary message from client (ignored)");
@@ -118,7 +418,9 @@ pub async fn handle_socket(mut socket: WebSocket, state: AppState) {
 mod tests {
     use super::*;
 
-    use shared::types::{CpuMetrics, MemoryMetrics, NetworkMetrics, SystemMetrics};
+    use shared::types::{
+        AnomalyCategory, AnomalySeverity, CpuMetrics, MemoryMetrics, NetworkMetrics,
+    };
 
     fn create_test_metrics() -> SystemMetrics {
         SystemMetrics {
@@ -146,20 +448,121 @@ mod tests {
                 tx_bytes: 500_000,
                 rx_packets: 10_000,
                 tx_packets: 5_000,
+                interfaces: vec![],
             },
             gpu: None,
+            processes: vec![],
+            zombies: Default::default(),
         }
     }
 
-    #[tokio::test]
-    async fn test_message_serialization() {
+    fn command(text: &str) -> ClientCommand {
+        serde_json::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(
+            command(r#"{"action":"subscribe","topics":["incidents"]}"#),
+            ClientCommand::Subscribe {
+                topics: vec!["incidents".to_string()]
+            }
+        );
+        assert_eq!(
+            command(r#"{"action":"set_interval","interval_ms":5000}"#),
+            ClientCommand::SetInterval { interval_ms: 5000 }
+        );
+        assert!(serde_json::from_str::<ClientCommand>(r#"{"action":"shout"}"#).is_err());
+    }
+
+    #[test]
+    fn test_subscription_commands() {
+        let mut subscription = Subscription::default();
+
+        subscription
+            .apply(&command(
+                r#"{"action":"subscribe","topics":["incidents","series:cpu.per_core[1]"]}"#,
+            ))
+            .unwrap();
+        subscription
+            .apply(&command(r#"{"action":"unsubscribe","topics":["metrics"]}"#))
+            .unwrap();
+        assert_eq!(
+            subscription.describe()["topics"],
+            json!(["anomalies", "incidents", "series:cpu.per_core[1]"])
+        );
+
+        // A failing command leaves the subscription unchanged
+        assert!(subscription
+            .apply(&command(
+                r#"{"action":"subscribe","topics":["metrics","series:cpu.per_core[*]"]}"#
+            ))
+            .is_err());
+        assert!(!subscription.metrics);
+        assert!(subscription
+            .apply(&command(r#"{"action":"subscribe","topics":["weather"]}"#))
+            .is_err());
+        assert!(subscription
+            .apply(&command(r#"{"action":"set_interval","interval_ms":10}"#))
+            .is_err());
+        assert!(subscription
+            .apply(&command(r#"{"action":"set_fields","fields":["cpus"]}"#))
+            .is_err());
+
+        subscription
+            .apply(&command(r#"{"action":"set_interval","interval_ms":5000}"#))
+            .unwrap();
+        assert_eq!(subscription.interval, Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_sample_messages() {
         let metrics = create_test_metrics();
-        let json_result = serde_json::to_string(&json!({
-            "type": "metrics",
-            "data": metrics,
-            "timestamp": chrono::Utc::now().to_rfc3339(),
-        }));
+        let mut subscription = Subscription::default();
+        subscription
+            .apply(&command(r#"{"action":"set_fields","fields":["cpu"]}"#))
+            .unwrap();
+        subscription
+            .apply(&command(
+                r#"{"action":"subscribe","topics":["series:cpu.per_core[1]","series:gpu.temperature"]}"#,
+            ))
+            .unwrap();
+
+        let messages = subscription.sample_messages(&metrics);
+        assert_eq!(messages.len(), 2);
+
+        let data = messages[0]["data"].as_object().unwrap();
+        assert_eq!(messages[0]["type"], "metrics");
+        assert!(data.contains_key("timestamp"));
+        assert!(data.contains_key("cpu"));
+        assert!(!data.contains_key("memory"));
+
+        assert_eq!(messages[1]["type"], "series");
+        assert_eq!(messages[1]["data"]["cpu.per_core[1]"], 55.0);
+        assert_eq!(messages[1]["data"]["gpu.temperature"], Value::Null);
+    }
+
+    #[test]
+    fn test_event_messages_follow_topics() {
+        let anomaly = LiveEvent::Anomaly(Anomaly {
+            id: "a1".to_string(),
+            timestamp: chrono::Utc::now(),
+            severity: AnomalySeverity::Critical,
+            category: AnomalyCategory::Cpu,
+            message: "CPU usage high".to_string(),
+            metrics: json!({}),
+        });
+        let mut subscription = Subscription::default();
+
+        let message = subscription.event_message(&anomaly).unwrap();
+        assert_eq!(message["type"], "anomaly");
+        assert_eq!(message["data"]["id"], "a1");
 
-        assert!(json_result.is_ok());
+        subscription
+            .apply(&command(
+                r#"{"action":"unsubscribe","topics":["anomalies"]}"#,
+            ))
+            .unwrap();
+        assert!(subscription.event_message(&anomaly).is_none());
     }
 }
@@ -0,0 +1,548 @@
+/// Command line of the collector binary
+///
+/// `run` (the default) lives in `main.rs`; the one-shot commands that check
+/// the configuration, replay or export recorded metrics and maintain the
+/// database are here.
+use anyhow::Context;
+use chrono::{DateTime, SecondsFormat, Utc};
+use clap::{Args, Parser, Subcommand};
+use collector::replay::{self, Replay, ReplayDiff, DEFAULT_MATCH_TOLERANCE};
+use collector::storage::{MaintenanceReport, MaintenanceTask};
+use collector::{AppConfig, MetricsRepository, RuleSet};
+use shared::types::{Anomaly, SystemMetrics};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing_subscriber::EnvFilter;
+
+/// System monitor collector
+///
+/// Without a subcommand the collector runs: it samples the system, detects
+/// anomalies and serves the API. Settings come from the config file, then
+/// the environment, then the flags below.
+#[derive(Parser)]
+#[command(name = "collector", version, about)]
+pub struct Cli {
+    /// Config file [default: COLLECTOR_CONFIG, else
+    /// /etc/system-monitor/collector.toml if it exists]
+    #[arg(long, global = true, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// SQLite database, overriding the config file and DATABASE_URL
+    #[arg(long, global = true, value_name = "URL")]
+    pub database_url: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Collect metrics, detect anomalies and serve the API (the default)
+    Run(RunArgs),
+    /// Validate the configuration, rules and notification sinks, then exit
+    CheckConfig(CheckConfigArgs),
+    /// Feed recorded metrics through the detection rules offline and compare
+    /// the anomalies that would have fired with those stored
+    Replay(ReplayArgs),
+    /// Write stored samples as JSON Lines, the format `replay --input` reads
+    Export(ExportArgs),
+    /// Database administration
+    Db {
+        #[command(subcommand)]
+        command: DbCommand,
+    },
+}
+
+#[derive(Args, Default)]
+pub struct RunArgs {
+    /// Address the API listens on
+    #[arg(long)]
+    pub host: Option<String>,
+
+    /// Port the API listens on
+    #[arg(long)]
+    pub port: Option<u16>,
+}
+
+#[derive(Args)]
+pub struct CheckConfigArgs {
+    /// Also print the effective configuration as TOML (may contain secrets)
+    #[arg(long)]
+    print: bool,
+}
+
+#[derive(Args)]
+pub struct ReplayArgs {
+    /// Start of the range: RFC3339 timestamp or a duration ago such as 7d
+    /// [default: 24h for the database, the whole file for --input]
+    #[arg(long, value_parser = parse_time_arg)]
+    from: Option<DateTime<Utc>>,
+
+    /// End of the range: RFC3339 timestamp or a duration ago [default: now]
+    #[arg(long, value_parser = parse_time_arg)]
+    to: Option<DateTime<Utc>>,
+
+    /// Read samples from an exported JSON Lines file instead of the database
+    #[arg(long, value_name = "FILE")]
+    input: Option<PathBuf>,
+
+    /// Candidate rules file [default: the configured rules]
+    #[arg(long, value_name = "FILE")]
+    rules: Option<PathBuf>,
+
+    /// Write the anomalies that would have fired to a JSON Lines file
+    #[arg(long, value_name = "FILE")]
+    output: Option<PathBuf>,
+
+    /// Do not compare with the anomalies stored in the database
+    #[arg(long)]
+    no_diff: bool,
+
+    /// Seconds between a replayed and a stored anomaly that still count as the same
+    #[arg(long, value_name = "SECS", default_value_t = DEFAULT_MATCH_TOLERANCE.num_seconds())]
+    tolerance: i64,
+
+    /// Core count for per_cpu rules [default: from the first sample]
+    #[arg(long)]
+    cpus: Option<usize>,
+
+    /// Print the report as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Args)]
+pub struct ExportArgs {
+    /// Start of the range: RFC3339 timestamp or a duration ago such as 7d
+    #[arg(long, value_parser = parse_time_arg, default_value = "24h")]
+    from: DateTime<Utc>,
+
+    /// End of the range: RFC3339 timestamp or a duration ago [default: now]
+    #[arg(long, value_parser = parse_time_arg)]
+    to: Option<DateTime<Utc>>,
+
+    /// File to write [default: standard output]
+    #[arg(long, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+pub enum DbCommand {
+    /// Create the database if needed and apply pending migrations
+    Migrate,
+    /// Apply retention and the size limit once, as the running collector
+    /// does every maintenance interval
+    Cleanup {
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+impl Cli {
+    /// Loads the layered configuration, applies the flags and validates it
+    ///
+    /// # Returns
+    /// The configuration and the config file it was read from, if any
+    pub fn load_config(&self) -> anyhow::Result<(AppConfig, Option<PathBuf>)> {
+        let (mut config, path) = AppConfig::load(self.config.as_deref())?;
+        if let Some(ref url) = self.database_url {
+            config.database_url = url.clone();
+        }
+        if let Some(Command::Run(ref args)) = self.command {
+            if let Some(ref host) = args.host {
+                config.server.host = host.clone();
+            }
+            if let Some(port) = args.port {
+                config.server.port = port;
+            }
+        }
+        config.validate().with_context(|| match path {
+            Some(ref path) => format!("Invalid configuration ({})", path.display()),
+            None => "Invalid configuration".to_string(),
+        })?;
+        Ok((config, path))
+    }
+}
+
+/// Sends logs to stderr, warnings only unless RUST_LOG says otherwise, so
+/// command output can be piped
+pub fn init_quiet_tracing() {
+    tracing_subscriber::fmt()
+        .with_writer(io::stderr)
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn")),
+        )
+        .init();
+}
+
+/// Loads everything the collector would load at startup and prints a summary
+///
+/// Fails on the first invalid value, so it can gate a deploy or a restart.
+pub fn check_config(
+    config: &AppConfig,
+    path: Option<PathBuf>,
+    args: CheckConfigArgs,
+) -> anyhow::Result<()> {
+    let rules = config.rules()?;
+    let notifications = config.notification_config()?;
+
+    match path {
+        Some(path) => println!("Config file:   {}", path.display()),
+        None => println!("Config file:   none (defaults and environment)"),
+    }
+    println!("Database:      {}", config.database_url);
+    println!(
+        "API server:    {}:{}",
+        config.server.host, config.server.port
+    );
+    if config.server.cors_origins.is_empty() {
+        println!("CORS:          disabled");
+    } else {
+        println!("CORS origins:  {}", config.server.cors_origins.join(", "));
+    }
+    println!("Interval:      {}s", config.collection_interval_secs);
+    match config.rules_file {
+        Some(ref file) => println!("Rules:         {} from {}", rules.len(), file.display()),
+        None => println!("Rules:         {} built-in", rules.len()),
+    }
+    println!("Notifications: {} sinks", notifications.sinks.len());
+
+    let retention = &config.retention;
+    println!(
+        "Retention:     metrics {}d, anomalies {}d, rollups {}d/{}d/{}d, maintenance every {}s",
+        retention.metrics_days,
+        retention.anomalies_days,
+        retention.rollup_1m_days,
+        retention.rollup_15m_days,
+        retention.rollup_1h_days,
+        retention.maintenance_interval_secs
+    );
+    if let Some(mb) = retention.max_db_size_mb {
+        println!("Size limit:    {} MiB", mb);
+    }
+    for (name, settings) in &config.collector_config().collectors {
+        if !settings.enabled {
+            println!("Collector:     {} disabled", name);
+        } else if let Some(interval) = settings.interval {
+            println!("Collector:     {} every {}s", name, interval.as_secs());
+        }
+    }
+
+    if args.print {
+        println!();
+        print!("{}", toml::to_string_pretty(config)?);
+    }
+    println!("Configuration OK");
+    Ok(())
+}
+
+/// Replays recorded metrics through a rule set and reports what would have fired
+///
+/// Samples come from the database (in chunks) or an exported file. Unless
+/// `--no-diff` is given, the result is compared with the anomalies stored for
+/// the same period. Muted rules are not applied, and the detector starts
+/// without the baselines learned by the running collector.
+pub async fn replay(config: &AppConfig, args: ReplayArgs) -> anyhow::Result<()> {
+    let rules = match args.rules {
+        Some(ref path) => RuleSet::from_file(path)?,
+        None => config.rules()?,
+    };
+    let rule_count = rules.len();
+    let tolerance = chrono::Duration::try_seconds(args.tolerance)
+        .filter(|tolerance| *tolerance >= chrono::Duration::zero())
+        .with_context(|| format!("Invalid --tolerance {}: expected seconds", args.tolerance))?;
+    let mut engine = Replay::new(
+        rules,
+        chrono::Duration::seconds(config.incident_cooldown_secs),
+    );
+    if let Some(cpus) = args.cpus {
+        engine = engine.with_num_cpus(cpus);
+    }
+
+    let mut output = match args.output {
+        Some(ref path) => Some(BufWriter::new(File::create(path)?)),
+        None => None,
+    };
+    let mut replayed = Vec::new();
+    let mut write_error = None;
+    let mut feed = |metrics: &SystemMetrics| {
+        for anomaly in engine.feed(metrics) {
+            if let Some(ref mut output) = output {
+                if let Err(e) = write_json_line(output, &anomaly) {
+                    write_error.get_or_insert(e);
+                }
+            }
+            replayed.push(anomaly);
+        }
+    };
+
+    let needs_db = args.input.is_none() || !args.no_diff;
+    let repo = if needs_db {
+        Some(MetricsRepository::new(&config.database_url).await?)
+    } else {
+        None
+    };
+
+    let now = Utc::now();
+    let to = args.to.unwrap_or(now);
+    let range = match (args.input, &repo) {
+        (Some(path), _) => {
+            let from = args.from.unwrap_or(DateTime::<Utc>::MIN_UTC);
+            for sample in replay::read_metrics_file(&path)? {
+                let metrics = sample?;
+                if metrics.timestamp >= from && metrics.timestamp <= to {
+                    feed(&metrics);
+                }
+            }
+            None
+        }
+        (None, Some(repo)) => {
+            let from = args.from.unwrap_or(now - chrono::Duration::hours(24));
+            replay::for_each_stored(repo, from, to, &mut feed).await?;
+            Some((from, to))
+        }
+        (None, None) => unreachable!("the database is opened when no input file is given"),
+    };
+    if let Some(e) = write_error {
+        return Err(e.into());
+    }
+    if let Some(mut output) = output {
+        output.flush()?;
+    }
+
+    // Compare with what was stored over the replayed period
+    let range = range.or_else(|| engine.span());
+    let diff = match (&repo, range) {
+        (Some(repo), Some((from, to))) if !args.no_diff => {
+            let (Some(start), Some(end)) = (
+                from.checked_sub_signed(tolerance),
+                to.checked_add_signed(tolerance),
+            ) else {
+                anyhow::bail!(
+                    "Invalid --tolerance {}: the replayed range is out of bounds",
+                    args.tolerance
+                );
+            };
+            let stored = repo.get_anomalies_range(start, end).await?;
+            Some(replay::diff_anomalies(&replayed, &stored, tolerance))
+        }
+        _ => None,
+    };
+
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&replay_report_json(
+                engine.samples(),
+                rule_count,
+                range,
+                &replayed,
+                diff.as_ref()
+            ))?
+        );
+    } else {
+        print_replay_report(
+            engine.samples(),
+            rule_count,
+            range,
+            &replayed,
+            diff.as_ref(),
+        );
+    }
+
+    if let Some(repo) = repo {
+        repo.close().await?;
+    }
+    Ok(())
+}
+
+/// Writes stored samples in time order, one JSON object per line
+pub async fn export(config: &AppConfig, args: ExportArgs) -> anyhow::Result<()> {
+    let to = args.to.unwrap_or_else(Utc::now);
+    let repo = MetricsRepository::new(&config.database_url).await?;
+
+    let mut output: BufWriter<Box<dyn Write>> = BufWriter::new(match args.output {
+        Some(ref path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout().lock()),
+    });
+    let mut write_error = None;
+    let count = replay::for_each_stored(&repo, args.from, to, |metrics| {
+        if write_error.is_none() {
+            if let Err(e) = write_json_line(&mut output, metrics) {
+                write_error = Some(e);
+            }
+        }
+    })
+    .await?;
+    let flushed = match write_error {
+        Some(e) => Err(e),
+        None => output.flush(),
+    };
+    match flushed {
+        // The reader, e.g. `head`, has seen enough
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {}
+        result => result?,
+    }
+
+    if let Some(ref path) = args.output {
+        eprintln!("Exported {} samples to {}", count, path.display());
+    }
+    repo.close().await?;
+    Ok(())
+}
+
+/// Creates the database if needed and brings its schema up to date
+pub async fn db_migrate(config: &AppConfig) -> anyhow::Result<()> {
+    let repo = MetricsRepository::new(&config.database_url).await?;
+    repo.run_migrations().await?;
+    println!("Database {} is up to date", config.database_url);
+    repo.close().await?;
+    Ok(())
+}
+
+/// Runs one maintenance pass with the configured retention and size limit
+pub async fn db_cleanup(config: &AppConfig, json: bool) -> anyhow::Result<()> {
+    let repo = Arc::new(MetricsRepository::new(&config.database_url).await?);
+    repo.run_migrations().await?;
+    let report = MaintenanceTask::new(Arc::clone(&repo), config.maintenance_config())
+        .run_once(Utc::now())
+        .await;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_maintenance_report(&report);
+    }
+    repo.close().await?;
+    match report.error {
+        Some(e) => anyhow::bail!("Maintenance stopped early: {}", e),
+        None => Ok(()),
+    }
+}
+
+/// Writes `value` as one line of JSON
+fn write_json_line(output: &mut impl Write, value: &impl serde::Serialize) -> io::Result<()> {
+    serde_json::to_writer(&mut *output, value)?;
+    output.write_all(b"\n")
+}
+
+/// Parses a `--from`/`--to` value for clap
+fn parse_time_arg(value: &str) -> Result<DateTime<Utc>, String> {
+    replay::parse_time(value, Utc::now()).ok_or_else(|| {
+        format!(
+            "invalid time '{}': expected an RFC3339 timestamp or a duration such as 30m, 12h or 7d",
+            value
+        )
+    })
+}
+
+/// Prints what a maintenance pass deleted and the database size
+fn print_maintenance_report(report: &MaintenanceReport) {
+    println!(
+        "Deleted {} rows in {} ms",
+        report.total_deleted(),
+        report.duration_ms
+    );
+    println!("  metrics:    {}", report.metrics_deleted);
+    println!("  anomalies:  {}", report.anomalies_deleted);
+    println!("  incidents:  {}", report.incidents_deleted);
+    for (tier, deleted) in &report.rollups_deleted {
+        println!("  rollup {:<4} {}", format!("{}:", tier), deleted);
+    }
+    if report.size_limit_metrics_deleted > 0 {
+        println!("  size limit: {}", report.size_limit_metrics_deleted);
+    }
+    println!(
+        "Database size: {:.1} MiB -> {:.1} MiB",
+        report.size_before_bytes as f64 / (1024.0 * 1024.0),
+        report.size_after_bytes as f64 / (1024.0 * 1024.0)
+    );
+}
+
+/// Prints a replay summary followed by the anomalies that differ
+///
+/// Without a diff every anomaly that would have fired is listed; with one,
+/// `+` marks anomalies only the replay raised and `-` stored ones it did not.
+fn print_replay_report(
+    samples: u64,
+    rules: usize,
+    range: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    replayed: &[Anomaly],
+    diff: Option<&ReplayDiff>,
+) {
+    match range {
+        Some((from, to)) => println!(
+            "Replayed {} samples from {} to {} with {} rules",
+            samples,
+            from.to_rfc3339_opts(SecondsFormat::Secs, true),
+            to.to_rfc3339_opts(SecondsFormat::Secs, true),
+            rules
+        ),
+        None => println!("Replayed {} samples with {} rules", samples, rules),
+    }
+    println!("Would fire: {} anomalies", replayed.len());
+
+    let line = |mark: char, anomaly: &Anomaly| {
+        println!(
+            "{} {} {:<8} {}",
+            mark,
+            anomaly.timestamp.to_rfc3339_opts(SecondsFormat::Secs, true),
+            format!("{:?}", anomaly.severity),
+            anomaly.message
+        );
+    };
+
+    match diff {
+        Some(diff) => {
+            println!(
+                "Stored:     {} anomalies",
+                diff.matched.len() + diff.removed.len()
+            );
+            println!("Matched:    {}", diff.matched.len());
+            println!("Only in replay (+): {}", diff.added.len());
+            println!("Only stored (-):    {}", diff.removed.len());
+            if !diff.added.is_empty() || !diff.removed.is_empty() {
+                println!();
+            }
+            for anomaly in &diff.added {
+                line('+', anomaly);
+            }
+            for anomaly in &diff.removed {
+                line('-', anomaly);
+            }
+        }
+        None => {
+            if !replayed.is_empty() {
+                println!();
+            }
+            for anomaly in replayed {
+                line('*', anomaly);
+            }
+        }
+    }
+}
+
+/// Builds the `--json` replay report
+fn replay_report_json(
+    samples: u64,
+    rules: usize,
+    range: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    replayed: &[Anomaly],
+    diff: Option<&ReplayDiff>,
+) -> serde_json::Value {
+    serde_json::json!({
+        "samples": samples,
+        "rules": rules,
+        "from": range.map(|(from, _)| from),
+        "to": range.map(|(_, to)| to),
+        "anomalies": replayed,
+        "diff": diff.map(|diff| serde_json::json!({
+            "matched": diff.matched.len(),
+            "added": diff.added,
+            "removed": diff.removed,
+        })),
+    })
+}
@@ -1,18 +1,29 @@
 use shared::types::CpuMetrics;
-use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
 use sysinfo::System;
 
+use super::source::{HostSource, SystemSource};
+use super::{Collector, Update};
+
 /// Collects CPU usage metrics including global usage, per-core usage, and load averages
 pub struct CpuCollector {
     system: System,
+    source: Arc<dyn SystemSource>,
 }
 
 impl CpuCollector {
     /// Creates a new CpuCollector
     pub fn new() -> Self {
+        Self::with_source(Arc::new(HostSource))
+    }
+
+    /// Creates a CpuCollector reading load averages from `source`
+    pub fn with_source(source: Arc<dyn SystemSource>) -> Self {
         let mut system = System::new();
         system.refresh_cpu_all();
-        Self { system }
+        Self { system, source }
     }
 
     /// Refreshes the CPU data from the system
@@ -36,7 +47,7 @@ impl CpuCollector {
             .map(|cpu| cpu.cpu_usage())
             .collect();
 
-        let (load_avg_1, load_avg_5, load_avg_15) = Self::get_load_average();
+        let (load_avg_1, load_avg_5, load_avg_15) = self.get_load_average();
 
         CpuMetrics {
             global_usage,
@@ -48,8 +59,8 @@ impl CpuCollector {
     }
 
     /// Reads load average from /proc/loadavg
-    fn get_load_average() -> (f64, f64, f64) {
-        if let Ok(content) = fs::read_to_string("/proc/loadavg") {
+    fn get_load_average(&self) -> (f64, f64, f64) {
+        if let Ok(content) = self.source.read_to_string(Path::new("/proc/loadavg")) {
             let parts: Vec<&str> = content.split_whitespace().collect();
             if parts.len() >= 3 {
                 let load1 = parts[0].parse::<f64>().unwrap_or(0.0);
@@ -62,6 +73,21 @@ impl CpuCollector {
     }
 }
 
+impl Collector for CpuCollector {
+    fn name(&self) -> &'static str {
+        "cpu"
+    }
+
+    fn refresh(&mut self) {
+        CpuCollector::refresh(self);
+    }
+
+    fn collect(&mut self) -> io::Result<Update> {
+        let cpu = CpuCollector::collect(self);
+        Ok(Box::new(move |metrics| metrics.cpu = cpu))
+    }
+}
+
 impl Default for CpuCollector {
     fn default() -> Self {
         Self::new()
@@ -70,6 +96,7 @@ impl Default for CpuCollector {
 
 #[cfg(test)]
 mod tests {
+    use super::super::source::tests::host_fixture;
     use super::*;
 
     #[test]
@@ -85,9 +112,19 @@ mod tests {
 
     #[test]
     fn test_load_average() {
-        let (load1, load5, load15) = CpuCollector::get_load_average();
+        let (load1, load5, load15) = CpuCollector::new().get_load_average();
         assert!(load1 >= 0.0);
         assert!(load5 >= 0.0);
         assert!(load15 >= 0.0);
     }
+
+    #[test]
+    fn test_fixture_load_average() {
+        let fixture = host_fixture();
+        let collector = CpuCollector::with_source(fixture.clone());
+        assert_eq!(collector.get_load_average(), (0.52, 0.58, 0.59));
+
+        fixture.advance();
+        assert_eq!(collector.get_load_average(), (1.25, 0.75, 0.62));
+    }
 }
@@ -1,13 +1,20 @@
 use shared::types::DiskMetrics;
-use std::collections::HashMap;
-use std::fs;
+use std::collections::{HashMap, HashSet};
 use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
 use sysinfo::Disks;
 
+use super::source::{HostSource, SystemSource};
+use super::{Collector, Update};
+
 /// Collects disk usage and I/O statistics
 pub struct DiskCollector {
     disks: Disks,
+    source: Arc<dyn SystemSource>,
     previous_stats: HashMap<String, DiskStats>,
+    last_read: Instant,
 }
 
 #[derive(Debug, Clone)]
@@ -19,11 +26,20 @@ struct DiskStats {
 impl DiskCollector {
     /// Creates a new DiskCollector
     pub fn new() -> io::Result<Self> {
+        Self::with_source(Arc::new(HostSource))
+    }
+
+    /// Creates a DiskCollector reading I/O counters from `source`
+    ///
+    /// Space usage still comes from the mounted filesystems of the host.
+    pub fn with_source(source: Arc<dyn SystemSource>) -> io::Result<Self> {
         let disks = Disks::new_with_refreshed_list();
-        let previous_stats = Self::read_diskstats()?;
+        let previous_stats = read_diskstats(source.as_ref())?;
 
         Ok(Self {
             disks,
+            last_read: source.now(),
+            source,
             previous_stats,
         })
     }
@@ -36,9 +52,7 @@ impl DiskCollector {
     /// Collects disk metrics including space usage and I/O stats
     pub fn collect(&mut self) -> Vec<DiskMetrics> {
         let mut metrics = Vec::new();
-
-        // Get current I/O stats
-        let current_stats = Self::read_diskstats().unwrap_or_default();
+        let io_rates = self.io_rates();
 
         for disk in self.disks.iter() {
             let name = disk.name().to_string_lossy().to_string();
@@ -55,8 +69,7 @@ impl DiskCollector {
                 0.0
             };
 
-            // Calculate I/O rates
-            let (read_mb, write_mb) = self.calculate_io_rate(&name, &current_stats);
+            let (read_mb, write_mb) = disk_io_rate(&io_rates, &name);
 
             metrics.push(DiskMetrics {
                 name,
@@ -70,78 +83,43 @@ impl DiskCollector {
             });
         }
 
-        // Update previous stats for next calculation
-        self.previous_stats = current_stats;
-
         metrics
     }
 
-    /// Reads /proc/diskstats to get I/O statistics
-    fn read_diskstats() -> io::Result<HashMap<String, DiskStats>> {
-        let mut stats = HashMap::new();
-
-        let content = fs::read_to_string("/proc/diskstats")?;
-
-        for line in content.lines() {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 14 {
-                let device = parts[2].to_string();
-
-                // Skip partitions, only track whole disks and major devices
-                // This filters out things like sda1, sda2, etc.
-                if device.chars().last().is_some_and(|c| c.is_numeric()) && device.len() > 3 {
-                    continue;
-                }
+    /// Reads the I/O counters and returns the read and write rate in MB/s of
+    /// each whole disk since the previous read
+    ///
+    /// Disks that appeared since the previous read have no rate yet.
+    fn io_rates(&mut self) -> HashMap<String, (f64, f64)> {
+        let current_stats = read_diskstats(self.source.as_ref()).unwrap_or_default();
+        let now = self.source.now();
+        let elapsed_secs = now.duration_since(self.last_read).as_secs_f64();
+
+        let mut rates = HashMap::new();
+        for (name, current) in &current_stats {
+            let Some(previous) = self.previous_stats.get(name) else {
+                continue;
+            };
+            if elapsed_secs <= 0.0 {
+                rates.insert(name.clone(), (0.0, 0.0));
+                continue;
+            }
 
-                let read_sectors = parts[5].parse::<u64>().unwrap_or(0);
-                let write_sectors = parts[9].parse::<u64>().unwrap_or(0);
+            // Calculate sectors read/written since last check
+            let read_sectors = current.read_sectors.saturating_sub(previous.read_sectors);
+            let write_sectors = current.write_sectors.saturating_sub(previous.write_sectors);
 
-                stats.insert(
-                    device,
-                    DiskStats {
-                        read_sectors,
-                        write_sectors,
-                    },
-                );
-            }
+            // Convert sectors to MB (assuming 512 bytes per sector)
+            let read_mb = (read_sectors * 512) as f64 / 1024.0 / 1024.0 / elapsed_secs;
+            let write_mb = (write_sectors * 512) as f64 / 1024.0 / 1024.0 / elapsed_secs;
+            rates.insert(name.clone(), (read_mb, write_mb));
         }
 
-        Ok(stats)
-    }
-
-    /// Calculates I/O rate in MB/s based on sector differences
-    fn calculate_io_rate(
-        &self,
-        disk_name: &str,
-        current_stats: &HashMap<String, DiskStats>,
-    ) -> (f64, f64) {
-        // Extract device name from path (e.g., "/dev/sda" -> "sda")
-        let device_name = disk_name.trim_start_matches("/dev/").to_string();
-
-        // Try different name variations
-        let possible_names = vec![
-            device_name.clone(),
-            device_name.trim_end_matches(char::is_numeric).to_string(),
-        ];
-
-        for name in possible_names {
-            if let (Some(current), Some(previous)) =
-                (current_stats.get(&name), self.previous_stats.get(&name))
-            {
-                // Calculate sectors read/written since last check
-                let read_sectors = current.read_sectors.saturating_sub(previous.read_sectors);
-                let write_sectors = current.write_sectors.saturating_sub(previous.write_sectors);
-
-                // Convert sectors to MB (assuming 512 bytes per sector)
-                // Divide by 2 to get approximate MB/s (since we sample every ~2 seconds)
-                let read_mb = (read_sectors * 512) as f64 / 1024.0 / 1024.0 / 2.0;
-                let write_mb = (write_sectors * 512) as f64 / 1024.0 / 1024.0 / 2.0;
-
-                return (read_mb, write_mb);
-            }
-        }
+        // Update previous stats for next calculation
+        self.previous_stats = current_stats;
+        self.last_read = now;
 
-        (0.0, 0.0)
+        rates
     }
 
     /// Gets the maximum disk usage percentage across all disks
@@ -171,6 +149,82 @@ impl DiskCollector {
     }
 }
 
+/// Looks up the I/O rate of a mounted disk, using the rate of the whole disk
+/// for a partition (e.g. "/dev/sda1" -> "sda", "/dev/nvme0n1p2" -> "nvme0n1")
+fn disk_io_rate(rates: &HashMap<String, (f64, f64)>, disk_name: &str) -> (f64, f64) {
+    let device_name = disk_name.trim_start_matches("/dev/");
+    let parent = device_name.trim_end_matches(char::is_numeric);
+    // nvme0n1p2 and mmcblk0p1 separate the partition number with a 'p'
+    let parent = match parent.strip_suffix('p') {
+        Some(disk) if disk.ends_with(char::is_numeric) => disk,
+        _ => parent,
+    };
+
+    [device_name, parent]
+        .iter()
+        .find_map(|name| rates.get(*name).copied())
+        .unwrap_or((0.0, 0.0))
+}
+
+/// Lists whole disks from /sys/block, or None when sysfs is not available
+fn whole_disks(source: &dyn SystemSource) -> Option<HashSet<String>> {
+    let entries = source.read_dir(Path::new("/sys/block")).ok()?;
+    Some(
+        entries
+            .iter()
+            .filter_map(|path| path.file_name()?.to_str().map(str::to_string))
+            .collect(),
+    )
+}
+
+/// Reads /proc/diskstats to get I/O statistics
+fn read_diskstats(source: &dyn SystemSource) -> io::Result<HashMap<String, DiskStats>> {
+    let mut stats = HashMap::new();
+
+    let content = source.read_to_string(Path::new("/proc/diskstats"))?;
+    let disks = whole_disks(source);
+
+    for line in content.lines() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() >= 14 {
+            let device = parts[2].to_string();
+
+            // Skip partitions such as sda1 or nvme0n1p1, only track whole disks
+            if disks.as_ref().is_some_and(|disks| !disks.contains(&device)) {
+                continue;
+            }
+
+            let read_sectors = parts[5].parse::<u64>().unwrap_or(0);
+            let write_sectors = parts[9].parse::<u64>().unwrap_or(0);
+
+            stats.insert(
+                device,
+                DiskStats {
+                    read_sectors,
+                    write_sectors,
+                },
+            );
+        }
+    }
+
+    Ok(stats)
+}
+
+impl Collector for DiskCollector {
+    fn name(&self) -> &'static str {
+        "disk"
+    }
+
+    fn refresh(&mut self) {
+        DiskCollector::refresh(self);
+    }
+
+    fn collect(&mut self) -> io::Result<Update> {
+        let disks = DiskCollector::collect(self);
+        Ok(Box::new(move |metrics| metrics.disks = disks))
+    }
+}
+
 impl Default for DiskCollector {
     fn default() -> Self {
         Self::new().expect("Failed to initialize DiskCollector")
@@ -179,6 +233,7 @@ impl Default for DiskCollector {
 
 #[cfg(test)]
 mod tests {
+    use super::super::source::tests::host_fixture;
     use super::*;
 
     #[test]
@@ -200,11 +255,46 @@ mod tests {
 
     #[test]
     fn test_read_diskstats() {
-        let stats = DiskCollector::read_diskstats();
-        assert!(stats.is_ok());
+        let stats = read_diskstats(host_fixture().as_ref()).unwrap();
+
+        let sda = &stats["sda"];
+        assert_eq!(sda.read_sectors, 2048);
+        assert_eq!(sda.write_sectors, 4096);
+        let nvme = &stats["nvme0n1"];
+        assert_eq!(nvme.read_sectors, 100);
+        assert_eq!(nvme.write_sectors, 200);
+        // Partitions are skipped
+        let mut names: Vec<&String> = stats.keys().collect();
+        names.sort();
+        assert_eq!(names, ["nvme0n1", "sda"]);
+    }
 
-        let stats = stats.unwrap();
-        assert!(!stats.is_empty());
+    #[test]
+    fn test_fixture_io_rates() {
+        let fixture = host_fixture();
+        let mut collector = DiskCollector::with_source(fixture.clone()).unwrap();
+
+        // 4096 sectors read and 8192 written on sda, 512 read on nvme0n1, over
+        // two seconds; the hot-plugged sdb has no baseline yet
+        fixture.advance();
+        let rates = collector.io_rates();
+        assert_eq!(rates.len(), 2);
+        assert_eq!(rates["sda"], (1.0, 2.0));
+        assert_eq!(rates["nvme0n1"], (0.125, 0.0));
+        assert_eq!(disk_io_rate(&rates, "/dev/sda1"), (1.0, 2.0));
+        assert_eq!(disk_io_rate(&rates, "/dev/nvme0n1p2"), (0.125, 0.0));
+        assert_eq!(disk_io_rate(&rates, "/dev/sdb"), (0.0, 0.0));
+
+        // sdb reads 4096 sectors; sda is idle
+        fixture.advance();
+        let rates = collector.io_rates();
+        assert_eq!(rates["sdb"], (1.0, 0.0));
+        assert_eq!(rates["sda"], (0.0, 0.0));
+        assert_eq!(rates["nvme0n1"], (0.125, 0.0));
+
+        // No time has passed since the last read
+        let rates = collector.io_rates();
+        assert!(rates.values().all(|&rate| rate == (0.0, 0.0)));
     }
 
     #[test]
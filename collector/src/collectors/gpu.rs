@@ -1,7 +1,11 @@
 use shared::types::GpuMetrics;
+use std::io;
 use std::process::Command;
+use std::time::Duration;
 use tracing::{debug, warn};
 
+use super::{Collector, Update};
+
 /// Collects GPU metrics using nvidia-smi for NVIDIA GPUs
 pub struct GpuCollector {
     nvidia_smi_available: bool,
@@ -22,7 +26,9 @@ impl GpuCollector {
             debug!("nvidia-smi not available, GPU metrics collection disabled");
         }
 
-        Self { nvidia_smi_available }
+        Self {
+            nvidia_smi_available,
+        }
     }
 
     /// Collects GPU metrics from nvidia-smi
@@ -104,6 +110,28 @@ impl GpuCollector {
     }
 }
 
+impl Collector for GpuCollector {
+    fn name(&self) -> &'static str {
+        "gpu"
+    }
+
+    /// Spawning nvidia-smi takes tens of milliseconds, too slow for every sample
+    fn interval(&self) -> Duration {
+        Duration::from_secs(10)
+    }
+
+    fn collect(&mut self) -> io::Result<Update> {
+        if !self.nvidia_smi_available {
+            return Ok(Box::new(|metrics| metrics.gpu = None));
+        }
+        // Keep the previous reading when a query fails
+        let gpu = self
+            .collect_nvidia_metrics()
+            .ok_or_else(|| io::Error::other("nvidia-smi query failed"))?;
+        Ok(Box::new(move |metrics| metrics.gpu = Some(gpu)))
+    }
+}
+
 impl Default for GpuCollector {
     fn default() -> Self {
         Self::new()
@@ -127,17 +155,29 @@ mod tests {
 
         if collector.is_available() {
             let metrics = collector.collect();
-            assert!(metrics.is_some(), "Should collect metrics when nvidia-smi is available");
+            assert!(
+                metrics.is_some(),
+                "Should collect metrics when nvidia-smi is available"
+            );
 
             if let Some(gpu) = metrics {
                 assert!(!gpu.name.is_empty(), "GPU name should not be empty");
-                assert!(gpu.temperature >= 0.0 && gpu.temperature <= 120.0, "Temperature should be reasonable");
-                assert!(gpu.usage_percent >= 0.0 && gpu.usage_percent <= 100.0, "Usage should be percentage");
+                assert!(
+                    gpu.temperature >= 0.0 && gpu.temperature <= 120.0,
+                    "Temperature should be reasonable"
+                );
+                assert!(
+                    gpu.usage_percent >= 0.0 && gpu.usage_percent <= 100.0,
+                    "Usage should be percentage"
+                );
                 assert!(gpu.memory_total_mb > 0, "Memory total should be positive");
             }
         } else {
             let metrics = collector.collect();
-            assert!(metrics.is_none(), "Should return None when nvidia-smi is not available");
+            assert!(
+                metrics.is_none(),
+                "Should return None when nvidia-smi is not available"
+            );
         }
     }
 }
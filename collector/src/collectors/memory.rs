@@ -1,6 +1,9 @@
 use shared::types::MemoryMetrics;
+use std::io;
 use sysinfo::System;
 
+use super::{Collector, Update};
+
 /// Collects memory usage metrics including RAM and swap
 pub struct MemoryCollector {
     system: System,
@@ -44,6 +47,21 @@ impl MemoryCollector {
     }
 }
 
+impl Collector for MemoryCollector {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn refresh(&mut self) {
+        MemoryCollector::refresh(self);
+    }
+
+    fn collect(&mut self) -> io::Result<Update> {
+        let memory = MemoryCollector::collect(self);
+        Ok(Box::new(move |metrics| metrics.memory = memory))
+    }
+}
+
 impl Default for MemoryCollector {
     fn default() -> Self {
         Self::new()
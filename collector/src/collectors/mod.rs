@@ -3,63 +3,341 @@ pub mod disk;
 pub mod gpu;
 pub mod memory;
 pub mod network;
+pub mod process;
+pub mod source;
 pub mod temperature;
 pub mod usb;
+pub mod zombie;
 
 use chrono::Utc;
-use shared::types::SystemMetrics;
+use serde_json::json;
+use shared::types::{Anomaly, AnomalyCategory, AnomalySeverity, CollectorStatus, SystemMetrics};
+use std::collections::HashMap;
 use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
 
 use cpu::CpuCollector;
 use disk::DiskCollector;
 use gpu::GpuCollector;
 use memory::MemoryCollector;
 use network::NetworkCollector;
+use process::ProcessCollector;
 use temperature::TemperatureCollector;
 use usb::UsbCollector;
+use zombie::ZombieCollector;
+
+/// Names of the built-in collectors, in collection order
+pub const COLLECTOR_NAMES: &[&str] = &[
+    "cpu",
+    "memory",
+    "temperature",
+    "disk",
+    "usb",
+    "network",
+    "gpu",
+    "process",
+    "zombie",
+];
+
+/// Longest a collector run may take unless configured otherwise
+pub const DEFAULT_COLLECTOR_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Rule name of the anomalies raised for collectors that time out
+pub const COLLECTOR_TIMEOUT_RULE: &str = "collector_timeout";
+
+/// Ticks jitter by a few milliseconds; without some slack a collector due
+/// every 10s on a 2s tick would only run every 12s
+const SCHEDULE_SLACK: Duration = Duration::from_millis(100);
+
+/// Writes one collector's readings into the sample
+pub type Update = Box<dyn FnOnce(&mut SystemMetrics) + Send>;
+
+/// A source of one part of the system sample
+///
+/// Collectors are run by `MetricsCollector`, each on its own interval and on
+/// a blocking thread, so they may sleep, read files or spawn commands. A
+/// collector that is not due, fails or times out keeps its part of the
+/// previous sample.
+pub trait Collector: Send {
+    /// Name used in configuration and status reports
+    fn name(&self) -> &'static str;
+
+    /// Default time between runs; zero means every sample
+    fn interval(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    /// Refreshes cached system state before `collect`
+    fn refresh(&mut self) {}
+
+    /// Reads the collector's part of the sample
+    ///
+    /// # Returns
+    /// The update writing the readings into the sample, applied once every
+    /// collector of the tick has finished or timed out
+    fn collect(&mut self) -> io::Result<Update>;
+}
+
+/// Whether, how often and for how long one collector runs
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollectorSettings {
+    /// False turns the collector off; its part of the sample stays empty
+    pub enabled: bool,
+    /// Overrides the collector's default interval
+    pub interval: Option<Duration>,
+    /// Overrides `DEFAULT_COLLECTOR_TIMEOUT`
+    pub timeout: Option<Duration>,
+}
+
+impl Default for CollectorSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval: None,
+            timeout: None,
+        }
+    }
+}
+
+/// Per-collector settings, keyed by collector name
+///
+/// Collectors without an entry run on their default interval.
+#[derive(Debug, Clone, Default)]
+pub struct CollectorConfig {
+    pub collectors: HashMap<String, CollectorSettings>,
+}
+
+impl CollectorConfig {
+    /// Returns the settings of `name`, or the defaults
+    pub fn settings(&self, name: &str) -> CollectorSettings {
+        self.collectors.get(name).cloned().unwrap_or_default()
+    }
+}
+
+/// Outcome of one run and how long it took
+type Run = (io::Result<Update>, Duration);
+
+/// A collector with its schedule and last outcome
+struct Scheduled {
+    /// Locked by the blocking thread for the length of a run
+    collector: Arc<Mutex<Box<dyn Collector>>>,
+    timeout: Duration,
+    last_run: Option<Instant>,
+    /// Run that outlived its timeout and may still hold the collector
+    stuck: Option<JoinHandle<Run>>,
+    status: CollectorStatus,
+}
+
+impl Scheduled {
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.status.interval_secs)
+    }
+
+    fn is_due(&self, now: Instant) -> bool {
+        self.status.enabled
+            && self
+                .last_run
+                .is_none_or(|last| now + SCHEDULE_SLACK >= last + self.interval())
+    }
+
+    /// Returns true while an abandoned run is still going
+    ///
+    /// A hung run is not queued behind: the collector is skipped until the
+    /// run returns, and its late readings are dropped.
+    fn is_stuck(&mut self) -> bool {
+        match &self.stuck {
+            Some(run) if !run.is_finished() => true,
+            _ => {
+                self.stuck = None;
+                false
+            }
+        }
+    }
+
+    /// Starts a run on a blocking thread
+    fn spawn(&self) -> JoinHandle<Run> {
+        let collector = Arc::clone(&self.collector);
+        tokio::task::spawn_blocking(move || {
+            let started = Instant::now();
+            // A panicking run leaves the collector usable for the next one
+            let mut collector = collector.lock().unwrap_or_else(|e| e.into_inner());
+            collector.refresh();
+            let result = collector.collect();
+            (result, started.elapsed())
+        })
+    }
+
+    fn record_run(&mut self, duration: Duration, error: Option<String>) {
+        let status = &mut self.status;
+        status.last_run = Some(Utc::now());
+        status.last_duration_ms = Some(duration.as_secs_f64() * 1000.0);
+        status.runs_total += 1;
+        if let Some(error) = error {
+            tracing::warn!("Collector {} failed: {}", status.name, error);
+            status.last_error = Some(error);
+            status.last_error_at = status.last_run;
+            status.errors_total += 1;
+        }
+    }
+}
 
 /// Main metrics collector that aggregates all individual collectors
 pub struct MetricsCollector {
-    cpu: CpuCollector,
-    memory: MemoryCollector,
-    temperature: TemperatureCollector,
-    disk: DiskCollector,
-    usb: UsbCollector,
-    network: NetworkCollector,
-    gpu: GpuCollector,
+    collectors: Vec<Scheduled>,
+    /// Latest reading of every collector, updated in place
+    sample: SystemMetrics,
+    /// Runs of the latest sample that failed or timed out
+    failures: usize,
 }
 
 impl MetricsCollector {
-    /// Creates a new MetricsCollector with all sub-collectors initialized
+    /// Creates a new MetricsCollector with all sub-collectors on their
+    /// default intervals
     pub fn new() -> io::Result<Self> {
-        Ok(Self {
-            cpu: CpuCollector::new(),
-            memory: MemoryCollector::new(),
-            temperature: TemperatureCollector::new(),
-            disk: DiskCollector::new()?,
-            usb: UsbCollector::new(),
-            network: NetworkCollector::new(),
-            gpu: GpuCollector::new(),
-        })
+        Self::with_config(&CollectorConfig::default())
     }
 
-    /// Collects all system metrics from all collectors
-    pub fn collect_all(&mut self) -> io::Result<SystemMetrics> {
-        // Refresh system state first
-        self.cpu.refresh();
-        self.memory.refresh();
-        self.disk.refresh();
-
-        Ok(SystemMetrics {
-            timestamp: Utc::now(),
-            cpu: self.cpu.collect(),
-            memory: self.memory.collect(),
-            temperatures: self.temperature.collect(),
-            disks: self.disk.collect(),
-            usb_devices: self.usb.collect(),
-            network: self.network.collect(),
-            gpu: self.gpu.collect(),
-        })
+    /// Creates a new MetricsCollector with the built-in collectors scheduled
+    /// according to `config`
+    pub fn with_config(config: &CollectorConfig) -> io::Result<Self> {
+        Ok(Self::with_collectors(default_collectors()?, config))
+    }
+
+    /// Creates a new MetricsCollector running the given collectors
+    pub fn with_collectors(collectors: Vec<Box<dyn Collector>>, config: &CollectorConfig) -> Self {
+        let collectors = collectors
+            .into_iter()
+            .map(|collector| {
+                let settings = config.settings(collector.name());
+                let interval = settings.interval.unwrap_or_else(|| collector.interval());
+                let timeout = settings.timeout.unwrap_or(DEFAULT_COLLECTOR_TIMEOUT);
+                let status = CollectorStatus {
+                    name: collector.name().to_string(),
+                    enabled: settings.enabled,
+                    interval_secs: interval.as_secs(),
+                    timeout_secs: timeout.as_secs_f64(),
+                    timed_out: false,
+                    last_run: None,
+                    last_duration_ms: None,
+                    last_error: None,
+                    last_error_at: None,
+                    runs_total: 0,
+                    errors_total: 0,
+                };
+                Scheduled {
+                    collector: Arc::new(Mutex::new(collector)),
+                    timeout,
+                    last_run: None,
+                    stuck: None,
+                    status,
+                }
+            })
+            .collect();
+
+        Self {
+            collectors,
+            sample: SystemMetrics::default(),
+            failures: 0,
+        }
+    }
+
+    /// Runs every collector that is due and returns the updated sample
+    ///
+    /// Collectors run concurrently on blocking threads. One that fails or
+    /// outlives its timeout keeps its previous readings and records the error
+    /// in its status; the rest of the sample is still returned.
+    pub async fn collect_all(&mut self) -> SystemMetrics {
+        self.collect_at(Instant::now()).await
+    }
+
+    /// Number of collector runs of the latest sample that failed or timed out
+    pub fn failures(&self) -> usize {
+        self.failures
+    }
+
+    /// Schedule and last outcome of every collector
+    pub fn statuses(&self) -> Vec<CollectorStatus> {
+        self.collectors
+            .iter()
+            .map(|scheduled| scheduled.status.clone())
+            .collect()
+    }
+
+    /// Anomalies for collectors whose last run timed out
+    ///
+    /// Raised on every sample until the collector completes a run again, so
+    /// the incident stays open while it is hung.
+    pub fn health_anomalies(&self) -> Vec<Anomaly> {
+        self.collectors
+            .iter()
+            .filter(|scheduled| scheduled.status.timed_out)
+            .map(|scheduled| {
+                let status = &scheduled.status;
+                Anomaly {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    timestamp: self.sample.timestamp,
+                    severity: AnomalySeverity::Warning,
+                    category: AnomalyCategory::System,
+                    message: format!(
+                        "Collector {} timed out after {}s; its readings are stale",
+                        status.name, status.timeout_secs
+                    ),
+                    metrics: json!({
+                        "rule": COLLECTOR_TIMEOUT_RULE,
+                        "subject": status.name,
+                        "timeout_secs": status.timeout_secs,
+                        "last_error_at": status.last_error_at,
+                    }),
+                }
+            })
+            .collect()
+    }
+
+    async fn collect_at(&mut self, now: Instant) -> SystemMetrics {
+        let mut runs = Vec::new();
+        for (i, scheduled) in self.collectors.iter_mut().enumerate() {
+            if scheduled.is_due(now) && !scheduled.is_stuck() {
+                runs.push((i, scheduled.spawn()));
+                scheduled.last_run = Some(now);
+            }
+        }
+
+        let runs_len = runs.len();
+        let mut updates = Vec::new();
+        for (i, mut run) in runs {
+            let scheduled = &mut self.collectors[i];
+            let deadline = tokio::time::Instant::from_std(now + scheduled.timeout);
+            match tokio::time::timeout_at(deadline, &mut run).await {
+                Ok(Ok((Ok(update), duration))) => {
+                    scheduled.record_run(duration, None);
+                    scheduled.status.timed_out = false;
+                    updates.push(update);
+                }
+                Ok(Ok((Err(e), duration))) => {
+                    scheduled.record_run(duration, Some(e.to_string()));
+                    scheduled.status.timed_out = false;
+                }
+                Ok(Err(e)) => {
+                    scheduled.record_run(now.elapsed(), Some(format!("panicked: {}", e)));
+                    scheduled.status.timed_out = false;
+                }
+                Err(_) => {
+                    let error = format!("timed out after {:?}", scheduled.timeout);
+                    scheduled.record_run(scheduled.timeout, Some(error));
+                    scheduled.status.timed_out = true;
+                    scheduled.stuck = Some(run);
+                }
+            }
+        }
+
+        self.failures = runs_len - updates.len();
+        for update in updates {
+            update(&mut self.sample);
+        }
+        self.sample.timestamp = Utc::now();
+        self.sample.clone()
     }
 }
 
@@ -68,3 +346,204 @@ impl Default for MetricsCollector {
         Self::new().expect("Failed to initialize MetricsCollector")
     }
 }
+
+/// Creates the built-in collectors in `COLLECTOR_NAMES` order
+fn default_collectors() -> io::Result<Vec<Box<dyn Collector>>> {
+    Ok(vec![
+        Box::new(CpuCollector::new()),
+        Box::new(MemoryCollector::new()),
+        Box::new(TemperatureCollector::new()),
+        Box::new(DiskCollector::new()?),
+        Box::new(UsbCollector::new()),
+        Box::new(NetworkCollector::new()),
+        Box::new(GpuCollector::new()),
+        Box::new(ProcessCollector::new()),
+        Box::new(ZombieCollector::new()),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Counts its runs in `cpu.global_usage`, or `memory.usage_percent` for
+    /// "slow"; fails or sleeps when asked to
+    struct FakeCollector {
+        name: &'static str,
+        interval: Duration,
+        runs: u32,
+        fail: bool,
+        sleep: Duration,
+    }
+
+    impl FakeCollector {
+        fn boxed(name: &'static str, interval_secs: u64, fail: bool) -> Box<dyn Collector> {
+            Box::new(Self {
+                name,
+                interval: Duration::from_secs(interval_secs),
+                runs: 0,
+                fail,
+                sleep: Duration::ZERO,
+            })
+        }
+
+        fn sleeping(name: &'static str, sleep: Duration) -> Box<dyn Collector> {
+            Box::new(Self {
+                name,
+                interval: Duration::ZERO,
+                runs: 0,
+                fail: false,
+                sleep,
+            })
+        }
+    }
+
+    impl Collector for FakeCollector {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn interval(&self) -> Duration {
+            self.interval
+        }
+
+        fn collect(&mut self) -> io::Result<Update> {
+            std::thread::sleep(self.sleep);
+            if self.fail {
+                return Err(io::Error::new(io::ErrorKind::NotFound, "sensor missing"));
+            }
+            self.runs += 1;
+            let runs = self.runs as f32;
+            Ok(match self.name {
+                "slow" => Box::new(move |metrics| metrics.memory.usage_percent = runs),
+                "hung" => Box::new(move |metrics| metrics.memory.swap_used = runs as u64),
+                _ => Box::new(move |metrics| metrics.cpu.global_usage = runs),
+            })
+        }
+    }
+
+    #[test]
+    fn test_default_collector_names() {
+        let names: Vec<_> = default_collectors()
+            .unwrap()
+            .iter()
+            .map(|collector| collector.name())
+            .collect();
+        assert_eq!(names, COLLECTOR_NAMES);
+    }
+
+    #[tokio::test]
+    async fn test_collectors_run_on_their_intervals() {
+        let mut collector = MetricsCollector::with_collectors(
+            vec![
+                FakeCollector::boxed("fast", 0, false),
+                FakeCollector::boxed("slow", 10, false),
+            ],
+            &CollectorConfig::default(),
+        );
+        let start = Instant::now();
+
+        // Both run on the first sample, then "slow" only every 10s
+        for tick in 0..6 {
+            collector
+                .collect_at(start + Duration::from_secs(2 * tick))
+                .await;
+        }
+        let sample = collector
+            .collect_at(start + Duration::from_millis(11_950))
+            .await;
+        assert_eq!(sample.cpu.global_usage, 7.0);
+        assert_eq!(sample.memory.usage_percent, 2.0);
+
+        let statuses = collector.statuses();
+        assert_eq!(statuses[1].interval_secs, 10);
+        assert_eq!(statuses[1].runs_total, 2);
+        assert!(statuses[1].last_duration_ms.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_config_disables_and_reschedules() {
+        let mut config = CollectorConfig::default();
+        config.collectors.insert(
+            "fast".to_string(),
+            CollectorSettings {
+                enabled: false,
+                ..Default::default()
+            },
+        );
+        config.collectors.insert(
+            "slow".to_string(),
+            CollectorSettings {
+                interval: Some(Duration::ZERO),
+                ..Default::default()
+            },
+        );
+        let mut collector = MetricsCollector::with_collectors(
+            vec![
+                FakeCollector::boxed("fast", 0, false),
+                FakeCollector::boxed("slow", 10, false),
+                FakeCollector::boxed("broken", 0, true),
+            ],
+            &config,
+        );
+        collector.collect_all().await;
+        let sample = collector.collect_all().await;
+
+        assert_eq!(sample.cpu.global_usage, 0.0);
+        assert_eq!(sample.memory.usage_percent, 2.0);
+
+        let statuses = collector.statuses();
+        assert!(!statuses[0].enabled);
+        assert_eq!(statuses[0].runs_total, 0);
+        assert_eq!(statuses[1].interval_secs, 0);
+        assert_eq!(statuses[2].errors_total, 2);
+        assert_eq!(statuses[2].last_error.as_deref(), Some("sensor missing"));
+        assert_eq!(statuses[2].last_error_at, statuses[2].last_run);
+        assert_eq!(collector.failures(), 1);
+        // Failures are reported in the status, not as anomalies
+        assert!(collector.health_anomalies().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_hung_collector_times_out() {
+        let mut config = CollectorConfig::default();
+        config.collectors.insert(
+            "hung".to_string(),
+            CollectorSettings {
+                timeout: Some(Duration::from_millis(50)),
+                ..Default::default()
+            },
+        );
+        let mut collector = MetricsCollector::with_collectors(
+            vec![
+                FakeCollector::boxed("fast", 0, false),
+                FakeCollector::sleeping("hung", Duration::from_millis(400)),
+            ],
+            &config,
+        );
+
+        // The other collectors still report while "hung" is abandoned
+        let started = Instant::now();
+        let sample = collector.collect_all().await;
+        assert!(started.elapsed() < Duration::from_millis(300));
+        assert_eq!(sample.cpu.global_usage, 1.0);
+        assert_eq!(sample.memory.swap_used, 0);
+
+        let anomalies = collector.health_anomalies();
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].metrics["rule"], COLLECTOR_TIMEOUT_RULE);
+        assert_eq!(anomalies[0].metrics["subject"], "hung");
+
+        // While the run is still going the collector is skipped, not queued
+        collector.collect_all().await;
+        let status = &collector.statuses()[1];
+        assert_eq!(status.runs_total, 1);
+        assert!(status.timed_out);
+        assert_eq!(collector.health_anomalies().len(), 1);
+
+        // Once the run returns, the collector is retried
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        collector.collect_all().await;
+        assert_eq!(collector.statuses()[1].runs_total, 2);
+    }
+}
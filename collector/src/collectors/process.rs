@@ -0,0 +1,200 @@
+use shared::constants::DEFAULT_TOP_PROCESSES;
+use shared::types::ProcessInfo;
+use std::collections::HashSet;
+use std::io;
+use std::time::Instant;
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind, Users};
+
+use super::{Collector, Update};
+
+/// Collects the top CPU, memory and disk I/O consuming processes
+pub struct ProcessCollector {
+    system: System,
+    users: Users,
+    top_n: usize,
+    last_refresh: Option<Instant>,
+    elapsed_secs: f64,
+}
+
+impl ProcessCollector {
+    /// Creates a new ProcessCollector reporting the default number of processes
+    pub fn new() -> Self {
+        Self::with_top_n(DEFAULT_TOP_PROCESSES)
+    }
+
+    /// Creates a new ProcessCollector reporting the top `top_n` processes per
+    /// resource (CPU, RSS and disk I/O)
+    pub fn with_top_n(top_n: usize) -> Self {
+        Self {
+            system: System::new(),
+            users: Users::new_with_refreshed_list(),
+            top_n,
+            last_refresh: None,
+            elapsed_secs: 0.0,
+        }
+    }
+
+    /// Refreshes the process table
+    ///
+    /// CPU usage and disk I/O are computed since the previous refresh, so the
+    /// first refresh only establishes a baseline.
+    pub fn refresh(&mut self) {
+        self.system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::new()
+                .with_cpu()
+                .with_memory()
+                .with_disk_usage()
+                .with_cmd(UpdateKind::OnlyIfNotSet)
+                .with_user(UpdateKind::OnlyIfNotSet),
+        );
+        self.users.refresh_list();
+
+        let now = Instant::now();
+        self.elapsed_secs = self
+            .last_refresh
+            .map(|last| now.duration_since(last).as_secs_f64())
+            .unwrap_or(0.0);
+        self.last_refresh = Some(now);
+    }
+
+    /// Collects the union of the top processes by CPU, RSS and disk I/O,
+    /// ordered by CPU usage
+    pub fn collect(&self) -> Vec<ProcessInfo> {
+        let all: Vec<ProcessInfo> = self
+            .system
+            .processes()
+            .values()
+            // Skip kernel/user threads; their parent process already accounts for them
+            .filter(|p| p.thread_kind().is_none())
+            .map(|p| {
+                let disk = p.disk_usage();
+                let per_sec = |bytes: u64| {
+                    if self.elapsed_secs > 0.0 {
+                        (bytes as f64 / self.elapsed_secs) as u64
+                    } else {
+                        0
+                    }
+                };
+
+                ProcessInfo {
+                    pid: p.pid().as_u32(),
+                    name: p.name().to_string_lossy().to_string(),
+                    cmdline: p
+                        .cmd()
+                        .iter()
+                        .map(|arg| arg.to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                    user: p
+                        .user_id()
+                        .and_then(|uid| self.users.get_user_by_id(uid))
+                        .map(|u| u.name().to_string())
+                        .unwrap_or_else(|| "unknown".to_string()),
+                    state: p.status().to_string(),
+                    cpu_usage: p.cpu_usage(),
+                    memory_rss: p.memory(),
+                    disk_read_bytes_per_sec: per_sec(disk.read_bytes),
+                    disk_write_bytes_per_sec: per_sec(disk.written_bytes),
+                }
+            })
+            .collect();
+
+        select_top(all, self.top_n)
+    }
+}
+
+impl Collector for ProcessCollector {
+    fn name(&self) -> &'static str {
+        "process"
+    }
+
+    fn refresh(&mut self) {
+        ProcessCollector::refresh(self);
+    }
+
+    fn collect(&mut self) -> io::Result<Update> {
+        let processes = ProcessCollector::collect(self);
+        Ok(Box::new(move |metrics| metrics.processes = processes))
+    }
+}
+
+impl Default for ProcessCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Keeps the top `n` processes by CPU, by RSS and by disk I/O, without duplicates
+fn select_top(mut processes: Vec<ProcessInfo>, n: usize) -> Vec<ProcessInfo> {
+    let mut selected = HashSet::new();
+
+    processes.sort_by(|a, b| b.cpu_usage.total_cmp(&a.cpu_usage));
+    selected.extend(processes.iter().take(n).map(|p| p.pid));
+
+    processes.sort_by_key(|p| std::cmp::Reverse(p.memory_rss));
+    selected.extend(processes.iter().take(n).map(|p| p.pid));
+
+    let io = |p: &ProcessInfo| p.disk_read_bytes_per_sec + p.disk_write_bytes_per_sec;
+    processes.sort_by_key(|p| std::cmp::Reverse(io(p)));
+    selected.extend(
+        processes
+            .iter()
+            .take(n)
+            .filter(|p| io(p) > 0)
+            .map(|p| p.pid),
+    );
+
+    processes.retain(|p| selected.contains(&p.pid));
+    processes.sort_by(|a, b| b.cpu_usage.total_cmp(&a.cpu_usage));
+    processes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, cpu_usage: f32, memory_rss: u64, io: u64) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: format!("proc{}", pid),
+            cmdline: String::new(),
+            user: "root".to_string(),
+            state: "Runnable".to_string(),
+            cpu_usage,
+            memory_rss,
+            disk_read_bytes_per_sec: io,
+            disk_write_bytes_per_sec: 0,
+        }
+    }
+
+    #[test]
+    fn test_process_collector() {
+        let mut collector = ProcessCollector::with_top_n(5);
+        collector.refresh();
+        let processes = collector.collect();
+
+        // At most top-N per resource (CPU, RSS, I/O)
+        assert!(!processes.is_empty());
+        assert!(processes.len() <= 15);
+        for process in &processes {
+            assert!(!process.name.is_empty());
+            assert!(!process.state.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_select_top() {
+        let processes = vec![
+            process(1, 90.0, 10, 0),
+            process(2, 5.0, 5000, 0),
+            process(3, 1.0, 20, 900),
+            process(4, 0.5, 30, 0),
+        ];
+
+        let top = select_top(processes, 1);
+        let pids: Vec<u32> = top.iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![1, 2, 3]);
+    }
+}
@@ -0,0 +1,201 @@
+/// Access to the kernel interfaces and commands collectors read
+///
+/// `HostSource` reads the live system. `FixtureSource` replays snapshots of
+/// `/proc`, `/sys` and command output recorded under a directory, so collector
+/// tests can assert exact values across samples.
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Time between fixture snapshots unless configured otherwise
+pub const DEFAULT_FIXTURE_STEP: Duration = Duration::from_secs(2);
+
+/// Files, directories, commands and time as seen by the collectors
+pub trait SystemSource: Send + Sync {
+    /// Reads a file such as `/proc/net/dev`
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+
+    /// Lists the entries of a directory such as `/sys/class/hwmon`, sorted
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>>;
+
+    /// Runs `program` and returns its standard output
+    ///
+    /// Fails if the program cannot be started or exits with an error.
+    fn command_output(&self, program: &str, args: &[&str]) -> io::Result<String>;
+
+    /// Current time, used to turn counters into rates
+    fn now(&self) -> Instant;
+}
+
+/// The live system
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HostSource;
+
+impl SystemSource for HostSource {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let mut entries = fs::read_dir(path)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<io::Result<Vec<_>>>()?;
+        entries.sort();
+        Ok(entries)
+    }
+
+    fn command_output(&self, program: &str, args: &[&str]) -> io::Result<String> {
+        let output = Command::new(program).args(args).output()?;
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "{} exited with {}",
+                program, output.status
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Snapshots recorded under a directory, replayed one at a time
+///
+/// Numbered subdirectories (`0`, `1`, ...) are replayed in order; a directory
+/// without them is a single snapshot. A snapshot holds files at their
+/// absolute paths, e.g. `0/proc/net/dev` or `0/sys/class/hwmon/hwmon0/name`,
+/// and the output of a command under `commands/<program>` (arguments are
+/// ignored). A path missing from a snapshot reads as not found, so devices
+/// can appear and disappear between snapshots.
+#[derive(Debug)]
+pub struct FixtureSource {
+    snapshots: Vec<PathBuf>,
+    current: AtomicUsize,
+    start: Instant,
+    step: Duration,
+}
+
+impl FixtureSource {
+    /// Loads the snapshots under `dir`, starting at the first
+    pub fn new(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = dir.as_ref();
+        let mut numbered: Vec<(usize, PathBuf)> = fs::read_dir(dir)?
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let index = path.file_name()?.to_str()?.parse().ok()?;
+                path.is_dir().then_some((index, path))
+            })
+            .collect();
+        numbered.sort();
+
+        let snapshots = if numbered.is_empty() {
+            vec![dir.to_path_buf()]
+        } else {
+            numbered.into_iter().map(|(_, path)| path).collect()
+        };
+
+        Ok(Self {
+            snapshots,
+            current: AtomicUsize::new(0),
+            start: Instant::now(),
+            step: DEFAULT_FIXTURE_STEP,
+        })
+    }
+
+    /// Sets how far `now` moves per snapshot
+    pub fn with_step(mut self, step: Duration) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// Moves to the next snapshot
+    ///
+    /// # Returns
+    /// False if the current snapshot is the last one
+    pub fn advance(&self) -> bool {
+        self.current
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |i| {
+                (i + 1 < self.snapshots.len()).then_some(i + 1)
+            })
+            .is_ok()
+    }
+
+    /// Index of the snapshot being replayed
+    pub fn position(&self) -> usize {
+        self.current.load(Ordering::SeqCst)
+    }
+
+    /// Maps an absolute path of the replayed system into the snapshot
+    fn resolve(&self, path: &Path) -> PathBuf {
+        let relative = path.strip_prefix("/").unwrap_or(path);
+        self.snapshots[self.position()].join(relative)
+    }
+}
+
+impl SystemSource for FixtureSource {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        fs::read_to_string(self.resolve(path))
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        // Entries are reported under the replayed path, not the fixture path
+        let mut entries = fs::read_dir(self.resolve(path))?
+            .map(|entry| entry.map(|e| path.join(e.file_name())))
+            .collect::<io::Result<Vec<_>>>()?;
+        entries.sort();
+        Ok(entries)
+    }
+
+    fn command_output(&self, program: &str, _args: &[&str]) -> io::Result<String> {
+        fs::read_to_string(self.resolve(&Path::new("/commands").join(program)))
+    }
+
+    fn now(&self) -> Instant {
+        self.start + self.step * self.position() as u32
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// Directory of the recorded host replayed by the collector tests
+    pub(crate) fn host_fixture() -> Arc<FixtureSource> {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/host");
+        Arc::new(FixtureSource::new(dir).unwrap())
+    }
+
+    #[test]
+    fn test_fixture_source_replays_snapshots() {
+        let fixture = host_fixture();
+        let start = fixture.now();
+
+        let loadavg = fixture.read_to_string(Path::new("/proc/loadavg")).unwrap();
+        assert!(loadavg.starts_with("0.52 "));
+        assert_eq!(
+            fixture.read_dir(Path::new("/sys/class/hwmon")).unwrap(),
+            vec![
+                PathBuf::from("/sys/class/hwmon/hwmon0"),
+                PathBuf::from("/sys/class/hwmon/hwmon1"),
+            ]
+        );
+        assert_eq!(fixture.command_output("dmesg", &["-T"]).unwrap(), "");
+        let missing = fixture.read_to_string(Path::new("/sys/class/net/wlan0/operstate"));
+        assert_eq!(missing.unwrap_err().kind(), io::ErrorKind::NotFound);
+
+        assert!(fixture.advance());
+        assert!(fixture.advance());
+        assert!(!fixture.advance());
+        assert_eq!(fixture.position(), 2);
+        assert_eq!(fixture.now() - start, DEFAULT_FIXTURE_STEP * 2);
+        assert!(fixture
+            .command_output("dmesg", &[])
+            .unwrap()
+            .contains("usb 1-2"));
+    }
+}
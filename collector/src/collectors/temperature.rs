@@ -1,23 +1,33 @@
 use shared::types::Temperature;
-use std::fs;
+use std::io;
 use std::path::Path;
+use std::sync::Arc;
+
+use super::source::{HostSource, SystemSource};
+use super::{Collector, Update};
 
 /// Collects temperature readings from various hardware sensors
-pub struct TemperatureCollector;
+pub struct TemperatureCollector {
+    source: Arc<dyn SystemSource>,
+}
 
 impl TemperatureCollector {
     /// Creates a new TemperatureCollector
     pub fn new() -> Self {
-        Self
+        Self::with_source(Arc::new(HostSource))
+    }
+
+    /// Creates a TemperatureCollector reading hwmon sensors from `source`
+    pub fn with_source(source: Arc<dyn SystemSource>) -> Self {
+        Self { source }
     }
 
     /// Collects all temperature sensor readings
     pub fn collect(&self) -> Vec<Temperature> {
         let mut temperatures = Vec::new();
 
-        if let Ok(entries) = fs::read_dir("/sys/class/hwmon") {
-            for entry in entries.flatten() {
-                let hwmon_dir = entry.path();
+        if let Ok(entries) = self.source.read_dir(Path::new("/sys/class/hwmon")) {
+            for hwmon_dir in entries {
                 self.collect_from_hwmon(&hwmon_dir, &mut temperatures);
             }
         }
@@ -28,7 +38,9 @@ impl TemperatureCollector {
     /// Collects temperature readings from a specific hwmon directory
     fn collect_from_hwmon(&self, hwmon_dir: &Path, temperatures: &mut Vec<Temperature>) {
         // Read the device/sensor name
-        let sensor_name = fs::read_to_string(hwmon_dir.join("name"))
+        let sensor_name = self
+            .source
+            .read_to_string(&hwmon_dir.join("name"))
             .unwrap_or_else(|_| "unknown".to_string())
             .trim()
             .to_string();
@@ -36,13 +48,15 @@ impl TemperatureCollector {
         // Try to read up to 10 temperature inputs (temp1_input through temp10_input)
         for i in 1..=10 {
             let temp_file = hwmon_dir.join(format!("temp{}_input", i));
-            if let Ok(temp_str) = fs::read_to_string(&temp_file) {
+            if let Ok(temp_str) = self.source.read_to_string(&temp_file) {
                 if let Ok(temp_raw) = temp_str.trim().parse::<i32>() {
                     // Temperature is in millidegrees Celsius
                     let temp_value = temp_raw as f32 / 1000.0;
 
                     // Try to read the label for this sensor
-                    let label = fs::read_to_string(hwmon_dir.join(format!("temp{}_label", i)))
+                    let label = self
+                        .source
+                        .read_to_string(&hwmon_dir.join(format!("temp{}_label", i)))
                         .unwrap_or_else(|_| format!("Sensor {}", i))
                         .trim()
                         .to_string();
@@ -94,6 +108,17 @@ impl TemperatureCollector {
     }
 }
 
+impl Collector for TemperatureCollector {
+    fn name(&self) -> &'static str {
+        "temperature"
+    }
+
+    fn collect(&mut self) -> io::Result<Update> {
+        let temperatures = TemperatureCollector::collect(self);
+        Ok(Box::new(move |metrics| metrics.temperatures = temperatures))
+    }
+}
+
 impl Default for TemperatureCollector {
     fn default() -> Self {
         Self::new()
@@ -102,6 +127,7 @@ impl Default for TemperatureCollector {
 
 #[cfg(test)]
 mod tests {
+    use super::super::source::tests::host_fixture;
     use super::*;
 
     #[test]
@@ -121,6 +147,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_fixture_temperatures() {
+        let fixture = host_fixture();
+        let collector = TemperatureCollector::with_source(fixture.clone());
+
+        let readings: Vec<(String, f32, String)> = collector
+            .collect()
+            .into_iter()
+            .map(|t| (t.sensor, t.value, t.label))
+            .collect();
+        assert_eq!(
+            readings,
+            [
+                ("coretemp".to_string(), 45.0, "Package id 0".to_string()),
+                ("coretemp".to_string(), 43.5, "Sensor 2".to_string()),
+                ("nvme".to_string(), 38.85, "Composite".to_string()),
+            ]
+        );
+
+        fixture.advance();
+        assert_eq!(collector.get_max_temp(), 72.0);
+        assert_eq!(collector.get_cpu_temps().len(), 2);
+        assert_eq!(collector.get_nvme_temps()[0].value, 39.0);
+    }
+
     #[test]
     fn test_max_temp() {
         let collector = TemperatureCollector::new();
@@ -0,0 +1,231 @@
+use shared::types::{ZombieGroup, ZombieMetrics};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::time::Instant;
+
+use super::{Collector, Update};
+
+/// Detects zombie (defunct) processes by scanning /proc/*/stat
+///
+/// Zombies are grouped by parent PID, since only the parent can reap them.
+/// Each zombie's first sighting is remembered so lingering zombies can be
+/// told apart from short-lived ones that are about to be reaped.
+pub struct ZombieCollector {
+    first_seen: HashMap<u32, Instant>,
+}
+
+/// Fields of interest from /proc/[pid]/stat
+#[derive(Debug, Clone, PartialEq)]
+struct ProcStat {
+    pid: u32,
+    comm: String,
+    state: char,
+    ppid: u32,
+}
+
+impl ZombieCollector {
+    /// Creates a new ZombieCollector
+    pub fn new() -> Self {
+        Self {
+            first_seen: HashMap::new(),
+        }
+    }
+
+    /// Scans the process table and collects zombie metrics
+    pub fn collect(&mut self) -> ZombieMetrics {
+        let stats = Self::read_proc_stats();
+        let now = Instant::now();
+
+        // Forget zombies that have been reaped
+        let zombie_pids: HashSet<u32> = stats
+            .iter()
+            .filter(|s| s.state == 'Z')
+            .map(|s| s.pid)
+            .collect();
+        self.first_seen.retain(|pid, _| zombie_pids.contains(pid));
+        for pid in &zombie_pids {
+            self.first_seen.entry(*pid).or_insert(now);
+        }
+
+        let ages: HashMap<u32, u64> = self
+            .first_seen
+            .iter()
+            .map(|(pid, seen)| (*pid, now.duration_since(*seen).as_secs()))
+            .collect();
+
+        group_zombies(&stats, &ages, Self::read_cmdline)
+    }
+
+    /// Reads and parses /proc/[pid]/stat for every process
+    fn read_proc_stats() -> Vec<ProcStat> {
+        let mut stats = Vec::new();
+
+        if let Ok(entries) = fs::read_dir("/proc") {
+            for entry in entries.flatten() {
+                let name = entry.file_name();
+                if !name.to_string_lossy().chars().all(|c| c.is_ascii_digit()) {
+                    continue;
+                }
+                // The process may exit between listing and reading
+                if let Ok(content) = fs::read_to_string(entry.path().join("stat")) {
+                    if let Some(stat) = parse_stat(&content) {
+                        stats.push(stat);
+                    }
+                }
+            }
+        }
+
+        stats
+    }
+
+    /// Reads the full command line of a process, if still available
+    fn read_cmdline(pid: u32) -> Option<String> {
+        let raw = fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+        let cmdline = raw
+            .split(|b| *b == 0)
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| String::from_utf8_lossy(arg).to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        (!cmdline.is_empty()).then_some(cmdline)
+    }
+}
+
+impl Collector for ZombieCollector {
+    fn name(&self) -> &'static str {
+        "zombie"
+    }
+
+    fn collect(&mut self) -> io::Result<Update> {
+        let zombies = ZombieCollector::collect(self);
+        Ok(Box::new(move |metrics| metrics.zombies = zombies))
+    }
+}
+
+impl Default for ZombieCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parses a /proc/[pid]/stat line
+///
+/// The command name is wrapped in parentheses and may itself contain spaces
+/// or parentheses, so the state and parent PID are read after the last ')'.
+fn parse_stat(content: &str) -> Option<ProcStat> {
+    let open = content.find('(')?;
+    let close = content.rfind(')')?;
+    let pid = content[..open].trim().parse().ok()?;
+    let comm = content[open + 1..close].to_string();
+
+    let mut rest = content[close + 1..].split_whitespace();
+    let state = rest.next()?.chars().next()?;
+    let ppid = rest.next()?.parse().ok()?;
+
+    Some(ProcStat {
+        pid,
+        comm,
+        state,
+        ppid,
+    })
+}
+
+/// Groups zombies by parent, largest groups first
+fn group_zombies(
+    stats: &[ProcStat],
+    ages: &HashMap<u32, u64>,
+    read_cmdline: impl Fn(u32) -> Option<String>,
+) -> ZombieMetrics {
+    let comms: HashMap<u32, &str> = stats.iter().map(|s| (s.pid, s.comm.as_str())).collect();
+    let mut by_parent: BTreeMap<u32, ZombieGroup> = BTreeMap::new();
+
+    for zombie in stats.iter().filter(|s| s.state == 'Z') {
+        let group = by_parent.entry(zombie.ppid).or_insert_with(|| ZombieGroup {
+            parent_pid: zombie.ppid,
+            parent_command: read_cmdline(zombie.ppid)
+                .or_else(|| comms.get(&zombie.ppid).map(|c| c.to_string()))
+                .unwrap_or_else(|| "unknown".to_string()),
+            count: 0,
+            commands: Vec::new(),
+            pids: Vec::new(),
+            oldest_age_secs: 0,
+        });
+
+        group.count += 1;
+        group.pids.push(zombie.pid);
+        if !group.commands.contains(&zombie.comm) {
+            group.commands.push(zombie.comm.clone());
+        }
+        let age = ages.get(&zombie.pid).copied().unwrap_or(0);
+        group.oldest_age_secs = group.oldest_age_secs.max(age);
+    }
+
+    let mut groups: Vec<ZombieGroup> = by_parent.into_values().collect();
+    groups.sort_by_key(|g| std::cmp::Reverse(g.count));
+
+    ZombieMetrics {
+        total: groups.iter().map(|g| g.count).sum(),
+        groups,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stat() {
+        let stat = parse_stat("1234 (my (weird) proc) Z 42 1234 1234 0 -1 4194372 0 0").unwrap();
+        assert_eq!(stat.pid, 1234);
+        assert_eq!(stat.comm, "my (weird) proc");
+        assert_eq!(stat.state, 'Z');
+        assert_eq!(stat.ppid, 42);
+
+        assert!(parse_stat("garbage").is_none());
+    }
+
+    #[test]
+    fn test_group_zombies() {
+        let stat = |pid, comm: &str, state, ppid| ProcStat {
+            pid,
+            comm: comm.to_string(),
+            state,
+            ppid,
+        };
+        let stats = vec![
+            stat(42, "supervisor", 'S', 1),
+            stat(100, "worker", 'Z', 42),
+            stat(101, "worker", 'Z', 42),
+            stat(102, "helper", 'Z', 42),
+            stat(200, "sh", 'Z', 7),
+            stat(300, "bash", 'R', 1),
+        ];
+        let ages = HashMap::from([(100, 10), (101, 600), (200, 5)]);
+
+        let metrics = group_zombies(&stats, &ages, |_| None);
+
+        assert_eq!(metrics.total, 4);
+        assert_eq!(metrics.groups.len(), 2);
+
+        let top = &metrics.groups[0];
+        assert_eq!(top.parent_pid, 42);
+        assert_eq!(top.parent_command, "supervisor");
+        assert_eq!(top.count, 3);
+        assert_eq!(top.commands, vec!["worker", "helper"]);
+        assert_eq!(top.oldest_age_secs, 600);
+
+        assert_eq!(metrics.groups[1].parent_command, "unknown");
+    }
+
+    #[test]
+    fn test_zombie_collector() {
+        let mut collector = ZombieCollector::new();
+        let metrics = collector.collect();
+
+        assert_eq!(
+            metrics.total,
+            metrics.groups.iter().map(|g| g.count).sum::<usize>()
+        );
+    }
+}
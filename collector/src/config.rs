@@ -0,0 +1,672 @@
+/// Collector configuration
+///
+/// Settings are layered: built-in defaults, then the TOML config file, then
+/// the environment variables documented in the README, then command-line
+/// flags. A value that does not parse or is out of range is an error naming
+/// the key it came from, rather than a silent fallback to the default.
+use anyhow::{anyhow, bail, Context};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::api::ServerConfig;
+use crate::collectors::{CollectorConfig, CollectorSettings, COLLECTOR_NAMES};
+use crate::detector::RuleSet;
+use crate::notify::{NotificationConfig, SinkConfig};
+use crate::storage::{MaintenanceConfig, RollupRetention};
+
+/// Config file read when `--config` and `COLLECTOR_CONFIG` are not given,
+/// if it exists
+pub const DEFAULT_CONFIG_PATH: &str = "/etc/system-monitor/collector.toml";
+
+/// Longest retention accepted for any `retention.*_days` key (100 years)
+const MAX_RETENTION_DAYS: i64 = 36_500;
+
+/// Longest accepted `incident_cooldown_secs` (30 days)
+const MAX_INCIDENT_COOLDOWN_SECS: i64 = 30 * 24 * 3600;
+
+/// Longest accepted `retention.maintenance_interval_secs` (30 days)
+const MAX_MAINTENANCE_INTERVAL_SECS: u64 = 30 * 24 * 3600;
+
+/// Complete collector configuration, as written in the config file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AppConfig {
+    /// SQLite database, e.g. `sqlite:///var/lib/system-monitor/metrics.db`
+    pub database_url: String,
+    /// Seconds between samples
+    pub collection_interval_secs: u64,
+    /// How long a resolved incident is reopened instead of raising a new alert
+    pub incident_cooldown_secs: i64,
+    /// Detection rules file; the built-in rules when unset
+    pub rules_file: Option<PathBuf>,
+    pub server: ServerSection,
+    pub retention: RetentionSection,
+    /// Per-collector overrides, keyed by collector name
+    pub collectors: BTreeMap<String, CollectorSection>,
+    pub notifications: NotificationSection,
+}
+
+/// `[server]`: API bind address and allowed browser origins
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerSection {
+    pub host: String,
+    pub port: u16,
+    /// Origins allowed by CORS; empty disables CORS
+    pub cors_origins: Vec<String>,
+}
+
+/// `[retention]`: how long data is kept and how often maintenance runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetentionSection {
+    pub metrics_days: i64,
+    /// Also applies to resolved incidents, by their last firing; open and
+    /// acknowledged incidents are never deleted
+    pub anomalies_days: i64,
+    pub rollup_1m_days: i64,
+    pub rollup_15m_days: i64,
+    pub rollup_1h_days: i64,
+    /// Oldest raw samples are deleted past this size; no limit when unset
+    pub max_db_size_mb: Option<u64>,
+    pub maintenance_interval_secs: u64,
+}
+
+/// `[collectors.<name>]`: overrides for one collector
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CollectorSection {
+    pub enabled: Option<bool>,
+    pub interval_secs: Option<u64>,
+    pub timeout_secs: Option<f64>,
+}
+
+/// `[notifications]`: sinks inline, or a separate notifications file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotificationSection {
+    /// File with `[[sinks]]` tables, as in `notifications.example.toml`
+    pub file: Option<PathBuf>,
+    pub sinks: Vec<SinkConfig>,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            database_url: "sqlite://./data/system-monitor.db".to_string(),
+            collection_interval_secs: 2,
+            incident_cooldown_secs: 300,
+            rules_file: None,
+            server: ServerSection::default(),
+            retention: RetentionSection::default(),
+            collectors: BTreeMap::new(),
+            notifications: NotificationSection::default(),
+        }
+    }
+}
+
+impl Default for ServerSection {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".to_string(),
+            port: 5253,
+            cors_origins: vec!["http://localhost:5252".to_string()],
+        }
+    }
+}
+
+impl Default for RetentionSection {
+    fn default() -> Self {
+        let maintenance = MaintenanceConfig::default();
+        Self {
+            metrics_days: maintenance.metrics_retention_days,
+            anomalies_days: maintenance.anomalies_retention_days,
+            rollup_1m_days: maintenance.rollups.minute_days,
+            rollup_15m_days: maintenance.rollups.fifteen_minute_days,
+            rollup_1h_days: maintenance.rollups.hour_days,
+            max_db_size_mb: maintenance.max_db_size_mb,
+            maintenance_interval_secs: 3600,
+        }
+    }
+}
+
+impl AppConfig {
+    /// Parses a config file's TOML text; missing keys keep their defaults
+    pub fn from_toml_str(content: &str) -> anyhow::Result<Self> {
+        toml::from_str(content).context("Failed to parse config")
+    }
+
+    /// Reads a config file
+    pub fn from_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        Self::from_toml_str(&content)
+            .with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    /// Loads defaults, the config file and the process environment
+    ///
+    /// # Arguments
+    /// * `path` - Config file; when None, `COLLECTOR_CONFIG` or
+    ///   `DEFAULT_CONFIG_PATH` if it exists
+    ///
+    /// # Returns
+    /// The configuration and the file it was read from, if any. It is not
+    /// validated yet, so command-line flags can still be applied.
+    pub fn load(path: Option<&Path>) -> anyhow::Result<(Self, Option<PathBuf>)> {
+        let path = path
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::var_os("COLLECTOR_CONFIG").map(PathBuf::from))
+            .or_else(|| {
+                let default = PathBuf::from(DEFAULT_CONFIG_PATH);
+                default.exists().then_some(default)
+            });
+
+        let mut config = match path {
+            Some(ref path) => Self::from_file(path)?,
+            None => Self::default(),
+        };
+        config.apply_env(|name| std::env::var(name).ok())?;
+        Ok((config, path))
+    }
+
+    /// Overrides settings from environment variables
+    ///
+    /// # Arguments
+    /// * `var` - Looks up a variable, e.g. `std::env::var(name).ok()`
+    pub fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> anyhow::Result<()> {
+        let env = Env(&var);
+
+        if let Some(url) = var("DATABASE_URL") {
+            self.database_url = url;
+        }
+        env.set(
+            "COLLECTION_INTERVAL_SECS",
+            "a number of seconds",
+            &mut self.collection_interval_secs,
+        )?;
+        env.set(
+            "INCIDENT_COOLDOWN_SECS",
+            "a number of seconds",
+            &mut self.incident_cooldown_secs,
+        )?;
+        if let Some(path) = var("RULES_FILE") {
+            self.rules_file = Some(PathBuf::from(path));
+        }
+        if let Some(path) = var("NOTIFICATIONS_FILE") {
+            // Replaces sinks declared inline in the config file
+            self.notifications.file = Some(PathBuf::from(path));
+            self.notifications.sinks.clear();
+        }
+
+        if let Some(host) = var("HOST") {
+            self.server.host = host;
+        }
+        env.set("PORT", "a port number", &mut self.server.port)?;
+        if let Some(origins) = var("CORS_ORIGINS") {
+            self.server.cors_origins = origins
+                .split(',')
+                .map(str::trim)
+                .filter(|o| !o.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+
+        let retention = &mut self.retention;
+        env.set(
+            "METRICS_RETENTION_DAYS",
+            "a number of days",
+            &mut retention.metrics_days,
+        )?;
+        env.set(
+            "ANOMALY_RETENTION_DAYS",
+            "a number of days",
+            &mut retention.anomalies_days,
+        )?;
+        env.set(
+            "ROLLUP_1M_RETENTION_DAYS",
+            "a number of days",
+            &mut retention.rollup_1m_days,
+        )?;
+        env.set(
+            "ROLLUP_15M_RETENTION_DAYS",
+            "a number of days",
+            &mut retention.rollup_15m_days,
+        )?;
+        env.set(
+            "ROLLUP_1H_RETENTION_DAYS",
+            "a number of days",
+            &mut retention.rollup_1h_days,
+        )?;
+        env.set(
+            "MAINTENANCE_INTERVAL_SECS",
+            "a number of seconds",
+            &mut retention.maintenance_interval_secs,
+        )?;
+        // 0 means no limit, as it always has for this variable
+        if let Some(mb) = env.parse::<u64>("MAX_DB_SIZE_MB", "a size in MiB")? {
+            retention.max_db_size_mb = (mb > 0).then_some(mb);
+        }
+
+        for name in COLLECTOR_NAMES {
+            let prefix = format!("{}_COLLECTOR_", name.to_uppercase());
+            let enabled = match var(&format!("{}ENABLED", prefix)) {
+                Some(value) => Some(parse_bool(&value).ok_or_else(|| {
+                    anyhow!(
+                        "Invalid {}ENABLED '{}': expected true or false",
+                        prefix,
+                        value
+                    )
+                })?),
+                None => None,
+            };
+            let interval = env.parse(&format!("{}INTERVAL_SECS", prefix), "a number of seconds")?;
+            let timeout = env.parse(&format!("{}TIMEOUT_SECS", prefix), "a number of seconds")?;
+
+            if enabled.is_some() || interval.is_some() || timeout.is_some() {
+                let section = self.collectors.entry(name.to_string()).or_default();
+                section.enabled = enabled.or(section.enabled);
+                section.interval_secs = interval.or(section.interval_secs);
+                section.timeout_secs = timeout.or(section.timeout_secs);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks every value is in range
+    ///
+    /// Rules and notification files are checked when they are loaded.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.database_url.trim().is_empty() {
+            bail!("database_url must not be empty");
+        }
+        if self.collection_interval_secs == 0 {
+            bail!("collection_interval_secs must be at least 1");
+        }
+        if !(0..=MAX_INCIDENT_COOLDOWN_SECS).contains(&self.incident_cooldown_secs) {
+            bail!(
+                "incident_cooldown_secs must be between 0 and {}",
+                MAX_INCIDENT_COOLDOWN_SECS
+            );
+        }
+
+        let server = &self.server;
+        if server.port == 0 {
+            bail!("server.port must be between 1 and 65535");
+        }
+        format!("{}:{}", server.host, server.port)
+            .parse::<SocketAddr>()
+            .with_context(|| format!("server.host '{}' is not an IP address", server.host))?;
+        for origin in &server.cors_origins {
+            let url = reqwest::Url::parse(origin)
+                .with_context(|| format!("server.cors_origins: invalid origin '{}'", origin))?;
+            if !matches!(url.scheme(), "http" | "https")
+                || url.path() != "/"
+                || origin.ends_with('/')
+            {
+                bail!(
+                    "server.cors_origins: invalid origin '{}': expected scheme://host[:port], e.g. http://localhost:5252",
+                    origin
+                );
+            }
+        }
+
+        let retention = &self.retention;
+        for (key, days) in [
+            ("metrics_days", retention.metrics_days),
+            ("anomalies_days", retention.anomalies_days),
+            ("rollup_1m_days", retention.rollup_1m_days),
+            ("rollup_15m_days", retention.rollup_15m_days),
+            ("rollup_1h_days", retention.rollup_1h_days),
+        ] {
+            if !(1..=MAX_RETENTION_DAYS).contains(&days) {
+                bail!(
+                    "retention.{} must be between 1 and {}",
+                    key,
+                    MAX_RETENTION_DAYS
+                );
+            }
+        }
+        if retention.max_db_size_mb == Some(0) {
+            bail!("retention.max_db_size_mb must be at least 1; leave it out for no limit");
+        }
+        if !(1..=MAX_MAINTENANCE_INTERVAL_SECS).contains(&retention.maintenance_interval_secs) {
+            bail!(
+                "retention.maintenance_interval_secs must be between 1 and {}",
+                MAX_MAINTENANCE_INTERVAL_SECS
+            );
+        }
+
+        for (name, section) in &self.collectors {
+            if !COLLECTOR_NAMES.contains(&name.as_str()) {
+                bail!(
+                    "collectors.{}: unknown collector, expected one of {}",
+                    name,
+                    COLLECTOR_NAMES.join(", ")
+                );
+            }
+            if let Some(timeout) = section.timeout_secs {
+                if !(timeout.is_finite() && timeout > 0.0) {
+                    bail!("collectors.{}.timeout_secs must be greater than 0", name);
+                }
+            }
+        }
+
+        if self.notifications.file.is_some() && !self.notifications.sinks.is_empty() {
+            bail!("notifications: set either file or sinks, not both");
+        }
+        NotificationConfig {
+            sinks: self.notifications.sinks.clone(),
+        }
+        .validate()
+        .context("notifications.sinks")?;
+
+        Ok(())
+    }
+
+    /// Loads the detection rules, or the built-in ones if no file is set
+    pub fn rules(&self) -> anyhow::Result<RuleSet> {
+        match self.rules_file {
+            Some(ref path) => RuleSet::from_file(path),
+            None => Ok(RuleSet::default()),
+        }
+    }
+
+    /// Loads the notification sinks from the file or the inline tables
+    pub fn notification_config(&self) -> anyhow::Result<NotificationConfig> {
+        match self.notifications.file {
+            Some(ref path) => NotificationConfig::from_file(path),
+            None => Ok(NotificationConfig {
+                sinks: self.notifications.sinks.clone(),
+            }),
+        }
+    }
+
+    /// API server settings; CORS is off when no origins are allowed
+    pub fn server_config(&self) -> ServerConfig {
+        ServerConfig {
+            host: self.server.host.clone(),
+            port: self.server.port,
+            enable_cors: !self.server.cors_origins.is_empty(),
+            cors_origins: self.server.cors_origins.clone(),
+        }
+    }
+
+    /// Retention and size limit applied by database maintenance
+    pub fn maintenance_config(&self) -> MaintenanceConfig {
+        let retention = &self.retention;
+        MaintenanceConfig {
+            metrics_retention_days: retention.metrics_days,
+            anomalies_retention_days: retention.anomalies_days,
+            rollups: RollupRetention {
+                minute_days: retention.rollup_1m_days,
+                fifteen_minute_days: retention.rollup_15m_days,
+                hour_days: retention.rollup_1h_days,
+            },
+            max_db_size_mb: retention.max_db_size_mb,
+        }
+    }
+
+    /// Per-collector switches, intervals and timeouts
+    pub fn collector_config(&self) -> CollectorConfig {
+        let collectors = self
+            .collectors
+            .iter()
+            .map(|(name, section)| {
+                let settings = CollectorSettings {
+                    enabled: section.enabled.unwrap_or(true),
+                    interval: section.interval_secs.map(Duration::from_secs),
+                    timeout: section.timeout_secs.map(Duration::from_secs_f64),
+                };
+                (name.clone(), settings)
+            })
+            .collect();
+        CollectorConfig { collectors }
+    }
+}
+
+/// Parses typed environment variables, failing on values that do not parse
+struct Env<'a, F: Fn(&str) -> Option<String>>(&'a F);
+
+impl<F: Fn(&str) -> Option<String>> Env<'_, F> {
+    fn parse<T: FromStr>(&self, name: &str, expected: &str) -> anyhow::Result<Option<T>> {
+        match (self.0)(name) {
+            Some(value) => value
+                .trim()
+                .parse()
+                .map(Some)
+                .map_err(|_| anyhow!("Invalid {} '{}': expected {}", name, value, expected)),
+            None => Ok(None),
+        }
+    }
+
+    fn set<T: FromStr>(&self, name: &str, expected: &str, target: &mut T) -> anyhow::Result<()> {
+        if let Some(value) = self.parse(name, expected)? {
+            *target = value;
+        }
+        Ok(())
+    }
+}
+
+/// Parses the spellings of a boolean accepted in environment variables
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" | "on" => Some(true),
+        "false" | "0" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const CONFIG: &str = r#"
+        database_url = "sqlite:///var/lib/system-monitor/metrics.db"
+        collection_interval_secs = 5
+        rules_file = "/etc/system-monitor/rules.toml"
+
+        [server]
+        host = "0.0.0.0"
+        cors_origins = ["http://localhost:5252", "https://monitor.example.com"]
+
+        [retention]
+        metrics_days = 14
+        max_db_size_mb = 2048
+
+        [collectors.gpu]
+        interval_secs = 30
+
+        [collectors.usb]
+        enabled = false
+
+        [[notifications.sinks]]
+        name = "ops"
+        type = "webhook"
+        url = "https://hooks.example.com/monitor"
+    "#;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn test_file_overrides_defaults() {
+        let config = AppConfig::from_toml_str(CONFIG).unwrap();
+        config.validate().unwrap();
+
+        assert_eq!(config.collection_interval_secs, 5);
+        assert_eq!(config.incident_cooldown_secs, 300);
+        assert_eq!(config.server.port, 5253);
+        assert_eq!(config.server.cors_origins.len(), 2);
+        assert_eq!(config.retention.metrics_days, 14);
+        assert_eq!(config.retention.anomalies_days, 90);
+
+        let maintenance = config.maintenance_config();
+        assert_eq!(maintenance.max_db_size_mb, Some(2048));
+        assert_eq!(maintenance.rollups, RollupRetention::default());
+
+        let collectors = config.collector_config();
+        assert_eq!(
+            collectors.settings("gpu").interval,
+            Some(Duration::from_secs(30))
+        );
+        assert!(!collectors.settings("usb").enabled);
+        assert!(collectors.settings("cpu").enabled);
+
+        assert_eq!(config.notification_config().unwrap().sinks.len(), 1);
+        assert!(config.server_config().enable_cors);
+    }
+
+    #[test]
+    fn test_env_overrides_file() {
+        let mut config = AppConfig::from_toml_str(CONFIG).unwrap();
+        config
+            .apply_env(env(&[
+                ("PORT", "8080"),
+                ("CORS_ORIGINS", "http://a.example.com, http://b.example.com"),
+                ("MAX_DB_SIZE_MB", "0"),
+                ("GPU_COLLECTOR_TIMEOUT_SECS", "2.5"),
+                ("USB_COLLECTOR_ENABLED", "yes"),
+            ]))
+            .unwrap();
+        config.validate().unwrap();
+
+        assert_eq!(config.server.port, 8080);
+        assert_eq!(
+            config.server.cors_origins,
+            ["http://a.example.com", "http://b.example.com"]
+        );
+        assert_eq!(config.retention.max_db_size_mb, None);
+        // Environment overrides single fields, the file's interval stays
+        assert_eq!(config.collectors["gpu"].interval_secs, Some(30));
+        assert_eq!(config.collectors["gpu"].timeout_secs, Some(2.5));
+        assert_eq!(config.collectors["usb"].enabled, Some(true));
+    }
+
+    #[test]
+    fn test_invalid_env_values_are_errors() {
+        let cases = [
+            (
+                "PORT",
+                "http",
+                "Invalid PORT 'http': expected a port number",
+            ),
+            (
+                "COLLECTION_INTERVAL_SECS",
+                "2s",
+                "Invalid COLLECTION_INTERVAL_SECS '2s': expected a number of seconds",
+            ),
+            (
+                "GPU_COLLECTOR_ENABLED",
+                "maybe",
+                "Invalid GPU_COLLECTOR_ENABLED 'maybe': expected true or false",
+            ),
+        ];
+
+        for (name, value, message) in cases {
+            let mut config = AppConfig::default();
+            let error = config.apply_env(env(&[(name, value)])).unwrap_err();
+            assert_eq!(error.to_string(), message);
+        }
+    }
+
+    #[test]
+    fn test_out_of_range_env_values_fail_validation() {
+        let cases = [
+            (
+                "METRICS_RETENTION_DAYS",
+                "999999999999",
+                "retention.metrics_days",
+            ),
+            (
+                "INCIDENT_COOLDOWN_SECS",
+                "9223372036854775807",
+                "incident_cooldown_secs",
+            ),
+        ];
+
+        for (name, value, key) in cases {
+            let mut config = AppConfig::default();
+            config.apply_env(env(&[(name, value)])).unwrap();
+            let error = config.validate().unwrap_err().to_string();
+            assert!(error.starts_with(key), "{}: {}", name, error);
+        }
+    }
+
+    #[test]
+    fn test_invalid_values_are_rejected() {
+        let cases = [
+            ("collection_interval_secs = 0", "collection_interval_secs"),
+            ("[server]\nhost = \"localhost:80\"", "server.host"),
+            ("[server]\ncors_origins = [\"localhost\"]", "server.cors_origins"),
+            (
+                "[server]\ncors_origins = [\"http://localhost:5252/\"]",
+                "server.cors_origins",
+            ),
+            ("[retention]\nmetrics_days = 0", "retention.metrics_days"),
+            (
+                "[retention]\nmetrics_days = 999999999999",
+                "retention.metrics_days",
+            ),
+            (
+                "[retention]\nrollup_1h_days = 36501",
+                "retention.rollup_1h_days",
+            ),
+            (
+                "[retention]\nmaintenance_interval_secs = 2592001",
+                "retention.maintenance_interval_secs",
+            ),
+            ("incident_cooldown_secs = -1", "incident_cooldown_secs"),
+            (
+                "incident_cooldown_secs = 9223372036854775807",
+                "incident_cooldown_secs",
+            ),
+            ("[retention]\nmax_db_size_mb = 0", "retention.max_db_size_mb"),
+            ("[collectors.gpus]\nenabled = false", "collectors.gpus"),
+            ("[collectors.gpu]\ntimeout_secs = 0.0", "collectors.gpu.timeout_secs"),
+            (
+                "[notifications]\nfile = \"n.toml\"\n[[notifications.sinks]]\nname = \"x\"\ntype = \"command\"\ncommand = \"true\"",
+                "notifications",
+            ),
+        ];
+
+        for (toml, key) in cases {
+            let config = AppConfig::from_toml_str(toml).unwrap();
+            let error = format!("{:#}", config.validate().unwrap_err());
+            assert!(error.starts_with(key), "{}: {}", toml, error);
+        }
+    }
+
+    #[test]
+    fn test_unknown_keys_are_rejected() {
+        let error = AppConfig::from_toml_str("[server]\nprot = 8080").unwrap_err();
+        assert!(format!("{:#}", error).contains("unknown field `prot`"));
+    }
+
+    #[test]
+    fn test_empty_cors_origins_disable_cors() {
+        let mut config = AppConfig::default();
+        config.apply_env(env(&[("CORS_ORIGINS", "")])).unwrap();
+        assert!(!config.server_config().enable_cors);
+    }
+
+    #[test]
+    fn test_example_file_is_valid() {
+        let config = AppConfig::from_toml_str(include_str!("../collector.example.toml")).unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.notification_config().unwrap().sinks.len(), 1);
+        assert!(!config.collector_config().settings("usb").enabled);
+    }
+}
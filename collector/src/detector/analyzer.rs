@@ -93,38 +93,6 @@ pub fn classify_severity(
     }
 }
 
-/// Get the maximum temperature from a metrics snapshot
-pub fn get_max_temperature(metrics: &SystemMetrics) -> f32 {
-    metrics
-        .temperatures
-        .iter()
-        .map(|t| t.value)
-        .fold(0.0f32, f32::max)
-}
-
-/// Get the maximum disk usage percentage from a metrics snapshot
-pub fn get_max_disk_usage(metrics: &SystemMetrics) -> f32 {
-    metrics
-        .disks
-        .iter()
-        .map(|d| d.usage_percent)
-        .fold(0.0f32, f32::max)
-}
-
-/// Get the maximum disk I/O (read + write) from a metrics snapshot
-pub fn get_max_disk_io(metrics: &SystemMetrics) -> f64 {
-    metrics
-        .disks
-        .iter()
-        .map(|d| d.read_mb + d.write_mb)
-        .fold(0.0f64, f64::max)
-}
-
-/// Check if any USB device has a timeout
-pub fn has_usb_timeout(metrics: &SystemMetrics) -> bool {
-    metrics.usb_devices.iter().any(|usb| usb.has_timeout)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -182,8 +150,11 @@ mod tests {
                 tx_bytes: 0,
                 rx_packets: 0,
                 tx_packets: 0,
+                interfaces: vec![],
             },
             gpu: None,
+            processes: vec![],
+            zombies: Default::default(),
         };
 
         let previous = SystemMetrics {
@@ -215,8 +186,11 @@ mod tests {
                 tx_bytes: 0,
                 rx_packets: 0,
                 tx_packets: 0,
+                interfaces: vec![],
             },
             gpu: None,
+            processes: vec![],
+            zombies: Default::default(),
         };
 
         let delta = calculate_delta(&current, &previous);
@@ -0,0 +1,179 @@
+/// Learned per-metric baselines for statistical rules
+///
+/// Rules in `baseline` mode keep an exponentially weighted moving average of
+/// the mean and variance of their metric, per subject and optionally per hour
+/// of day, and compare each new value against it as a z-score. A build server
+/// that always runs hot then stays quiet, while a usually idle laptop that
+/// suddenly jumps is flagged.
+use chrono::{DateTime, Timelike, Utc};
+use shared::types::MetricBaseline;
+
+use super::config::BaselineSettings;
+
+/// Identifies one learned baseline
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct BaselineKey {
+    pub rule: String,
+    /// Empty for rules without a subject
+    pub subject: String,
+    /// Hour of day (UTC) for seasonal baselines
+    pub hour: Option<u8>,
+}
+
+impl BaselineKey {
+    pub fn new(
+        rule: &str,
+        subject: &str,
+        settings: &BaselineSettings,
+        timestamp: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            rule: rule.to_string(),
+            subject: subject.to_string(),
+            hour: settings.seasonal.then(|| timestamp.hour() as u8),
+        }
+    }
+}
+
+/// EWMA estimate of a metric's mean and variance
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Baseline {
+    pub mean: f64,
+    pub variance: f64,
+    pub samples: u64,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl Baseline {
+    /// Returns how many standard deviations `value` is from the mean
+    ///
+    /// Returns None while the baseline is still warming up, or when it has no
+    /// spread at all (a constant metric with `min_stddev` of zero).
+    pub fn zscore(&self, value: f64, settings: &BaselineSettings) -> Option<f64> {
+        if self.samples < u64::from(settings.warmup_samples) {
+            return None;
+        }
+        let stddev = self.stddev().max(settings.min_stddev);
+        if stddev <= f64::EPSILON {
+            return None;
+        }
+        Some((value - self.mean) / stddev)
+    }
+
+    pub fn stddev(&self) -> f64 {
+        self.variance.max(0.0).sqrt()
+    }
+
+    /// Folds `value` into the estimate
+    pub fn update(&mut self, value: f64, alpha: f64, timestamp: DateTime<Utc>) {
+        if self.samples == 0 {
+            self.mean = value;
+            self.variance = 0.0;
+        } else {
+            // Incremental EWMA of mean and variance (West, 1979)
+            let diff = value - self.mean;
+            let increment = alpha * diff;
+            self.mean += increment;
+            self.variance = (1.0 - alpha) * (self.variance + diff * increment);
+        }
+        self.samples = self.samples.saturating_add(1);
+        self.updated_at = Some(timestamp);
+    }
+
+    pub fn from_record(record: &MetricBaseline) -> (BaselineKey, Self) {
+        (
+            BaselineKey {
+                rule: record.rule.clone(),
+                subject: record.subject.clone().unwrap_or_default(),
+                hour: record.hour,
+            },
+            Self {
+                mean: record.mean,
+                variance: record.variance,
+                samples: record.samples,
+                updated_at: Some(record.updated_at),
+            },
+        )
+    }
+
+    pub fn to_record(&self, key: &BaselineKey) -> MetricBaseline {
+        MetricBaseline {
+            rule: key.rule.clone(),
+            subject: (!key.subject.is_empty()).then(|| key.subject.clone()),
+            hour: key.hour,
+            mean: self.mean,
+            variance: self.variance,
+            samples: self.samples,
+            updated_at: self.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> BaselineSettings {
+        BaselineSettings {
+            alpha: 0.1,
+            warmup_samples: 10,
+            min_stddev: 0.5,
+            seasonal: false,
+        }
+    }
+
+    #[test]
+    fn test_learns_mean_and_spread() {
+        let settings = settings();
+        let mut baseline = Baseline::default();
+        let now = Utc::now();
+
+        for i in 0..200 {
+            let value = if i % 2 == 0 { 84.0 } else { 86.0 };
+            assert!(i >= 10 || baseline.zscore(value, &settings).is_none());
+            baseline.update(value, settings.alpha, now);
+        }
+
+        assert!((baseline.mean - 85.0).abs() < 0.5);
+        assert!((baseline.stddev() - 1.0).abs() < 0.2);
+
+        // Normal for this machine
+        assert!(baseline.zscore(86.0, &settings).unwrap().abs() < 2.0);
+        // Far outside its usual range
+        assert!(baseline.zscore(99.0, &settings).unwrap() > 10.0);
+        assert!(baseline.zscore(60.0, &settings).unwrap() < -10.0);
+    }
+
+    #[test]
+    fn test_min_stddev_floor() {
+        let settings = settings();
+        let mut baseline = Baseline::default();
+        for _ in 0..50 {
+            baseline.update(2.0, settings.alpha, Utc::now());
+        }
+
+        assert_eq!(baseline.stddev(), 0.0);
+        assert_eq!(baseline.zscore(3.0, &settings), Some(2.0));
+
+        let no_floor = BaselineSettings {
+            min_stddev: 0.0,
+            ..settings
+        };
+        assert_eq!(baseline.zscore(3.0, &no_floor), None);
+    }
+
+    #[test]
+    fn test_record_round_trip() {
+        let mut baseline = Baseline::default();
+        baseline.update(10.0, 0.1, Utc::now());
+        let key = BaselineKey {
+            rule: "cpu_baseline".to_string(),
+            subject: String::new(),
+            hour: Some(13),
+        };
+
+        let record = baseline.to_record(&key);
+        assert_eq!(record.subject, None);
+        assert_eq!(Baseline::from_record(&record), (key, baseline));
+    }
+}
@@ -0,0 +1,418 @@
+/// Declarative anomaly rule definitions
+///
+/// Rules are loaded from a TOML file with one `[[rules]]` table per rule. The
+/// defaults shipped in `default_rules.toml` reproduce the detector's original
+/// hardcoded thresholds.
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+use shared::types::{AnomalyCategory, AnomalySeverity};
+use std::collections::HashSet;
+use std::path::Path;
+
+use super::path::MetricPath;
+
+/// Default rule set embedded at compile time
+const DEFAULT_RULES: &str = include_str!("default_rules.toml");
+
+/// What value a rule compares against its thresholds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleMode {
+    /// The current value of the metric
+    #[default]
+    Value,
+    /// The change since the previous sample
+    Delta,
+    /// How many standard deviations the value is from its learned baseline;
+    /// thresholds are z-scores
+    Baseline,
+}
+
+/// Comparison operator between the observed value and a threshold
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+    #[default]
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+impl Comparison {
+    /// Returns true if `value` breaches `threshold`
+    pub fn breaches(self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparison::Gt => value > threshold,
+            Comparison::Gte => value >= threshold,
+            Comparison::Lt => value < threshold,
+            Comparison::Lte => value <= threshold,
+        }
+    }
+}
+
+/// How a `baseline` rule learns what is normal
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BaselineSettings {
+    /// EWMA smoothing factor in (0, 1]; smaller values learn more slowly
+    pub alpha: f64,
+    /// Samples to learn from before the rule can fire
+    pub warmup_samples: u32,
+    /// Floor for the standard deviation, in the metric's unit, so a metric
+    /// that barely moves does not flag tiny changes
+    pub min_stddev: f64,
+    /// Learn a separate baseline for each hour of day (UTC)
+    pub seasonal: bool,
+}
+
+impl Default for BaselineSettings {
+    fn default() -> Self {
+        Self {
+            alpha: 0.05,
+            warmup_samples: 30,
+            min_stddev: 0.0,
+            seasonal: false,
+        }
+    }
+}
+
+/// A single detection rule as written in the rules file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleDefinition {
+    /// Unique rule name, e.g. "cpu_spike"
+    pub name: String,
+    pub category: AnomalyCategory,
+    /// Metric path, e.g. "cpu.global_usage" or "max(disks[*].usage_percent)"
+    pub metric: String,
+    #[serde(default)]
+    pub mode: RuleMode,
+    #[serde(default)]
+    pub comparison: Comparison,
+    /// Learning settings for `baseline` mode
+    #[serde(default)]
+    pub baseline: BaselineSettings,
+    pub warning: Option<f64>,
+    pub critical: Option<f64>,
+    /// Number of consecutive samples the condition must hold before firing
+    #[serde(default = "default_for_samples")]
+    pub for_samples: u32,
+    /// Fire only when the condition starts holding, not on every sample
+    #[serde(default)]
+    pub edge: bool,
+    /// Multiply thresholds by the number of CPUs
+    #[serde(default)]
+    pub per_cpu: bool,
+    /// Message template; supports {value}, {previous}, {delta}, {threshold},
+    /// {subject} and {item.FIELD} (a field of the selected array element), and
+    /// for baseline rules {mean}, {stddev} and {zscore}, with an optional
+    /// precision such as {value:.1}
+    pub message: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_for_samples() -> u32 {
+    1
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl RuleDefinition {
+    /// Returns the severity and threshold breached by `value`, if any
+    ///
+    /// Critical takes precedence over warning. Thresholds are multiplied by
+    /// `num_cpus` for rules with `per_cpu` set.
+    pub fn classify(&self, value: f64, num_cpus: usize) -> Option<(AnomalySeverity, f64)> {
+        let scale = if self.per_cpu { num_cpus as f64 } else { 1.0 };
+
+        if let Some(critical) = self.critical.map(|t| t * scale) {
+            if self.comparison.breaches(value, critical) {
+                return Some((AnomalySeverity::Critical, critical));
+            }
+        }
+        if let Some(warning) = self.warning.map(|t| t * scale) {
+            if self.comparison.breaches(value, warning) {
+                return Some((AnomalySeverity::Warning, warning));
+            }
+        }
+        None
+    }
+}
+
+/// A rule with its metric path parsed
+#[derive(Debug, Clone)]
+pub struct CompiledRule {
+    pub definition: RuleDefinition,
+    pub path: MetricPath,
+}
+
+#[derive(Debug, Deserialize)]
+struct RulesFile {
+    #[serde(default)]
+    rules: Vec<RuleDefinition>,
+}
+
+/// A validated set of detection rules
+#[derive(Debug, Clone)]
+pub struct RuleSet {
+    rules: Vec<CompiledRule>,
+}
+
+impl RuleSet {
+    /// Parses and validates rules from TOML text
+    pub fn from_toml_str(content: &str) -> anyhow::Result<Self> {
+        let file: RulesFile = toml::from_str(content).context("Failed to parse rules")?;
+        Self::from_definitions(file.rules)
+    }
+
+    /// Loads and validates rules from a TOML file
+    pub fn from_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read rules file {}", path.display()))?;
+        Self::from_toml_str(&content)
+            .with_context(|| format!("Invalid rules file {}", path.display()))
+    }
+
+    /// Validates rule definitions and compiles their metric paths
+    pub fn from_definitions(definitions: Vec<RuleDefinition>) -> anyhow::Result<Self> {
+        let mut names = HashSet::new();
+        let mut rules = Vec::with_capacity(definitions.len());
+
+        for definition in definitions {
+            if definition.name.trim().is_empty() {
+                bail!("Rule name must not be empty");
+            }
+            if !names.insert(definition.name.clone()) {
+                bail!("Duplicate rule name '{}'", definition.name);
+            }
+            if definition.warning.is_none() && definition.critical.is_none() {
+                bail!(
+                    "Rule '{}' must define a warning or critical level",
+                    definition.name
+                );
+            }
+            if definition.for_samples == 0 {
+                bail!("Rule '{}': for_samples must be at least 1", definition.name);
+            }
+            if definition.mode == RuleMode::Baseline {
+                let baseline = &definition.baseline;
+                if !(baseline.alpha > 0.0 && baseline.alpha <= 1.0) {
+                    bail!(
+                        "Rule '{}': baseline.alpha must be in (0, 1]",
+                        definition.name
+                    );
+                }
+                if baseline.warmup_samples < 2 {
+                    bail!(
+                        "Rule '{}': baseline.warmup_samples must be at least 2",
+                        definition.name
+                    );
+                }
+                if baseline.min_stddev.is_nan() || baseline.min_stddev < 0.0 {
+                    bail!(
+                        "Rule '{}': baseline.min_stddev must not be negative",
+                        definition.name
+                    );
+                }
+            }
+            let path = MetricPath::parse(&definition.metric)
+                .with_context(|| format!("Rule '{}'", definition.name))?;
+
+            rules.push(CompiledRule { definition, path });
+        }
+
+        Ok(Self { rules })
+    }
+
+    /// Returns the compiled rules in file order
+    pub fn rules(&self) -> &[CompiledRule] {
+        &self.rules
+    }
+
+    /// Returns the rule definitions in file order
+    pub fn definitions(&self) -> Vec<RuleDefinition> {
+        self.rules.iter().map(|r| r.definition.clone()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self::from_toml_str(DEFAULT_RULES).expect("Default rules must be valid")
+    }
+}
+
+/// Renders a rule message template
+///
+/// Placeholders look like `{name}` or `{name:.N}`; unknown names are left as-is.
+pub fn render_message(template: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('}') else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        let placeholder = &after[..end];
+        let (name, precision) = match placeholder.split_once(":.") {
+            Some((name, p)) => (name, p.parse::<usize>().ok()),
+            None => (placeholder, None),
+        };
+
+        match lookup(name) {
+            Some(value) => match (precision, value.parse::<f64>()) {
+                (Some(p), Ok(number)) => out.push_str(&format!("{:.*}", p, number)),
+                _ => out.push_str(&value),
+            },
+            None => {
+                out.push('{');
+                out.push_str(placeholder);
+                out.push('}');
+            }
+        }
+        rest = &after[end + 1..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_rules_are_valid() {
+        let rules = RuleSet::default();
+        assert!(!rules.is_empty());
+        assert!(rules
+            .rules()
+            .iter()
+            .any(|r| r.definition.name == "cpu_spike"));
+    }
+
+    #[test]
+    fn test_default_baseline_rules_are_disabled() {
+        // Enabling them by default would add alerts the hardcoded rules never raised
+        let rules = RuleSet::default();
+        let baseline: Vec<_> = rules
+            .rules()
+            .iter()
+            .filter(|r| r.definition.mode == RuleMode::Baseline)
+            .collect();
+        assert!(!baseline.is_empty());
+        assert!(baseline.iter().all(|r| !r.definition.enabled));
+    }
+
+    #[test]
+    fn test_invalid_rules_rejected() {
+        let missing_levels = r#"
+            [[rules]]
+            name = "no_levels"
+            category = "Cpu"
+            metric = "cpu.global_usage"
+            message = "x"
+        "#;
+        assert!(RuleSet::from_toml_str(missing_levels).is_err());
+
+        let bad_path = r#"
+            [[rules]]
+            name = "bad_path"
+            category = "Cpu"
+            metric = "median(cpu.per_core[*])"
+            warning = 1.0
+            message = "x"
+        "#;
+        assert!(RuleSet::from_toml_str(bad_path).is_err());
+
+        let duplicate = r#"
+            [[rules]]
+            name = "dup"
+            category = "Cpu"
+            metric = "cpu.global_usage"
+            warning = 1.0
+            message = "x"
+
+            [[rules]]
+            name = "dup"
+            category = "Memory"
+            metric = "memory.usage_percent"
+            warning = 1.0
+            message = "y"
+        "#;
+        assert!(RuleSet::from_toml_str(duplicate).is_err());
+
+        let bad_alpha = r#"
+            [[rules]]
+            name = "bad_alpha"
+            category = "Cpu"
+            metric = "cpu.global_usage"
+            mode = "baseline"
+            baseline = { alpha = 1.5 }
+            warning = 3.0
+            message = "x"
+        "#;
+        assert!(RuleSet::from_toml_str(bad_alpha).is_err());
+    }
+
+    #[test]
+    fn test_classify() {
+        let rules = RuleSet::default();
+        let load = rules
+            .rules()
+            .iter()
+            .find(|r| r.definition.name == "load_average_critical")
+            .unwrap();
+
+        assert_eq!(load.definition.classify(10.0, 8), None);
+        assert_eq!(
+            load.definition.classify(17.0, 8),
+            Some((AnomalySeverity::Critical, 16.0))
+        );
+    }
+
+    #[test]
+    fn test_default_network_rules() {
+        let rules = RuleSet::default();
+        let levels = |name: &str| {
+            let rule = rules
+                .rules()
+                .iter()
+                .find(|r| r.definition.name == name)
+                .unwrap();
+            (rule.definition.warning, rule.definition.critical)
+        };
+
+        // 100 MB/s and 500 MB/s per interface
+        for name in ["network_rx_high", "network_tx_high"] {
+            assert_eq!(levels(name), (Some(100_000_000.0), Some(500_000_000.0)));
+        }
+    }
+
+    #[test]
+    fn test_render_message() {
+        let lookup = |name: &str| match name {
+            "value" => Some("91.46".to_string()),
+            "subject" => Some("/home".to_string()),
+            _ => None,
+        };
+        assert_eq!(
+            render_message("Disk {subject} at {value:.0}% {unknown}", lookup),
+            "Disk /home at 91% {unknown}"
+        );
+    }
+}
@@ -0,0 +1,335 @@
+/// Anomaly deduplication into incidents
+///
+/// While a rule keeps firing for the same subject, its anomalies are folded into
+/// one incident: only the first firing (and any severity escalation) is passed
+/// on to be stored and notified, later firings just bump the occurrence count.
+/// The incident resolves once the rule's condition no longer holds. If it fires
+/// again within the cooldown, the resolved incident is reopened silently
+/// instead of raising a new alert, which keeps flapping conditions quiet.
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use shared::types::{Anomaly, Incident, IncidentStatus};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// Identifies the rule and subject an incident groups
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IncidentKey {
+    pub rule: String,
+    pub subject: Option<String>,
+}
+
+impl IncidentKey {
+    /// Reads the rule name and subject from an anomaly's metrics payload
+    ///
+    /// Anomalies not raised by a rule are keyed by category and message.
+    pub fn from_anomaly(anomaly: &Anomaly) -> Self {
+        let rule = anomaly.metrics["rule"]
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{:?}: {}", anomaly.category, anomaly.message));
+        let subject = anomaly.metrics["subject"].as_str().map(str::to_string);
+
+        Self { rule, subject }
+    }
+
+    fn from_incident(incident: &Incident) -> Self {
+        Self {
+            rule: incident.rule.clone(),
+            subject: incident.subject.clone(),
+        }
+    }
+}
+
+/// Result of folding one sample's anomalies into incidents
+#[derive(Debug, Default)]
+pub struct IncidentUpdate {
+    /// Anomalies that opened or escalated an incident; these should be stored
+    /// and notified. Each carries `incident_id` in its metrics payload.
+    pub anomalies: Vec<Anomaly>,
+    /// Incidents created or changed by this sample, to be persisted
+    pub incidents: Vec<Incident>,
+}
+
+/// Groups anomalies into incidents and tracks their lifecycle
+pub struct IncidentTracker {
+    cooldown: Duration,
+    /// Latest incident per key, including ones resolved within the cooldown
+    incidents: HashMap<IncidentKey, Incident>,
+}
+
+impl IncidentTracker {
+    /// Creates a tracker with no known incidents
+    ///
+    /// # Arguments
+    /// * `cooldown` - How long a resolved incident can be reopened without a new alert
+    pub fn new(cooldown: Duration) -> Self {
+        Self {
+            cooldown,
+            incidents: HashMap::new(),
+        }
+    }
+
+    /// Creates a tracker that continues the given unresolved incidents,
+    /// e.g. loaded from the database after a restart
+    pub fn with_incidents(cooldown: Duration, incidents: Vec<Incident>) -> Self {
+        let mut tracker = Self::new(cooldown);
+        for incident in incidents {
+            tracker
+                .incidents
+                .insert(IncidentKey::from_incident(&incident), incident);
+        }
+        tracker
+    }
+
+    /// Returns the number of incidents that are not resolved
+    pub fn active_count(&self) -> usize {
+        self.incidents
+            .values()
+            .filter(|i| i.status != IncidentStatus::Resolved)
+            .count()
+    }
+
+    /// Folds one sample's anomalies into incidents
+    ///
+    /// # Arguments
+    /// * `anomalies` - Anomalies raised by the detector for this sample
+    /// * `breaching` - Rules and subjects whose condition holds on this sample,
+    ///   including edge and `for_samples` rules that did not fire this time
+    /// * `now` - Time of the sample
+    pub fn process(
+        &mut self,
+        anomalies: Vec<Anomaly>,
+        breaching: &HashSet<IncidentKey>,
+        now: DateTime<Utc>,
+    ) -> IncidentUpdate {
+        let cooldown = self.cooldown;
+        let mut update = IncidentUpdate::default();
+        let mut changed = HashSet::new();
+        let mut fired = HashSet::new();
+
+        for mut anomaly in anomalies {
+            let key = IncidentKey::from_anomaly(&anomaly);
+            fired.insert(key.clone());
+
+            let emit = match self.incidents.get_mut(&key) {
+                Some(incident) if incident.status != IncidentStatus::Resolved => {
+                    record_firing(incident, &anomaly)
+                }
+                Some(incident) if within_cooldown(incident, cooldown, now) => {
+                    incident.status = IncidentStatus::Open;
+                    incident.resolved_at = None;
+                    incident.acknowledged_at = None;
+                    record_firing(incident, &anomaly)
+                }
+                _ => {
+                    let incident = Incident {
+                        id: Uuid::new_v4().to_string(),
+                        rule: key.rule.clone(),
+                        subject: key.subject.clone(),
+                        category: anomaly.category.clone(),
+                        severity: anomaly.severity.clone(),
+                        status: IncidentStatus::Open,
+                        message: anomaly.message.clone(),
+                        first_seen: anomaly.timestamp,
+                        last_seen: anomaly.timestamp,
+                        occurrences: 1,
+                        acknowledged_at: None,
+                        resolved_at: None,
+                        last_anomaly_id: anomaly.id.clone(),
+                    };
+                    self.incidents.insert(key.clone(), incident);
+                    true
+                }
+            };
+
+            if emit {
+                let incident = &self.incidents[&key];
+                anomaly.metrics["incident_id"] = json!(incident.id);
+                update.anomalies.push(anomaly);
+            }
+            changed.insert(key);
+        }
+
+        // Resolve incidents whose condition cleared
+        for (key, incident) in self.incidents.iter_mut() {
+            if incident.status != IncidentStatus::Resolved
+                && !breaching.contains(key)
+                && !fired.contains(key)
+            {
+                incident.status = IncidentStatus::Resolved;
+                incident.resolved_at = Some(now);
+                changed.insert(key.clone());
+            }
+        }
+
+        update.incidents = changed
+            .iter()
+            .filter_map(|key| self.incidents.get(key).cloned())
+            .collect();
+
+        // Forget resolved incidents once they can no longer be reopened
+        self.incidents.retain(|_, incident| {
+            incident.status != IncidentStatus::Resolved || within_cooldown(incident, cooldown, now)
+        });
+
+        update
+    }
+}
+
+/// Returns true if a resolved incident can still be reopened at `now`
+fn within_cooldown(incident: &Incident, cooldown: Duration, now: DateTime<Utc>) -> bool {
+    incident
+        .resolved_at
+        .is_some_and(|resolved_at| now - resolved_at <= cooldown)
+}
+
+/// Counts a repeated firing; returns true if it raised the severity
+fn record_firing(incident: &mut Incident, anomaly: &Anomaly) -> bool {
+    incident.occurrences += 1;
+    incident.last_seen = anomaly.timestamp;
+    incident.message = anomaly.message.clone();
+
+    if anomaly.severity > incident.severity {
+        incident.severity = anomaly.severity.clone();
+        incident.last_anomaly_id = anomaly.id.clone();
+        true
+    } else {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::types::{AnomalyCategory, AnomalySeverity};
+
+    fn anomaly(severity: AnomalySeverity, subject: &str, at: DateTime<Utc>) -> Anomaly {
+        Anomaly {
+            id: Uuid::new_v4().to_string(),
+            timestamp: at,
+            severity,
+            category: AnomalyCategory::Disk,
+            message: format!("Disk {} almost full", subject),
+            metrics: json!({ "rule": "disk_usage_critical", "subject": subject }),
+        }
+    }
+
+    fn key(subject: &str) -> IncidentKey {
+        IncidentKey {
+            rule: "disk_usage_critical".to_string(),
+            subject: Some(subject.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_repeated_firings_are_deduplicated() {
+        let mut tracker = IncidentTracker::new(Duration::minutes(5));
+        let t0 = Utc::now();
+        let breaching = HashSet::from([key("/home")]);
+
+        let first = tracker.process(
+            vec![anomaly(AnomalySeverity::Warning, "/home", t0)],
+            &breaching,
+            t0,
+        );
+        assert_eq!(first.anomalies.len(), 1);
+        assert_eq!(first.incidents.len(), 1);
+        let incident_id = first.incidents[0].id.clone();
+        assert_eq!(
+            first.anomalies[0].metrics["incident_id"],
+            incident_id.as_str()
+        );
+
+        for i in 1..=10 {
+            let at = t0 + Duration::seconds(2 * i);
+            let update = tracker.process(
+                vec![anomaly(AnomalySeverity::Warning, "/home", at)],
+                &breaching,
+                at,
+            );
+            assert!(update.anomalies.is_empty());
+            assert_eq!(update.incidents[0].id, incident_id);
+        }
+
+        // Escalation is passed on
+        let at = t0 + Duration::seconds(30);
+        let update = tracker.process(
+            vec![anomaly(AnomalySeverity::Critical, "/home", at)],
+            &breaching,
+            at,
+        );
+        assert_eq!(update.anomalies.len(), 1);
+        let incident = &update.incidents[0];
+        assert_eq!(incident.occurrences, 12);
+        assert_eq!(incident.severity, AnomalySeverity::Critical);
+        assert_eq!(incident.first_seen, t0);
+        assert_eq!(incident.last_seen, at);
+    }
+
+    #[test]
+    fn test_incident_resolves_and_reopens_within_cooldown() {
+        let mut tracker = IncidentTracker::new(Duration::minutes(5));
+        let t0 = Utc::now();
+
+        tracker.process(
+            vec![anomaly(AnomalySeverity::Warning, "/", t0)],
+            &HashSet::from([key("/")]),
+            t0,
+        );
+
+        // Condition still holds (edge rule did not fire again): stays open
+        let t1 = t0 + Duration::seconds(2);
+        let update = tracker.process(vec![], &HashSet::from([key("/")]), t1);
+        assert!(update.incidents.is_empty());
+        assert_eq!(tracker.active_count(), 1);
+
+        // Condition cleared
+        let t2 = t0 + Duration::seconds(4);
+        let update = tracker.process(vec![], &HashSet::new(), t2);
+        assert_eq!(update.incidents[0].status, IncidentStatus::Resolved);
+        assert_eq!(update.incidents[0].resolved_at, Some(t2));
+        let incident_id = update.incidents[0].id.clone();
+
+        // Fires again within the cooldown: same incident, no new alert
+        let t3 = t0 + Duration::seconds(60);
+        let update = tracker.process(
+            vec![anomaly(AnomalySeverity::Warning, "/", t3)],
+            &HashSet::from([key("/")]),
+            t3,
+        );
+        assert!(update.anomalies.is_empty());
+        assert_eq!(update.incidents[0].id, incident_id);
+        assert_eq!(update.incidents[0].status, IncidentStatus::Open);
+        assert_eq!(update.incidents[0].resolved_at, None);
+
+        // Resolves, then fires after the cooldown: new incident
+        let t4 = t3 + Duration::seconds(2);
+        tracker.process(vec![], &HashSet::new(), t4);
+        let t5 = t4 + Duration::minutes(10);
+        let update = tracker.process(
+            vec![anomaly(AnomalySeverity::Warning, "/", t5)],
+            &HashSet::from([key("/")]),
+            t5,
+        );
+        assert_eq!(update.anomalies.len(), 1);
+        assert_ne!(update.incidents[0].id, incident_id);
+    }
+
+    #[test]
+    fn test_subjects_are_separate_incidents() {
+        let mut tracker = IncidentTracker::new(Duration::minutes(5));
+        let t0 = Utc::now();
+
+        let update = tracker.process(
+            vec![
+                anomaly(AnomalySeverity::Warning, "/", t0),
+                anomaly(AnomalySeverity::Warning, "/home", t0),
+            ],
+            &HashSet::from([key("/"), key("/home")]),
+            t0,
+        );
+        assert_eq!(update.anomalies.len(), 2);
+        assert_eq!(tracker.active_count(), 2);
+    }
+}
@@ -2,8 +2,21 @@ mod analyzer;
 /// Anomaly detection module
 ///
 /// This module provides functionality for detecting anomalies in system metrics,
-/// including rule-based detection and analysis helpers.
+/// including rule-based detection driven by a declarative rules file (static
+/// thresholds and learned statistical baselines), grouping of repeated
+/// anomalies into incidents, rule muting, and analysis helpers.
+mod baseline;
+mod config;
+mod incidents;
+mod mutes;
+mod path;
+mod reload;
 mod rules;
 
 pub use analyzer::{calculate_delta, calculate_rate, classify_severity, MetricsDelta};
+pub use config::{BaselineSettings, Comparison, RuleDefinition, RuleMode, RuleSet};
+pub use incidents::{IncidentKey, IncidentTracker, IncidentUpdate};
+pub use mutes::RuleMutes;
+pub use path::{snapshot, MetricPath, MetricSample};
+pub use reload::{ReloadStatus, ReloadTrigger, RulesReloader};
 pub use rules::AnomalyRules;
@@ -0,0 +1,145 @@
+/// Muting of detection rules
+///
+/// A muted rule keeps being evaluated, but the anomalies it raises are
+/// dropped before incident tracking, so they are not stored, streamed or
+/// notified and do not open or reopen incidents. An incident already open
+/// when the mute starts still resolves once its condition clears. Mutes are
+/// set through the API, persisted by the repository and mirrored here so the
+/// collection loop can check them without a query per sample.
+use chrono::{DateTime, Utc};
+use shared::types::{Anomaly, RuleMute};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use super::incidents::IncidentKey;
+
+/// Rules muted until a given time, shared by the API and the collection loop
+#[derive(Debug, Default)]
+pub struct RuleMutes {
+    mutes: RwLock<HashMap<String, DateTime<Utc>>>,
+}
+
+impl RuleMutes {
+    /// Creates the set from mutes loaded from the database
+    pub fn new(mutes: Vec<RuleMute>) -> Self {
+        Self {
+            mutes: RwLock::new(
+                mutes
+                    .into_iter()
+                    .map(|mute| (mute.rule, mute.muted_until))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Mutes `rule` until `muted_until`, replacing any existing mute
+    pub fn mute(&self, rule: &str, muted_until: DateTime<Utc>) {
+        self.mutes
+            .write()
+            .unwrap()
+            .insert(rule.to_string(), muted_until);
+    }
+
+    /// Lifts the mute on `rule`
+    pub fn unmute(&self, rule: &str) {
+        self.mutes.write().unwrap().remove(rule);
+    }
+
+    /// Returns true if `rule` is muted at `now`
+    pub fn is_muted(&self, rule: &str, now: DateTime<Utc>) -> bool {
+        self.mutes
+            .read()
+            .unwrap()
+            .get(rule)
+            .is_some_and(|until| *until > now)
+    }
+
+    /// Drops the anomalies raised by rules muted at `now`
+    ///
+    /// # Returns
+    /// The number of anomalies dropped
+    pub fn retain_unmuted(&self, anomalies: &mut Vec<Anomaly>, now: DateTime<Utc>) -> usize {
+        let mut mutes = self.mutes.write().unwrap();
+        mutes.retain(|_, until| *until > now);
+        if mutes.is_empty() {
+            return 0;
+        }
+
+        let before = anomalies.len();
+        anomalies.retain(|anomaly| !mutes.contains_key(&IncidentKey::from_anomaly(anomaly).rule));
+        before - anomalies.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detector::IncidentTracker;
+    use chrono::Duration;
+    use serde_json::json;
+    use shared::types::{AnomalyCategory, AnomalySeverity};
+    use std::collections::HashSet;
+
+    fn anomaly(rule: &str) -> Anomaly {
+        Anomaly {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: Utc::now(),
+            severity: AnomalySeverity::Warning,
+            category: AnomalyCategory::Cpu,
+            message: format!("{} fired", rule),
+            metrics: json!({ "rule": rule }),
+        }
+    }
+
+    #[test]
+    fn test_retain_unmuted() {
+        let now = Utc::now();
+        let mutes = RuleMutes::new(vec![RuleMute {
+            rule: "cpu_spike".to_string(),
+            muted_until: now + Duration::hours(1),
+            created_at: now,
+        }]);
+        mutes.mute("memory_spike", now - Duration::seconds(1));
+
+        let mut anomalies = vec![
+            anomaly("cpu_spike"),
+            anomaly("memory_spike"),
+            anomaly("cpu_spike"),
+        ];
+        assert_eq!(mutes.retain_unmuted(&mut anomalies, now), 2);
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].metrics["rule"], "memory_spike");
+
+        // Mutes end on their own or when lifted
+        assert!(!mutes.is_muted("cpu_spike", now + Duration::hours(2)));
+        mutes.unmute("cpu_spike");
+        assert!(!mutes.is_muted("cpu_spike", now));
+    }
+
+    #[test]
+    fn test_muted_rule_opens_no_incident() {
+        let now = Utc::now();
+        let mutes = RuleMutes::default();
+        mutes.mute("cpu_spike", now + Duration::hours(1));
+        let mut tracker = IncidentTracker::new(Duration::minutes(5));
+        let breaching: HashSet<_> = [IncidentKey::from_anomaly(&anomaly("cpu_spike"))]
+            .into_iter()
+            .collect();
+
+        // Filtered as in the collection loop, before incident tracking
+        let mut raised = vec![anomaly("cpu_spike")];
+        mutes.retain_unmuted(&mut raised, now);
+        let update = tracker.process(raised, &breaching, now);
+        assert!(update.anomalies.is_empty());
+        assert!(update.incidents.is_empty());
+        assert_eq!(tracker.active_count(), 0);
+
+        // Once the mute is lifted the rule opens an incident again
+        mutes.unmute("cpu_spike");
+        let mut raised = vec![anomaly("cpu_spike")];
+        mutes.retain_unmuted(&mut raised, now);
+        let update = tracker.process(raised, &breaching, now);
+        assert_eq!(update.anomalies.len(), 1);
+        assert_eq!(update.incidents.len(), 1);
+    }
+}
@@ -0,0 +1,514 @@
+/// Metric path resolution
+///
+/// A metric path addresses numeric values inside a `SystemMetrics` snapshot using
+/// dotted field names, e.g. `cpu.global_usage` or `gpu.temperature`. Array fields
+/// accept a selector: `[*]` for every element, `[3]` for an index, or `[key]` to
+/// match an element by mount point, name, id or `sensor:label`. Elements without
+/// those fields are identified by `pid` or `parent_pid`. A path may be
+/// wrapped in an aggregate (`max`, `min`, `sum`, `avg`, `count`) to reduce the
+/// matched values to a single one.
+use serde_json::Value;
+use shared::types::SystemMetrics;
+use std::fmt;
+
+/// Selector applied to an array field
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    All,
+    Index(usize),
+    Key(String),
+}
+
+/// One dotted component of a path, e.g. `disks[*]`
+#[derive(Debug, Clone, PartialEq)]
+struct Segment {
+    field: String,
+    selector: Option<Selector>,
+}
+
+/// Aggregate function reducing all matched values to one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Max,
+    Min,
+    Sum,
+    Avg,
+    /// Number of non-zero values (booleans count as 1 when true)
+    Count,
+}
+
+impl Aggregate {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "max" => Some(Self::Max),
+            "min" => Some(Self::Min),
+            "sum" => Some(Self::Sum),
+            "avg" => Some(Self::Avg),
+            "count" => Some(Self::Count),
+            _ => None,
+        }
+    }
+}
+
+/// A single value resolved from a path
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricSample {
+    /// Element the value belongs to (e.g. a mount point), if the path selected one
+    pub subject: Option<String>,
+    pub value: f64,
+    /// Elements that contributed to an aggregate (arg-max for `max`, non-zero
+    /// elements for `sum`/`count`)
+    pub sources: Vec<String>,
+    /// Array element the value was read from, e.g. the whole disk object
+    pub element: Option<Value>,
+}
+
+/// Parsed metric path
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricPath {
+    raw: String,
+    aggregate: Option<Aggregate>,
+    segments: Vec<Segment>,
+}
+
+/// Error returned when a metric path cannot be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathError(String);
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for PathError {}
+
+impl MetricPath {
+    /// Parses a metric path such as `max(disks[*].usage_percent)`
+    pub fn parse(raw: &str) -> Result<Self, PathError> {
+        let trimmed = raw.trim();
+        let err = |msg: &str| PathError(format!("invalid metric path '{}': {}", raw, msg));
+
+        let (aggregate, body) = match trimmed.find('(') {
+            Some(open) => {
+                let name = &trimmed[..open];
+                let aggregate =
+                    Aggregate::parse(name).ok_or_else(|| err("unknown aggregate function"))?;
+                let body = trimmed[open + 1..]
+                    .strip_suffix(')')
+                    .ok_or_else(|| err("missing closing parenthesis"))?;
+                (Some(aggregate), body)
+            }
+            None => (None, trimmed),
+        };
+
+        let mut segments = Vec::new();
+        let mut chars = body.chars().peekable();
+        loop {
+            let mut field = String::new();
+            while let Some(&c) = chars.peek() {
+                if c == '.' || c == '[' {
+                    break;
+                }
+                field.push(c);
+                chars.next();
+            }
+            if field.is_empty() {
+                return Err(err("empty field name"));
+            }
+
+            let selector = if chars.peek() == Some(&'[') {
+                chars.next();
+                let mut key = String::new();
+                loop {
+                    match chars.next() {
+                        Some(']') => break,
+                        Some(c) => key.push(c),
+                        None => return Err(err("missing closing bracket")),
+                    }
+                }
+                Some(match key.as_str() {
+                    "" => return Err(err("empty selector")),
+                    "*" => Selector::All,
+                    _ => match key.parse::<usize>() {
+                        Ok(index) => Selector::Index(index),
+                        Err(_) => Selector::Key(key),
+                    },
+                })
+            } else {
+                None
+            };
+
+            segments.push(Segment { field, selector });
+
+            match chars.next() {
+                Some('.') => continue,
+                Some(c) => return Err(err(&format!("unexpected character '{}'", c))),
+                None => break,
+            }
+        }
+
+        Ok(Self {
+            raw: trimmed.to_string(),
+            aggregate,
+            segments,
+        })
+    }
+
+    /// Returns the path as originally written
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    /// Returns true if the path can resolve to more than one value, i.e. it
+    /// has a `[*]` selector and no aggregate
+    pub fn selects_many(&self) -> bool {
+        self.aggregate.is_none()
+            && self
+                .segments
+                .iter()
+                .any(|segment| segment.selector == Some(Selector::All))
+    }
+
+    /// Resolves the path against a metrics snapshot built with [`snapshot`]
+    ///
+    /// Returns one sample per matched element, or a single sample when the path
+    /// is aggregated. Missing fields (e.g. `gpu` when no GPU is present) yield
+    /// no samples.
+    pub fn resolve(&self, snapshot: &Value) -> Vec<MetricSample> {
+        let mut samples = Vec::new();
+        walk(snapshot, &self.segments, None, None, &mut samples);
+
+        match self.aggregate {
+            Some(aggregate) => reduce(aggregate, samples).into_iter().collect(),
+            None => samples,
+        }
+    }
+
+    /// Convenience wrapper around [`snapshot`] and [`MetricPath::resolve`]
+    pub fn resolve_metrics(&self, metrics: &SystemMetrics) -> Vec<MetricSample> {
+        self.resolve(&snapshot(metrics))
+    }
+}
+
+impl fmt::Display for MetricPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+/// Converts metrics into the JSON tree that paths are resolved against
+///
+/// Besides the serialized fields, a few computed fields are added:
+/// - `disks[*].io_mb`: combined read + write throughput
+/// - `memory.swap_usage_percent`: swap usage as a percentage
+pub fn snapshot(metrics: &SystemMetrics) -> Value {
+    let mut value = serde_json::to_value(metrics).unwrap_or(Value::Null);
+
+    if let Some(disks) = value.get_mut("disks").and_then(Value::as_array_mut) {
+        for disk in disks {
+            let io_mb = disk.get("read_mb").and_then(Value::as_f64).unwrap_or(0.0)
+                + disk.get("write_mb").and_then(Value::as_f64).unwrap_or(0.0);
+            if let Some(obj) = disk.as_object_mut() {
+                obj.insert("io_mb".to_string(), io_mb.into());
+            }
+        }
+    }
+
+    if let Some(memory) = value.get_mut("memory").and_then(Value::as_object_mut) {
+        let swap_total = memory
+            .get("swap_total")
+            .and_then(Value::as_f64)
+            .unwrap_or(0.0);
+        let swap_used = memory
+            .get("swap_used")
+            .and_then(Value::as_f64)
+            .unwrap_or(0.0);
+        let swap_percent = if swap_total > 0.0 {
+            swap_used / swap_total * 100.0
+        } else {
+            0.0
+        };
+        memory.insert("swap_usage_percent".to_string(), swap_percent.into());
+    }
+
+    value
+}
+
+fn walk(
+    value: &Value,
+    segments: &[Segment],
+    subject: Option<String>,
+    element: Option<&Value>,
+    out: &mut Vec<MetricSample>,
+) {
+    let Some((segment, rest)) = segments.split_first() else {
+        let number = match value {
+            Value::Number(n) => n.as_f64(),
+            Value::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+            _ => None,
+        };
+        if let Some(value) = number {
+            out.push(MetricSample {
+                subject,
+                value,
+                sources: Vec::new(),
+                element: element.cloned(),
+            });
+        }
+        return;
+    };
+
+    let Some(field) = value.get(&segment.field) else {
+        return;
+    };
+
+    let Some(ref selector) = segment.selector else {
+        walk(field, rest, subject, element, out);
+        return;
+    };
+
+    let Some(items) = field.as_array() else {
+        return;
+    };
+
+    for (index, item) in items.iter().enumerate() {
+        let matched = match selector {
+            Selector::All => true,
+            Selector::Index(i) => *i == index,
+            Selector::Key(key) => element_matches(item, index, key),
+        };
+        if matched {
+            let key = element_key(item, index);
+            let subject = match &subject {
+                Some(parent) => Some(format!("{}.{}", parent, key)),
+                None => Some(key),
+            };
+            walk(item, rest, subject, Some(item), out);
+        }
+    }
+}
+
+/// Preferred identifier of an array element, used as the sample subject
+fn element_key(item: &Value, index: usize) -> String {
+    let field = |name: &str| item.get(name).and_then(Value::as_str);
+    let number = |name: &str| item.get(name).and_then(Value::as_u64);
+
+    if let Some(mount_point) = field("mount_point") {
+        return mount_point.to_string();
+    }
+    if let (Some(sensor), Some(label)) = (field("sensor"), field("label")) {
+        return format!("{}:{}", sensor, label);
+    }
+    if let Some(id) = field("id") {
+        return id.to_string();
+    }
+    if let Some(pid) = number("pid").or_else(|| number("parent_pid")) {
+        return pid.to_string();
+    }
+    field("name")
+        .map(str::to_string)
+        .unwrap_or_else(|| index.to_string())
+}
+
+fn element_matches(item: &Value, index: usize, key: &str) -> bool {
+    if element_key(item, index) == key {
+        return true;
+    }
+    ["mount_point", "name", "id", "label"]
+        .iter()
+        .any(|name| item.get(*name).and_then(Value::as_str) == Some(key))
+}
+
+fn reduce(aggregate: Aggregate, samples: Vec<MetricSample>) -> Option<MetricSample> {
+    let subject_of = |s: &MetricSample| s.subject.clone().into_iter().collect::<Vec<_>>();
+
+    match aggregate {
+        Aggregate::Max | Aggregate::Min => {
+            let pick = samples.into_iter().reduce(|best, s| {
+                let better = match aggregate {
+                    Aggregate::Max => s.value > best.value,
+                    _ => s.value < best.value,
+                };
+                if better {
+                    s
+                } else {
+                    best
+                }
+            })?;
+            Some(MetricSample {
+                subject: None,
+                value: pick.value,
+                sources: subject_of(&pick),
+                element: pick.element,
+            })
+        }
+        Aggregate::Avg => {
+            if samples.is_empty() {
+                return None;
+            }
+            let sum: f64 = samples.iter().map(|s| s.value).sum();
+            Some(MetricSample {
+                subject: None,
+                value: sum / samples.len() as f64,
+                sources: Vec::new(),
+                element: None,
+            })
+        }
+        Aggregate::Sum | Aggregate::Count => {
+            let non_zero: Vec<&MetricSample> = samples.iter().filter(|s| s.value != 0.0).collect();
+            let value = match aggregate {
+                Aggregate::Sum => non_zero.iter().map(|s| s.value).sum(),
+                _ => non_zero.len() as f64,
+            };
+            Some(MetricSample {
+                subject: None,
+                value,
+                sources: non_zero.iter().flat_map(|s| subject_of(s)).collect(),
+                element: None,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use shared::types::{
+        CpuMetrics, DiskMetrics, MemoryMetrics, NetworkMetrics, Temperature, UsbDevice,
+    };
+
+    fn create_test_metrics() -> SystemMetrics {
+        SystemMetrics {
+            timestamp: Utc::now(),
+            cpu: CpuMetrics {
+                global_usage: 42.0,
+                per_core: vec![10.0, 20.0, 30.0, 40.0],
+                load_avg_1: 1.0,
+                load_avg_5: 1.0,
+                load_avg_15: 1.0,
+            },
+            memory: MemoryMetrics {
+                total: 1000,
+                used: 500,
+                available: 500,
+                usage_percent: 50.0,
+                swap_total: 1000,
+                swap_used: 250,
+            },
+            temperatures: vec![
+                Temperature {
+                    sensor: "coretemp".to_string(),
+                    value: 60.0,
+                    label: "Core 0".to_string(),
+                },
+                Temperature {
+                    sensor: "nvme".to_string(),
+                    value: 45.0,
+                    label: "Composite".to_string(),
+                },
+            ],
+            disks: vec![
+                DiskMetrics {
+                    name: "sda".to_string(),
+                    mount_point: "/".to_string(),
+                    total: 1000,
+                    used: 500,
+                    available: 500,
+                    usage_percent: 50.0,
+                    read_mb: 1.0,
+                    write_mb: 2.0,
+                },
+                DiskMetrics {
+                    name: "sdb".to_string(),
+                    mount_point: "/home".to_string(),
+                    total: 1000,
+                    used: 900,
+                    available: 100,
+                    usage_percent: 90.0,
+                    read_mb: 0.0,
+                    write_mb: 0.0,
+                },
+            ],
+            usb_devices: vec![UsbDevice {
+                id: "1d6b:0002".to_string(),
+                manufacturer: "Linux".to_string(),
+                product: "Hub".to_string(),
+                has_timeout: true,
+            }],
+            network: NetworkMetrics {
+                rx_bytes: 0,
+                tx_bytes: 0,
+                rx_packets: 0,
+                tx_packets: 0,
+                interfaces: vec![],
+            },
+            gpu: None,
+            processes: vec![],
+            zombies: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(MetricPath::parse("cpu.global_usage").is_ok());
+        assert!(MetricPath::parse("median(cpu.per_core[*])").is_err());
+        assert!(MetricPath::parse("max(cpu.per_core[*]").is_err());
+        assert!(MetricPath::parse("disks[/home").is_err());
+        assert!(MetricPath::parse("cpu..global_usage").is_err());
+    }
+
+    #[test]
+    fn test_selects_many() {
+        let selects_many = |p: &str| MetricPath::parse(p).unwrap().selects_many();
+
+        assert!(selects_many("cpu.per_core[*]"));
+        assert!(!selects_many("cpu.per_core[3]"));
+        assert!(!selects_many("disks[/home].usage_percent"));
+        assert!(!selects_many("max(disks[*].usage_percent)"));
+    }
+
+    #[test]
+    fn test_resolve_scalar_and_selectors() {
+        let metrics = create_test_metrics();
+        let resolve = |p: &str| MetricPath::parse(p).unwrap().resolve_metrics(&metrics);
+
+        assert_eq!(resolve("cpu.global_usage")[0].value, 42.0);
+        assert_eq!(resolve("cpu.per_core[3]")[0].value, 40.0);
+        assert_eq!(resolve("cpu.per_core[*]").len(), 4);
+        assert_eq!(resolve("disks[/home].usage_percent")[0].value, 90.0);
+        assert_eq!(
+            resolve("temperatures[coretemp:Core 0].value")[0].value,
+            60.0
+        );
+        assert_eq!(resolve("disks[/].io_mb")[0].value, 3.0);
+        assert_eq!(resolve("memory.swap_usage_percent")[0].value, 25.0);
+        assert_eq!(
+            resolve("disks[/home].usage_percent")[0]
+                .element
+                .as_ref()
+                .unwrap()["mount_point"],
+            "/home"
+        );
+        assert!(resolve("cpu.global_usage")[0].element.is_none());
+        assert!(resolve("gpu.temperature").is_empty());
+    }
+
+    #[test]
+    fn test_resolve_aggregates() {
+        let metrics = create_test_metrics();
+        let resolve = |p: &str| MetricPath::parse(p).unwrap().resolve_metrics(&metrics);
+
+        let max = resolve("max(disks[*].usage_percent)");
+        assert_eq!(max[0].value, 90.0);
+        assert_eq!(max[0].sources, vec!["/home".to_string()]);
+
+        let count = resolve("count(usb_devices[*].has_timeout)");
+        assert_eq!(count[0].value, 1.0);
+        assert_eq!(count[0].sources, vec!["1d6b:0002".to_string()]);
+
+        assert_eq!(resolve("avg(cpu.per_core[*])")[0].value, 25.0);
+        assert!(resolve("max(gpu.temperature)").is_empty());
+    }
+}
@@ -0,0 +1,210 @@
+/// Hot-reloading of detection rules
+///
+/// `RulesReloader` owns the path of the rules file and publishes each
+/// successfully parsed `RuleSet` on a watch channel. The collection loop
+/// subscribes to that channel and swaps the rules between samples, so detector
+/// history is kept. Reloads can be triggered by the API, by SIGHUP or by the
+/// file watcher; the outcome of the last attempt is kept for the API.
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::sync::{watch, RwLock};
+use tokio::time::{interval, Duration};
+use tokio_util::sync::CancellationToken;
+
+use super::config::RuleSet;
+
+/// What caused a reload attempt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReloadTrigger {
+    Api,
+    Signal,
+    FileChange,
+}
+
+/// Outcome of a reload attempt
+#[derive(Debug, Clone, Serialize)]
+pub struct ReloadStatus {
+    pub timestamp: DateTime<Utc>,
+    pub trigger: ReloadTrigger,
+    pub success: bool,
+    /// Number of rules now active (on success)
+    pub rules_loaded: Option<usize>,
+    /// Parse or validation error (on failure); the previous rules stay active
+    pub error: Option<String>,
+}
+
+/// Loads rules from disk and publishes them to the collection loop
+pub struct RulesReloader {
+    path: Option<PathBuf>,
+    sender: watch::Sender<RuleSet>,
+    last_status: RwLock<Option<ReloadStatus>>,
+}
+
+impl RulesReloader {
+    /// Creates a reloader publishing `initial` as the active rule set
+    ///
+    /// # Arguments
+    /// * `path` - Rules file to reload from, or `None` when using built-in defaults
+    /// * `initial` - Rules already loaded at startup
+    pub fn new(path: Option<PathBuf>, initial: RuleSet) -> Self {
+        let (sender, _) = watch::channel(initial);
+        Self {
+            path,
+            sender,
+            last_status: RwLock::new(None),
+        }
+    }
+
+    /// Returns the rules file path, if one is configured
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Subscribes to rule set changes
+    pub fn subscribe(&self) -> watch::Receiver<RuleSet> {
+        self.sender.subscribe()
+    }
+
+    /// Returns the currently published rule set
+    pub fn current(&self) -> RuleSet {
+        self.sender.borrow().clone()
+    }
+
+    /// Returns the outcome of the last reload attempt
+    pub async fn last_status(&self) -> Option<ReloadStatus> {
+        self.last_status.read().await.clone()
+    }
+
+    /// Re-reads the rules file and publishes it if valid
+    ///
+    /// On failure the active rules are left untouched and the error is recorded.
+    pub async fn reload(&self, trigger: ReloadTrigger) -> ReloadStatus {
+        let result = match self.path {
+            Some(ref path) => {
+                // Reading and compiling the file blocks; keep it off the runtime threads
+                let path = path.clone();
+                tokio::task::spawn_blocking(move || RuleSet::from_file(path))
+                    .await
+                    .unwrap_or_else(|e| Err(anyhow::anyhow!("Rules reload task failed: {}", e)))
+            }
+            None => Err(anyhow::anyhow!(
+                "No rules file configured; built-in defaults are in use"
+            )),
+        };
+
+        let status = match result {
+            Ok(rules) => {
+                let count = rules.len();
+                self.sender.send_replace(rules);
+                tracing::info!("Reloaded {} detection rules ({:?})", count, trigger);
+                ReloadStatus {
+                    timestamp: Utc::now(),
+                    trigger,
+                    success: true,
+                    rules_loaded: Some(count),
+                    error: None,
+                }
+            }
+            Err(e) => {
+                let error = format!("{:#}", e);
+                tracing::error!("Failed to reload detection rules: {}", error);
+                ReloadStatus {
+                    timestamp: Utc::now(),
+                    trigger,
+                    success: false,
+                    rules_loaded: None,
+                    error: Some(error),
+                }
+            }
+        };
+
+        *self.last_status.write().await = Some(status.clone());
+        status
+    }
+
+    /// Polls the rules file and reloads it whenever its modification time changes
+    ///
+    /// Returns immediately if no rules file is configured.
+    pub async fn watch_file(self: Arc<Self>, poll_interval: Duration, shutdown: CancellationToken) {
+        let Some(path) = self.path.clone() else {
+            return;
+        };
+
+        let mut last_modified = modified_time(&path).await;
+        let mut tick = interval(poll_interval);
+
+        tracing::info!("Watching rules file {} for changes", path.display());
+
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = tick.tick() => {
+                    let modified = modified_time(&path).await;
+                    if modified.is_some() && modified != last_modified {
+                        last_modified = modified;
+                        self.reload(ReloadTrigger::FileChange).await;
+                    }
+                }
+            }
+        }
+    }
+}
+
+async fn modified_time(path: &Path) -> Option<SystemTime> {
+    tokio::fs::metadata(path)
+        .await
+        .and_then(|m| m.modified())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULES: &str = r#"
+        [[rules]]
+        name = "memory_critical"
+        category = "Memory"
+        metric = "memory.usage_percent"
+        critical = 80.0
+        message = "Memory at {value:.0}%"
+    "#;
+
+    #[tokio::test]
+    async fn test_reload_publishes_rules() {
+        let path = std::env::temp_dir().join(format!("rules-{}.toml", uuid::Uuid::new_v4()));
+        std::fs::write(&path, RULES).unwrap();
+
+        let reloader = RulesReloader::new(Some(path.clone()), RuleSet::default());
+        let mut rx = reloader.subscribe();
+
+        let status = reloader.reload(ReloadTrigger::Api).await;
+        assert!(status.success);
+        assert_eq!(status.rules_loaded, Some(1));
+        assert!(rx.has_changed().unwrap());
+        assert_eq!(rx.borrow_and_update().len(), 1);
+
+        // Invalid file keeps the previous rules
+        std::fs::write(&path, "[[rules]]\nname = 1").unwrap();
+        let status = reloader.reload(ReloadTrigger::Signal).await;
+        assert!(!status.success);
+        assert!(status.error.is_some());
+        assert!(!rx.has_changed().unwrap());
+        assert_eq!(reloader.current().len(), 1);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_reload_without_file() {
+        let reloader = RulesReloader::new(None, RuleSet::default());
+        let status = reloader.reload(ReloadTrigger::Api).await;
+
+        assert!(!status.success);
+        assert!(reloader.last_status().await.is_some());
+    }
+}
@@ -1,50 +1,105 @@
 /// Anomaly detection rules
 ///
-/// This module implements the AnomalyRules struct that detects various system anomalies
-/// by comparing current metrics against previous values and thresholds.
-use chrono::Utc;
-use shared::types::{Anomaly, AnomalyCategory, AnomalySeverity, SystemMetrics};
+/// This module implements the AnomalyRules struct that evaluates a declarative
+/// `RuleSet` against each metrics snapshot, tracking per-rule state across samples.
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use shared::types::{
+    Anomaly, AnomalyCategory, AnomalySeverity, MetricBaseline, ProcessInfo, SystemMetrics,
+};
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
-use super::analyzer::{get_max_disk_io, get_max_disk_usage, get_max_temperature, has_usb_timeout};
-
-/// Threshold constants for anomaly detection
-/// These values are based on the original implementation in main.rs
-const CPU_SPIKE_THRESHOLD: f32 = 40.0; // % increase
-const CPU_CRITICAL_THRESHOLD: f32 = 90.0; // % usage
-const MEMORY_SPIKE_THRESHOLD: f32 = 20.0; // % increase
-const MEMORY_CRITICAL_THRESHOLD: f32 = 95.0; // % usage
-const TEMPERATURE_CRITICAL: f32 = 85.0; // °C
-const TEMPERATURE_DROP_THRESHOLD: f32 = 30.0; // °C decrease
-const DISK_CRITICAL_THRESHOLD: f32 = 90.0; // % usage
-const DISK_IO_HIGH_THRESHOLD: f64 = 500.0; // MB/s
-const LOAD_AVG_MULTIPLIER: f64 = 2.0; // times number of CPUs
-const GPU_TEMP_CRITICAL: f32 = 90.0; // °C
-const GPU_USAGE_CRITICAL: f32 = 95.0; // % usage
-const GPU_MEMORY_CRITICAL: f32 = 95.0; // % usage
-
-/// Stores state for anomaly detection and implements detection rules
+use super::baseline::{Baseline, BaselineKey};
+use super::config::{render_message, CompiledRule, RuleMode, RuleSet};
+use super::incidents::IncidentKey;
+use super::path::{self, MetricSample};
+
+/// Number of top processes attached to CPU and memory anomalies
+const CULPRIT_PROCESSES: usize = 5;
+
+/// Identifies the state of one rule for one subject (e.g. a mount point)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct RuleKey {
+    rule: String,
+    subject: String,
+}
+
+/// Per-rule, per-subject evaluation history
+#[derive(Debug, Clone, Default)]
+struct RuleState {
+    /// Value observed on the previous sample
+    last_value: Option<f64>,
+    /// Consecutive samples the condition has held
+    streak: u32,
+    /// Consecutive samples the condition has been evaluated
+    seen: u32,
+}
+
+/// Stores state for anomaly detection and evaluates detection rules
 pub struct AnomalyRules {
-    previous_metrics: Option<SystemMetrics>,
+    rules: RuleSet,
+    state: HashMap<RuleKey, RuleState>,
+    /// Learned baselines of `baseline` rules; unlike `state` these are kept
+    /// when a subject is missing from a sample, so hourly buckets survive
+    baselines: HashMap<BaselineKey, Baseline>,
+    samples_seen: u64,
     num_cpus: usize,
 }
 
 impl AnomalyRules {
-    /// Create a new AnomalyRules instance
+    /// Create a new AnomalyRules instance with the default rule set
     ///
     /// # Arguments
     /// * `num_cpus` - Number of CPU cores in the system (used for load average checks)
     pub fn new(num_cpus: usize) -> Self {
+        Self::with_rules(num_cpus, RuleSet::default())
+    }
+
+    /// Create a new AnomalyRules instance with a custom rule set
+    ///
+    /// # Arguments
+    /// * `num_cpus` - Number of CPU cores in the system (used for `per_cpu` rules)
+    /// * `rules` - Validated rule set, e.g. from `RuleSet::from_file`
+    pub fn with_rules(num_cpus: usize, rules: RuleSet) -> Self {
         Self {
-            previous_metrics: None,
+            rules,
+            state: HashMap::new(),
+            baselines: HashMap::new(),
+            samples_seen: 0,
             num_cpus,
         }
     }
 
+    /// Returns the active rule set
+    pub fn rules(&self) -> &RuleSet {
+        &self.rules
+    }
+
+    /// Replace the active rule set, keeping history for rules that still exist
+    ///
+    /// Streaks and previous values are matched by rule name, so a reload that
+    /// only changes thresholds does not restart `for_samples` counts or delta
+    /// comparisons.
+    pub fn replace_rules(&mut self, rules: RuleSet) {
+        let names: HashSet<&str> = rules
+            .rules()
+            .iter()
+            .map(|r| r.definition.name.as_str())
+            .collect();
+        self.state
+            .retain(|key, _| names.contains(key.rule.as_str()));
+        self.baselines
+            .retain(|key, _| names.contains(key.rule.as_str()));
+        self.rules = rules;
+    }
+
     /// Check for anomalies in the current metrics
     ///
-    /// This method compares current metrics against previous values and defined thresholds
-    /// to detect various types of anomalies.
+    /// Every enabled rule is resolved against the snapshot and compared with its
+    /// thresholds. Delta rules compare against the previous sample, and rules with
+    /// `for_samples` or `edge` use the history kept between calls. Baseline
+    /// rules compare against, then learn from, the value.
     ///
     /// # Arguments
     /// * `current` - The current system metrics snapshot
@@ -52,272 +107,266 @@ impl AnomalyRules {
     /// # Returns
     /// A vector of detected anomalies
     pub fn check(&mut self, current: &SystemMetrics) -> Vec<Anomaly> {
+        let snapshot = path::snapshot(current);
         let mut anomalies = Vec::new();
+        let mut next_state = HashMap::new();
 
-        // If we have previous metrics, check for changes/spikes
-        if let Some(ref previous) = self.previous_metrics {
-            self.check_comparative_anomalies(current, previous, &mut anomalies);
-        }
+        for rule in self.rules.rules() {
+            if !rule.definition.enabled {
+                continue;
+            }
 
-        // Check absolute threshold anomalies (don't require previous state)
-        self.check_absolute_anomalies(current, &mut anomalies);
+            for sample in rule.path.resolve(&snapshot) {
+                let key = RuleKey {
+                    rule: rule.definition.name.clone(),
+                    subject: sample.subject.clone().unwrap_or_default(),
+                };
+                let mut state = self.state.remove(&key).unwrap_or_default();
+                let baseline = (rule.definition.mode == RuleMode::Baseline).then(|| {
+                    self.baselines
+                        .entry(BaselineKey::new(
+                            &key.rule,
+                            &key.subject,
+                            &rule.definition.baseline,
+                            current.timestamp,
+                        ))
+                        .or_default()
+                });
+
+                if let Some(mut anomaly) = evaluate(
+                    rule,
+                    &sample,
+                    &mut state,
+                    baseline,
+                    current.timestamp,
+                    self.num_cpus,
+                ) {
+                    attach_culprits(&mut anomaly, current);
+                    anomalies.push(anomaly);
+                }
+                next_state.insert(key, state);
+            }
+        }
 
-        // Store current metrics for next iteration
-        self.previous_metrics = Some(current.clone());
+        // Subjects that disappeared (unmounted disk, removed GPU) lose their history
+        self.state = next_state;
+        self.samples_seen += 1;
 
         anomalies
     }
 
-    /// Check anomalies that require comparison with previous metrics
-    fn check_comparative_anomalies(
-        &self,
-        current: &SystemMetrics,
-        previous: &SystemMetrics,
-        anomalies: &mut Vec<Anomaly>,
-    ) {
-        // CPU spike detection
-        let cpu_delta = current.cpu.global_usage - previous.cpu.global_usage;
-        if cpu_delta > CPU_SPIKE_THRESHOLD {
-            anomalies.push(self.create_anomaly(
-                AnomalySeverity::Warning,
-                AnomalyCategory::Cpu,
-                format!(
-                    "CPU spike detected: {:.0}% → {:.0}%",
-                    previous.cpu.global_usage, current.cpu.global_usage
-                ),
-                serde_json::json!({
-                    "previous": previous.cpu.global_usage,
-                    "current": current.cpu.global_usage,
-                    "delta": cpu_delta,
-                }),
-            ));
-        }
-
-        // CPU sustained high usage (critical only if both current and previous are high)
-        if current.cpu.global_usage > CPU_CRITICAL_THRESHOLD
-            && previous.cpu.global_usage > CPU_CRITICAL_THRESHOLD
-        {
-            anomalies.push(self.create_anomaly(
-                AnomalySeverity::Critical,
-                AnomalyCategory::Cpu,
-                format!(
-                    "Sustained critical CPU usage: {:.0}%",
-                    current.cpu.global_usage
-                ),
-                serde_json::json!({
-                    "usage": current.cpu.global_usage,
-                }),
-            ));
-        }
-
-        // Memory spike detection
-        let mem_delta = current.memory.usage_percent - previous.memory.usage_percent;
-        if mem_delta > MEMORY_SPIKE_THRESHOLD {
-            anomalies.push(self.create_anomaly(
-                AnomalySeverity::Warning,
-                AnomalyCategory::Memory,
-                format!(
-                    "Memory spike detected: {:.0}% → {:.0}%",
-                    previous.memory.usage_percent, current.memory.usage_percent
-                ),
-                serde_json::json!({
-                    "previous": previous.memory.usage_percent,
-                    "current": current.memory.usage_percent,
-                    "delta": mem_delta,
-                }),
-            ));
-        }
-
-        // Temperature spike detection
-        let current_max_temp = get_max_temperature(current);
-        let previous_max_temp = get_max_temperature(previous);
-
-        if current_max_temp > TEMPERATURE_CRITICAL && previous_max_temp <= TEMPERATURE_CRITICAL {
-            anomalies.push(self.create_anomaly(
-                AnomalySeverity::Critical,
-                AnomalyCategory::Temperature,
-                format!("Critical temperature reached: {:.0}°C", current_max_temp),
-                serde_json::json!({
-                    "temperature": current_max_temp,
-                }),
-            ));
-        }
-
-        // Temperature drop detection (possible sensor issue or sudden cooling)
-        let temp_delta = previous_max_temp - current_max_temp;
-        if temp_delta > TEMPERATURE_DROP_THRESHOLD {
-            anomalies.push(self.create_anomaly(
-                AnomalySeverity::Warning,
-                AnomalyCategory::Temperature,
-                format!(
-                    "Sudden temperature drop: {:.0}°C → {:.0}°C",
-                    previous_max_temp, current_max_temp
-                ),
-                serde_json::json!({
-                    "previous": previous_max_temp,
-                    "current": current_max_temp,
-                    "delta": temp_delta,
-                }),
-            ));
-        }
+    /// Returns the rules and subjects whose condition held on the last sample
+    ///
+    /// This includes `for_samples` and edge rules that did not fire on it, so
+    /// an incident stays open until its condition actually clears.
+    pub fn breaching(&self) -> HashSet<IncidentKey> {
+        self.state
+            .iter()
+            .filter(|(_, state)| state.streak > 0)
+            .map(|(key, _)| IncidentKey {
+                rule: key.rule.clone(),
+                subject: (!key.subject.is_empty()).then(|| key.subject.clone()),
+            })
+            .collect()
+    }
 
-        // Swap activation detection (swap was off, now it's on)
-        if current.memory.swap_used > 0 && previous.memory.swap_used == 0 {
-            anomalies.push(self.create_anomaly(
-                AnomalySeverity::Warning,
-                AnomalyCategory::Memory,
-                "SWAP memory activated".to_string(),
-                serde_json::json!({
-                    "swap_used": current.memory.swap_used,
-                }),
-            ));
-        }
+    /// Returns the learned baselines, for persisting between runs
+    pub fn baselines(&self) -> Vec<MetricBaseline> {
+        self.baselines
+            .iter()
+            .map(|(key, baseline)| baseline.to_record(key))
+            .collect()
     }
 
-    /// Check anomalies based on absolute thresholds (no previous state needed)
-    fn check_absolute_anomalies(&self, current: &SystemMetrics, anomalies: &mut Vec<Anomaly>) {
-        // Memory critical threshold
-        if current.memory.usage_percent > MEMORY_CRITICAL_THRESHOLD {
-            anomalies.push(self.create_anomaly(
-                AnomalySeverity::Critical,
-                AnomalyCategory::Memory,
-                format!(
-                    "Critical memory usage: {:.0}%",
-                    current.memory.usage_percent
-                ),
-                serde_json::json!({
-                    "usage": current.memory.usage_percent,
-                }),
-            ));
+    /// Restores baselines saved by a previous run
+    ///
+    /// Baselines of rules that no longer exist or are not in baseline mode
+    /// are ignored.
+    pub fn restore_baselines(&mut self, baselines: Vec<MetricBaseline>) {
+        let names: HashSet<&str> = self
+            .rules
+            .rules()
+            .iter()
+            .filter(|r| r.definition.mode == RuleMode::Baseline)
+            .map(|r| r.definition.name.as_str())
+            .collect();
+
+        for record in &baselines {
+            if names.contains(record.rule.as_str()) {
+                let (key, baseline) = Baseline::from_record(record);
+                self.baselines.insert(key, baseline);
+            }
         }
+    }
 
-        // Disk critical threshold
-        let max_disk_usage = get_max_disk_usage(current);
-        if max_disk_usage > DISK_CRITICAL_THRESHOLD {
-            anomalies.push(self.create_anomaly(
-                AnomalySeverity::Warning,
-                AnomalyCategory::Disk,
-                format!("Critical disk usage: {:.0}%", max_disk_usage),
-                serde_json::json!({
-                    "usage": max_disk_usage,
-                }),
-            ));
-        }
+    /// Reset the state (clear rule history and learned baselines)
+    pub fn reset(&mut self) {
+        self.state.clear();
+        self.baselines.clear();
+        self.samples_seen = 0;
+    }
 
-        // USB timeout detection
-        if has_usb_timeout(current) {
-            anomalies.push(self.create_anomaly(
-                AnomalySeverity::Critical,
-                AnomalyCategory::Usb,
-                "USB timeout detected".to_string(),
-                serde_json::json!({
-                    "devices": current.usb_devices.iter()
-                        .filter(|d| d.has_timeout)
-                        .map(|d| &d.id)
-                        .collect::<Vec<_>>(),
-                }),
-            ));
-        }
+    /// Check if there are previous metrics stored
+    pub fn has_previous_metrics(&self) -> bool {
+        self.samples_seen > 0
+    }
+}
 
-        // High disk I/O detection
-        let max_disk_io = get_max_disk_io(current);
-        if max_disk_io > DISK_IO_HIGH_THRESHOLD {
-            anomalies.push(self.create_anomaly(
-                AnomalySeverity::Warning,
-                AnomalyCategory::Disk,
-                format!("High disk I/O: {:.0} MB/s", max_disk_io),
-                serde_json::json!({
-                    "io_mbs": max_disk_io,
-                }),
+/// Evaluates one rule for one sample, updating its history
+fn evaluate(
+    rule: &CompiledRule,
+    sample: &MetricSample,
+    state: &mut RuleState,
+    baseline: Option<&mut Baseline>,
+    timestamp: DateTime<Utc>,
+    num_cpus: usize,
+) -> Option<Anomaly> {
+    let definition = &rule.definition;
+    let previous = state.last_value.replace(sample.value);
+    // (mean, stddev, samples) of the baseline the value was compared with
+    let mut learned = None;
+
+    let observed = match definition.mode {
+        RuleMode::Value => sample.value,
+        RuleMode::Delta => match previous {
+            Some(previous) => sample.value - previous,
+            None => {
+                state.streak = 0;
+                state.seen = 0;
+                return None;
+            }
+        },
+        RuleMode::Baseline => {
+            let baseline = baseline?;
+            let settings = &definition.baseline;
+            let zscore = baseline.zscore(sample.value, settings);
+            learned = Some((
+                baseline.mean,
+                baseline.stddev().max(settings.min_stddev),
+                baseline.samples,
             ));
+            baseline.update(sample.value, settings.alpha, timestamp);
+
+            match zscore {
+                Some(zscore) => zscore,
+                None => {
+                    // Still warming up
+                    state.streak = 0;
+                    state.seen = 0;
+                    return None;
+                }
+            }
         }
+    };
+
+    state.seen = state.seen.saturating_add(1);
+    let Some((severity, threshold)) = definition.classify(observed, num_cpus) else {
+        state.streak = 0;
+        return None;
+    };
+    state.streak = state.streak.saturating_add(1);
+
+    let fire = if definition.edge {
+        // Only on the transition, and only if we saw the condition not holding
+        state.streak == definition.for_samples && state.seen > state.streak
+    } else {
+        state.streak >= definition.for_samples
+    };
+    if !fire {
+        return None;
+    }
 
-        // High load average detection
-        let load_threshold = (self.num_cpus as f64) * LOAD_AVG_MULTIPLIER;
-        if current.cpu.load_avg_15 > load_threshold {
-            anomalies.push(self.create_anomaly(
-                AnomalySeverity::Critical,
-                AnomalyCategory::Cpu,
-                format!("Critical load average: {:.2}", current.cpu.load_avg_15),
-                serde_json::json!({
-                    "load_avg_15": current.cpu.load_avg_15,
-                    "threshold": load_threshold,
-                    "num_cpus": self.num_cpus,
-                }),
-            ));
-        }
+    let delta = previous.map(|p| sample.value - p);
+    let message = render_message(&definition.message, |name| match name {
+        "value" => Some(sample.value.to_string()),
+        "previous" => previous.map(|p| p.to_string()),
+        "delta" => delta.map(|d| d.to_string()),
+        "threshold" => Some(threshold.to_string()),
+        "subject" => sample.subject.clone(),
+        "mean" => learned.map(|(mean, _, _)| mean.to_string()),
+        "stddev" => learned.map(|(_, stddev, _)| stddev.to_string()),
+        "zscore" => learned.map(|_| observed.to_string()),
+        _ => name
+            .strip_prefix("item.")
+            .and_then(|field| sample.element.as_ref()?.get(field))
+            .map(|v| match v {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            }),
+    });
+
+    let mut metrics = json!({
+        "rule": definition.name,
+        "metric": rule.path.as_str(),
+        "value": sample.value,
+        "threshold": threshold,
+    });
+    if let Some(previous) = previous {
+        metrics["previous"] = json!(previous);
+    }
+    if let Some(delta) = delta {
+        metrics["delta"] = json!(delta);
+    }
+    if let Some(ref subject) = sample.subject {
+        metrics["subject"] = json!(subject);
+    }
+    if !sample.sources.is_empty() {
+        metrics["sources"] = json!(sample.sources);
+    }
+    if let Some(ref element) = sample.element {
+        metrics["details"] = element.clone();
+    }
+    if definition.per_cpu {
+        metrics["num_cpus"] = json!(num_cpus);
+    }
+    if let Some((mean, stddev, samples)) = learned {
+        metrics["baseline"] = json!({
+            "mean": mean,
+            "stddev": stddev,
+            "zscore": observed,
+            "samples": samples,
+        });
+    }
 
-        // GPU anomalies detection
-        if let Some(ref gpu) = current.gpu {
-            // GPU temperature critical
-            if gpu.temperature > GPU_TEMP_CRITICAL {
-                anomalies.push(self.create_anomaly(
-                    AnomalySeverity::Critical,
-                    AnomalyCategory::Gpu,
-                    format!("Critical GPU temperature: {:.0}°C", gpu.temperature),
-                    serde_json::json!({
-                        "temperature": gpu.temperature,
-                        "gpu_name": gpu.name,
-                    }),
-                ));
-            }
+    Some(create_anomaly(
+        severity,
+        definition.category.clone(),
+        message,
+        metrics,
+    ))
+}
 
-            // GPU usage critical
-            if gpu.usage_percent > GPU_USAGE_CRITICAL {
-                anomalies.push(self.create_anomaly(
-                    AnomalySeverity::Warning,
-                    AnomalyCategory::Gpu,
-                    format!("Critical GPU usage: {:.0}%", gpu.usage_percent),
-                    serde_json::json!({
-                        "usage": gpu.usage_percent,
-                        "gpu_name": gpu.name,
-                    }),
-                ));
-            }
+/// Adds the top CPU or memory consumers to the anomaly's metrics payload
+fn attach_culprits(anomaly: &mut Anomaly, current: &SystemMetrics) {
+    let mut processes: Vec<&ProcessInfo> = current.processes.iter().collect();
 
-            // GPU memory critical
-            if gpu.memory_usage_percent > GPU_MEMORY_CRITICAL {
-                anomalies.push(self.create_anomaly(
-                    AnomalySeverity::Warning,
-                    AnomalyCategory::Gpu,
-                    format!("Critical GPU memory usage: {:.0}%", gpu.memory_usage_percent),
-                    serde_json::json!({
-                        "memory_usage": gpu.memory_usage_percent,
-                        "memory_used_mb": gpu.memory_used_mb,
-                        "memory_total_mb": gpu.memory_total_mb,
-                        "gpu_name": gpu.name,
-                    }),
-                ));
-            }
-        }
+    match anomaly.category {
+        AnomalyCategory::Cpu => processes.sort_by(|a, b| b.cpu_usage.total_cmp(&a.cpu_usage)),
+        AnomalyCategory::Memory => processes.sort_by_key(|p| std::cmp::Reverse(p.memory_rss)),
+        _ => return,
     }
 
-    /// Helper method to create an anomaly with consistent structure
-    fn create_anomaly(
-        &self,
-        severity: AnomalySeverity,
-        category: AnomalyCategory,
-        message: String,
-        metrics: serde_json::Value,
-    ) -> Anomaly {
-        Anomaly {
-            id: Uuid::new_v4().to_string(),
-            timestamp: Utc::now(),
-            severity,
-            category,
-            message,
-            metrics,
-        }
-    }
-
-    /// Reset the state (clear previous metrics)
-    pub fn reset(&mut self) {
-        self.previous_metrics = None;
+    if processes.is_empty() {
+        return;
     }
+    processes.truncate(CULPRIT_PROCESSES);
+    anomaly.metrics["processes"] = json!(processes);
+}
 
-    /// Check if there are previous metrics stored
-    pub fn has_previous_metrics(&self) -> bool {
-        self.previous_metrics.is_some()
+/// Helper function to create an anomaly with consistent structure
+fn create_anomaly(
+    severity: AnomalySeverity,
+    category: AnomalyCategory,
+    message: String,
+    metrics: serde_json::Value,
+) -> Anomaly {
+    Anomaly {
+        id: Uuid::new_v4().to_string(),
+        timestamp: Utc::now(),
+        severity,
+        category,
+        message,
+        metrics,
     }
 }
 
@@ -372,8 +421,11 @@ mod tests {
                 tx_bytes: 0,
                 rx_packets: 0,
                 tx_packets: 0,
+                interfaces: vec![],
             },
             gpu: None,
+            processes: vec![],
+            zombies: Default::default(),
         }
     }
 
@@ -454,4 +506,281 @@ mod tests {
         rules.reset();
         assert!(!rules.has_previous_metrics());
     }
+
+    #[test]
+    fn test_disk_and_drop_rules() {
+        let mut rules = AnomalyRules::new(8);
+
+        rules.check(&create_test_metrics(50.0, 50.0, 80.0, 0, 50.0));
+        let anomalies = rules.check(&create_test_metrics(50.0, 50.0, 45.0, 0, 92.0));
+
+        let disk = anomalies
+            .iter()
+            .find(|a| matches!(a.category, AnomalyCategory::Disk))
+            .expect("disk anomaly");
+        assert_eq!(disk.severity, AnomalySeverity::Warning);
+        assert_eq!(disk.message, "Critical disk usage: 92%");
+        assert_eq!(disk.metrics["sources"][0], "/");
+
+        assert!(anomalies
+            .iter()
+            .any(|a| a.message == "Sudden temperature drop: 80°C → 45°C"));
+    }
+
+    #[test]
+    fn test_custom_rules_for_samples() {
+        let rule_set = RuleSet::from_toml_str(
+            r#"
+            [[rules]]
+            name = "busy_core"
+            category = "Cpu"
+            metric = "cpu.per_core[*]"
+            warning = 80.0
+            critical = 95.0
+            for_samples = 3
+            message = "Core {subject} busy: {value:.0}%"
+            "#,
+        )
+        .unwrap();
+        let mut rules = AnomalyRules::with_rules(2, rule_set);
+
+        let mut metrics = create_test_metrics(50.0, 50.0, 60.0, 0, 50.0);
+        metrics.cpu.per_core = vec![85.0, 10.0];
+
+        assert!(rules.check(&metrics).is_empty());
+        assert!(rules.check(&metrics).is_empty());
+
+        metrics.cpu.per_core = vec![97.0, 10.0];
+        let anomalies = rules.check(&metrics);
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].severity, AnomalySeverity::Critical);
+        assert_eq!(anomalies[0].message, "Core 0 busy: 97%");
+
+        // Condition clearing resets the streak
+        metrics.cpu.per_core = vec![10.0, 10.0];
+        assert!(rules.check(&metrics).is_empty());
+        metrics.cpu.per_core = vec![85.0, 10.0];
+        assert!(rules.check(&metrics).is_empty());
+    }
+
+    #[test]
+    fn test_temperature_edge_fires_once() {
+        let mut rules = AnomalyRules::new(8);
+
+        // Already hot on the first sample: no transition observed
+        let hot = create_test_metrics(50.0, 50.0, 90.0, 0, 50.0);
+        assert!(rules.check(&hot).is_empty());
+
+        let normal = create_test_metrics(50.0, 50.0, 70.0, 0, 50.0);
+        rules.check(&normal);
+
+        let hot = create_test_metrics(50.0, 50.0, 88.0, 0, 50.0);
+        assert_eq!(rules.check(&hot).len(), 1);
+        assert!(rules.check(&hot).is_empty());
+
+        // Still breaching although it no longer fires
+        assert!(rules.breaching().contains(&IncidentKey {
+            rule: "temperature_critical".to_string(),
+            subject: None,
+        }));
+
+        rules.check(&normal);
+        assert!(rules.breaching().is_empty());
+    }
+
+    #[test]
+    fn test_replace_rules_keeps_history() {
+        let mut rules = AnomalyRules::new(8);
+
+        rules.check(&create_test_metrics(30.0, 50.0, 60.0, 0, 50.0));
+
+        // Lower the spike threshold; the previous sample must still be known
+        let mut definitions = rules.rules().definitions();
+        for definition in &mut definitions {
+            if definition.name == "cpu_spike" {
+                definition.warning = Some(10.0);
+            }
+        }
+        rules.replace_rules(RuleSet::from_definitions(definitions).unwrap());
+
+        let anomalies = rules.check(&create_test_metrics(45.0, 50.0, 60.0, 0, 50.0));
+        assert!(anomalies
+            .iter()
+            .any(|a| a.message == "CPU spike detected: 30% → 45%"));
+    }
+
+    #[test]
+    fn test_cpu_anomaly_lists_culprits() {
+        let mut rules = AnomalyRules::new(8);
+
+        let process = |pid: u32, cpu_usage: f32| ProcessInfo {
+            pid,
+            name: format!("proc{}", pid),
+            cmdline: String::new(),
+            user: "root".to_string(),
+            state: "Runnable".to_string(),
+            cpu_usage,
+            memory_rss: 1024,
+            disk_read_bytes_per_sec: 0,
+            disk_write_bytes_per_sec: 0,
+        };
+
+        rules.check(&create_test_metrics(20.0, 50.0, 60.0, 0, 50.0));
+        let mut metrics = create_test_metrics(95.0, 50.0, 60.0, 0, 50.0);
+        metrics.processes = vec![process(10, 3.0), process(42, 370.0)];
+
+        let anomalies = rules.check(&metrics);
+        let spike = anomalies
+            .iter()
+            .find(|a| matches!(a.category, AnomalyCategory::Cpu))
+            .expect("cpu anomaly");
+        assert_eq!(spike.metrics["processes"][0]["pid"], 42);
+        assert_eq!(spike.metrics["processes"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_zombie_pileup_names_parent() {
+        use shared::types::{ZombieGroup, ZombieMetrics};
+
+        let mut rules = AnomalyRules::new(8);
+        let mut metrics = create_test_metrics(20.0, 50.0, 60.0, 0, 50.0);
+        metrics.zombies = ZombieMetrics {
+            total: 8,
+            groups: vec![ZombieGroup {
+                parent_pid: 4242,
+                parent_command: "/usr/bin/buggy-daemon --foreground".to_string(),
+                count: 8,
+                commands: vec!["worker".to_string()],
+                pids: (100..108).collect(),
+                oldest_age_secs: 10,
+            }],
+        };
+
+        // Transient zombies that get reaped within a sample are ignored
+        assert!(!rules
+            .check(&metrics)
+            .iter()
+            .any(|a| a.category == AnomalyCategory::Process));
+
+        let anomalies = rules.check(&metrics);
+        let zombie = anomalies
+            .iter()
+            .find(|a| a.category == AnomalyCategory::Process)
+            .expect("process anomaly");
+        assert!(matches!(zombie.severity, AnomalySeverity::Warning));
+        assert_eq!(
+            zombie.message,
+            "8 zombie processes under /usr/bin/buggy-daemon --foreground (pid 4242); \
+             the parent is not reaping its children"
+        );
+        assert_eq!(zombie.metrics["subject"], "4242");
+        assert_eq!(
+            zombie.metrics["details"]["pids"].as_array().unwrap().len(),
+            8
+        );
+    }
+
+    #[test]
+    fn test_network_rate_per_interface() {
+        use shared::types::NetworkInterface;
+
+        let interface = |name: &str, rx_bytes_per_sec: u64| NetworkInterface {
+            name: name.to_string(),
+            rx_bytes_per_sec,
+            tx_bytes_per_sec: 0,
+            rx_packets_per_sec: 0,
+            tx_packets_per_sec: 0,
+            rx_errors: 0,
+            tx_errors: 0,
+            rx_dropped: 0,
+            tx_dropped: 0,
+            operstate: "up".to_string(),
+            speed_mbps: Some(10_000),
+        };
+
+        let mut rules = AnomalyRules::new(8);
+        let mut metrics = create_test_metrics(20.0, 50.0, 60.0, 0, 50.0);
+        // 80 MB/s on each link; only the per-interface rate is checked
+        metrics.network.interfaces = vec![
+            interface("eth0", 80_000_000),
+            interface("eth1", 80_000_000),
+            interface("eth2", 600_000_000),
+        ];
+
+        let network: Vec<_> = rules
+            .check(&metrics)
+            .into_iter()
+            .filter(|a| a.category == AnomalyCategory::Network)
+            .collect();
+        assert_eq!(network.len(), 1);
+        assert!(matches!(network[0].severity, AnomalySeverity::Critical));
+        assert_eq!(network[0].metrics["subject"], "eth2");
+    }
+
+    const BASELINE_RULE: &str = r#"
+        [[rules]]
+        name = "cpu_unusual"
+        category = "Cpu"
+        metric = "cpu.global_usage"
+        mode = "baseline"
+        baseline = { alpha = 0.1, warmup_samples = 20, min_stddev = 1.0 }
+        warning = 4.0
+        critical = 8.0
+        message = "CPU {value:.0}% (normally {mean:.0}% ± {stddev:.0}, z={zscore:.1})"
+    "#;
+
+    #[test]
+    fn test_baseline_learns_per_machine_normal() {
+        let rule_set = RuleSet::from_toml_str(BASELINE_RULE).unwrap();
+        let mut build_server = AnomalyRules::with_rules(8, rule_set.clone());
+        let mut laptop = AnomalyRules::with_rules(8, rule_set);
+
+        for i in 0..100 {
+            let jitter = if i % 2 == 0 { -1.0 } else { 1.0 };
+            let busy = create_test_metrics(85.0 + jitter, 50.0, 60.0, 0, 50.0);
+            let idle = create_test_metrics(5.0 + jitter, 50.0, 60.0, 0, 50.0);
+            assert!(build_server.check(&busy).is_empty());
+            assert!(laptop.check(&idle).is_empty());
+        }
+
+        // 85% is normal for the build server but not for the laptop
+        let busy = create_test_metrics(86.0, 50.0, 60.0, 0, 50.0);
+        assert!(build_server.check(&busy).is_empty());
+
+        let anomalies = laptop.check(&create_test_metrics(60.0, 50.0, 60.0, 0, 50.0));
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].severity, AnomalySeverity::Critical);
+        assert!(anomalies[0].message.starts_with("CPU 60% (normally 5% ± 1"));
+        let baseline = &anomalies[0].metrics["baseline"];
+        assert!(baseline["zscore"].as_f64().unwrap() > 8.0);
+        assert_eq!(anomalies[0].metrics["threshold"], 8.0);
+    }
+
+    #[test]
+    fn test_baseline_warmup_and_restore() {
+        let rule_set = RuleSet::from_toml_str(BASELINE_RULE).unwrap();
+        let mut rules = AnomalyRules::with_rules(8, rule_set.clone());
+
+        // Nothing is known yet, so even a large jump cannot fire
+        for value in [5.0, 90.0, 5.0] {
+            assert!(rules
+                .check(&create_test_metrics(value, 50.0, 60.0, 0, 50.0))
+                .is_empty());
+        }
+
+        rules.reset();
+        for _ in 0..30 {
+            rules.check(&create_test_metrics(5.0, 50.0, 60.0, 0, 50.0));
+        }
+        let saved = rules.baselines();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].rule, "cpu_unusual");
+        assert_eq!(saved[0].samples, 30);
+
+        // A restarted detector continues from the saved baseline
+        let mut restarted = AnomalyRules::with_rules(8, rule_set);
+        restarted.restore_baselines(saved);
+        let anomalies = restarted.check(&create_test_metrics(60.0, 50.0, 60.0, 0, 50.0));
+        assert_eq!(anomalies.len(), 1);
+    }
 }
@@ -1,14 +1,21 @@
 /// Collector service library
 ///
 /// This library provides the core functionality for the system monitoring collector,
-/// including metrics collection, storage, and anomaly detection.
+/// including metrics collection, storage, anomaly detection and offline replay.
 pub mod api;
 pub mod collectors;
+pub mod config;
 pub mod detector;
+pub mod notify;
+pub mod replay;
 pub mod storage;
+pub mod telemetry;
 
 // Re-export commonly used types
 pub use api::{start_server, AppState, ServerConfig};
 pub use collectors::MetricsCollector;
-pub use detector::AnomalyRules;
+pub use config::AppConfig;
+pub use detector::{AnomalyRules, IncidentTracker, RuleMutes, RuleSet, RulesReloader};
+pub use notify::{NotificationConfig, NotificationDispatcher};
 pub use storage::{AnomalyRow, MetricsRepository, MetricsRow};
+pub use telemetry::CollectorTelemetry;
@@ -1,34 +1,109 @@
-use collector::api::{start_server_with_shutdown, ServerConfig};
-use collector::{AnomalyRules, MetricsCollector, MetricsRepository};
-use shared::types::SystemMetrics;
+mod cli;
+
+use clap::Parser;
+use cli::{Cli, Command, DbCommand, RunArgs};
+use collector::api::{start_server_with_state, AppState, LiveEvent, LIVE_EVENT_CAPACITY};
+use collector::detector::ReloadTrigger;
+use collector::storage::{MaintenanceTask, RollupWorker};
+use collector::{
+    AnomalyRules, AppConfig, CollectorTelemetry, IncidentTracker, MetricsCollector,
+    MetricsRepository, NotificationDispatcher, RuleMutes, RuleSet, RulesReloader,
+};
+use shared::types::{Anomaly, SystemMetrics};
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use tokio::time::{interval, timeout, Duration};
+use std::time::Instant;
+use tokio::sync::{broadcast, watch, RwLock};
+use tokio::time::{interval, timeout, Duration, MissedTickBehavior};
 use tokio_util::sync::CancellationToken;
 
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
+/// How often learned detection baselines are written to the database
+const BASELINE_SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often new samples are rolled up into the history tiers
+const ROLLUP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How long exit waits for collector runs still blocked after a timeout
+const BLOCKING_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(1);
+
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    // Invalid settings stop the collector before anything starts
+    let (config, config_path) = cli.load_config()?;
+    let command = cli.command.unwrap_or(Command::Run(RunArgs::default()));
+    if !matches!(command, Command::Run(_)) {
+        cli::init_quiet_tracing();
+    }
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    let result = runtime.block_on(async {
+        match command {
+            Command::Run(_) => run(config, config_path).await,
+            Command::CheckConfig(args) => cli::check_config(&config, config_path, args),
+            Command::Replay(args) => cli::replay(&config, args).await,
+            Command::Export(args) => cli::export(&config, args).await,
+            Command::Db {
+                command: DbCommand::Migrate,
+            } => cli::db_migrate(&config).await,
+            Command::Db {
+                command: DbCommand::Cleanup { json },
+            } => cli::db_cleanup(&config, json).await,
+        }
+    });
+    // Dropping the runtime would wait for a hung nvidia-smi or dmesg to return
+    runtime.shutdown_timeout(BLOCKING_SHUTDOWN_TIMEOUT);
+    result
+}
+
+async fn run(config: AppConfig, config_path: Option<std::path::PathBuf>) -> anyhow::Result<()> {
     // Initialize tracing
     tracing_subscriber::fmt::init();
 
     tracing::info!("Starting System Monitor Collector");
 
     // Configuration
-    let database_url = std::env::var("DATABASE_URL")
-        .unwrap_or_else(|_| "sqlite://./data/system-monitor.db".to_string());
-    let collection_interval = std::env::var("COLLECTION_INTERVAL_SECS")
-        .unwrap_or_else(|_| "2".to_string())
-        .parse::<u64>()
-        .unwrap_or(2);
-    let host = std::env::var("HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
-    let port = std::env::var("PORT")
-        .unwrap_or_else(|_| "5253".to_string())
-        .parse::<u16>()
-        .unwrap_or(5253);
-
+    let database_url = config.database_url.clone();
+    let collection_interval = config.collection_interval_secs;
+    let collector_config = config.collector_config();
+    let maintenance_config = config.maintenance_config();
+    let maintenance_interval = config.retention.maintenance_interval_secs;
+    let server_config = config.server_config();
+
+    if let Some(ref path) = config_path {
+        tracing::info!("Config file: {}", path.display());
+    }
     tracing::info!("Database: {}", database_url);
     tracing::info!("Collection interval: {}s", collection_interval);
-    tracing::info!("API server: {}:{}", host, port);
+    for (name, settings) in &collector_config.collectors {
+        if !settings.enabled {
+            tracing::info!("Collector {} disabled", name);
+        } else if let Some(interval) = settings.interval {
+            tracing::info!("Collector {} interval: {}s", name, interval.as_secs());
+        }
+    }
+    tracing::info!("API server: {}:{}", server_config.host, server_config.port);
+    tracing::info!(
+        "Retention: metrics {}d, anomalies {}d, maintenance every {}s",
+        maintenance_config.metrics_retention_days,
+        maintenance_config.anomalies_retention_days,
+        maintenance_interval
+    );
+    if let Some(mb) = maintenance_config.max_db_size_mb {
+        tracing::info!("Database size limit: {} MiB", mb);
+    }
+
+    // Load detection rules (defaults reproduce the built-in thresholds)
+    let rules = config.rules()?;
+    match config.rules_file {
+        Some(ref path) => tracing::info!(
+            "Loaded {} detection rules from {}",
+            rules.len(),
+            path.display()
+        ),
+        None => tracing::info!("Using default detection rules"),
+    }
+
+    // Load notification sinks (none configured means anomalies are only logged)
+    let notifications = config.notification_config()?;
 
     // Initialize database
     let repo = Arc::new(MetricsRepository::new(&database_url).await?);
@@ -38,35 +113,73 @@ async fn main() -> anyhow::Result<()> {
     repo.run_migrations().await?;
     tracing::info!("Database migrations completed");
 
+    // Continue incidents left open by a previous run
+    let incidents = IncidentTracker::with_incidents(
+        chrono::Duration::seconds(config.incident_cooldown_secs),
+        repo.get_active_incidents().await?,
+    );
+    tracing::info!("Resuming {} open incidents", incidents.active_count());
+
+    // Restore rules muted through the API before the restart
+    let mutes = Arc::new(RuleMutes::new(
+        repo.get_rule_mutes(chrono::Utc::now()).await?,
+    ));
+
     // Create shutdown token for coordinated shutdown
     let shutdown_token = CancellationToken::new();
 
+    // Rules reloader: file watcher, SIGHUP and the API all publish through it
+    let reloader = Arc::new(RulesReloader::new(config.rules_file.clone(), rules));
+    let rules_rx = reloader.subscribe();
+
+    tokio::spawn(Arc::clone(&reloader).watch_file(Duration::from_secs(2), shutdown_token.clone()));
+    tokio::spawn(reload_on_sighup(
+        Arc::clone(&reloader),
+        shutdown_token.clone(),
+    ));
+
+    // Downsample raw samples for long-range history queries
+    tokio::spawn(RollupWorker::new(Arc::clone(&repo)).run(ROLLUP_INTERVAL, shutdown_token.clone()));
+
+    // Retention, vacuuming and the size limit, next to the collection loop
+    let maintenance = Arc::new(MaintenanceTask::new(Arc::clone(&repo), maintenance_config));
+    tokio::spawn(Arc::clone(&maintenance).run(
+        Duration::from_secs(maintenance_interval),
+        shutdown_token.clone(),
+    ));
+
+    // New anomalies are broadcast to the notification sinks
+    let (anomaly_tx, _) = broadcast::channel::<Anomaly>(256);
+    let (events_tx, _) = broadcast::channel::<LiveEvent>(LIVE_EVENT_CAPACITY);
+    let hostname = sysinfo::System::host_name().unwrap_or_else(|| "unknown".to_string());
+    let dispatcher = NotificationDispatcher::new(&notifications, &hostname)?;
+    if dispatcher.is_empty() {
+        tracing::info!("No notification sinks configured");
+    } else {
+        tracing::info!("Starting {} notification sinks", dispatcher.len());
+    }
+    dispatcher.start(&anomaly_tx, shutdown_token.clone());
+
     // Shared state
     let current_metrics: Arc<RwLock<Option<SystemMetrics>>> = Arc::new(RwLock::new(None));
     let recent_anomalies = Arc::new(RwLock::new(Vec::new()));
+    let telemetry = Arc::new(CollectorTelemetry::new());
 
     // Start API server
-    let server_config = ServerConfig {
-        host: host.clone(),
-        port,
-        enable_cors: true,
-        cors_origins: vec!["http://localhost:5252".to_string()],
-    };
-
-    let api_metrics = Arc::clone(&current_metrics);
-    let api_anomalies = Arc::clone(&recent_anomalies);
-    let api_repo = Arc::clone(&repo);
+    let api_state = AppState::new(
+        Arc::clone(&current_metrics),
+        Arc::clone(&recent_anomalies),
+        Arc::clone(&repo),
+    )
+    .with_rules_reloader(Arc::clone(&reloader))
+    .with_telemetry(Arc::clone(&telemetry))
+    .with_maintenance(maintenance)
+    .with_events(events_tx.clone())
+    .with_mutes(Arc::clone(&mutes));
     let api_shutdown_token = shutdown_token.clone();
 
     let api_handle = tokio::spawn(async move {
-        if let Err(e) = start_server_with_shutdown(
-            server_config,
-            api_metrics,
-            api_anomalies,
-            api_repo,
-            api_shutdown_token,
-        )
-        .await
+        if let Err(e) = start_server_with_state(server_config, api_state, api_shutdown_token).await
         {
             tracing::error!("API server error: {}", e);
         }
@@ -74,13 +187,23 @@ async fn main() -> anyhow::Result<()> {
 
     // Start collection loop
     let collection_shutdown_token = shutdown_token.clone();
-    let collection_repo = Arc::clone(&repo);
+    let collector = MetricsCollector::with_config(&collector_config)?;
+    let outputs = CollectionOutputs {
+        repo: Arc::clone(&repo),
+        current_metrics,
+        recent_anomalies,
+        telemetry,
+        anomaly_tx,
+        events_tx,
+        mutes,
+    };
     let collection_handle = tokio::spawn(async move {
         run_collection_loop(
+            collector,
             collection_interval,
-            collection_repo,
-            current_metrics,
-            recent_anomalies,
+            rules_rx,
+            incidents,
+            outputs,
             collection_shutdown_token,
         )
         .await
@@ -120,21 +243,62 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Where the collection loop publishes samples and anomalies
+struct CollectionOutputs {
+    repo: Arc<MetricsRepository>,
+    current_metrics: Arc<RwLock<Option<SystemMetrics>>>,
+    recent_anomalies: Arc<RwLock<Vec<Anomaly>>>,
+    /// Collection timings and anomaly counters for /metrics
+    telemetry: Arc<CollectorTelemetry>,
+    /// New anomalies, consumed by the notification sinks
+    anomaly_tx: broadcast::Sender<Anomaly>,
+    /// Samples, anomalies and incidents for WebSocket clients
+    events_tx: broadcast::Sender<LiveEvent>,
+    /// Rules whose anomalies are dropped
+    mutes: Arc<RuleMutes>,
+}
+
 /// Runs the metrics collection loop
 ///
-/// This function collects system metrics at regular intervals, detects anomalies,
-/// and stores the data in the database. It responds to the shutdown token for
+/// This function collects system metrics at regular intervals, running each
+/// collector on its own schedule, detects anomalies, and stores the data in
+/// the database. Anomalies of muted rules are dropped, and the rest are
+/// grouped into incidents so only new or escalated ones are stored and
+/// notified. Rule sets published on `rules_rx` are swapped in between samples
+/// without losing detector history. It responds to the shutdown token for
 /// graceful termination.
 async fn run_collection_loop(
+    mut collector: MetricsCollector,
     collection_interval: u64,
-    repo: Arc<MetricsRepository>,
-    current_metrics: Arc<RwLock<Option<SystemMetrics>>>,
-    recent_anomalies: Arc<RwLock<Vec<shared::types::Anomaly>>>,
+    mut rules_rx: watch::Receiver<RuleSet>,
+    mut incidents: IncidentTracker,
+    outputs: CollectionOutputs,
     shutdown_token: CancellationToken,
 ) -> anyhow::Result<()> {
-    let mut collector = MetricsCollector::new()?;
-    let mut detector = AnomalyRules::new(num_cpus::get());
+    let CollectionOutputs {
+        repo,
+        current_metrics,
+        recent_anomalies,
+        telemetry,
+        anomaly_tx,
+        events_tx,
+        mutes,
+    } = outputs;
+    let initial_rules = rules_rx.borrow_and_update().clone();
+    let mut detector = AnomalyRules::with_rules(num_cpus::get(), initial_rules);
     let mut tick = interval(Duration::from_secs(collection_interval));
+    // A sample held up by a slow collector should not cause a burst of catch-up ticks
+    tick.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+    // Continue learning from the baselines of the previous run
+    match repo.load_baselines().await {
+        Ok(baselines) => {
+            tracing::info!("Restored {} detection baselines", baselines.len());
+            detector.restore_baselines(baselines);
+        }
+        Err(e) => tracing::warn!("Failed to load detection baselines: {}", e),
+    }
+    let mut baselines_saved = Instant::now();
 
     tracing::info!("Starting collection loop");
 
@@ -142,23 +306,53 @@ async fn run_collection_loop(
         tokio::select! {
             _ = shutdown_token.cancelled() => {
                 tracing::info!("Collection loop received shutdown signal");
+                save_baselines(&repo, &detector).await;
                 break;
             }
+            changed = rules_rx.changed() => {
+                if changed.is_err() {
+                    // Reloader dropped; keep running with the current rules
+                    continue;
+                }
+                let rules = rules_rx.borrow_and_update().clone();
+                tracing::info!("Applying {} detection rules", rules.len());
+                detector.replace_rules(rules);
+            }
             _ = tick.tick() => {
                 // Collect metrics
-                let metrics = match collector.collect_all() {
-                    Ok(m) => m,
-                    Err(e) => {
-                        tracing::error!("Failed to collect metrics: {}", e);
-                        continue;
-                    }
-                };
+                let started = Instant::now();
+                // Collectors run on blocking threads; a hung one is abandoned
+                // and the rest of the sample is used
+                let metrics = collector.collect_all().await;
+                telemetry.record_collection(started.elapsed());
+                if collector.failures() > 0 {
+                    telemetry.record_collection_error();
+                }
+                telemetry.record_collectors(collector.statuses());
+
+                // Detect anomalies, including hung collectors, drop those of
+                // muted rules so they cannot open or reopen incidents, and
+                // fold repeats into incidents
+                let mut raised = detector.check(&metrics);
+                raised.extend(collector.health_anomalies());
+                telemetry.record_anomalies(&raised);
+                let muted = mutes.retain_unmuted(&mut raised, metrics.timestamp);
+                if muted > 0 {
+                    tracing::debug!("Dropped {} anomalies from muted rules", muted);
+                }
+                let update = incidents.process(raised, &detector.breaching(), metrics.timestamp);
+                let anomalies = update.anomalies;
 
-                // Detect anomalies
-                let anomalies = detector.check(&metrics);
+                if baselines_saved.elapsed() >= BASELINE_SAVE_INTERVAL {
+                    save_baselines(&repo, &detector).await;
+                    baselines_saved = Instant::now();
+                }
 
                 // Store in database
-                if let Err(e) = repo.store_metrics(&metrics).await {
+                let started = Instant::now();
+                let stored = repo.store_metrics(&metrics).await;
+                telemetry.record_db_write(started.elapsed(), stored.is_ok());
+                if let Err(e) = stored {
                     tracing::error!("Failed to store metrics: {}", e);
                 }
 
@@ -168,9 +362,20 @@ async fn run_collection_loop(
                     } else {
                         tracing::warn!("[{:?}] {}", anomaly.severity, anomaly.message);
                     }
+                    // Only fails when no sink or client is subscribed
+                    let _ = anomaly_tx.send(anomaly.clone());
+                    let _ = events_tx.send(LiveEvent::Anomaly(anomaly.clone()));
                 }
 
-                // Update shared state for API
+                for incident in &update.incidents {
+                    if let Err(e) = repo.upsert_incident(incident).await {
+                        tracing::error!("Failed to store incident {}: {}", incident.id, e);
+                    }
+                    let _ = events_tx.send(LiveEvent::Incident(incident.clone()));
+                }
+
+                // Update shared state for API and WebSocket clients
+                let _ = events_tx.send(LiveEvent::Metrics(Arc::new(metrics.clone())));
                 *current_metrics.write().await = Some(metrics);
 
                 // Keep last 100 anomalies in memory
@@ -190,6 +395,48 @@ async fn run_collection_loop(
     Ok(())
 }
 
+/// Persists the detector's learned baselines so they survive restarts
+async fn save_baselines(repo: &MetricsRepository, detector: &AnomalyRules) {
+    let baselines = detector.baselines();
+    if baselines.is_empty() {
+        return;
+    }
+    if let Err(e) = repo.save_baselines(&baselines).await {
+        tracing::error!("Failed to save detection baselines: {}", e);
+    }
+}
+
+/// Reloads the detection rules whenever the process receives SIGHUP
+async fn reload_on_sighup(reloader: Arc<RulesReloader>, shutdown_token: CancellationToken) {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut hangup = match signal(SignalKind::hangup()) {
+            Ok(s) => s,
+            Err(e) => {
+                tracing::warn!("Failed to install SIGHUP handler: {}", e);
+                return;
+            }
+        };
+
+        loop {
+            tokio::select! {
+                _ = shutdown_token.cancelled() => break,
+                _ = hangup.recv() => {
+                    tracing::info!("Received SIGHUP, reloading detection rules");
+                    reloader.reload(ReloadTrigger::Signal).await;
+                }
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = (reloader, shutdown_token);
+    }
+}
+
 /// Waits for shutdown signal (SIGINT or SIGTERM) and cancels the token
 async fn shutdown_signal(shutdown_token: CancellationToken) {
     use tokio::signal;
@@ -0,0 +1,318 @@
+/// Notification sink configuration
+///
+/// Sinks are declared in a TOML file with one `[[sinks]]` table per sink. Each
+/// sink has a routing filter (minimum severity and categories) and its own
+/// retry policy.
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+use shared::types::{Anomaly, AnomalyCategory, AnomalySeverity};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use std::time::Duration;
+
+/// Delivery retry policy with exponential backoff
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Total delivery attempts, including the first
+    pub max_attempts: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff_ms: 1000,
+            max_backoff_ms: 60_000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns the delay before retry number `retry` (1-based)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u64.saturating_pow(retry.saturating_sub(1));
+        let delay = self.initial_backoff_ms.saturating_mul(factor);
+        Duration::from_millis(delay.min(self.max_backoff_ms))
+    }
+}
+
+/// Connection security for SMTP
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    /// Plain connection upgraded with STARTTLS
+    #[default]
+    Starttls,
+    /// TLS from the start (usually port 465)
+    Tls,
+    /// No encryption; only for local relays
+    None,
+}
+
+/// Where a sink delivers notifications
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SinkKind {
+    /// POSTs the anomaly as JSON
+    Webhook {
+        url: String,
+        #[serde(default)]
+        headers: BTreeMap<String, String>,
+        #[serde(default = "default_timeout_secs")]
+        timeout_secs: u64,
+    },
+    /// Sends a plain-text email
+    Smtp {
+        host: String,
+        #[serde(default = "default_smtp_port")]
+        port: u16,
+        #[serde(default)]
+        security: SmtpSecurity,
+        username: Option<String>,
+        password: Option<String>,
+        from: String,
+        to: Vec<String>,
+        #[serde(default = "default_timeout_secs")]
+        timeout_secs: u64,
+    },
+    /// Runs a local command, e.g. notify-send
+    Command {
+        command: String,
+        /// Arguments; {severity}, {category}, {message}, {timestamp} and {id}
+        /// are substituted. No shell is involved.
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default = "default_timeout_secs")]
+        timeout_secs: u64,
+    },
+}
+
+fn default_timeout_secs() -> u64 {
+    10
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// A notification sink as written in the notifications file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SinkConfig {
+    /// Unique sink name, used in logs
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: SinkKind,
+    /// Least severe anomaly delivered to this sink
+    #[serde(default = "default_min_severity")]
+    pub min_severity: AnomalySeverity,
+    /// Categories delivered to this sink; empty means all
+    #[serde(default)]
+    pub categories: Vec<AnomalyCategory>,
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_min_severity() -> AnomalySeverity {
+    AnomalySeverity::Warning
+}
+
+impl SinkConfig {
+    /// Returns true if `anomaly` should be delivered to this sink
+    pub fn accepts(&self, anomaly: &Anomaly) -> bool {
+        self.enabled
+            && anomaly.severity >= self.min_severity
+            && (self.categories.is_empty() || self.categories.contains(&anomaly.category))
+    }
+}
+
+/// Validated set of notification sinks
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotificationConfig {
+    #[serde(default)]
+    pub sinks: Vec<SinkConfig>,
+}
+
+impl NotificationConfig {
+    /// Parses and validates sinks from TOML text
+    pub fn from_toml_str(content: &str) -> anyhow::Result<Self> {
+        let config: Self = toml::from_str(content).context("Failed to parse notifications")?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Loads and validates sinks from a TOML file
+    pub fn from_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read notifications file {}", path.display()))?;
+        Self::from_toml_str(&content)
+            .with_context(|| format!("Invalid notifications file {}", path.display()))
+    }
+
+    /// Checks names are unique and each sink is complete
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let mut names = HashSet::new();
+
+        for sink in &self.sinks {
+            if sink.name.trim().is_empty() {
+                bail!("Sink name must not be empty");
+            }
+            if !names.insert(sink.name.as_str()) {
+                bail!("Duplicate sink name '{}'", sink.name);
+            }
+            if sink.retry.max_attempts == 0 {
+                bail!(
+                    "Sink '{}': retry.max_attempts must be at least 1",
+                    sink.name
+                );
+            }
+
+            match sink.kind {
+                SinkKind::Webhook { ref url, .. } => {
+                    reqwest::Url::parse(url)
+                        .with_context(|| format!("Sink '{}': invalid url", sink.name))?;
+                }
+                SinkKind::Smtp {
+                    ref from, ref to, ..
+                } => {
+                    if to.is_empty() {
+                        bail!("Sink '{}': at least one recipient is required", sink.name);
+                    }
+                    for address in std::iter::once(from).chain(to) {
+                        address
+                            .parse::<lettre::message::Mailbox>()
+                            .with_context(|| {
+                                format!("Sink '{}': invalid address '{}'", sink.name, address)
+                            })?;
+                    }
+                }
+                SinkKind::Command { ref command, .. } => {
+                    if command.trim().is_empty() {
+                        bail!("Sink '{}': command must not be empty", sink.name);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+        [[sinks]]
+        name = "ops"
+        type = "webhook"
+        url = "https://hooks.example.com/monitor"
+        headers = { Authorization = "Bearer secret" }
+        min_severity = "Critical"
+
+        [[sinks]]
+        name = "mail"
+        type = "smtp"
+        host = "smtp.example.com"
+        from = "Monitor <monitor@example.com>"
+        to = ["oncall@example.com"]
+        categories = ["Disk", "Temperature"]
+        retry = { max_attempts = 5, initial_backoff_ms = 200 }
+
+        [[sinks]]
+        name = "desktop"
+        type = "command"
+        command = "notify-send"
+        args = ["{severity}: {category}", "{message}"]
+    "#;
+
+    fn anomaly(severity: AnomalySeverity, category: AnomalyCategory) -> Anomaly {
+        Anomaly {
+            id: "1".to_string(),
+            timestamp: chrono::Utc::now(),
+            severity,
+            category,
+            message: "test".to_string(),
+            metrics: serde_json::json!({}),
+        }
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config = NotificationConfig::from_toml_str(CONFIG).unwrap();
+        assert_eq!(config.sinks.len(), 3);
+        assert!(matches!(config.sinks[0].kind, SinkKind::Webhook { .. }));
+        assert_eq!(config.sinks[1].retry.max_attempts, 5);
+        assert_eq!(config.sinks[1].retry.max_backoff_ms, 60_000);
+        assert_eq!(config.sinks[2].min_severity, AnomalySeverity::Warning);
+    }
+
+    #[test]
+    fn test_routing() {
+        let config = NotificationConfig::from_toml_str(CONFIG).unwrap();
+        let (ops, mail, desktop) = (&config.sinks[0], &config.sinks[1], &config.sinks[2]);
+
+        let warning_disk = anomaly(AnomalySeverity::Warning, AnomalyCategory::Disk);
+        assert!(!ops.accepts(&warning_disk));
+        assert!(mail.accepts(&warning_disk));
+        assert!(desktop.accepts(&warning_disk));
+
+        let critical_cpu = anomaly(AnomalySeverity::Critical, AnomalyCategory::Cpu);
+        assert!(ops.accepts(&critical_cpu));
+        assert!(!mail.accepts(&critical_cpu));
+
+        let info = anomaly(AnomalySeverity::Info, AnomalyCategory::Cpu);
+        assert!(!desktop.accepts(&info));
+    }
+
+    #[test]
+    fn test_invalid_config_rejected() {
+        let no_recipients = r#"
+            [[sinks]]
+            name = "mail"
+            type = "smtp"
+            host = "localhost"
+            from = "monitor@example.com"
+            to = []
+        "#;
+        assert!(NotificationConfig::from_toml_str(no_recipients).is_err());
+
+        let bad_url = r#"
+            [[sinks]]
+            name = "hook"
+            type = "webhook"
+            url = "not a url"
+        "#;
+        assert!(NotificationConfig::from_toml_str(bad_url).is_err());
+    }
+
+    #[test]
+    fn test_example_file_is_valid() {
+        let config =
+            NotificationConfig::from_toml_str(include_str!("../../notifications.example.toml"))
+                .unwrap();
+        assert_eq!(config.sinks.len(), 3);
+        assert!(!config.sinks[2].enabled);
+    }
+
+    #[test]
+    fn test_backoff() {
+        let retry = RetryPolicy {
+            max_attempts: 5,
+            initial_backoff_ms: 100,
+            max_backoff_ms: 350,
+        };
+        assert_eq!(retry.backoff(1), Duration::from_millis(100));
+        assert_eq!(retry.backoff(2), Duration::from_millis(200));
+        assert_eq!(retry.backoff(3), Duration::from_millis(350));
+    }
+}
@@ -0,0 +1,364 @@
+/// Routes new anomalies to notification sinks
+///
+/// Each sink runs in its own task with its own subscription to the anomaly
+/// broadcast channel, so a slow or failing sink (retrying with backoff) never
+/// delays delivery to the others.
+use shared::types::Anomaly;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use super::config::{NotificationConfig, RetryPolicy, SinkConfig};
+use super::sinks::Sink;
+
+/// Delivers anomalies to every enabled sink whose routing filter matches
+pub struct NotificationDispatcher {
+    sinks: Vec<(SinkConfig, Sink)>,
+}
+
+impl NotificationDispatcher {
+    /// Builds the enabled sinks from `config`
+    ///
+    /// # Arguments
+    /// * `config` - Validated notification configuration
+    /// * `hostname` - Name of this machine, included in notifications
+    pub fn new(config: &NotificationConfig, hostname: &str) -> anyhow::Result<Self> {
+        let mut sinks = Vec::new();
+        for sink_config in config.sinks.iter().filter(|s| s.enabled) {
+            let sink = Sink::from_config(&sink_config.kind, hostname)
+                .map_err(|e| e.context(format!("Sink '{}'", sink_config.name)))?;
+            sinks.push((sink_config.clone(), sink));
+        }
+        Ok(Self { sinks })
+    }
+
+    pub fn len(&self) -> usize {
+        self.sinks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+
+    /// Spawns one delivery task per sink, subscribed to `anomalies`
+    pub fn start(
+        self,
+        anomalies: &broadcast::Sender<Anomaly>,
+        shutdown: CancellationToken,
+    ) -> Vec<JoinHandle<()>> {
+        self.sinks
+            .into_iter()
+            .map(|(config, sink)| {
+                tokio::spawn(run_sink(
+                    config,
+                    sink,
+                    anomalies.subscribe(),
+                    shutdown.clone(),
+                ))
+            })
+            .collect()
+    }
+}
+
+async fn run_sink(
+    config: SinkConfig,
+    sink: Sink,
+    mut rx: broadcast::Receiver<Anomaly>,
+    shutdown: CancellationToken,
+) {
+    tracing::info!("Notification sink '{}' started", config.name);
+
+    loop {
+        let anomaly = tokio::select! {
+            _ = shutdown.cancelled() => break,
+            received = rx.recv() => match received {
+                Ok(anomaly) => anomaly,
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!(
+                        "Notification sink '{}' fell behind, skipped {} anomalies",
+                        config.name,
+                        skipped
+                    );
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            },
+        };
+
+        if config.accepts(&anomaly) {
+            deliver(&config.name, &sink, &config.retry, &anomaly, &shutdown).await;
+        }
+    }
+
+    tracing::info!("Notification sink '{}' stopped", config.name);
+}
+
+/// Sends `anomaly`, retrying with exponential backoff
+///
+/// Returns true if the anomaly was delivered.
+async fn deliver(
+    name: &str,
+    sink: &Sink,
+    retry: &RetryPolicy,
+    anomaly: &Anomaly,
+    shutdown: &CancellationToken,
+) -> bool {
+    for attempt in 1..=retry.max_attempts {
+        match sink.send(anomaly).await {
+            Ok(()) => {
+                tracing::debug!("Sent anomaly {} to '{}'", anomaly.id, name);
+                return true;
+            }
+            Err(e) if attempt < retry.max_attempts => {
+                let delay = retry.backoff(attempt);
+                tracing::warn!(
+                    "Notification sink '{}' failed (attempt {}/{}), retrying in {:?}: {:#}",
+                    name,
+                    attempt,
+                    retry.max_attempts,
+                    delay,
+                    e
+                );
+                tokio::select! {
+                    _ = shutdown.cancelled() => return false,
+                    _ = tokio::time::sleep(delay) => {}
+                }
+            }
+            Err(e) => {
+                tracing::error!(
+                    "Notification sink '{}' gave up on anomaly {} after {} attempts: {:#}",
+                    name,
+                    anomaly.id,
+                    retry.max_attempts,
+                    e
+                );
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, http::StatusCode, routing::post, Json, Router};
+    use shared::types::{AnomalyCategory, AnomalySeverity};
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    fn anomaly(severity: AnomalySeverity) -> Anomaly {
+        Anomaly {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: chrono::Utc::now(),
+            severity,
+            category: AnomalyCategory::Disk,
+            message: "Disk /home at 97%".to_string(),
+            metrics: serde_json::json!({ "value": 97.0 }),
+        }
+    }
+
+    /// HTTP stub that fails the first `failures` requests and records bodies
+    async fn webhook_stub(failures: usize) -> (String, Arc<Mutex<Vec<serde_json::Value>>>) {
+        #[derive(Clone)]
+        struct Stub {
+            failures: Arc<Mutex<usize>>,
+            received: Arc<Mutex<Vec<serde_json::Value>>>,
+        }
+
+        async fn handler(
+            State(stub): State<Stub>,
+            Json(body): Json<serde_json::Value>,
+        ) -> StatusCode {
+            let mut failures = stub.failures.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                return StatusCode::SERVICE_UNAVAILABLE;
+            }
+            stub.received.lock().unwrap().push(body);
+            StatusCode::OK
+        }
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let stub = Stub {
+            failures: Arc::new(Mutex::new(failures)),
+            received: Arc::clone(&received),
+        };
+        let app = Router::new().route("/hook", post(handler)).with_state(stub);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (url, received)
+    }
+
+    /// Minimal SMTP server accepting one message per connection
+    async fn smtp_stub() -> (u16, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let stored = Arc::clone(&messages);
+
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let stored = Arc::clone(&stored);
+                tokio::spawn(async move {
+                    let (read, mut write) = stream.into_split();
+                    let mut lines = BufReader::new(read).lines();
+                    write.write_all(b"220 stub ESMTP\r\n").await.unwrap();
+
+                    let mut data: Option<String> = None;
+                    while let Ok(Some(line)) = lines.next_line().await {
+                        if let Some(ref mut body) = data {
+                            if line == "." {
+                                stored.lock().unwrap().push(std::mem::take(body));
+                                data = None;
+                                write.write_all(b"250 queued\r\n").await.unwrap();
+                            } else {
+                                body.push_str(&line);
+                                body.push('\n');
+                            }
+                            continue;
+                        }
+
+                        let command = line.to_ascii_uppercase();
+                        let reply: &[u8] = if command.starts_with("EHLO") {
+                            b"250-stub\r\n250 8BITMIME\r\n"
+                        } else if command.starts_with("DATA") {
+                            data = Some(String::new());
+                            b"354 go ahead\r\n"
+                        } else if command.starts_with("QUIT") {
+                            write.write_all(b"221 bye\r\n").await.unwrap();
+                            break;
+                        } else {
+                            b"250 ok\r\n"
+                        };
+                        write.write_all(reply).await.unwrap();
+                    }
+                });
+            }
+        });
+
+        (port, messages)
+    }
+
+    fn fast_retry() -> String {
+        "retry = { max_attempts = 3, initial_backoff_ms = 10, max_backoff_ms = 50 }".to_string()
+    }
+
+    #[tokio::test]
+    async fn test_webhook_retries_until_delivered() {
+        let (url, received) = webhook_stub(2).await;
+        let config = NotificationConfig::from_toml_str(&format!(
+            "[[sinks]]\nname = \"hook\"\ntype = \"webhook\"\nurl = \"{}\"\n{}\n",
+            url,
+            fast_retry()
+        ))
+        .unwrap();
+        let dispatcher = NotificationDispatcher::new(&config, "test-host").unwrap();
+        let (config, sink) = &dispatcher.sinks[0];
+
+        let anomaly = anomaly(AnomalySeverity::Critical);
+        let shutdown = CancellationToken::new();
+        assert!(deliver("hook", sink, &config.retry, &anomaly, &shutdown).await);
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0]["hostname"], "test-host");
+        assert_eq!(received[0]["anomaly"]["id"], anomaly.id.as_str());
+    }
+
+    #[tokio::test]
+    async fn test_webhook_gives_up() {
+        let (url, received) = webhook_stub(10).await;
+        let config = NotificationConfig::from_toml_str(&format!(
+            "[[sinks]]\nname = \"hook\"\ntype = \"webhook\"\nurl = \"{}\"\n{}\n",
+            url,
+            fast_retry()
+        ))
+        .unwrap();
+        let dispatcher = NotificationDispatcher::new(&config, "test-host").unwrap();
+        let (config, sink) = &dispatcher.sinks[0];
+
+        let shutdown = CancellationToken::new();
+        let anomaly = anomaly(AnomalySeverity::Critical);
+        assert!(!deliver("hook", sink, &config.retry, &anomaly, &shutdown).await);
+        assert!(received.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dispatcher_routes_to_smtp() {
+        let (port, messages) = smtp_stub().await;
+        let config = NotificationConfig::from_toml_str(&format!(
+            r#"
+            [[sinks]]
+            name = "mail"
+            type = "smtp"
+            host = "127.0.0.1"
+            port = {}
+            security = "none"
+            from = "monitor@example.com"
+            to = ["oncall@example.com"]
+            min_severity = "Critical"
+            {}
+            "#,
+            port,
+            fast_retry()
+        ))
+        .unwrap();
+
+        let (tx, _) = broadcast::channel(16);
+        let shutdown = CancellationToken::new();
+        let handles = NotificationDispatcher::new(&config, "test-host")
+            .unwrap()
+            .start(&tx, shutdown.clone());
+
+        // Filtered out by min_severity
+        tx.send(anomaly(AnomalySeverity::Warning)).unwrap();
+        tx.send(anomaly(AnomalySeverity::Critical)).unwrap();
+
+        for _ in 0..100 {
+            if !messages.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        shutdown.cancel();
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        let messages = messages.lock().unwrap();
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].contains("Subject: [Critical] Disk anomaly on test-host"));
+        assert!(messages[0].contains("Disk /home at 97%"));
+    }
+
+    #[tokio::test]
+    async fn test_command_sink() {
+        let path = std::env::temp_dir().join(format!("notify-{}.txt", uuid::Uuid::new_v4()));
+        let config = NotificationConfig::from_toml_str(&format!(
+            r#"
+            [[sinks]]
+            name = "cmd"
+            type = "command"
+            command = "sh"
+            args = ["-c", "printf '%s' \"$0\" > {}", "{{severity}} {{message}}"]
+            "#,
+            path.display()
+        ))
+        .unwrap();
+        let dispatcher = NotificationDispatcher::new(&config, "test-host").unwrap();
+        let (config, sink) = &dispatcher.sinks[0];
+
+        let shutdown = CancellationToken::new();
+        let anomaly = anomaly(AnomalySeverity::Warning);
+        assert!(deliver("cmd", sink, &config.retry, &anomaly, &shutdown).await);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "Warning Disk /home at 97%"
+        );
+
+        std::fs::remove_file(&path).ok();
+    }
+}
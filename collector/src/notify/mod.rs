@@ -0,0 +1,10 @@
+/// Anomaly notification module
+///
+/// Routes anomalies raised by the collection loop to configured sinks: JSON
+/// webhooks, SMTP email and local commands such as notify-send.
+mod config;
+mod dispatcher;
+mod sinks;
+
+pub use config::{NotificationConfig, RetryPolicy, SinkConfig, SinkKind, SmtpSecurity};
+pub use dispatcher::NotificationDispatcher;
@@ -0,0 +1,244 @@
+/// Notification sink implementations
+///
+/// Each sink delivers a single anomaly per call and reports failure as an
+/// error; retries are handled by the dispatcher.
+use anyhow::{bail, Context};
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde_json::json;
+use shared::types::Anomaly;
+use std::time::Duration;
+
+use super::config::{SinkKind, SmtpSecurity};
+
+/// A configured delivery target
+pub enum Sink {
+    Webhook(WebhookSink),
+    // The SMTP transport is large; keep the enum small
+    Smtp(Box<SmtpSink>),
+    Command(CommandSink),
+}
+
+impl Sink {
+    /// Builds the sink described by `kind`
+    ///
+    /// # Arguments
+    /// * `kind` - Sink settings from the notifications file
+    /// * `hostname` - Name of this machine, included in every notification
+    pub fn from_config(kind: &SinkKind, hostname: &str) -> anyhow::Result<Self> {
+        let sink = match kind {
+            SinkKind::Webhook {
+                url,
+                headers,
+                timeout_secs,
+            } => {
+                let mut header_map = reqwest::header::HeaderMap::new();
+                for (name, value) in headers {
+                    header_map.insert(
+                        reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                            .with_context(|| format!("Invalid header name '{}'", name))?,
+                        reqwest::header::HeaderValue::from_str(value)
+                            .with_context(|| format!("Invalid value for header '{}'", name))?,
+                    );
+                }
+                let client = reqwest::Client::builder()
+                    .timeout(Duration::from_secs(*timeout_secs))
+                    .default_headers(header_map)
+                    .build()
+                    .context("Failed to build HTTP client")?;
+
+                Sink::Webhook(WebhookSink {
+                    client,
+                    url: url.clone(),
+                    hostname: hostname.to_string(),
+                })
+            }
+            SinkKind::Smtp {
+                host,
+                port,
+                security,
+                username,
+                password,
+                from,
+                to,
+                timeout_secs,
+            } => {
+                let mut builder = match security {
+                    SmtpSecurity::Starttls => {
+                        AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?
+                    }
+                    SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host)?,
+                    SmtpSecurity::None => {
+                        AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host)
+                    }
+                }
+                .port(*port)
+                .timeout(Some(Duration::from_secs(*timeout_secs)));
+
+                if let (Some(username), Some(password)) = (username, password) {
+                    builder =
+                        builder.credentials(Credentials::new(username.clone(), password.clone()));
+                }
+
+                Sink::Smtp(Box::new(SmtpSink {
+                    transport: builder.build(),
+                    from: from.parse()?,
+                    to: to
+                        .iter()
+                        .map(|address| address.parse())
+                        .collect::<Result<_, _>>()?,
+                    hostname: hostname.to_string(),
+                }))
+            }
+            SinkKind::Command {
+                command,
+                args,
+                timeout_secs,
+            } => Sink::Command(CommandSink {
+                command: command.clone(),
+                args: args.clone(),
+                timeout: Duration::from_secs(*timeout_secs),
+            }),
+        };
+
+        Ok(sink)
+    }
+
+    /// Delivers one anomaly
+    pub async fn send(&self, anomaly: &Anomaly) -> anyhow::Result<()> {
+        match self {
+            Sink::Webhook(sink) => sink.send(anomaly).await,
+            Sink::Smtp(sink) => sink.send(anomaly).await,
+            Sink::Command(sink) => sink.send(anomaly).await,
+        }
+    }
+}
+
+/// POSTs `{"hostname": ..., "anomaly": {...}}` to a URL
+pub struct WebhookSink {
+    client: reqwest::Client,
+    url: String,
+    hostname: String,
+}
+
+impl WebhookSink {
+    async fn send(&self, anomaly: &Anomaly) -> anyhow::Result<()> {
+        self.client
+            .post(&self.url)
+            .json(&json!({
+                "hostname": self.hostname,
+                "anomaly": anomaly,
+            }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Sends a plain-text email through an SMTP relay
+pub struct SmtpSink {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+    hostname: String,
+}
+
+impl SmtpSink {
+    async fn send(&self, anomaly: &Anomaly) -> anyhow::Result<()> {
+        let mut builder = Message::builder().from(self.from.clone()).subject(format!(
+            "[{:?}] {:?} anomaly on {}: {}",
+            anomaly.severity, anomaly.category, self.hostname, anomaly.message
+        ));
+        for to in &self.to {
+            builder = builder.to(to.clone());
+        }
+
+        let body = format!(
+            "{}\n\nHost: {}\nSeverity: {:?}\nCategory: {:?}\nTime: {}\nId: {}\n\nDetails:\n{}\n",
+            anomaly.message,
+            self.hostname,
+            anomaly.severity,
+            anomaly.category,
+            anomaly.timestamp.to_rfc3339(),
+            anomaly.id,
+            serde_json::to_string_pretty(&anomaly.metrics).unwrap_or_default(),
+        );
+        let email = builder.header(ContentType::TEXT_PLAIN).body(body)?;
+
+        self.transport.send(email).await?;
+        Ok(())
+    }
+}
+
+/// Runs a local command with the anomaly substituted into its arguments
+pub struct CommandSink {
+    command: String,
+    args: Vec<String>,
+    timeout: Duration,
+}
+
+impl CommandSink {
+    async fn send(&self, anomaly: &Anomaly) -> anyhow::Result<()> {
+        let args: Vec<String> = self
+            .args
+            .iter()
+            .map(|arg| substitute(arg, anomaly))
+            .collect();
+
+        let child = tokio::process::Command::new(&self.command)
+            .args(&args)
+            .kill_on_drop(true)
+            .output();
+        let output = tokio::time::timeout(self.timeout, child)
+            .await
+            .with_context(|| format!("'{}' timed out after {:?}", self.command, self.timeout))?
+            .with_context(|| format!("Failed to run '{}'", self.command))?;
+
+        if !output.status.success() {
+            bail!(
+                "'{}' exited with {}: {}",
+                self.command,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Replaces anomaly placeholders in a command argument
+fn substitute(template: &str, anomaly: &Anomaly) -> String {
+    template
+        .replace("{severity}", &format!("{:?}", anomaly.severity))
+        .replace("{category}", &format!("{:?}", anomaly.category))
+        .replace("{message}", &anomaly.message)
+        .replace("{timestamp}", &anomaly.timestamp.to_rfc3339())
+        .replace("{id}", &anomaly.id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::types::{AnomalyCategory, AnomalySeverity};
+
+    #[test]
+    fn test_substitute() {
+        let anomaly = Anomaly {
+            id: "abc".to_string(),
+            timestamp: chrono::Utc::now(),
+            severity: AnomalySeverity::Critical,
+            category: AnomalyCategory::Temperature,
+            message: "CPU at 95°C".to_string(),
+            metrics: json!({}),
+        };
+
+        assert_eq!(
+            substitute("{severity}: {category} ({id})", &anomaly),
+            "Critical: Temperature (abc)"
+        );
+        assert_eq!(substitute("{message}", &anomaly), "CPU at 95°C");
+    }
+}
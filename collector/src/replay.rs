@@ -0,0 +1,352 @@
+/// Offline replay of recorded metrics through the detection rules
+///
+/// Samples from the database or an exported file are fed through a fresh
+/// `AnomalyRules` and `IncidentTracker`, exactly like the collection loop
+/// does, to see which anomalies a rule set would have raised. The result can
+/// be diffed against the anomalies that were actually stored, which answers
+/// "what would the new thresholds have fired last week?".
+use chrono::{DateTime, Duration, Utc};
+use shared::types::{Anomaly, SystemMetrics};
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::Path;
+
+use crate::api::parse_duration;
+use crate::collectors::COLLECTOR_TIMEOUT_RULE;
+use crate::detector::{AnomalyRules, IncidentKey, IncidentTracker, RuleSet};
+use crate::storage::MetricsRepository;
+use sqlx::Error as SqlxError;
+
+/// Span of samples loaded from the database at a time
+pub const REPLAY_CHUNK: Duration = Duration::hours(1);
+
+/// Default distance between a replayed and a stored anomaly that still
+/// counts as the same firing
+pub const DEFAULT_MATCH_TOLERANCE: Duration = Duration::seconds(5);
+
+/// Feeds samples through a rule set and collects what would have fired
+pub struct Replay {
+    rules: Option<RuleSet>,
+    num_cpus: Option<usize>,
+    detector: Option<AnomalyRules>,
+    incidents: IncidentTracker,
+    samples: u64,
+    first: Option<DateTime<Utc>>,
+    last: Option<DateTime<Utc>>,
+}
+
+impl Replay {
+    /// Creates a replay of `rules` with a fresh detector and no open incidents
+    ///
+    /// # Arguments
+    /// * `rules` - Rule set to evaluate, e.g. a candidate rules file
+    /// * `cooldown` - Incident cooldown, as configured for the collector
+    pub fn new(rules: RuleSet, cooldown: Duration) -> Self {
+        Self {
+            rules: Some(rules),
+            num_cpus: None,
+            detector: None,
+            incidents: IncidentTracker::new(cooldown),
+            samples: 0,
+            first: None,
+            last: None,
+        }
+    }
+
+    /// Sets the core count used by `per_cpu` rules
+    ///
+    /// By default it is taken from the first replayed sample.
+    pub fn with_num_cpus(mut self, num_cpus: usize) -> Self {
+        self.num_cpus = Some(num_cpus);
+        self
+    }
+
+    /// Evaluates one sample
+    ///
+    /// # Returns
+    /// The anomalies the collection loop would have stored for this sample,
+    /// i.e. those opening or escalating an incident. Their timestamp is the
+    /// sample's.
+    pub fn feed(&mut self, metrics: &SystemMetrics) -> Vec<Anomaly> {
+        let detector = match self.detector {
+            Some(ref mut detector) => detector,
+            None => {
+                let num_cpus = self
+                    .num_cpus
+                    .or_else(|| Some(metrics.cpu.per_core.len()).filter(|n| *n > 0))
+                    .unwrap_or_else(num_cpus::get);
+                let rules = self.rules.take().unwrap_or_default();
+                self.detector
+                    .insert(AnomalyRules::with_rules(num_cpus, rules))
+            }
+        };
+
+        let mut raised = detector.check(metrics);
+        for anomaly in &mut raised {
+            anomaly.timestamp = metrics.timestamp;
+        }
+        let update = self
+            .incidents
+            .process(raised, &detector.breaching(), metrics.timestamp);
+
+        self.samples += 1;
+        self.first.get_or_insert(metrics.timestamp);
+        self.last = Some(metrics.timestamp);
+
+        update.anomalies
+    }
+
+    /// Number of samples fed so far
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    /// Timestamps of the first and last sample fed, if any
+    pub fn span(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        Some((self.first?, self.last?))
+    }
+}
+
+/// Comparison of replayed anomalies with stored ones
+#[derive(Debug, Default)]
+pub struct ReplayDiff {
+    /// Replayed anomalies paired with the stored anomaly they match
+    pub matched: Vec<(Anomaly, Anomaly)>,
+    /// Would have fired but were not stored
+    pub added: Vec<Anomaly>,
+    /// Were stored but would not have fired
+    pub removed: Vec<Anomaly>,
+}
+
+/// Pairs replayed and stored anomalies of the same rule and subject
+///
+/// A replayed anomaly matches the closest unmatched stored anomaly of its
+/// rule and subject within `tolerance`. Stored `collector_timeout` anomalies
+/// are skipped since replay cannot reproduce a hung collector.
+pub fn diff_anomalies(replayed: &[Anomaly], stored: &[Anomaly], tolerance: Duration) -> ReplayDiff {
+    let mut candidates: Vec<(IncidentKey, &Anomaly)> = stored
+        .iter()
+        .map(|a| (IncidentKey::from_anomaly(a), a))
+        .filter(|(key, _)| key.rule != COLLECTOR_TIMEOUT_RULE)
+        .collect();
+    let mut diff = ReplayDiff::default();
+
+    for anomaly in replayed {
+        let key = IncidentKey::from_anomaly(anomaly);
+        let closest = candidates
+            .iter()
+            .enumerate()
+            .filter(|(_, (k, _))| *k == key)
+            .map(|(i, (_, s))| (i, (s.timestamp - anomaly.timestamp).abs()))
+            .filter(|(_, distance)| *distance <= tolerance)
+            .min_by_key(|(_, distance)| *distance);
+
+        match closest {
+            Some((i, _)) => {
+                let (_, stored) = candidates.remove(i);
+                diff.matched.push((anomaly.clone(), stored.clone()));
+            }
+            None => diff.added.push(anomaly.clone()),
+        }
+    }
+
+    diff.removed = candidates.into_iter().map(|(_, a)| a.clone()).collect();
+    diff
+}
+
+/// Streams stored samples with `start <= timestamp <= end` in time order
+///
+/// Samples are loaded `REPLAY_CHUNK` at a time so long ranges do not have
+/// to fit in memory.
+///
+/// # Arguments
+/// * `repo` - Database to read from
+/// * `start` - Start of time range
+/// * `end` - End of time range (inclusive, as in `get_metrics_range`)
+/// * `each` - Called with every sample
+///
+/// # Returns
+/// Number of samples read
+pub async fn for_each_stored(
+    repo: &MetricsRepository,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    mut each: impl FnMut(&SystemMetrics),
+) -> Result<u64, SqlxError> {
+    let mut count = 0;
+    let mut cursor = start;
+
+    while cursor <= end {
+        let next = cursor + REPLAY_CHUNK;
+        let samples = if next > end {
+            repo.get_metrics_range(cursor, end).await?
+        } else {
+            repo.get_metrics_between(cursor, next).await?
+        };
+        for metrics in &samples {
+            each(metrics);
+        }
+        count += samples.len() as u64;
+        if next > end {
+            break;
+        }
+        cursor = next;
+    }
+
+    Ok(count)
+}
+
+/// Reads samples exported as JSON, one `SystemMetrics` object per line
+///
+/// Concatenated or pretty-printed objects are accepted as well. Samples are
+/// parsed lazily, so the file does not have to fit in memory.
+pub fn read_metrics_file(
+    path: impl AsRef<Path>,
+) -> io::Result<impl Iterator<Item = io::Result<SystemMetrics>>> {
+    let reader = BufReader::new(File::open(path)?);
+    Ok(serde_json::Deserializer::from_reader(reader)
+        .into_iter::<SystemMetrics>()
+        .map(|sample| sample.map_err(io::Error::from)))
+}
+
+/// Parses a replay bound: an RFC3339 timestamp, or a duration before `now`
+/// such as `7d` or `12h`
+pub fn parse_time(value: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+    parse_duration(value).and_then(|ago| now.checked_sub_signed(ago))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use shared::types::{AnomalyCategory, AnomalySeverity};
+
+    fn sample(at: DateTime<Utc>, cpu: f32) -> SystemMetrics {
+        let mut metrics = SystemMetrics {
+            timestamp: at,
+            ..Default::default()
+        };
+        metrics.cpu.global_usage = cpu;
+        metrics.cpu.per_core = vec![cpu; 4];
+        metrics
+    }
+
+    fn anomaly(rule: &str, subject: Option<&str>, at: DateTime<Utc>) -> Anomaly {
+        Anomaly {
+            id: format!("{}-{}", rule, at.timestamp()),
+            timestamp: at,
+            severity: AnomalySeverity::Warning,
+            category: AnomalyCategory::Cpu,
+            message: rule.to_string(),
+            metrics: json!({ "rule": rule, "subject": subject }),
+        }
+    }
+
+    #[test]
+    fn test_replay_folds_repeats_into_incidents() {
+        let rules = RuleSet::from_toml_str(
+            r#"
+            [[rules]]
+            name = "cpu_busy"
+            metric = "cpu.global_usage"
+            category = "Cpu"
+            warning = 90.0
+            message = "CPU at {value}%"
+            "#,
+        )
+        .unwrap();
+        let mut replay = Replay::new(rules, Duration::minutes(5));
+        let t0 = Utc::now() - Duration::hours(1);
+
+        let fired: Vec<usize> = [50.0, 95.0, 96.0, 40.0, 97.0]
+            .iter()
+            .enumerate()
+            .map(|(i, cpu)| {
+                let at = t0 + Duration::seconds(2 * i as i64);
+                replay.feed(&sample(at, *cpu)).len()
+            })
+            .collect();
+
+        // The second firing repeats the open incident, the last reopens it
+        // within the cooldown, so neither is stored again
+        assert_eq!(fired, [0, 1, 0, 0, 0]);
+        assert_eq!(replay.samples(), 5);
+        assert_eq!(replay.span(), Some((t0, t0 + Duration::seconds(8))));
+    }
+
+    #[test]
+    fn test_replayed_anomalies_carry_sample_time() {
+        let mut replay = Replay::new(RuleSet::default(), Duration::minutes(5));
+        let at = Utc::now() - Duration::days(3);
+        let mut metrics = sample(at, 10.0);
+        metrics.memory.usage_percent = 99.0;
+
+        let anomalies = replay.feed(&metrics);
+        assert!(!anomalies.is_empty());
+        assert!(anomalies.iter().all(|a| a.timestamp == at));
+    }
+
+    #[test]
+    fn test_diff_anomalies() {
+        let t0 = Utc::now();
+        let replayed = vec![
+            anomaly("cpu_busy", None, t0),
+            anomaly("disk_full", Some("/"), t0),
+            anomaly("disk_full", Some("/home"), t0),
+        ];
+        let stored = vec![
+            anomaly("cpu_busy", None, t0 + Duration::seconds(1)),
+            anomaly("disk_full", Some("/"), t0 + Duration::minutes(5)),
+            anomaly(COLLECTOR_TIMEOUT_RULE, Some("gpu"), t0),
+            anomaly("memory_high", None, t0),
+        ];
+
+        let diff = diff_anomalies(&replayed, &stored, DEFAULT_MATCH_TOLERANCE);
+        let rules = |anomalies: &[Anomaly]| -> Vec<String> {
+            anomalies
+                .iter()
+                .map(IncidentKey::from_anomaly)
+                .map(|k| format!("{}{}", k.rule, k.subject.unwrap_or_default()))
+                .collect()
+        };
+
+        assert_eq!(diff.matched.len(), 1);
+        assert_eq!(diff.matched[0].1.id, stored[0].id);
+        assert_eq!(rules(&diff.added), ["disk_full/", "disk_full/home"]);
+        assert_eq!(rules(&diff.removed), ["disk_full/", "memory_high"]);
+    }
+
+    #[test]
+    fn test_read_metrics_file() {
+        let t0 = Utc::now();
+        let path = std::env::temp_dir().join(format!("replay-{}.jsonl", uuid::Uuid::new_v4()));
+        let lines: Vec<String> = (0..3)
+            .map(|i| serde_json::to_string(&sample(t0 + Duration::seconds(i), 10.0)).unwrap())
+            .collect();
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        let samples: Vec<SystemMetrics> = read_metrics_file(&path)
+            .unwrap()
+            .collect::<io::Result<_>>()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(samples.len(), 3);
+        assert_eq!(samples[2].timestamp, t0 + Duration::seconds(2));
+    }
+
+    #[test]
+    fn test_parse_time() {
+        let now = Utc::now();
+        assert_eq!(parse_time("7d", now), Some(now - Duration::days(7)));
+        assert_eq!(
+            parse_time("2026-10-10T00:00:00Z", now),
+            Some("2026-10-10T00:00:00Z".parse().unwrap())
+        );
+        assert_eq!(parse_time("last week", now), None);
+        assert_eq!(parse_time("99999999999d", now), None);
+    }
+}
@@ -0,0 +1,141 @@
+/// Filtering, keyset pagination and aggregation of stored anomalies
+///
+/// Anomalies are paged by `(timestamp, id)` rather than by offset, so a page
+/// stays stable while new anomalies are written. The position after the last
+/// returned anomaly is handed to clients as an opaque cursor token.
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use shared::types::{Anomaly, AnomalyCategory, AnomalySeverity};
+use std::collections::BTreeMap;
+
+/// Filters for anomaly queries; empty or unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct AnomalyFilter {
+    /// Any of these severities
+    pub severities: Vec<AnomalySeverity>,
+    /// Any of these categories
+    pub categories: Vec<AnomalyCategory>,
+    /// Inclusive start of the time range
+    pub start: Option<DateTime<Utc>>,
+    /// Inclusive end of the time range
+    pub end: Option<DateTime<Utc>>,
+    /// Case-insensitive substring of the message
+    pub text: Option<String>,
+}
+
+/// Order of anomalies by timestamp
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    /// Newest first
+    #[default]
+    Desc,
+    /// Oldest first
+    Asc,
+}
+
+impl SortOrder {
+    /// Parses `asc` or `desc` (case-insensitive)
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "desc" => Some(Self::Desc),
+            "asc" => Some(Self::Asc),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn sql(self) -> &'static str {
+        match self {
+            Self::Desc => "DESC",
+            Self::Asc => "ASC",
+        }
+    }
+}
+
+/// Position just after the last anomaly of a page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnomalyCursor {
+    /// Stored timestamp text, compared as stored
+    pub(crate) timestamp: String,
+    /// Row id, breaking ties between anomalies with the same timestamp
+    pub(crate) id: i64,
+}
+
+impl AnomalyCursor {
+    /// Encodes the cursor as a URL-safe token
+    pub fn encode(&self) -> String {
+        format!("{}|{}", self.timestamp, self.id)
+            .bytes()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    /// Decodes a token produced by `encode`; None if it is malformed
+    pub fn decode(token: &str) -> Option<Self> {
+        // An odd trailing digit makes `get` return None
+        let bytes = (0..token.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(token.get(i..i + 2)?, 16).ok())
+            .collect::<Option<Vec<u8>>>()?;
+        let text = String::from_utf8(bytes).ok()?;
+        let (timestamp, id) = text.rsplit_once('|')?;
+
+        Some(Self {
+            timestamp: timestamp.to_string(),
+            id: id.parse().ok()?,
+        })
+    }
+}
+
+/// One page of anomalies
+#[derive(Debug, Clone, Serialize)]
+pub struct AnomalyPage {
+    pub anomalies: Vec<Anomaly>,
+    /// Token for the next page; None on the last page
+    pub next_cursor: Option<String>,
+}
+
+/// Anomaly counts for dashboards
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AnomalyStats {
+    pub total: u64,
+    pub by_severity: BTreeMap<String, u64>,
+    pub by_category: BTreeMap<String, u64>,
+    /// Counts per time bucket, oldest first
+    pub buckets: Vec<AnomalyStatsBucket>,
+}
+
+/// Anomaly counts within one time bucket
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AnomalyStatsBucket {
+    pub start: DateTime<Utc>,
+    pub total: u64,
+    pub by_severity: BTreeMap<String, u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = AnomalyCursor {
+            timestamp: "2024-01-29T12:00:00.123456+00:00".to_string(),
+            id: 42,
+        };
+        let token = cursor.encode();
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(AnomalyCursor::decode(&token), Some(cursor));
+
+        assert_eq!(AnomalyCursor::decode("zz"), None);
+        assert_eq!(AnomalyCursor::decode("abc"), None);
+        assert_eq!(AnomalyCursor::decode(""), None);
+    }
+
+    #[test]
+    fn test_sort_order_parse() {
+        assert_eq!(SortOrder::parse("ASC"), Some(SortOrder::Asc));
+        assert_eq!(SortOrder::parse("desc"), Some(SortOrder::Desc));
+        assert_eq!(SortOrder::parse("newest"), None);
+    }
+}
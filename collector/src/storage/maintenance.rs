@@ -0,0 +1,417 @@
+/// Scheduled database maintenance
+///
+/// Applies retention to raw metrics, anomalies, resolved incidents and each
+/// rollup tier, then returns freed pages to the filesystem with an incremental
+/// vacuum and refreshes planner statistics. When a maximum database size is
+/// configured and the file is still larger, the oldest raw samples are deleted
+/// in batches until it fits. Every run produces a [`MaintenanceReport`] that is
+/// logged and served by the API.
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use shared::constants::DEFAULT_RETENTION_DAYS;
+use sqlx::Error as SqlxError;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+use tokio_util::sync::CancellationToken;
+
+use super::repository::MetricsRepository;
+use super::rollups::{Resolution, RollupRetention};
+
+/// Raw samples deleted per round while enforcing the size limit
+const SIZE_LIMIT_BATCH: u32 = 1000;
+
+/// Upper bound on size limit rounds per run, so one run cannot stall writes
+/// for long; anything left over is handled by the next run
+const MAX_SIZE_LIMIT_ROUNDS: usize = 50;
+
+/// Retention and size limits applied by the maintenance task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MaintenanceConfig {
+    /// Days of raw metric samples to keep
+    pub metrics_retention_days: i64,
+    /// Days of anomalies to keep; resolved incidents last seen before the
+    /// same cutoff are deleted too
+    pub anomalies_retention_days: i64,
+    /// Retention of each rollup tier
+    pub rollups: RollupRetention,
+    /// Maximum database file size in MiB; None disables the limit
+    pub max_db_size_mb: Option<u64>,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            metrics_retention_days: i64::from(DEFAULT_RETENTION_DAYS),
+            anomalies_retention_days: 90,
+            rollups: RollupRetention::default(),
+            max_db_size_mb: None,
+        }
+    }
+}
+
+impl MaintenanceConfig {
+    fn max_db_size_bytes(&self) -> Option<u64> {
+        self.max_db_size_mb.map(|mb| mb.saturating_mul(1024 * 1024))
+    }
+}
+
+/// Outcome of one maintenance run
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MaintenanceReport {
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    /// Raw samples past their retention
+    pub metrics_deleted: u64,
+    pub anomalies_deleted: u64,
+    pub incidents_deleted: u64,
+    /// Rollup rows past their retention, by tier name
+    pub rollups_deleted: BTreeMap<String, u64>,
+    /// Oldest raw samples deleted to get under `max_db_size_mb`
+    pub size_limit_metrics_deleted: u64,
+    pub size_before_bytes: u64,
+    pub size_after_bytes: u64,
+    /// True if this run switched the database to incremental auto-vacuum
+    pub vacuum_mode_converted: bool,
+    /// Set if the run stopped early; counts cover the steps that completed
+    pub error: Option<String>,
+}
+
+impl MaintenanceReport {
+    /// Returns the total number of rows deleted by this run
+    pub fn total_deleted(&self) -> u64 {
+        self.metrics_deleted
+            + self.anomalies_deleted
+            + self.incidents_deleted
+            + self.rollups_deleted.values().sum::<u64>()
+            + self.size_limit_metrics_deleted
+    }
+}
+
+/// Runs retention and vacuuming on a schedule or on demand
+pub struct MaintenanceTask {
+    repo: Arc<MetricsRepository>,
+    config: MaintenanceConfig,
+    last_report: RwLock<Option<MaintenanceReport>>,
+    /// Keeps scheduled and API-triggered runs from overlapping
+    running: Mutex<()>,
+}
+
+impl MaintenanceTask {
+    /// Creates a maintenance task
+    ///
+    /// # Arguments
+    /// * `repo` - Database repository to maintain
+    /// * `config` - Retention and size limits
+    pub fn new(repo: Arc<MetricsRepository>, config: MaintenanceConfig) -> Self {
+        Self {
+            repo,
+            config,
+            last_report: RwLock::new(None),
+            running: Mutex::new(()),
+        }
+    }
+
+    /// Returns the configured retention and size limits
+    pub fn config(&self) -> &MaintenanceConfig {
+        &self.config
+    }
+
+    /// Returns the report of the last completed run
+    pub async fn last_report(&self) -> Option<MaintenanceReport> {
+        self.last_report.read().await.clone()
+    }
+
+    /// Runs one maintenance pass and records its report
+    ///
+    /// Errors do not abort the report: the failing step is recorded in
+    /// `error` alongside the counts of the steps that completed.
+    pub async fn run_once(&self, now: DateTime<Utc>) -> MaintenanceReport {
+        let _running = self.running.lock().await;
+        let started = std::time::Instant::now();
+        let mut report = MaintenanceReport {
+            started_at: now,
+            ..Default::default()
+        };
+
+        if let Err(e) = self.apply(now, &mut report).await {
+            tracing::error!("Database maintenance failed: {}", e);
+            report.error = Some(e.to_string());
+        }
+        report.duration_ms = started.elapsed().as_millis() as u64;
+
+        tracing::info!(
+            "Database maintenance: deleted {} metrics, {} anomalies, {} incidents, {} rollup rows, {} metrics over size limit; size {} -> {} bytes in {}ms",
+            report.metrics_deleted,
+            report.anomalies_deleted,
+            report.incidents_deleted,
+            report.rollups_deleted.values().sum::<u64>(),
+            report.size_limit_metrics_deleted,
+            report.size_before_bytes,
+            report.size_after_bytes,
+            report.duration_ms
+        );
+
+        *self.last_report.write().await = Some(report.clone());
+        report
+    }
+
+    async fn apply(
+        &self,
+        now: DateTime<Utc>,
+        report: &mut MaintenanceReport,
+    ) -> Result<(), SqlxError> {
+        let repo = &self.repo;
+        report.size_before_bytes = repo.database_size().await?;
+
+        // Retention
+        let metrics_cutoff = now - Duration::days(self.config.metrics_retention_days);
+        report.metrics_deleted = repo.delete_metrics_before(metrics_cutoff).await?;
+
+        let anomalies_cutoff = now - Duration::days(self.config.anomalies_retention_days);
+        report.anomalies_deleted = repo.delete_anomalies_before(anomalies_cutoff).await?;
+        report.incidents_deleted = repo
+            .delete_resolved_incidents_before(anomalies_cutoff)
+            .await?;
+
+        for resolution in Resolution::TIERS {
+            if let Some(retention) = self.config.rollups.for_resolution(resolution) {
+                let pruned = repo.prune_rollups(resolution, now - retention).await?;
+                report
+                    .rollups_deleted
+                    .insert(resolution.as_str().to_string(), pruned);
+            }
+        }
+
+        // Older databases need a one-time full VACUUM before pages can be freed
+        if repo.enable_incremental_vacuum().await? {
+            tracing::info!("Switched database to incremental auto-vacuum");
+            report.vacuum_mode_converted = true;
+        }
+        repo.incremental_vacuum().await?;
+
+        // Size limit
+        if let Some(max_bytes) = self.config.max_db_size_bytes() {
+            let mut size = repo.database_size().await?;
+            for _ in 0..MAX_SIZE_LIMIT_ROUNDS {
+                if size <= max_bytes {
+                    break;
+                }
+                let deleted = repo.delete_oldest_metrics(SIZE_LIMIT_BATCH).await?;
+                if deleted == 0 {
+                    break;
+                }
+                report.size_limit_metrics_deleted += deleted;
+                repo.incremental_vacuum().await?;
+                size = repo.database_size().await?;
+            }
+            if size > max_bytes {
+                tracing::warn!(
+                    "Database is {} bytes, still above the {} byte limit",
+                    size,
+                    max_bytes
+                );
+            }
+        }
+
+        repo.analyze().await?;
+        report.size_after_bytes = repo.database_size().await?;
+        Ok(())
+    }
+
+    /// Runs a pass every `period` until `shutdown` is cancelled
+    ///
+    /// The first pass runs one `period` after start, so a large deletion or
+    /// the one-time VACUUM does not hold up collector startup.
+    pub async fn run(self: Arc<Self>, period: std::time::Duration, shutdown: CancellationToken) {
+        let mut tick = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = tick.tick() => {
+                    self.run_once(Utc::now()).await;
+                }
+            }
+        }
+
+        tracing::info!("Maintenance task stopped");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use shared::types::{
+        Anomaly, AnomalyCategory, AnomalySeverity, CpuMetrics, Incident, IncidentStatus,
+        MemoryMetrics, NetworkMetrics, SystemMetrics,
+    };
+    use uuid::Uuid;
+
+    fn metrics_at(timestamp: DateTime<Utc>) -> SystemMetrics {
+        SystemMetrics {
+            timestamp,
+            cpu: CpuMetrics {
+                global_usage: 10.0,
+                per_core: vec![10.0; 64],
+                load_avg_1: 0.5,
+                load_avg_5: 0.5,
+                load_avg_15: 0.5,
+            },
+            memory: MemoryMetrics {
+                total: 16_000_000_000,
+                used: 8_000_000_000,
+                available: 8_000_000_000,
+                usage_percent: 50.0,
+                swap_total: 0,
+                swap_used: 0,
+            },
+            temperatures: vec![],
+            disks: vec![],
+            usb_devices: vec![],
+            network: NetworkMetrics {
+                rx_bytes: 0,
+                tx_bytes: 0,
+                rx_packets: 0,
+                tx_packets: 0,
+                interfaces: vec![],
+            },
+            gpu: None,
+            processes: vec![],
+            zombies: Default::default(),
+        }
+    }
+
+    fn anomaly_at(timestamp: DateTime<Utc>) -> Anomaly {
+        Anomaly {
+            id: Uuid::new_v4().to_string(),
+            timestamp,
+            severity: AnomalySeverity::Warning,
+            category: AnomalyCategory::Cpu,
+            message: "CPU usage high".to_string(),
+            metrics: json!({}),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retention_is_applied_per_kind() {
+        let repo = MetricsRepository::new("sqlite::memory:").await.unwrap();
+        repo.run_migrations().await.unwrap();
+        let now = Utc::now();
+
+        for days in [1, 10, 40] {
+            let at = now - Duration::days(days);
+            repo.store_metrics(&metrics_at(at)).await.unwrap();
+            repo.store_anomaly(&anomaly_at(at)).await.unwrap();
+        }
+
+        let task = MaintenanceTask::new(
+            Arc::new(repo),
+            MaintenanceConfig {
+                metrics_retention_days: 7,
+                anomalies_retention_days: 30,
+                ..Default::default()
+            },
+        );
+        let report = task.run_once(now).await;
+
+        assert_eq!(report.error, None);
+        assert_eq!(report.metrics_deleted, 2);
+        assert_eq!(report.anomalies_deleted, 1);
+        assert_eq!(report.rollups_deleted.len(), 3);
+        assert_eq!(report.total_deleted(), 3);
+        assert_eq!(task.last_report().await.unwrap().metrics_deleted, 2);
+
+        // Nothing left to delete on the next run
+        assert_eq!(task.run_once(now).await.total_deleted(), 0);
+    }
+
+    fn incident_at(last_seen: DateTime<Utc>, status: IncidentStatus) -> Incident {
+        Incident {
+            id: Uuid::new_v4().to_string(),
+            rule: "cpu_critical".to_string(),
+            subject: None,
+            category: AnomalyCategory::Cpu,
+            severity: AnomalySeverity::Critical,
+            status,
+            message: "CPU usage critical".to_string(),
+            first_seen: last_seen,
+            last_seen,
+            occurrences: 1,
+            acknowledged_at: None,
+            resolved_at: (status == IncidentStatus::Resolved).then_some(last_seen),
+            last_anomaly_id: Uuid::new_v4().to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_only_old_resolved_incidents_are_deleted() {
+        let repo = MetricsRepository::new("sqlite::memory:").await.unwrap();
+        repo.run_migrations().await.unwrap();
+        let now = Utc::now();
+
+        let old = now - Duration::days(40);
+        for incident in [
+            incident_at(old, IncidentStatus::Resolved),
+            incident_at(old, IncidentStatus::Open),
+            incident_at(now - Duration::days(1), IncidentStatus::Resolved),
+        ] {
+            repo.upsert_incident(&incident).await.unwrap();
+        }
+
+        let repo = Arc::new(repo);
+        let task = MaintenanceTask::new(
+            Arc::clone(&repo),
+            MaintenanceConfig {
+                anomalies_retention_days: 30,
+                ..Default::default()
+            },
+        );
+        let report = task.run_once(now).await;
+
+        assert_eq!(report.error, None);
+        assert_eq!(report.incidents_deleted, 1);
+        assert_eq!(repo.get_active_incidents().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_size_limit_deletes_oldest_samples() {
+        let path = std::env::temp_dir().join(format!("maintenance-{}.db", Uuid::new_v4()));
+        let url = format!("sqlite://{}", path.display());
+        let repo = Arc::new(MetricsRepository::new(&url).await.unwrap());
+        repo.run_migrations().await.unwrap();
+
+        let now = Utc::now();
+        for i in 0..3000 {
+            let at = now - Duration::seconds(3000 - i);
+            repo.store_metrics(&metrics_at(at)).await.unwrap();
+        }
+        let size = repo.database_size().await.unwrap();
+        assert!(size > 1024 * 1024);
+
+        let task = MaintenanceTask::new(
+            Arc::clone(&repo),
+            MaintenanceConfig {
+                max_db_size_mb: Some(1),
+                ..Default::default()
+            },
+        );
+        let report = task.run_once(now).await;
+
+        assert_eq!(report.error, None);
+        assert_eq!(report.metrics_deleted, 0);
+        assert!(!report.vacuum_mode_converted);
+        assert!(report.size_limit_metrics_deleted > 0);
+        assert!(report.size_after_bytes <= 1024 * 1024);
+        assert!(report.size_after_bytes < report.size_before_bytes);
+
+        // The newest samples are kept
+        let latest = repo.get_recent_metrics(1).await.unwrap();
+        assert_eq!(latest[0].timestamp, now - Duration::seconds(1));
+
+        repo.close().await.unwrap();
+        let _ = std::fs::remove_file(&path);
+    }
+}
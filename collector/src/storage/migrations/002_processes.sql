@@ -0,0 +1,3 @@
+-- Top processes by CPU, memory and disk I/O
+-- JSON array of ProcessInfo captured with each metrics sample
+ALTER TABLE metrics ADD COLUMN processes TEXT;
@@ -0,0 +1,26 @@
+-- Zombie processes grouped by parent
+-- JSON object of ZombieMetrics captured with each metrics sample
+ALTER TABLE metrics ADD COLUMN zombies TEXT;
+
+-- Allow the 'Process' anomaly category
+-- SQLite cannot alter a CHECK constraint, so the table is rebuilt
+CREATE TABLE anomalies_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp DATETIME NOT NULL,
+    severity TEXT NOT NULL CHECK(severity IN ('Info', 'Warning', 'Critical')),
+    category TEXT NOT NULL CHECK(category IN ('Cpu', 'Memory', 'Temperature', 'Disk', 'Usb', 'Network', 'Gpu', 'Process', 'System')),
+    message TEXT NOT NULL,
+    metrics TEXT NOT NULL,  -- JSON object with relevant metrics
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO anomalies_new (id, timestamp, severity, category, message, metrics, created_at)
+SELECT id, timestamp, severity, category, message, metrics, created_at FROM anomalies;
+
+DROP TABLE anomalies;
+ALTER TABLE anomalies_new RENAME TO anomalies;
+
+CREATE INDEX IF NOT EXISTS idx_anomalies_timestamp ON anomalies(timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_anomalies_severity ON anomalies(severity);
+CREATE INDEX IF NOT EXISTS idx_anomalies_category ON anomalies(category);
+CREATE INDEX IF NOT EXISTS idx_anomalies_created_at ON anomalies(created_at DESC);
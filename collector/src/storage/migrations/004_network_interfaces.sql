@@ -0,0 +1,3 @@
+-- Per-interface network rates, error counters and link state
+-- JSON array of NetworkInterface captured with each metrics sample
+ALTER TABLE metrics ADD COLUMN network_interfaces TEXT;
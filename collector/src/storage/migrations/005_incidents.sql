@@ -0,0 +1,22 @@
+-- Incidents group repeated anomalies from one rule and subject
+-- subject is '' for rules without one so (rule, subject) can be indexed
+CREATE TABLE IF NOT EXISTS incidents (
+    id TEXT PRIMARY KEY,
+    rule TEXT NOT NULL,
+    subject TEXT NOT NULL DEFAULT '',
+    category TEXT NOT NULL CHECK(category IN ('Cpu', 'Memory', 'Temperature', 'Disk', 'Usb', 'Network', 'Gpu', 'Process', 'System')),
+    severity TEXT NOT NULL CHECK(severity IN ('Info', 'Warning', 'Critical')),
+    status TEXT NOT NULL CHECK(status IN ('Open', 'Acknowledged', 'Resolved')),
+    message TEXT NOT NULL,
+    first_seen TEXT NOT NULL,
+    last_seen TEXT NOT NULL,
+    occurrences INTEGER NOT NULL DEFAULT 1,
+    acknowledged_at TEXT,
+    resolved_at TEXT,
+    last_anomaly_id TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_incidents_status ON incidents(status);
+CREATE INDEX IF NOT EXISTS idx_incidents_last_seen ON incidents(last_seen DESC);
+CREATE INDEX IF NOT EXISTS idx_incidents_rule_subject ON incidents(rule, subject);
@@ -0,0 +1,13 @@
+-- Learned EWMA baselines of statistical detection rules
+-- subject is '' for rules without one and hour is -1 for non-seasonal
+-- baselines so every column of the key is NOT NULL
+CREATE TABLE IF NOT EXISTS baselines (
+    rule TEXT NOT NULL,
+    subject TEXT NOT NULL DEFAULT '',
+    hour INTEGER NOT NULL DEFAULT -1 CHECK(hour BETWEEN -1 AND 23),
+    mean REAL NOT NULL,
+    variance REAL NOT NULL,
+    samples INTEGER NOT NULL,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (rule, subject, hour)
+);
@@ -0,0 +1,38 @@
+-- Downsampled history: one row per metric path per bucket
+-- bucket_start is the RFC 3339 start of the bucket
+CREATE TABLE IF NOT EXISTS rollups_1m (
+    bucket_start TEXT NOT NULL,
+    metric TEXT NOT NULL,
+    min REAL NOT NULL,
+    avg REAL NOT NULL,
+    max REAL NOT NULL,
+    p95 REAL NOT NULL,
+    samples INTEGER NOT NULL,
+    PRIMARY KEY (bucket_start, metric)
+);
+
+CREATE TABLE IF NOT EXISTS rollups_15m (
+    bucket_start TEXT NOT NULL,
+    metric TEXT NOT NULL,
+    min REAL NOT NULL,
+    avg REAL NOT NULL,
+    max REAL NOT NULL,
+    p95 REAL NOT NULL,
+    samples INTEGER NOT NULL,
+    PRIMARY KEY (bucket_start, metric)
+);
+
+CREATE TABLE IF NOT EXISTS rollups_1h (
+    bucket_start TEXT NOT NULL,
+    metric TEXT NOT NULL,
+    min REAL NOT NULL,
+    avg REAL NOT NULL,
+    max REAL NOT NULL,
+    p95 REAL NOT NULL,
+    samples INTEGER NOT NULL,
+    PRIMARY KEY (bucket_start, metric)
+);
+
+CREATE INDEX IF NOT EXISTS idx_rollups_1m_metric ON rollups_1m(metric, bucket_start);
+CREATE INDEX IF NOT EXISTS idx_rollups_15m_metric ON rollups_15m(metric, bucket_start);
+CREATE INDEX IF NOT EXISTS idx_rollups_1h_metric ON rollups_1h(metric, bucket_start);
@@ -0,0 +1,8 @@
+-- Persist the anomaly UUID so any stored anomaly can be looked up by the id
+-- clients see. Existing rows were exposed by their integer rowid, so that is
+-- kept as their id.
+ALTER TABLE anomalies ADD COLUMN uuid TEXT;
+
+UPDATE anomalies SET uuid = CAST(id AS TEXT) WHERE uuid IS NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_anomalies_uuid ON anomalies(uuid);
@@ -0,0 +1,124 @@
+-- Disks, temperature sensors, GPU and USB devices in child tables keyed by
+-- metric id, so one disk, sensor or device can be read over a time range
+-- without deserializing whole samples. The sample timestamp is copied into
+-- each row so series queries are served by a single index.
+
+CREATE TABLE IF NOT EXISTS disk_samples (
+    metric_id INTEGER NOT NULL REFERENCES metrics(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,  -- order within the sample
+    timestamp DATETIME NOT NULL,
+    name TEXT NOT NULL,
+    mount_point TEXT NOT NULL,
+    total INTEGER NOT NULL,
+    used INTEGER NOT NULL,
+    available INTEGER NOT NULL,
+    usage_percent REAL NOT NULL,
+    read_mb REAL NOT NULL,
+    write_mb REAL NOT NULL,
+    PRIMARY KEY (metric_id, position)
+);
+CREATE INDEX IF NOT EXISTS idx_disk_samples_mount_point ON disk_samples(mount_point, timestamp);
+CREATE INDEX IF NOT EXISTS idx_disk_samples_name ON disk_samples(name, timestamp);
+
+CREATE TABLE IF NOT EXISTS temperature_samples (
+    metric_id INTEGER NOT NULL REFERENCES metrics(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    timestamp DATETIME NOT NULL,
+    sensor TEXT NOT NULL,  -- hwmon chip, e.g. coretemp
+    label TEXT NOT NULL,   -- e.g. Package id 0
+    value REAL NOT NULL,
+    PRIMARY KEY (metric_id, position)
+);
+CREATE INDEX IF NOT EXISTS idx_temperature_samples_sensor ON temperature_samples(sensor, label, timestamp);
+
+CREATE TABLE IF NOT EXISTS gpu_samples (
+    metric_id INTEGER NOT NULL REFERENCES metrics(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    timestamp DATETIME NOT NULL,
+    name TEXT NOT NULL,
+    temperature REAL NOT NULL,
+    usage_percent REAL NOT NULL,
+    memory_usage_percent REAL NOT NULL,
+    memory_total_mb INTEGER NOT NULL,
+    memory_used_mb INTEGER NOT NULL,
+    memory_free_mb INTEGER NOT NULL,
+    power_draw_watts REAL NOT NULL,
+    fan_speed_percent REAL NOT NULL,
+    PRIMARY KEY (metric_id, position)
+);
+CREATE INDEX IF NOT EXISTS idx_gpu_samples_name ON gpu_samples(name, timestamp);
+
+CREATE TABLE IF NOT EXISTS usb_snapshots (
+    metric_id INTEGER NOT NULL REFERENCES metrics(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    timestamp DATETIME NOT NULL,
+    device_id TEXT NOT NULL,
+    manufacturer TEXT NOT NULL,
+    product TEXT NOT NULL,
+    has_timeout INTEGER NOT NULL,
+    PRIMARY KEY (metric_id, position)
+);
+CREATE INDEX IF NOT EXISTS idx_usb_snapshots_device ON usb_snapshots(device_id, timestamp);
+
+-- Move existing JSON data into the child tables
+INSERT INTO disk_samples (
+    metric_id, position, timestamp, name, mount_point, total, used, available,
+    usage_percent, read_mb, write_mb
+)
+SELECT
+    m.id, d.key, m.timestamp,
+    COALESCE(json_extract(d.value, '$.name'), ''),
+    COALESCE(json_extract(d.value, '$.mount_point'), ''),
+    COALESCE(json_extract(d.value, '$.total'), 0),
+    COALESCE(json_extract(d.value, '$.used'), 0),
+    COALESCE(json_extract(d.value, '$.available'), 0),
+    COALESCE(json_extract(d.value, '$.usage_percent'), 0),
+    COALESCE(json_extract(d.value, '$.read_mb'), 0),
+    COALESCE(json_extract(d.value, '$.write_mb'), 0)
+FROM metrics m, json_each(m.disks) d
+WHERE m.disks IS NOT NULL AND json_valid(m.disks);
+
+INSERT INTO temperature_samples (metric_id, position, timestamp, sensor, label, value)
+SELECT
+    m.id, t.key, m.timestamp,
+    COALESCE(json_extract(t.value, '$.sensor'), ''),
+    COALESCE(json_extract(t.value, '$.label'), ''),
+    COALESCE(json_extract(t.value, '$.value'), 0)
+FROM metrics m, json_each(m.temperatures) t
+WHERE m.temperatures IS NOT NULL AND json_valid(m.temperatures);
+
+INSERT INTO gpu_samples (
+    metric_id, position, timestamp, name, temperature, usage_percent,
+    memory_usage_percent, memory_total_mb, memory_used_mb, memory_free_mb,
+    power_draw_watts, fan_speed_percent
+)
+SELECT
+    m.id, 0, m.timestamp,
+    COALESCE(json_extract(m.gpu, '$.name'), ''),
+    COALESCE(json_extract(m.gpu, '$.temperature'), 0),
+    COALESCE(json_extract(m.gpu, '$.usage_percent'), 0),
+    COALESCE(json_extract(m.gpu, '$.memory_usage_percent'), 0),
+    COALESCE(json_extract(m.gpu, '$.memory_total_mb'), 0),
+    COALESCE(json_extract(m.gpu, '$.memory_used_mb'), 0),
+    COALESCE(json_extract(m.gpu, '$.memory_free_mb'), 0),
+    COALESCE(json_extract(m.gpu, '$.power_draw_watts'), 0),
+    COALESCE(json_extract(m.gpu, '$.fan_speed_percent'), 0)
+FROM metrics m
+WHERE m.gpu IS NOT NULL AND json_valid(m.gpu) AND json_type(m.gpu) = 'object';
+
+INSERT INTO usb_snapshots (
+    metric_id, position, timestamp, device_id, manufacturer, product, has_timeout
+)
+SELECT
+    m.id, u.key, m.timestamp,
+    COALESCE(json_extract(u.value, '$.id'), ''),
+    COALESCE(json_extract(u.value, '$.manufacturer'), ''),
+    COALESCE(json_extract(u.value, '$.product'), ''),
+    COALESCE(json_extract(u.value, '$.has_timeout'), 0)
+FROM metrics m, json_each(m.usb_devices) u
+WHERE m.usb_devices IS NOT NULL AND json_valid(m.usb_devices);
+
+ALTER TABLE metrics DROP COLUMN temperatures;
+ALTER TABLE metrics DROP COLUMN disks;
+ALTER TABLE metrics DROP COLUMN usb_devices;
+ALTER TABLE metrics DROP COLUMN gpu;
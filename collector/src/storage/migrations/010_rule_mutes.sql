@@ -0,0 +1,7 @@
+-- Rules muted through the API; their anomalies are not stored, streamed or
+-- notified until muted_until. Expired rows are replaced by the next mute.
+CREATE TABLE IF NOT EXISTS rule_mutes (
+    rule TEXT PRIMARY KEY,
+    muted_until TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
@@ -5,9 +5,27 @@
 /// - Database migrations for schema setup
 /// - Data models for database rows
 /// - Repository pattern for data access
+/// - Anomaly filtering, pagination and counts
+/// - Background rollups of raw samples into downsampled tiers
+/// - Scheduled retention, vacuuming and size limits
+/// - Single-metric series for charting
+pub mod anomaly_query;
+pub mod maintenance;
 pub mod models;
 pub mod repository;
+pub mod rollups;
+pub mod series;
 
 // Re-export commonly used types
-pub use models::{AnomalyRow, ConfigRow, MetricsRow, NewAnomaly, NewConfig, NewMetrics};
+pub use anomaly_query::{
+    AnomalyCursor, AnomalyFilter, AnomalyPage, AnomalyStats, AnomalyStatsBucket, SortOrder,
+};
+pub use maintenance::{MaintenanceConfig, MaintenanceReport, MaintenanceTask};
+pub use models::{
+    AnomalyRow, BaselineRow, ConfigRow, DiskSampleRow, GpuSampleRow, IncidentRow, MetricsRow,
+    NewAnomaly, NewConfig, NewMetrics, RollupRow, RuleMuteRow, TemperatureSampleRow,
+    UsbSnapshotRow,
+};
 pub use repository::MetricsRepository;
+pub use rollups::{Resolution, RollupRetention, RollupWorker};
+pub use series::{Series, SeriesAggregate};
@@ -23,11 +23,7 @@ pub struct MetricsRow {
     pub swap_total: i64,
     pub swap_used: i64,
 
-    // Complex metrics as JSON
-    pub temperatures: Option<String>, // JSON array
-    pub disks: Option<String>,        // JSON array
-    pub usb_devices: Option<String>,  // JSON array
-    pub gpu: Option<String>,          // JSON object
+    // Temperatures, disks, USB devices and GPU live in child tables (migration 009)
 
     // Network metrics
     pub network_rx: i64,
@@ -36,6 +32,75 @@ pub struct MetricsRow {
     pub network_tx_packets: i64,
 
     pub created_at: String, // SQLite stores as TEXT
+
+    // Top processes as JSON (added by migration 002)
+    pub processes: Option<String>, // JSON array
+
+    // Zombie process groups as JSON (added by migration 003)
+    pub zombies: Option<String>, // JSON object
+
+    // Per-interface network metrics as JSON (added by migration 004)
+    pub network_interfaces: Option<String>, // JSON array
+}
+
+/// Database model for one disk of a metrics sample
+/// Maps to the disk_samples table in SQLite
+#[derive(Debug, Clone, FromRow)]
+pub struct DiskSampleRow {
+    pub metric_id: i64,
+    pub position: i64,
+    pub timestamp: String, // SQLite stores as TEXT
+    pub name: String,
+    pub mount_point: String,
+    pub total: i64,
+    pub used: i64,
+    pub available: i64,
+    pub usage_percent: f32,
+    pub read_mb: f64,
+    pub write_mb: f64,
+}
+
+/// Database model for one temperature sensor of a metrics sample
+/// Maps to the temperature_samples table in SQLite
+#[derive(Debug, Clone, FromRow)]
+pub struct TemperatureSampleRow {
+    pub metric_id: i64,
+    pub position: i64,
+    pub timestamp: String, // SQLite stores as TEXT
+    pub sensor: String,
+    pub label: String,
+    pub value: f32,
+}
+
+/// Database model for the GPU of a metrics sample
+/// Maps to the gpu_samples table in SQLite
+#[derive(Debug, Clone, FromRow)]
+pub struct GpuSampleRow {
+    pub metric_id: i64,
+    pub position: i64,
+    pub timestamp: String, // SQLite stores as TEXT
+    pub name: String,
+    pub temperature: f32,
+    pub usage_percent: f32,
+    pub memory_usage_percent: f32,
+    pub memory_total_mb: i64,
+    pub memory_used_mb: i64,
+    pub memory_free_mb: i64,
+    pub power_draw_watts: f32,
+    pub fan_speed_percent: f32,
+}
+
+/// Database model for one USB device seen in a metrics sample
+/// Maps to the usb_snapshots table in SQLite
+#[derive(Debug, Clone, FromRow)]
+pub struct UsbSnapshotRow {
+    pub metric_id: i64,
+    pub position: i64,
+    pub timestamp: String, // SQLite stores as TEXT
+    pub device_id: String,
+    pub manufacturer: String,
+    pub product: String,
+    pub has_timeout: bool,
 }
 
 /// Database model for anomalies
@@ -43,6 +108,8 @@ pub struct MetricsRow {
 #[derive(Debug, Clone, FromRow)]
 pub struct AnomalyRow {
     pub id: i64,
+    /// Anomaly UUID; None only if written by an older schema
+    pub uuid: Option<String>,
     pub timestamp: String, // SQLite stores as TEXT
     pub severity: String,
     pub category: String,
@@ -51,6 +118,61 @@ pub struct AnomalyRow {
     pub created_at: String, // SQLite stores as TEXT
 }
 
+/// Database model for incidents
+/// Maps to the incidents table in SQLite
+#[derive(Debug, Clone, FromRow)]
+pub struct IncidentRow {
+    pub id: String,
+    pub rule: String,
+    pub subject: String, // empty when the rule has no subject
+    pub category: String,
+    pub severity: String,
+    pub status: String,
+    pub message: String,
+    pub first_seen: String, // SQLite stores as TEXT
+    pub last_seen: String,  // SQLite stores as TEXT
+    pub occurrences: i64,
+    pub acknowledged_at: Option<String>,
+    pub resolved_at: Option<String>,
+    pub last_anomaly_id: String,
+    pub created_at: String, // SQLite stores as TEXT
+}
+
+/// Database model for learned metric baselines
+/// Maps to the baselines table in SQLite
+#[derive(Debug, Clone, FromRow)]
+pub struct BaselineRow {
+    pub rule: String,
+    pub subject: String, // empty when the rule has no subject
+    pub hour: i64,       // -1 when not seasonal
+    pub mean: f64,
+    pub variance: f64,
+    pub samples: i64,
+    pub updated_at: String, // SQLite stores as TEXT
+}
+
+/// Database model for muted rules
+/// Maps to the rule_mutes table in SQLite
+#[derive(Debug, Clone, FromRow)]
+pub struct RuleMuteRow {
+    pub rule: String,
+    pub muted_until: String, // SQLite stores as TEXT
+    pub created_at: String,
+}
+
+/// Database model for rolled-up metrics
+/// Maps to the rollups_1m, rollups_15m and rollups_1h tables in SQLite
+#[derive(Debug, Clone, FromRow)]
+pub struct RollupRow {
+    pub bucket_start: String, // SQLite stores as TEXT
+    pub metric: String,       // metric path
+    pub min: f64,
+    pub avg: f64,
+    pub max: f64,
+    pub p95: f64,
+    pub samples: i64,
+}
+
 /// Database model for configuration
 /// Maps to the config table in SQLite
 #[derive(Debug, Clone, FromRow)]
@@ -75,19 +197,19 @@ pub struct NewMetrics {
     pub memory_percent: f32,
     pub swap_total: i64,
     pub swap_used: i64,
-    pub temperatures: Option<String>, // JSON serialized
-    pub disks: Option<String>,        // JSON serialized
-    pub usb_devices: Option<String>,  // JSON serialized
-    pub gpu: Option<String>,          // JSON serialized
     pub network_rx: i64,
     pub network_tx: i64,
     pub network_rx_packets: i64,
     pub network_tx_packets: i64,
+    pub processes: Option<String>,          // JSON serialized
+    pub zombies: Option<String>,            // JSON serialized
+    pub network_interfaces: Option<String>, // JSON serialized
 }
 
 /// Insert model for new anomaly
 #[derive(Debug, Clone)]
 pub struct NewAnomaly {
+    pub uuid: String,
     pub timestamp: DateTime<Utc>,
     pub severity: String,
     pub category: String,
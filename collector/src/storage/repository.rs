@@ -1,11 +1,44 @@
 use chrono::{DateTime, Duration, Utc};
-use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
-use sqlx::{Error as SqlxError, Row};
+use sqlx::sqlite::{
+    SqliteAutoVacuum, SqliteConnectOptions, SqlitePool, SqlitePoolOptions, SqliteRow,
+};
+use sqlx::{Error as SqlxError, FromRow, QueryBuilder, Row, Sqlite};
 use std::str::FromStr;
 
-use shared::types::{Anomaly, AnomalyCategory, AnomalySeverity, SystemMetrics};
+use shared::types::{
+    Anomaly, AnomalyCategory, AnomalySeverity, DiskMetrics, GpuMetrics, Incident, IncidentStatus,
+    MetricBaseline, RollupPoint, RuleMute, SeriesSample, SystemMetrics, Temperature, UsbDevice,
+};
+use std::collections::{BTreeMap, HashMap};
 
-use super::models::{AnomalyRow, MetricsRow, NewAnomaly, NewMetrics};
+use super::anomaly_query::{
+    AnomalyCursor, AnomalyFilter, AnomalyPage, AnomalyStats, AnomalyStatsBucket, SortOrder,
+};
+use super::models::{
+    AnomalyRow, BaselineRow, DiskSampleRow, GpuSampleRow, IncidentRow, MetricsRow, NewAnomaly,
+    NewMetrics, RollupRow, RuleMuteRow, TemperatureSampleRow, UsbSnapshotRow,
+};
+use super::rollups::Resolution;
+
+/// Schema migrations, applied in order; the version is stored in `PRAGMA user_version`
+const MIGRATIONS: &[(i64, &str)] = &[
+    (1, include_str!("migrations/001_initial.sql")),
+    (2, include_str!("migrations/002_processes.sql")),
+    (3, include_str!("migrations/003_zombies.sql")),
+    (4, include_str!("migrations/004_network_interfaces.sql")),
+    (5, include_str!("migrations/005_incidents.sql")),
+    (6, include_str!("migrations/006_baselines.sql")),
+    (7, include_str!("migrations/007_rollups.sql")),
+    (8, include_str!("migrations/008_anomaly_uuid.sql")),
+    (9, include_str!("migrations/009_normalized_samples.sql")),
+    (10, include_str!("migrations/010_rule_mutes.sql")),
+];
+
+/// Metric ids per query when loading child rows, well under SQLite's bound parameter limit
+const CHILD_LOOKUP_CHUNK: usize = 500;
+
+/// `PRAGMA auto_vacuum` value for incremental mode
+const AUTO_VACUUM_INCREMENTAL: i64 = 2;
 
 /// Repository for managing system metrics and anomalies in SQLite database
 pub struct MetricsRepository {
@@ -24,7 +57,9 @@ impl MetricsRepository {
         // Parse the database URL and enable create_if_missing
         let options = SqliteConnectOptions::from_str(database_url)?
             .create_if_missing(true)
-            .foreign_keys(true);
+            .foreign_keys(true)
+            // Takes effect for new databases; existing ones are converted by maintenance
+            .auto_vacuum(SqliteAutoVacuum::Incremental);
 
         // Create connection pool
         let pool = SqlitePoolOptions::new()
@@ -40,10 +75,26 @@ impl MetricsRepository {
     /// # Returns
     /// Result indicating success or SqlxError
     pub async fn run_migrations(&self) -> Result<(), SqlxError> {
-        // Read and execute the migration SQL
-        let migration_sql = include_str!("migrations/001_initial.sql");
+        // The applied schema version is tracked in SQLite's user_version pragma
+        let current: i64 = sqlx::query_scalar("PRAGMA user_version")
+            .fetch_one(&self.pool)
+            .await?;
 
-        sqlx::query(migration_sql).execute(&self.pool).await?;
+        for (version, migration_sql) in MIGRATIONS {
+            if *version <= current {
+                continue;
+            }
+
+            let mut tx = self.pool.begin().await?;
+            sqlx::query(migration_sql).execute(&mut *tx).await?;
+            // PRAGMA does not accept bound parameters
+            sqlx::query(&format!("PRAGMA user_version = {}", version))
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+
+            tracing::info!("Applied database migration {}", version);
+        }
 
         Ok(())
     }
@@ -66,6 +117,10 @@ impl MetricsRepository {
     pub async fn store_metrics(&self, metrics: &SystemMetrics) -> Result<i64, SqlxError> {
         // Convert SystemMetrics to NewMetrics format
         let new_metrics = self.convert_to_new_metrics(metrics)?;
+        let timestamp = new_metrics.timestamp.to_rfc3339();
+
+        // The sample and its child rows are written together
+        let mut tx = self.pool.begin().await?;
 
         let result = sqlx::query(
             r#"
@@ -82,18 +137,17 @@ impl MetricsRepository {
                 memory_percent,
                 swap_total,
                 swap_used,
-                temperatures,
-                disks,
-                usb_devices,
-                gpu,
                 network_rx,
                 network_tx,
                 network_rx_packets,
-                network_tx_packets
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                network_tx_packets,
+                processes,
+                zombies,
+                network_interfaces
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
-        .bind(new_metrics.timestamp.to_rfc3339())
+        .bind(&timestamp)
         .bind(new_metrics.cpu_global)
         .bind(&new_metrics.cpu_per_core)
         .bind(new_metrics.load_avg_1)
@@ -105,18 +159,113 @@ impl MetricsRepository {
         .bind(new_metrics.memory_percent)
         .bind(new_metrics.swap_total)
         .bind(new_metrics.swap_used)
-        .bind(&new_metrics.temperatures)
-        .bind(&new_metrics.disks)
-        .bind(&new_metrics.usb_devices)
-        .bind(&new_metrics.gpu)
         .bind(new_metrics.network_rx)
         .bind(new_metrics.network_tx)
         .bind(new_metrics.network_rx_packets)
         .bind(new_metrics.network_tx_packets)
-        .execute(&self.pool)
+        .bind(&new_metrics.processes)
+        .bind(&new_metrics.zombies)
+        .bind(&new_metrics.network_interfaces)
+        .execute(&mut *tx)
         .await?;
+        let metric_id = result.last_insert_rowid();
 
-        Ok(result.last_insert_rowid())
+        if !metrics.disks.is_empty() {
+            let mut query = QueryBuilder::<Sqlite>::new(
+                "INSERT INTO disk_samples (metric_id, position, timestamp, name, mount_point, \
+                 total, used, available, usage_percent, read_mb, write_mb) ",
+            );
+            query.push_values(metrics.disks.iter().enumerate(), |mut row, (i, disk)| {
+                row.push_bind(metric_id)
+                    .push_bind(i as i64)
+                    .push_bind(&timestamp)
+                    .push_bind(&disk.name)
+                    .push_bind(&disk.mount_point)
+                    .push_bind(disk.total as i64)
+                    .push_bind(disk.used as i64)
+                    .push_bind(disk.available as i64)
+                    .push_bind(disk.usage_percent)
+                    .push_bind(disk.read_mb)
+                    .push_bind(disk.write_mb);
+            });
+            query.build().execute(&mut *tx).await?;
+        }
+
+        if !metrics.temperatures.is_empty() {
+            let mut query = QueryBuilder::<Sqlite>::new(
+                "INSERT INTO temperature_samples (metric_id, position, timestamp, sensor, label, value) ",
+            );
+            query.push_values(
+                metrics.temperatures.iter().enumerate(),
+                |mut row, (i, temp)| {
+                    row.push_bind(metric_id)
+                        .push_bind(i as i64)
+                        .push_bind(&timestamp)
+                        .push_bind(&temp.sensor)
+                        .push_bind(&temp.label)
+                        .push_bind(temp.value);
+                },
+            );
+            query.build().execute(&mut *tx).await?;
+        }
+
+        if let Some(ref gpu) = metrics.gpu {
+            sqlx::query(
+                r#"
+                INSERT INTO gpu_samples (
+                    metric_id,
+                    position,
+                    timestamp,
+                    name,
+                    temperature,
+                    usage_percent,
+                    memory_usage_percent,
+                    memory_total_mb,
+                    memory_used_mb,
+                    memory_free_mb,
+                    power_draw_watts,
+                    fan_speed_percent
+                ) VALUES (?, 0, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(metric_id)
+            .bind(&timestamp)
+            .bind(&gpu.name)
+            .bind(gpu.temperature)
+            .bind(gpu.usage_percent)
+            .bind(gpu.memory_usage_percent)
+            .bind(gpu.memory_total_mb as i64)
+            .bind(gpu.memory_used_mb as i64)
+            .bind(gpu.memory_free_mb as i64)
+            .bind(gpu.power_draw_watts)
+            .bind(gpu.fan_speed_percent)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        if !metrics.usb_devices.is_empty() {
+            let mut query = QueryBuilder::<Sqlite>::new(
+                "INSERT INTO usb_snapshots (metric_id, position, timestamp, device_id, \
+                 manufacturer, product, has_timeout) ",
+            );
+            query.push_values(
+                metrics.usb_devices.iter().enumerate(),
+                |mut row, (i, usb)| {
+                    row.push_bind(metric_id)
+                        .push_bind(i as i64)
+                        .push_bind(&timestamp)
+                        .push_bind(&usb.id)
+                        .push_bind(&usb.manufacturer)
+                        .push_bind(&usb.product)
+                        .push_bind(usb.has_timeout);
+                },
+            );
+            query.build().execute(&mut *tx).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(metric_id)
     }
 
     /// Stores an anomaly in the database
@@ -132,14 +281,16 @@ impl MetricsRepository {
         let result = sqlx::query(
             r#"
             INSERT INTO anomalies (
+                uuid,
                 timestamp,
                 severity,
                 category,
                 message,
                 metrics
-            ) VALUES (?, ?, ?, ?, ?)
+            ) VALUES (?, ?, ?, ?, ?, ?)
             "#,
         )
+        .bind(&new_anomaly.uuid)
         .bind(new_anomaly.timestamp.to_rfc3339())
         .bind(&new_anomaly.severity)
         .bind(&new_anomaly.category)
@@ -176,12 +327,169 @@ impl MetricsRepository {
         .fetch_all(&self.pool)
         .await?;
 
-        let metrics: Result<Vec<SystemMetrics>, SqlxError> = rows
-            .into_iter()
-            .map(|row| self.convert_from_metrics_row(row))
-            .collect();
+        self.load_metrics(rows).await
+    }
 
-        metrics
+    /// Retrieves metrics with `start <= timestamp < end`
+    ///
+    /// Unlike `get_metrics_range` the end is exclusive, so adjacent buckets do
+    /// not share samples.
+    pub async fn get_metrics_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<SystemMetrics>, SqlxError> {
+        let rows = sqlx::query_as::<_, MetricsRow>(
+            r#"
+            SELECT * FROM metrics
+            WHERE timestamp >= ? AND timestamp < ?
+            ORDER BY timestamp ASC
+            "#,
+        )
+        .bind(start.to_rfc3339())
+        .bind(end.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await?;
+
+        self.load_metrics(rows).await
+    }
+
+    /// Returns the timestamp of the first metrics sample at or after `from`
+    pub async fn next_metrics_timestamp(
+        &self,
+        from: DateTime<Utc>,
+    ) -> Result<Option<DateTime<Utc>>, SqlxError> {
+        let timestamp: Option<String> =
+            sqlx::query_scalar("SELECT MIN(timestamp) FROM metrics WHERE timestamp >= ?")
+                .bind(from.to_rfc3339())
+                .fetch_one(&self.pool)
+                .await?;
+
+        timestamp.as_deref().map(parse_timestamp).transpose()
+    }
+
+    /// Stores the aggregates of one bucket, replacing any previous copy
+    ///
+    /// # Arguments
+    /// * `resolution` - Rollup tier; raw is ignored
+    /// * `points` - Aggregate per metric path, all for the same bucket
+    pub async fn store_rollups(
+        &self,
+        resolution: Resolution,
+        points: &BTreeMap<&str, RollupPoint>,
+    ) -> Result<(), SqlxError> {
+        let Some(table) = resolution.table() else {
+            return Ok(());
+        };
+        let sql = format!(
+            "INSERT OR REPLACE INTO {} (bucket_start, metric, min, avg, max, p95, samples) \
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+            table
+        );
+
+        let mut tx = self.pool.begin().await?;
+        for (metric, point) in points {
+            sqlx::query(&sql)
+                .bind(point.timestamp.to_rfc3339())
+                .bind(metric)
+                .bind(point.min)
+                .bind(point.avg)
+                .bind(point.max)
+                .bind(point.p95)
+                .bind(point.samples as i64)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await
+    }
+
+    /// Returns the start of the latest stored bucket of a rollup tier
+    pub async fn last_rollup_bucket(
+        &self,
+        resolution: Resolution,
+    ) -> Result<Option<DateTime<Utc>>, SqlxError> {
+        let Some(table) = resolution.table() else {
+            return Ok(None);
+        };
+        let timestamp: Option<String> =
+            sqlx::query_scalar(&format!("SELECT MAX(bucket_start) FROM {}", table))
+                .fetch_one(&self.pool)
+                .await?;
+
+        timestamp.as_deref().map(parse_timestamp).transpose()
+    }
+
+    /// Retrieves rolled-up series within a time range
+    ///
+    /// # Arguments
+    /// * `resolution` - Rollup tier to read
+    /// * `start` - Start of time range (bucket start, inclusive)
+    /// * `end` - End of time range (bucket start, inclusive)
+    /// * `metrics` - Metric paths to return; empty returns every metric
+    ///
+    /// # Returns
+    /// Result containing the points of each metric in time order, or SqlxError
+    pub async fn get_rollups(
+        &self,
+        resolution: Resolution,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        metrics: &[String],
+    ) -> Result<BTreeMap<String, Vec<RollupPoint>>, SqlxError> {
+        let Some(table) = resolution.table() else {
+            return Err(SqlxError::Protocol(
+                "Raw samples are not stored as rollups".to_string(),
+            ));
+        };
+        let rows = sqlx::query_as::<_, RollupRow>(&format!(
+            r#"
+            SELECT * FROM {}
+            WHERE bucket_start BETWEEN ? AND ?
+            ORDER BY bucket_start ASC
+            "#,
+            table
+        ))
+        .bind(start.to_rfc3339())
+        .bind(end.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut series: BTreeMap<String, Vec<RollupPoint>> = BTreeMap::new();
+        for row in rows {
+            if !metrics.is_empty() && !metrics.contains(&row.metric) {
+                continue;
+            }
+            series.entry(row.metric).or_default().push(RollupPoint {
+                timestamp: parse_timestamp(&row.bucket_start)?,
+                min: row.min,
+                avg: row.avg,
+                max: row.max,
+                p95: row.p95,
+                samples: row.samples as u32,
+            });
+        }
+
+        Ok(series)
+    }
+
+    /// Deletes rollup buckets that start before `cutoff`
+    ///
+    /// # Returns
+    /// Result containing the number of deleted rows or SqlxError
+    pub async fn prune_rollups(
+        &self,
+        resolution: Resolution,
+        cutoff: DateTime<Utc>,
+    ) -> Result<u64, SqlxError> {
+        let Some(table) = resolution.table() else {
+            return Ok(0);
+        };
+        let result = sqlx::query(&format!("DELETE FROM {} WHERE bucket_start < ?", table))
+            .bind(cutoff.to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
     }
 
     /// Retrieves anomalies within a time range
@@ -236,12 +544,7 @@ impl MetricsRepository {
         .fetch_all(&self.pool)
         .await?;
 
-        let metrics: Result<Vec<SystemMetrics>, SqlxError> = rows
-            .into_iter()
-            .map(|row| self.convert_from_metrics_row(row))
-            .collect();
-
-        metrics
+        self.load_metrics(rows).await
     }
 
     /// Retrieves recent anomalies with a limit
@@ -271,213 +574,1073 @@ impl MetricsRepository {
         anomalies
     }
 
-    /// Removes old data based on retention period
+    /// Retrieves a single anomaly by its id
     ///
     /// # Arguments
-    /// * `retention_days` - Number of days to retain data
+    /// * `id` - The anomaly's UUID (or rowid, for anomalies stored before UUIDs were kept)
     ///
     /// # Returns
-    /// Result containing tuple of (deleted_metrics_count, deleted_anomalies_count) or SqlxError
-    pub async fn cleanup_old_data(&self, retention_days: i64) -> Result<(u64, u64), SqlxError> {
-        let cutoff_date = Utc::now() - Duration::days(retention_days);
+    /// Result containing the Anomaly, None if it does not exist, or SqlxError
+    pub async fn get_anomaly_by_id(&self, id: &str) -> Result<Option<Anomaly>, SqlxError> {
+        let row = sqlx::query_as::<_, AnomalyRow>("SELECT * FROM anomalies WHERE uuid = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(|row| self.convert_from_anomaly_row(row))
+            .transpose()
+    }
 
-        // Delete old metrics
-        let metrics_result = sqlx::query(
+    /// Retrieves one disk over a time range
+    ///
+    /// # Arguments
+    /// * `disk` - Mount point (e.g. `/home`) or device name (e.g. `/dev/sda1`)
+    /// * `start` - Start of time range
+    /// * `end` - End of time range
+    ///
+    /// # Returns
+    /// Result containing the disk's readings, oldest first, or SqlxError
+    pub async fn get_disk_series(
+        &self,
+        disk: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<SeriesSample<DiskMetrics>>, SqlxError> {
+        let rows = sqlx::query_as::<_, DiskSampleRow>(
             r#"
-            DELETE FROM metrics
-            WHERE timestamp < ?
+            SELECT * FROM disk_samples
+            WHERE (mount_point = ? OR name = ?) AND timestamp BETWEEN ? AND ?
+            ORDER BY timestamp ASC
             "#,
         )
-        .bind(cutoff_date.to_rfc3339())
-        .execute(&self.pool)
+        .bind(disk)
+        .bind(disk)
+        .bind(start.to_rfc3339())
+        .bind(end.to_rfc3339())
+        .fetch_all(&self.pool)
         .await?;
 
-        // Delete old anomalies
-        let anomalies_result = sqlx::query(
+        rows.into_iter()
+            .map(|row| {
+                Ok(SeriesSample {
+                    timestamp: parse_timestamp(&row.timestamp)?,
+                    value: self.convert_from_disk_row(row),
+                })
+            })
+            .collect()
+    }
+
+    /// Retrieves one temperature sensor over a time range
+    ///
+    /// # Arguments
+    /// * `sensor` - Sensor chip, e.g. `coretemp`
+    /// * `label` - Sensor label, e.g. `Package id 0`
+    /// * `start` - Start of time range
+    /// * `end` - End of time range
+    ///
+    /// # Returns
+    /// Result containing the sensor's readings, oldest first, or SqlxError
+    pub async fn get_temperature_series(
+        &self,
+        sensor: &str,
+        label: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<SeriesSample<Temperature>>, SqlxError> {
+        let rows = sqlx::query_as::<_, TemperatureSampleRow>(
             r#"
-            DELETE FROM anomalies
-            WHERE timestamp < ?
+            SELECT * FROM temperature_samples
+            WHERE sensor = ? AND label = ? AND timestamp BETWEEN ? AND ?
+            ORDER BY timestamp ASC
             "#,
         )
-        .bind(cutoff_date.to_rfc3339())
-        .execute(&self.pool)
+        .bind(sensor)
+        .bind(label)
+        .bind(start.to_rfc3339())
+        .bind(end.to_rfc3339())
+        .fetch_all(&self.pool)
         .await?;
 
-        Ok((
-            metrics_result.rows_affected(),
-            anomalies_result.rows_affected(),
-        ))
+        rows.into_iter()
+            .map(|row| {
+                Ok(SeriesSample {
+                    timestamp: parse_timestamp(&row.timestamp)?,
+                    value: self.convert_from_temperature_row(row),
+                })
+            })
+            .collect()
     }
 
-    /// Stores or updates a configuration value
+    /// Retrieves one GPU over a time range
     ///
     /// # Arguments
-    /// * `key` - Configuration key
-    /// * `value` - Configuration value
+    /// * `name` - GPU name as reported by the driver
+    /// * `start` - Start of time range
+    /// * `end` - End of time range
     ///
     /// # Returns
-    /// Result indicating success or SqlxError
-    pub async fn set_config(&self, key: &str, value: &str) -> Result<(), SqlxError> {
-        sqlx::query(
+    /// Result containing the GPU's readings, oldest first, or SqlxError
+    pub async fn get_gpu_series(
+        &self,
+        name: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<SeriesSample<GpuMetrics>>, SqlxError> {
+        let rows = sqlx::query_as::<_, GpuSampleRow>(
             r#"
-            INSERT INTO config (key, value, updated_at)
-            VALUES (?, ?, CURRENT_TIMESTAMP)
-            ON CONFLICT(key) DO UPDATE SET
-                value = excluded.value,
-                updated_at = CURRENT_TIMESTAMP
+            SELECT * FROM gpu_samples
+            WHERE name = ? AND timestamp BETWEEN ? AND ?
+            ORDER BY timestamp ASC
             "#,
         )
-        .bind(key)
-        .bind(value)
-        .execute(&self.pool)
+        .bind(name)
+        .bind(start.to_rfc3339())
+        .bind(end.to_rfc3339())
+        .fetch_all(&self.pool)
         .await?;
 
-        Ok(())
+        rows.into_iter()
+            .map(|row| {
+                Ok(SeriesSample {
+                    timestamp: parse_timestamp(&row.timestamp)?,
+                    value: self.convert_from_gpu_row(row),
+                })
+            })
+            .collect()
     }
 
-    /// Retrieves a configuration value
+    /// Retrieves the samples in which a USB device was present over a time range
+    ///
+    /// Gaps between samples mean the device was unplugged.
     ///
     /// # Arguments
-    /// * `key` - Configuration key
+    /// * `device_id` - USB vendor:product id, e.g. `046d:c52b`
+    /// * `start` - Start of time range
+    /// * `end` - End of time range
     ///
     /// # Returns
-    /// Result containing Option<String> with the value or SqlxError
-    pub async fn get_config(&self, key: &str) -> Result<Option<String>, SqlxError> {
-        let result = sqlx::query(
+    /// Result containing the device's snapshots, oldest first, or SqlxError
+    pub async fn get_usb_series(
+        &self,
+        device_id: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<SeriesSample<UsbDevice>>, SqlxError> {
+        let rows = sqlx::query_as::<_, UsbSnapshotRow>(
             r#"
-            SELECT value FROM config WHERE key = ?
+            SELECT * FROM usb_snapshots
+            WHERE device_id = ? AND timestamp BETWEEN ? AND ?
+            ORDER BY timestamp ASC
             "#,
         )
-        .bind(key)
-        .fetch_optional(&self.pool)
+        .bind(device_id)
+        .bind(start.to_rfc3339())
+        .bind(end.to_rfc3339())
+        .fetch_all(&self.pool)
         .await?;
 
-        Ok(result.map(|row| row.get("value")))
+        rows.into_iter()
+            .map(|row| {
+                Ok(SeriesSample {
+                    timestamp: parse_timestamp(&row.timestamp)?,
+                    value: self.convert_from_usb_row(row),
+                })
+            })
+            .collect()
     }
 
-    // Helper methods for conversion
+    /// Retrieves one page of anomalies matching `filter`
+    ///
+    /// # Arguments
+    /// * `filter` - Severity, category, time range and message filters
+    /// * `sort` - Order by timestamp
+    /// * `cursor` - Position returned with the previous page, None for the first page
+    /// * `limit` - Maximum number of anomalies on the page
+    ///
+    /// # Returns
+    /// Result containing the page and the cursor of the next one, or SqlxError
+    pub async fn search_anomalies(
+        &self,
+        filter: &AnomalyFilter,
+        sort: SortOrder,
+        cursor: Option<&AnomalyCursor>,
+        limit: i64,
+    ) -> Result<AnomalyPage, SqlxError> {
+        let limit = limit.max(1);
+        let mut query = QueryBuilder::<Sqlite>::new("SELECT * FROM anomalies WHERE 1 = 1");
+        push_anomaly_filter(&mut query, filter);
 
-    fn convert_to_new_metrics(&self, metrics: &SystemMetrics) -> Result<NewMetrics, SqlxError> {
-        Ok(NewMetrics {
-            timestamp: metrics.timestamp,
-            cpu_global: metrics.cpu.global_usage,
-            cpu_per_core: serde_json::to_string(&metrics.cpu.per_core).map_err(|e| {
-                SqlxError::Protocol(format!("Failed to serialize cpu_per_core: {}", e))
-            })?,
-            load_avg_1: metrics.cpu.load_avg_1,
-            load_avg_5: metrics.cpu.load_avg_5,
-            load_avg_15: metrics.cpu.load_avg_15,
-            memory_total: metrics.memory.total as i64,
-            memory_used: metrics.memory.used as i64,
-            memory_available: metrics.memory.available as i64,
-            memory_percent: metrics.memory.usage_percent,
-            swap_total: metrics.memory.swap_total as i64,
-            swap_used: metrics.memory.swap_used as i64,
-            temperatures: if metrics.temperatures.is_empty() {
-                None
-            } else {
-                Some(serde_json::to_string(&metrics.temperatures).map_err(|e| {
-                    SqlxError::Protocol(format!("Failed to serialize temperatures: {}", e))
-                })?)
-            },
-            disks: if metrics.disks.is_empty() {
-                None
-            } else {
-                Some(serde_json::to_string(&metrics.disks).map_err(|e| {
-                    SqlxError::Protocol(format!("Failed to serialize disks: {}", e))
-                })?)
-            },
-            usb_devices: if metrics.usb_devices.is_empty() {
-                None
-            } else {
-                Some(serde_json::to_string(&metrics.usb_devices).map_err(|e| {
-                    SqlxError::Protocol(format!("Failed to serialize usb_devices: {}", e))
-                })?)
-            },
-            gpu: metrics.gpu.as_ref().map(|g| {
-                serde_json::to_string(g).unwrap_or_default()
-            }),
-            network_rx: metrics.network.rx_bytes as i64,
-            network_tx: metrics.network.tx_bytes as i64,
-            network_rx_packets: metrics.network.rx_packets as i64,
-            network_tx_packets: metrics.network.tx_packets as i64,
-        })
-    }
+        if let Some(cursor) = cursor {
+            let op = match sort {
+                SortOrder::Desc => "<",
+                SortOrder::Asc => ">",
+            };
+            query
+                .push(format!(" AND (timestamp {} ", op))
+                .push_bind(cursor.timestamp.clone())
+                .push(" OR (timestamp = ")
+                .push_bind(cursor.timestamp.clone())
+                .push(format!(" AND id {} ", op))
+                .push_bind(cursor.id)
+                .push("))");
+        }
 
-    fn convert_to_new_anomaly(&self, anomaly: &Anomaly) -> Result<NewAnomaly, SqlxError> {
-        let severity = match anomaly.severity {
-            AnomalySeverity::Info => "Info",
-            AnomalySeverity::Warning => "Warning",
-            AnomalySeverity::Critical => "Critical",
-        };
+        query
+            .push(format!(
+                " ORDER BY timestamp {0}, id {0} LIMIT ",
+                sort.sql()
+            ))
+            // One extra row tells whether there is a next page
+            .push_bind(limit + 1);
 
-        let category = match anomaly.category {
-            AnomalyCategory::Cpu => "Cpu",
-            AnomalyCategory::Memory => "Memory",
-            AnomalyCategory::Temperature => "Temperature",
-            AnomalyCategory::Disk => "Disk",
-            AnomalyCategory::Usb => "Usb",
-            AnomalyCategory::Network => "Network",
-            AnomalyCategory::Gpu => "Gpu",
-            AnomalyCategory::System => "System",
+        let mut rows = query
+            .build_query_as::<AnomalyRow>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        let next_cursor = if rows.len() as i64 > limit {
+            rows.truncate(limit as usize);
+            rows.last().map(|row| {
+                AnomalyCursor {
+                    timestamp: row.timestamp.clone(),
+                    id: row.id,
+                }
+                .encode()
+            })
+        } else {
+            None
         };
 
-        Ok(NewAnomaly {
-            timestamp: anomaly.timestamp,
-            severity: severity.to_string(),
-            category: category.to_string(),
-            message: anomaly.message.clone(),
-            metrics: serde_json::to_string(&anomaly.metrics)
-                .map_err(|e| SqlxError::Protocol(format!("Failed to serialize metrics: {}", e)))?,
+        let anomalies = rows
+            .into_iter()
+            .map(|row| self.convert_from_anomaly_row(row))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(AnomalyPage {
+            anomalies,
+            next_cursor,
         })
     }
 
-    fn convert_from_metrics_row(&self, row: MetricsRow) -> Result<SystemMetrics, SqlxError> {
-        use shared::types::{
-            CpuMetrics, DiskMetrics, GpuMetrics, MemoryMetrics, NetworkMetrics, Temperature, UsbDevice,
-        };
+    /// Counts anomalies matching `filter` by severity, category and time bucket
+    ///
+    /// Buckets are aligned to multiples of `bucket` since the Unix epoch. When
+    /// the filter has both a start and an end, buckets without anomalies are
+    /// included with zero counts.
+    ///
+    /// # Arguments
+    /// * `filter` - Severity, category, time range and message filters
+    /// * `bucket` - Width of each time bucket
+    pub async fn anomaly_stats(
+        &self,
+        filter: &AnomalyFilter,
+        bucket: Duration,
+    ) -> Result<AnomalyStats, SqlxError> {
+        let bucket_secs = bucket.num_seconds().max(1);
+        let mut query =
+            QueryBuilder::<Sqlite>::new("SELECT (CAST(strftime('%s', timestamp) AS INTEGER) / ");
+        query
+            .push_bind(bucket_secs)
+            .push(") * ")
+            .push_bind(bucket_secs)
+            .push(
+                " AS bucket, severity, category, COUNT(*) AS count \
+                 FROM anomalies WHERE 1 = 1",
+            );
+        push_anomaly_filter(&mut query, filter);
+        query.push(" GROUP BY bucket, severity, category ORDER BY bucket ASC");
 
-        let timestamp = DateTime::parse_from_rfc3339(&row.timestamp)
-            .map_err(|e| SqlxError::Protocol(format!("Failed to parse timestamp: {}", e)))?
-            .with_timezone(&Utc);
+        let rows = query.build().fetch_all(&self.pool).await?;
 
-        let cpu_per_core: Vec<f32> = serde_json::from_str(&row.cpu_per_core).map_err(|e| {
-            SqlxError::Protocol(format!("Failed to deserialize cpu_per_core: {}", e))
-        })?;
+        let mut stats = AnomalyStats::default();
+        let mut buckets: BTreeMap<i64, AnomalyStatsBucket> = BTreeMap::new();
 
-        let temperatures: Vec<Temperature> = if let Some(temps_json) = row.temperatures {
-            serde_json::from_str(&temps_json).map_err(|e| {
-                SqlxError::Protocol(format!("Failed to deserialize temperatures: {}", e))
-            })?
-        } else {
-            Vec::new()
-        };
+        if let (Some(start), Some(end)) = (filter.start, filter.end) {
+            let first = start.timestamp().div_euclid(bucket_secs) * bucket_secs;
+            for bucket_start in (first..=end.timestamp()).step_by(bucket_secs as usize) {
+                buckets.insert(bucket_start, empty_bucket(bucket_start)?);
+            }
+        }
 
-        let disks: Vec<DiskMetrics> = if let Some(disks_json) = row.disks {
-            serde_json::from_str(&disks_json)
-                .map_err(|e| SqlxError::Protocol(format!("Failed to deserialize disks: {}", e)))?
-        } else {
-            Vec::new()
-        };
+        for row in rows {
+            let bucket_start: i64 = row.try_get("bucket")?;
+            let severity: String = row.try_get("severity")?;
+            let category: String = row.try_get("category")?;
+            let count = row.try_get::<i64, _>("count")?.max(0) as u64;
 
-        let usb_devices: Vec<UsbDevice> = if let Some(usb_json) = row.usb_devices {
-            serde_json::from_str(&usb_json).map_err(|e| {
-                SqlxError::Protocol(format!("Failed to deserialize usb_devices: {}", e))
-            })?
-        } else {
-            Vec::new()
-        };
+            stats.total += count;
+            *stats.by_severity.entry(severity.clone()).or_default() += count;
+            *stats.by_category.entry(category).or_default() += count;
 
-        let gpu: Option<GpuMetrics> = if let Some(gpu_json) = row.gpu {
-            serde_json::from_str(&gpu_json).ok()
-        } else {
-            None
-        };
+            let entry = match buckets.entry(bucket_start) {
+                std::collections::btree_map::Entry::Occupied(entry) => entry.into_mut(),
+                std::collections::btree_map::Entry::Vacant(entry) => {
+                    entry.insert(empty_bucket(bucket_start)?)
+                }
+            };
+            entry.total += count;
+            *entry.by_severity.entry(severity).or_default() += count;
+        }
 
-        Ok(SystemMetrics {
-            timestamp,
-            cpu: CpuMetrics {
-                global_usage: row.cpu_global,
+        stats.buckets = buckets.into_values().collect();
+        Ok(stats)
+    }
+
+    /// Retrieves the metrics sample closest in time to `timestamp`
+    ///
+    /// Looks at the nearest sample on either side, so this works for
+    /// timestamps between samples as well as outside the stored range.
+    ///
+    /// # Returns
+    /// Result containing the SystemMetrics, None if no metrics are stored, or SqlxError
+    pub async fn get_metrics_nearest(
+        &self,
+        timestamp: DateTime<Utc>,
+    ) -> Result<Option<SystemMetrics>, SqlxError> {
+        let at = timestamp.to_rfc3339();
+
+        let before = sqlx::query_as::<_, MetricsRow>(
+            r#"
+            SELECT * FROM metrics
+            WHERE timestamp <= ?
+            ORDER BY timestamp DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(&at)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let after = sqlx::query_as::<_, MetricsRow>(
+            r#"
+            SELECT * FROM metrics
+            WHERE timestamp > ?
+            ORDER BY timestamp ASC
+            LIMIT 1
+            "#,
+        )
+        .bind(&at)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let nearest = match (before, after) {
+            (Some(b), Some(a)) => {
+                let b_ts = parse_timestamp(&b.timestamp)?;
+                let a_ts = parse_timestamp(&a.timestamp)?;
+                if timestamp - b_ts <= a_ts - timestamp {
+                    Some(b)
+                } else {
+                    Some(a)
+                }
+            }
+            (b, a) => b.or(a),
+        };
+
+        Ok(self
+            .load_metrics(nearest.into_iter().collect())
+            .await?
+            .pop())
+    }
+
+    /// Removes old data based on retention period
+    ///
+    /// # Arguments
+    /// * `retention_days` - Number of days to retain data
+    ///
+    /// # Returns
+    /// Result containing tuple of (deleted_metrics_count, deleted_anomalies_count) or SqlxError
+    pub async fn cleanup_old_data(&self, retention_days: i64) -> Result<(u64, u64), SqlxError> {
+        let cutoff_date = Utc::now() - Duration::days(retention_days);
+
+        let metrics_deleted = self.delete_metrics_before(cutoff_date).await?;
+        let anomalies_deleted = self.delete_anomalies_before(cutoff_date).await?;
+
+        Ok((metrics_deleted, anomalies_deleted))
+    }
+
+    /// Deletes raw metric samples older than `cutoff`
+    ///
+    /// # Returns
+    /// Result containing the number of deleted samples or SqlxError
+    pub async fn delete_metrics_before(&self, cutoff: DateTime<Utc>) -> Result<u64, SqlxError> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM metrics
+            WHERE timestamp < ?
+            "#,
+        )
+        .bind(cutoff.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Deletes anomalies older than `cutoff`
+    ///
+    /// # Returns
+    /// Result containing the number of deleted anomalies or SqlxError
+    pub async fn delete_anomalies_before(&self, cutoff: DateTime<Utc>) -> Result<u64, SqlxError> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM anomalies
+            WHERE timestamp < ?
+            "#,
+        )
+        .bind(cutoff.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Deletes resolved incidents that were last seen before `cutoff`
+    ///
+    /// Open and acknowledged incidents are kept regardless of age.
+    ///
+    /// # Returns
+    /// Result containing the number of deleted incidents or SqlxError
+    pub async fn delete_resolved_incidents_before(
+        &self,
+        cutoff: DateTime<Utc>,
+    ) -> Result<u64, SqlxError> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM incidents
+            WHERE status = ? AND last_seen < ?
+            "#,
+        )
+        .bind(status_name(IncidentStatus::Resolved))
+        .bind(cutoff.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Deletes the `count` oldest raw metric samples
+    ///
+    /// Used to bring the database back under its size limit.
+    ///
+    /// # Returns
+    /// Result containing the number of deleted samples or SqlxError
+    pub async fn delete_oldest_metrics(&self, count: u32) -> Result<u64, SqlxError> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM metrics
+            WHERE id IN (
+                SELECT id FROM metrics
+                ORDER BY timestamp ASC
+                LIMIT ?
+            )
+            "#,
+        )
+        .bind(count)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Returns the size of the database file in bytes
+    ///
+    /// This is `page_count * page_size`; free pages are included until an
+    /// incremental vacuum returns them to the filesystem.
+    pub async fn database_size(&self) -> Result<u64, SqlxError> {
+        let page_count: i64 = sqlx::query_scalar("PRAGMA page_count")
+            .fetch_one(&self.pool)
+            .await?;
+        let page_size: i64 = sqlx::query_scalar("PRAGMA page_size")
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok((page_count.max(0) as u64) * (page_size.max(0) as u64))
+    }
+
+    /// Switches the database to incremental auto-vacuum
+    ///
+    /// Databases created before this was enabled need one full VACUUM to
+    /// change mode, which rewrites the whole file, so this is done by the
+    /// maintenance task rather than during startup.
+    ///
+    /// # Returns
+    /// Result containing true if the database was converted, false if it
+    /// already used incremental auto-vacuum
+    pub async fn enable_incremental_vacuum(&self) -> Result<bool, SqlxError> {
+        let mode: i64 = sqlx::query_scalar("PRAGMA auto_vacuum")
+            .fetch_one(&self.pool)
+            .await?;
+        if mode == AUTO_VACUUM_INCREMENTAL {
+            return Ok(false);
+        }
+
+        // Both statements must run on the same connection, outside a transaction
+        let mut conn = self.pool.acquire().await?;
+        sqlx::query("PRAGMA auto_vacuum = INCREMENTAL")
+            .execute(&mut *conn)
+            .await?;
+        sqlx::query("VACUUM").execute(&mut *conn).await?;
+
+        Ok(true)
+    }
+
+    /// Returns free pages to the filesystem
+    ///
+    /// Only shrinks the file once incremental auto-vacuum is enabled.
+    pub async fn incremental_vacuum(&self) -> Result<(), SqlxError> {
+        sqlx::query("PRAGMA incremental_vacuum")
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Refreshes the query planner's table and index statistics
+    pub async fn analyze(&self) -> Result<(), SqlxError> {
+        sqlx::query("ANALYZE").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    /// Stores or updates a configuration value
+    ///
+    /// # Arguments
+    /// * `key` - Configuration key
+    /// * `value` - Configuration value
+    ///
+    /// # Returns
+    /// Result indicating success or SqlxError
+    pub async fn set_config(&self, key: &str, value: &str) -> Result<(), SqlxError> {
+        sqlx::query(
+            r#"
+            INSERT INTO config (key, value, updated_at)
+            VALUES (?, ?, CURRENT_TIMESTAMP)
+            ON CONFLICT(key) DO UPDATE SET
+                value = excluded.value,
+                updated_at = CURRENT_TIMESTAMP
+            "#,
+        )
+        .bind(key)
+        .bind(value)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Retrieves a configuration value
+    ///
+    /// # Arguments
+    /// * `key` - Configuration key
+    ///
+    /// # Returns
+    /// Result containing Option<String> with the value or SqlxError
+    pub async fn get_config(&self, key: &str) -> Result<Option<String>, SqlxError> {
+        let result = sqlx::query(
+            r#"
+            SELECT value FROM config WHERE key = ?
+            "#,
+        )
+        .bind(key)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result.map(|row| row.get("value")))
+    }
+
+    /// Inserts an incident or updates the stored copy
+    ///
+    /// An acknowledgement made through the API is kept while the incident
+    /// stays unresolved; reopening a resolved incident clears it.
+    ///
+    /// # Arguments
+    /// * `incident` - Incident as tracked by the collection loop
+    ///
+    /// # Returns
+    /// Result indicating success or SqlxError
+    pub async fn upsert_incident(&self, incident: &Incident) -> Result<(), SqlxError> {
+        sqlx::query(
+            r#"
+            INSERT INTO incidents (
+                id,
+                rule,
+                subject,
+                category,
+                severity,
+                status,
+                message,
+                first_seen,
+                last_seen,
+                occurrences,
+                acknowledged_at,
+                resolved_at,
+                last_anomaly_id
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(id) DO UPDATE SET
+                severity = excluded.severity,
+                status = CASE
+                    WHEN excluded.status = 'Open' AND incidents.status = 'Acknowledged'
+                        THEN 'Acknowledged'
+                    ELSE excluded.status
+                END,
+                acknowledged_at = CASE
+                    WHEN incidents.status = 'Resolved' AND excluded.status != 'Resolved'
+                        THEN NULL
+                    ELSE incidents.acknowledged_at
+                END,
+                message = excluded.message,
+                last_seen = excluded.last_seen,
+                occurrences = excluded.occurrences,
+                resolved_at = excluded.resolved_at,
+                last_anomaly_id = excluded.last_anomaly_id
+            "#,
+        )
+        .bind(&incident.id)
+        .bind(&incident.rule)
+        .bind(incident.subject.as_deref().unwrap_or_default())
+        .bind(category_name(&incident.category))
+        .bind(severity_name(&incident.severity))
+        .bind(status_name(incident.status))
+        .bind(&incident.message)
+        .bind(incident.first_seen.to_rfc3339())
+        .bind(incident.last_seen.to_rfc3339())
+        .bind(incident.occurrences as i64)
+        .bind(incident.acknowledged_at.map(|t| t.to_rfc3339()))
+        .bind(incident.resolved_at.map(|t| t.to_rfc3339()))
+        .bind(&incident.last_anomaly_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Marks an open incident as acknowledged
+    ///
+    /// Acknowledging an incident that is already acknowledged or resolved
+    /// leaves it unchanged.
+    ///
+    /// # Arguments
+    /// * `id` - Incident ID
+    ///
+    /// # Returns
+    /// Result containing the incident after the update, or None if it does not exist
+    pub async fn acknowledge_incident(&self, id: &str) -> Result<Option<Incident>, SqlxError> {
+        sqlx::query(
+            r#"
+            UPDATE incidents
+            SET status = 'Acknowledged', acknowledged_at = ?
+            WHERE id = ? AND status = 'Open'
+            "#,
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        self.get_incident(id).await
+    }
+
+    /// Retrieves one incident by ID
+    ///
+    /// # Arguments
+    /// * `id` - Incident ID
+    ///
+    /// # Returns
+    /// Result containing the incident, or None if it does not exist
+    pub async fn get_incident(&self, id: &str) -> Result<Option<Incident>, SqlxError> {
+        let row = sqlx::query_as::<_, IncidentRow>("SELECT * FROM incidents WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(|row| self.convert_from_incident_row(row))
+            .transpose()
+    }
+
+    /// Retrieves incidents, most recently seen first
+    ///
+    /// # Arguments
+    /// * `status` - Only return incidents in this state, or all when None
+    /// * `limit` - Maximum number of records to retrieve
+    ///
+    /// # Returns
+    /// Result containing Vec of Incident or SqlxError
+    pub async fn get_incidents(
+        &self,
+        status: Option<IncidentStatus>,
+        limit: i64,
+    ) -> Result<Vec<Incident>, SqlxError> {
+        let status = status.map(status_name);
+        let rows = sqlx::query_as::<_, IncidentRow>(
+            r#"
+            SELECT * FROM incidents
+            WHERE ? IS NULL OR status = ?
+            ORDER BY last_seen DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(status)
+        .bind(status)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| self.convert_from_incident_row(row))
+            .collect()
+    }
+
+    /// Retrieves every incident that is not resolved
+    ///
+    /// Used at startup so the collection loop continues existing incidents
+    /// instead of opening duplicates.
+    pub async fn get_active_incidents(&self) -> Result<Vec<Incident>, SqlxError> {
+        let rows = sqlx::query_as::<_, IncidentRow>(
+            r#"
+            SELECT * FROM incidents
+            WHERE status != 'Resolved'
+            ORDER BY last_seen DESC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| self.convert_from_incident_row(row))
+            .collect()
+    }
+
+    /// Mutes a rule until the given time, replacing any existing mute
+    ///
+    /// # Arguments
+    /// * `rule` - Rule name, as in an incident's `rule`
+    /// * `muted_until` - When the rule's anomalies are let through again
+    ///
+    /// # Returns
+    /// Result containing the stored mute or SqlxError
+    pub async fn mute_rule(
+        &self,
+        rule: &str,
+        muted_until: DateTime<Utc>,
+    ) -> Result<RuleMute, SqlxError> {
+        let mute = RuleMute {
+            rule: rule.to_string(),
+            muted_until,
+            created_at: Utc::now(),
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO rule_mutes (rule, muted_until, created_at)
+            VALUES (?, ?, ?)
+            ON CONFLICT(rule) DO UPDATE SET
+                muted_until = excluded.muted_until,
+                created_at = excluded.created_at
+            "#,
+        )
+        .bind(&mute.rule)
+        .bind(mute.muted_until.to_rfc3339())
+        .bind(mute.created_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(mute)
+    }
+
+    /// Removes a rule's mute
+    ///
+    /// # Arguments
+    /// * `rule` - Rule name
+    ///
+    /// # Returns
+    /// Result containing true if the rule was muted (expired mutes count as
+    /// not muted), or SqlxError
+    pub async fn unmute_rule(&self, rule: &str) -> Result<bool, SqlxError> {
+        let removed = sqlx::query("DELETE FROM rule_mutes WHERE rule = ? RETURNING muted_until")
+            .bind(rule)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match removed {
+            Some(row) => Ok(parse_timestamp(row.get("muted_until"))? > Utc::now()),
+            None => Ok(false),
+        }
+    }
+
+    /// Retrieves the mutes still in effect at `now`, soonest to expire first
+    ///
+    /// # Arguments
+    /// * `now` - Mutes ending at or before this time are left out
+    ///
+    /// # Returns
+    /// Result containing Vec of RuleMute or SqlxError
+    pub async fn get_rule_mutes(&self, now: DateTime<Utc>) -> Result<Vec<RuleMute>, SqlxError> {
+        let rows = sqlx::query_as::<_, RuleMuteRow>(
+            r#"
+            SELECT * FROM rule_mutes
+            WHERE muted_until > ?
+            ORDER BY muted_until ASC
+            "#,
+        )
+        .bind(now.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(RuleMute {
+                    rule: row.rule,
+                    muted_until: parse_timestamp(&row.muted_until)?,
+                    created_at: parse_timestamp(&row.created_at)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Saves learned baselines, replacing stored copies
+    ///
+    /// All baselines are written in one transaction.
+    ///
+    /// # Arguments
+    /// * `baselines` - Baselines exported by the detector
+    ///
+    /// # Returns
+    /// Result indicating success or SqlxError
+    pub async fn save_baselines(&self, baselines: &[MetricBaseline]) -> Result<(), SqlxError> {
+        let mut tx = self.pool.begin().await?;
+
+        for baseline in baselines {
+            sqlx::query(
+                r#"
+                INSERT INTO baselines (rule, subject, hour, mean, variance, samples, updated_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                ON CONFLICT(rule, subject, hour) DO UPDATE SET
+                    mean = excluded.mean,
+                    variance = excluded.variance,
+                    samples = excluded.samples,
+                    updated_at = excluded.updated_at
+                "#,
+            )
+            .bind(&baseline.rule)
+            .bind(baseline.subject.as_deref().unwrap_or_default())
+            .bind(baseline.hour.map_or(-1, i64::from))
+            .bind(baseline.mean)
+            .bind(baseline.variance)
+            .bind(baseline.samples as i64)
+            .bind(baseline.updated_at.to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await
+    }
+
+    /// Retrieves every stored baseline
+    ///
+    /// # Returns
+    /// Result containing Vec of MetricBaseline or SqlxError
+    pub async fn load_baselines(&self) -> Result<Vec<MetricBaseline>, SqlxError> {
+        let rows = sqlx::query_as::<_, BaselineRow>("SELECT * FROM baselines")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(MetricBaseline {
+                    rule: row.rule,
+                    subject: (!row.subject.is_empty()).then_some(row.subject),
+                    hour: u8::try_from(row.hour).ok(),
+                    mean: row.mean,
+                    variance: row.variance,
+                    samples: row.samples as u64,
+                    updated_at: parse_timestamp(&row.updated_at)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Converts metrics rows and attaches their disks, temperatures, GPU and USB devices
+    async fn load_metrics(&self, rows: Vec<MetricsRow>) -> Result<Vec<SystemMetrics>, SqlxError> {
+        let ids: Vec<i64> = rows.iter().map(|row| row.id).collect();
+        let mut disks: HashMap<i64, Vec<DiskMetrics>> = HashMap::new();
+        let mut temperatures: HashMap<i64, Vec<Temperature>> = HashMap::new();
+        let mut gpus: HashMap<i64, GpuMetrics> = HashMap::new();
+        let mut usb_devices: HashMap<i64, Vec<UsbDevice>> = HashMap::new();
+
+        for chunk in ids.chunks(CHILD_LOOKUP_CHUNK) {
+            for row in self
+                .fetch_children::<DiskSampleRow>("disk_samples", chunk)
+                .await?
+            {
+                let metric_id = row.metric_id;
+                disks
+                    .entry(metric_id)
+                    .or_default()
+                    .push(self.convert_from_disk_row(row));
+            }
+            for row in self
+                .fetch_children::<TemperatureSampleRow>("temperature_samples", chunk)
+                .await?
+            {
+                let metric_id = row.metric_id;
+                temperatures
+                    .entry(metric_id)
+                    .or_default()
+                    .push(self.convert_from_temperature_row(row));
+            }
+            for row in self
+                .fetch_children::<GpuSampleRow>("gpu_samples", chunk)
+                .await?
+            {
+                gpus.insert(row.metric_id, self.convert_from_gpu_row(row));
+            }
+            for row in self
+                .fetch_children::<UsbSnapshotRow>("usb_snapshots", chunk)
+                .await?
+            {
+                let metric_id = row.metric_id;
+                usb_devices
+                    .entry(metric_id)
+                    .or_default()
+                    .push(self.convert_from_usb_row(row));
+            }
+        }
+
+        rows.into_iter()
+            .map(|row| {
+                let id = row.id;
+                let mut metrics = self.convert_from_metrics_row(row)?;
+                metrics.disks = disks.remove(&id).unwrap_or_default();
+                metrics.temperatures = temperatures.remove(&id).unwrap_or_default();
+                metrics.gpu = gpus.remove(&id);
+                metrics.usb_devices = usb_devices.remove(&id).unwrap_or_default();
+                Ok(metrics)
+            })
+            .collect()
+    }
+
+    /// Fetches the rows of a child table belonging to `metric_ids`, in sample order
+    async fn fetch_children<R>(&self, table: &str, metric_ids: &[i64]) -> Result<Vec<R>, SqlxError>
+    where
+        R: for<'r> FromRow<'r, SqliteRow> + Send + Unpin,
+    {
+        if metric_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut query =
+            QueryBuilder::<Sqlite>::new(format!("SELECT * FROM {} WHERE metric_id IN (", table));
+        let mut ids = query.separated(", ");
+        for id in metric_ids {
+            ids.push_bind(*id);
+        }
+        query.push(") ORDER BY metric_id, position");
+
+        query.build_query_as::<R>().fetch_all(&self.pool).await
+    }
+
+    // Helper methods for conversion
+
+    fn convert_to_new_metrics(&self, metrics: &SystemMetrics) -> Result<NewMetrics, SqlxError> {
+        Ok(NewMetrics {
+            timestamp: metrics.timestamp,
+            cpu_global: metrics.cpu.global_usage,
+            cpu_per_core: serde_json::to_string(&metrics.cpu.per_core).map_err(|e| {
+                SqlxError::Protocol(format!("Failed to serialize cpu_per_core: {}", e))
+            })?,
+            load_avg_1: metrics.cpu.load_avg_1,
+            load_avg_5: metrics.cpu.load_avg_5,
+            load_avg_15: metrics.cpu.load_avg_15,
+            memory_total: metrics.memory.total as i64,
+            memory_used: metrics.memory.used as i64,
+            memory_available: metrics.memory.available as i64,
+            memory_percent: metrics.memory.usage_percent,
+            swap_total: metrics.memory.swap_total as i64,
+            swap_used: metrics.memory.swap_used as i64,
+            network_rx: metrics.network.rx_bytes as i64,
+            network_tx: metrics.network.tx_bytes as i64,
+            network_rx_packets: metrics.network.rx_packets as i64,
+            network_tx_packets: metrics.network.tx_packets as i64,
+            processes: if metrics.processes.is_empty() {
+                None
+            } else {
+                Some(serde_json::to_string(&metrics.processes).map_err(|e| {
+                    SqlxError::Protocol(format!("Failed to serialize processes: {}", e))
+                })?)
+            },
+            zombies: if metrics.zombies.total == 0 {
+                None
+            } else {
+                Some(serde_json::to_string(&metrics.zombies).map_err(|e| {
+                    SqlxError::Protocol(format!("Failed to serialize zombies: {}", e))
+                })?)
+            },
+            network_interfaces: if metrics.network.interfaces.is_empty() {
+                None
+            } else {
+                Some(
+                    serde_json::to_string(&metrics.network.interfaces).map_err(|e| {
+                        SqlxError::Protocol(format!(
+                            "Failed to serialize network interfaces: {}",
+                            e
+                        ))
+                    })?,
+                )
+            },
+        })
+    }
+
+    fn convert_to_new_anomaly(&self, anomaly: &Anomaly) -> Result<NewAnomaly, SqlxError> {
+        Ok(NewAnomaly {
+            uuid: anomaly.id.clone(),
+            timestamp: anomaly.timestamp,
+            severity: severity_name(&anomaly.severity).to_string(),
+            category: category_name(&anomaly.category).to_string(),
+            message: anomaly.message.clone(),
+            metrics: serde_json::to_string(&anomaly.metrics)
+                .map_err(|e| SqlxError::Protocol(format!("Failed to serialize metrics: {}", e)))?,
+        })
+    }
+
+    fn convert_from_metrics_row(&self, row: MetricsRow) -> Result<SystemMetrics, SqlxError> {
+        use shared::types::{
+            CpuMetrics, MemoryMetrics, NetworkInterface, NetworkMetrics, ProcessInfo, ZombieMetrics,
+        };
+
+        let timestamp = DateTime::parse_from_rfc3339(&row.timestamp)
+            .map_err(|e| SqlxError::Protocol(format!("Failed to parse timestamp: {}", e)))?
+            .with_timezone(&Utc);
+
+        let cpu_per_core: Vec<f32> = serde_json::from_str(&row.cpu_per_core).map_err(|e| {
+            SqlxError::Protocol(format!("Failed to deserialize cpu_per_core: {}", e))
+        })?;
+
+        let processes: Vec<ProcessInfo> = if let Some(processes_json) = row.processes {
+            serde_json::from_str(&processes_json).map_err(|e| {
+                SqlxError::Protocol(format!("Failed to deserialize processes: {}", e))
+            })?
+        } else {
+            Vec::new()
+        };
+
+        let zombies: ZombieMetrics = if let Some(zombies_json) = row.zombies {
+            serde_json::from_str(&zombies_json)
+                .map_err(|e| SqlxError::Protocol(format!("Failed to deserialize zombies: {}", e)))?
+        } else {
+            ZombieMetrics::default()
+        };
+
+        let interfaces: Vec<NetworkInterface> =
+            if let Some(interfaces_json) = row.network_interfaces {
+                serde_json::from_str(&interfaces_json).map_err(|e| {
+                    SqlxError::Protocol(format!("Failed to deserialize network interfaces: {}", e))
+                })?
+            } else {
+                Vec::new()
+            };
+
+        Ok(SystemMetrics {
+            timestamp,
+            cpu: CpuMetrics {
+                global_usage: row.cpu_global,
                 per_core: cpu_per_core,
                 load_avg_1: row.load_avg_1,
                 load_avg_5: row.load_avg_5,
@@ -491,58 +1654,81 @@ impl MetricsRepository {
                 swap_total: row.swap_total as u64,
                 swap_used: row.swap_used as u64,
             },
-            temperatures,
-            disks,
-            usb_devices,
+            // Filled in from the child tables by `load_metrics`
+            temperatures: Vec::new(),
+            disks: Vec::new(),
+            usb_devices: Vec::new(),
             network: NetworkMetrics {
                 rx_bytes: row.network_rx as u64,
                 tx_bytes: row.network_tx as u64,
                 rx_packets: row.network_rx_packets as u64,
                 tx_packets: row.network_tx_packets as u64,
+                interfaces,
             },
-            gpu,
+            gpu: None,
+            processes,
+            zombies,
         })
     }
 
+    fn convert_from_disk_row(&self, row: DiskSampleRow) -> DiskMetrics {
+        DiskMetrics {
+            name: row.name,
+            mount_point: row.mount_point,
+            total: row.total as u64,
+            used: row.used as u64,
+            available: row.available as u64,
+            usage_percent: row.usage_percent,
+            read_mb: row.read_mb,
+            write_mb: row.write_mb,
+        }
+    }
+
+    fn convert_from_temperature_row(&self, row: TemperatureSampleRow) -> Temperature {
+        Temperature {
+            sensor: row.sensor,
+            value: row.value,
+            label: row.label,
+        }
+    }
+
+    fn convert_from_gpu_row(&self, row: GpuSampleRow) -> GpuMetrics {
+        GpuMetrics {
+            name: row.name,
+            temperature: row.temperature,
+            usage_percent: row.usage_percent,
+            memory_usage_percent: row.memory_usage_percent,
+            memory_total_mb: row.memory_total_mb as u64,
+            memory_used_mb: row.memory_used_mb as u64,
+            memory_free_mb: row.memory_free_mb as u64,
+            power_draw_watts: row.power_draw_watts,
+            fan_speed_percent: row.fan_speed_percent,
+        }
+    }
+
+    fn convert_from_usb_row(&self, row: UsbSnapshotRow) -> UsbDevice {
+        UsbDevice {
+            id: row.device_id,
+            manufacturer: row.manufacturer,
+            product: row.product,
+            has_timeout: row.has_timeout,
+        }
+    }
+
     fn convert_from_anomaly_row(&self, row: AnomalyRow) -> Result<Anomaly, SqlxError> {
         let timestamp = DateTime::parse_from_rfc3339(&row.timestamp)
             .map_err(|e| SqlxError::Protocol(format!("Failed to parse timestamp: {}", e)))?
             .with_timezone(&Utc);
 
-        let severity = match row.severity.as_str() {
-            "Info" => AnomalySeverity::Info,
-            "Warning" => AnomalySeverity::Warning,
-            "Critical" => AnomalySeverity::Critical,
-            _ => {
-                return Err(SqlxError::Protocol(format!(
-                    "Invalid severity: {}",
-                    row.severity
-                )))
-            }
-        };
-
-        let category = match row.category.as_str() {
-            "Cpu" => AnomalyCategory::Cpu,
-            "Memory" => AnomalyCategory::Memory,
-            "Temperature" => AnomalyCategory::Temperature,
-            "Disk" => AnomalyCategory::Disk,
-            "Usb" => AnomalyCategory::Usb,
-            "Network" => AnomalyCategory::Network,
-            "Gpu" => AnomalyCategory::Gpu,
-            "System" => AnomalyCategory::System,
-            _ => {
-                return Err(SqlxError::Protocol(format!(
-                    "Invalid category: {}",
-                    row.category
-                )))
-            }
-        };
+        let severity = parse_severity(&row.severity)?;
+        let category = parse_category(&row.category)?;
 
         let metrics: serde_json::Value = serde_json::from_str(&row.metrics)
             .map_err(|e| SqlxError::Protocol(format!("Failed to deserialize metrics: {}", e)))?;
 
         Ok(Anomaly {
-            id: row.id.to_string(),
+            // Rows written before UUIDs were stored keep their rowid
+            id: row.uuid.unwrap_or_else(|| row.id.to_string()),
             timestamp,
             severity,
             category,
@@ -550,6 +1736,158 @@ impl MetricsRepository {
             metrics,
         })
     }
+
+    fn convert_from_incident_row(&self, row: IncidentRow) -> Result<Incident, SqlxError> {
+        let status = match row.status.as_str() {
+            "Open" => IncidentStatus::Open,
+            "Acknowledged" => IncidentStatus::Acknowledged,
+            "Resolved" => IncidentStatus::Resolved,
+            _ => {
+                return Err(SqlxError::Protocol(format!(
+                    "Invalid incident status: {}",
+                    row.status
+                )))
+            }
+        };
+
+        Ok(Incident {
+            id: row.id,
+            rule: row.rule,
+            subject: (!row.subject.is_empty()).then_some(row.subject),
+            category: parse_category(&row.category)?,
+            severity: parse_severity(&row.severity)?,
+            status,
+            message: row.message,
+            first_seen: parse_timestamp(&row.first_seen)?,
+            last_seen: parse_timestamp(&row.last_seen)?,
+            occurrences: row.occurrences as u64,
+            acknowledged_at: row
+                .acknowledged_at
+                .as_deref()
+                .map(parse_timestamp)
+                .transpose()?,
+            resolved_at: row
+                .resolved_at
+                .as_deref()
+                .map(parse_timestamp)
+                .transpose()?,
+            last_anomaly_id: row.last_anomaly_id,
+        })
+    }
+}
+
+fn parse_timestamp(value: &str) -> Result<DateTime<Utc>, SqlxError> {
+    Ok(DateTime::parse_from_rfc3339(value)
+        .map_err(|e| SqlxError::Protocol(format!("Failed to parse timestamp: {}", e)))?
+        .with_timezone(&Utc))
+}
+
+/// Appends `AND ...` clauses for every set field of `filter`
+fn push_anomaly_filter(query: &mut QueryBuilder<'_, Sqlite>, filter: &AnomalyFilter) {
+    if !filter.severities.is_empty() {
+        query.push(" AND severity IN (");
+        let mut list = query.separated(", ");
+        for severity in &filter.severities {
+            list.push_bind(severity_name(severity));
+        }
+        query.push(")");
+    }
+
+    if !filter.categories.is_empty() {
+        query.push(" AND category IN (");
+        let mut list = query.separated(", ");
+        for category in &filter.categories {
+            list.push_bind(category_name(category));
+        }
+        query.push(")");
+    }
+
+    if let Some(start) = filter.start {
+        query
+            .push(" AND timestamp >= ")
+            .push_bind(start.to_rfc3339());
+    }
+    if let Some(end) = filter.end {
+        query.push(" AND timestamp <= ").push_bind(end.to_rfc3339());
+    }
+
+    if let Some(text) = filter.text.as_deref().filter(|t| !t.is_empty()) {
+        // LIKE is case-insensitive for ASCII; escape its wildcards in the search text
+        let escaped = text
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        query
+            .push(" AND message LIKE ")
+            .push_bind(format!("%{}%", escaped))
+            .push(" ESCAPE '\\'");
+    }
+}
+
+/// Creates a stats bucket with no anomalies starting at `start` (Unix seconds)
+fn empty_bucket(start: i64) -> Result<AnomalyStatsBucket, SqlxError> {
+    let start = DateTime::from_timestamp(start, 0)
+        .ok_or_else(|| SqlxError::Protocol(format!("Invalid bucket start: {}", start)))?;
+
+    Ok(AnomalyStatsBucket {
+        start,
+        total: 0,
+        by_severity: BTreeMap::new(),
+    })
+}
+
+fn severity_name(severity: &AnomalySeverity) -> &'static str {
+    match severity {
+        AnomalySeverity::Info => "Info",
+        AnomalySeverity::Warning => "Warning",
+        AnomalySeverity::Critical => "Critical",
+    }
+}
+
+fn category_name(category: &AnomalyCategory) -> &'static str {
+    match category {
+        AnomalyCategory::Cpu => "Cpu",
+        AnomalyCategory::Memory => "Memory",
+        AnomalyCategory::Temperature => "Temperature",
+        AnomalyCategory::Disk => "Disk",
+        AnomalyCategory::Usb => "Usb",
+        AnomalyCategory::Network => "Network",
+        AnomalyCategory::Gpu => "Gpu",
+        AnomalyCategory::Process => "Process",
+        AnomalyCategory::System => "System",
+    }
+}
+
+fn status_name(status: IncidentStatus) -> &'static str {
+    match status {
+        IncidentStatus::Open => "Open",
+        IncidentStatus::Acknowledged => "Acknowledged",
+        IncidentStatus::Resolved => "Resolved",
+    }
+}
+
+fn parse_severity(value: &str) -> Result<AnomalySeverity, SqlxError> {
+    match value {
+        "Info" => Ok(AnomalySeverity::Info),
+        "Warning" => Ok(AnomalySeverity::Warning),
+        "Critical" => Ok(AnomalySeverity::Critical),
+        _ => Err(SqlxError::Protocol(format!("Invalid severity: {}", value))),
+    }
+}
+
+fn parse_category(value: &str) -> Result<AnomalyCategory, SqlxError> {
+    match value {
+        "Cpu" => Ok(AnomalyCategory::Cpu),
+        "Memory" => Ok(AnomalyCategory::Memory),
+        "Temperature" => Ok(AnomalyCategory::Temperature),
+        "Disk" => Ok(AnomalyCategory::Disk),
+        "Usb" => Ok(AnomalyCategory::Usb),
+        "Network" => Ok(AnomalyCategory::Network),
+        "Gpu" => Ok(AnomalyCategory::Gpu),
+        "Process" => Ok(AnomalyCategory::Process),
+        "System" => Ok(AnomalyCategory::System),
+        _ => Err(SqlxError::Protocol(format!("Invalid category: {}", value))),
+    }
 }
 
 #[cfg(test)]
@@ -561,4 +1899,562 @@ mod tests {
         let repo = MetricsRepository::new("sqlite::memory:").await;
         assert!(repo.is_ok());
     }
+
+    fn sample_metrics() -> SystemMetrics {
+        use shared::types::{CpuMetrics, MemoryMetrics, NetworkMetrics};
+
+        SystemMetrics {
+            timestamp: Utc::now(),
+            cpu: CpuMetrics {
+                global_usage: 10.0,
+                per_core: vec![10.0],
+                load_avg_1: 0.1,
+                load_avg_5: 0.1,
+                load_avg_15: 0.1,
+            },
+            memory: MemoryMetrics {
+                total: 1000,
+                used: 500,
+                available: 500,
+                usage_percent: 50.0,
+                swap_total: 0,
+                swap_used: 0,
+            },
+            temperatures: vec![],
+            disks: vec![],
+            usb_devices: vec![],
+            network: NetworkMetrics {
+                rx_bytes: 0,
+                tx_bytes: 0,
+                rx_packets: 0,
+                tx_packets: 0,
+                interfaces: vec![],
+            },
+            gpu: None,
+            processes: vec![],
+            zombies: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_store_and_load_processes() {
+        use shared::types::ProcessInfo;
+
+        let repo = MetricsRepository::new("sqlite::memory:").await.unwrap();
+        repo.run_migrations().await.unwrap();
+        // Migrations are idempotent
+        repo.run_migrations().await.unwrap();
+
+        let mut metrics = sample_metrics();
+        metrics.processes = vec![ProcessInfo {
+            pid: 1,
+            name: "init".to_string(),
+            cmdline: "/sbin/init".to_string(),
+            user: "root".to_string(),
+            state: "Sleeping".to_string(),
+            cpu_usage: 0.5,
+            memory_rss: 4096,
+            disk_read_bytes_per_sec: 0,
+            disk_write_bytes_per_sec: 0,
+        }];
+
+        repo.store_metrics(&metrics).await.unwrap();
+        let stored = repo.get_recent_metrics(1).await.unwrap();
+
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].processes.len(), 1);
+        assert_eq!(stored[0].processes[0].cmdline, "/sbin/init");
+    }
+
+    #[tokio::test]
+    async fn test_store_zombies_and_process_anomaly() {
+        use shared::types::{ZombieGroup, ZombieMetrics};
+
+        let repo = MetricsRepository::new("sqlite::memory:").await.unwrap();
+        repo.run_migrations().await.unwrap();
+
+        let mut metrics = sample_metrics();
+        metrics.zombies = ZombieMetrics {
+            total: 3,
+            groups: vec![ZombieGroup {
+                parent_pid: 42,
+                parent_command: "supervisor --daemon".to_string(),
+                count: 3,
+                commands: vec!["worker".to_string()],
+                pids: vec![100, 101, 102],
+                oldest_age_secs: 120,
+            }],
+        };
+        repo.store_metrics(&metrics).await.unwrap();
+
+        let stored = repo.get_recent_metrics(1).await.unwrap();
+        assert_eq!(stored[0].zombies.total, 3);
+        assert_eq!(stored[0].zombies.groups[0].parent_pid, 42);
+
+        let anomaly = Anomaly {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: Utc::now(),
+            severity: AnomalySeverity::Warning,
+            category: AnomalyCategory::Process,
+            message: "3 zombie processes under supervisor --daemon (pid 42)".to_string(),
+            metrics: serde_json::json!({}),
+        };
+        repo.store_anomaly(&anomaly).await.unwrap();
+
+        let anomalies = repo.get_recent_anomalies(1).await.unwrap();
+        assert_eq!(anomalies[0].category, AnomalyCategory::Process);
+    }
+
+    #[tokio::test]
+    async fn test_get_anomaly_by_id_and_nearest_metrics() {
+        let repo = MetricsRepository::new("sqlite::memory:").await.unwrap();
+        repo.run_migrations().await.unwrap();
+
+        let now = Utc::now();
+        for secs in [0, 10, 20] {
+            let mut metrics = sample_metrics();
+            metrics.timestamp = now + Duration::seconds(secs);
+            repo.store_metrics(&metrics).await.unwrap();
+        }
+
+        let anomaly = Anomaly {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: now + Duration::seconds(13),
+            severity: AnomalySeverity::Critical,
+            category: AnomalyCategory::Cpu,
+            message: "CPU usage critical".to_string(),
+            metrics: serde_json::json!({ "rule": "cpu_critical", "value": 97.5 }),
+        };
+        repo.store_anomaly(&anomaly).await.unwrap();
+
+        let stored = repo.get_anomaly_by_id(&anomaly.id).await.unwrap().unwrap();
+        assert_eq!(stored.id, anomaly.id);
+        assert_eq!(stored.severity, AnomalySeverity::Critical);
+        assert_eq!(stored.metrics["value"], 97.5);
+        assert!(repo.get_anomaly_by_id("missing").await.unwrap().is_none());
+
+        let nearest = repo.get_metrics_nearest(stored.timestamp).await.unwrap();
+        assert_eq!(nearest.unwrap().timestamp, now + Duration::seconds(10));
+        let nearest = repo
+            .get_metrics_nearest(now + Duration::seconds(17))
+            .await
+            .unwrap();
+        assert_eq!(nearest.unwrap().timestamp, now + Duration::seconds(20));
+        let nearest = repo
+            .get_metrics_nearest(now - Duration::hours(1))
+            .await
+            .unwrap();
+        assert_eq!(nearest.unwrap().timestamp, now);
+    }
+
+    #[tokio::test]
+    async fn test_search_anomalies_and_stats() {
+        let repo = MetricsRepository::new("sqlite::memory:").await.unwrap();
+        repo.run_migrations().await.unwrap();
+
+        let start = Utc::now() - Duration::hours(3);
+        for i in 0..10 {
+            let (severity, category, message) = if i % 2 == 0 {
+                (
+                    AnomalySeverity::Warning,
+                    AnomalyCategory::Cpu,
+                    "CPU 95% busy",
+                )
+            } else {
+                (
+                    AnomalySeverity::Critical,
+                    AnomalyCategory::Disk,
+                    "Disk /home 100%_full",
+                )
+            };
+            let anomaly = Anomaly {
+                id: uuid::Uuid::new_v4().to_string(),
+                timestamp: start + Duration::minutes(15 * i),
+                severity,
+                category,
+                message: message.to_string(),
+                metrics: serde_json::json!({}),
+            };
+            repo.store_anomaly(&anomaly).await.unwrap();
+        }
+
+        // Keyset pagination walks every match exactly once
+        let filter = AnomalyFilter {
+            severities: vec![AnomalySeverity::Warning],
+            ..Default::default()
+        };
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = repo
+                .search_anomalies(&filter, SortOrder::Asc, cursor.as_ref(), 2)
+                .await
+                .unwrap();
+            seen.extend(page.anomalies.into_iter().map(|a| a.timestamp));
+            match page.next_cursor {
+                Some(token) => cursor = AnomalyCursor::decode(&token),
+                None => break,
+            }
+        }
+        assert_eq!(seen.len(), 5);
+        assert!(seen.windows(2).all(|w| w[0] < w[1]));
+
+        // Category, time range and literal text (with LIKE wildcards) combined
+        let filter = AnomalyFilter {
+            categories: vec![AnomalyCategory::Disk],
+            start: Some(start + Duration::minutes(30)),
+            end: Some(start + Duration::minutes(120)),
+            text: Some("100%_FULL".to_string()),
+            ..Default::default()
+        };
+        let page = repo
+            .search_anomalies(&filter, SortOrder::Desc, None, 100)
+            .await
+            .unwrap();
+        assert_eq!(page.anomalies.len(), 3);
+        assert_eq!(page.anomalies[0].timestamp, start + Duration::minutes(105));
+        assert!(page.next_cursor.is_none());
+        let filter = AnomalyFilter {
+            text: Some("95_".to_string()),
+            ..Default::default()
+        };
+        let page = repo
+            .search_anomalies(&filter, SortOrder::Desc, None, 100)
+            .await
+            .unwrap();
+        assert!(page.anomalies.is_empty());
+
+        let filter = AnomalyFilter {
+            start: Some(start),
+            end: Some(start + Duration::hours(3)),
+            ..Default::default()
+        };
+        let stats = repo
+            .anomaly_stats(&filter, Duration::hours(1))
+            .await
+            .unwrap();
+        assert_eq!(stats.total, 10);
+        assert_eq!(stats.by_severity["Critical"], 5);
+        assert_eq!(stats.by_category["Cpu"], 5);
+        assert_eq!(
+            stats.buckets.iter().map(|b| b.total).sum::<u64>(),
+            stats.total
+        );
+        assert!(stats.buckets.len() >= 3);
+        assert!(stats.buckets.windows(2).all(|w| w[0].start < w[1].start));
+    }
+
+    fn sample_with_devices(timestamp: DateTime<Utc>, home_usage: f32) -> SystemMetrics {
+        use shared::types::{DiskMetrics, GpuMetrics, Temperature, UsbDevice};
+
+        let disk = |name: &str, mount_point: &str, usage_percent: f32| DiskMetrics {
+            name: name.to_string(),
+            mount_point: mount_point.to_string(),
+            total: 1000,
+            used: (usage_percent * 10.0) as u64,
+            available: 1000 - (usage_percent * 10.0) as u64,
+            usage_percent,
+            read_mb: 1.5,
+            write_mb: 0.5,
+        };
+
+        let mut metrics = sample_metrics();
+        metrics.timestamp = timestamp;
+        metrics.disks = vec![
+            disk("/dev/sda1", "/", 40.0),
+            disk("/dev/sdb1", "/home", home_usage),
+        ];
+        metrics.temperatures = vec![Temperature {
+            sensor: "coretemp".to_string(),
+            value: 40.0 + home_usage / 10.0,
+            label: "Package id 0".to_string(),
+        }];
+        metrics.gpu = Some(GpuMetrics {
+            name: "RTX 3080".to_string(),
+            temperature: 60.0,
+            usage_percent: 35.0,
+            memory_usage_percent: 20.0,
+            memory_total_mb: 10240,
+            memory_used_mb: 2048,
+            memory_free_mb: 8192,
+            power_draw_watts: 120.0,
+            fan_speed_percent: 40.0,
+        });
+        metrics.usb_devices = vec![UsbDevice {
+            id: "046d:c52b".to_string(),
+            manufacturer: "Logitech".to_string(),
+            product: "Unifying Receiver".to_string(),
+            has_timeout: false,
+        }];
+        metrics
+    }
+
+    #[tokio::test]
+    async fn test_child_tables_round_trip_and_series() {
+        let repo = MetricsRepository::new("sqlite::memory:").await.unwrap();
+        repo.run_migrations().await.unwrap();
+
+        let start = Utc::now();
+        for i in 0..5 {
+            let metrics = sample_with_devices(start + Duration::seconds(i), 50.0 + i as f32);
+            repo.store_metrics(&metrics).await.unwrap();
+        }
+
+        let stored = repo.get_recent_metrics(1).await.unwrap().remove(0);
+        let expected = sample_with_devices(start + Duration::seconds(4), 54.0);
+        assert_eq!(
+            serde_json::to_value(&stored.disks).unwrap(),
+            serde_json::to_value(&expected.disks).unwrap()
+        );
+        assert_eq!(stored.temperatures[0].label, "Package id 0");
+        assert_eq!(stored.gpu.unwrap().memory_total_mb, 10240);
+        assert_eq!(stored.usb_devices[0].product, "Unifying Receiver");
+
+        let end = start + Duration::seconds(10);
+        let home = repo.get_disk_series("/home", start, end).await.unwrap();
+        assert_eq!(home.len(), 5);
+        assert_eq!(home[0].timestamp, start);
+        assert_eq!(home[4].value.usage_percent, 54.0);
+        let by_name = repo.get_disk_series("/dev/sdb1", start, end).await.unwrap();
+        assert_eq!(by_name.len(), 5);
+        assert_eq!(by_name[4].value.mount_point, "/home");
+
+        let package = repo
+            .get_temperature_series(
+                "coretemp",
+                "Package id 0",
+                start + Duration::seconds(3),
+                end,
+            )
+            .await
+            .unwrap();
+        assert_eq!(package.len(), 2);
+        assert_eq!(package[1].value.value, 45.4);
+
+        assert_eq!(
+            repo.get_gpu_series("RTX 3080", start, end)
+                .await
+                .unwrap()
+                .len(),
+            5
+        );
+        assert_eq!(
+            repo.get_usb_series("046d:c52b", start, end)
+                .await
+                .unwrap()
+                .len(),
+            5
+        );
+        assert!(repo
+            .get_usb_series("dead:beef", start, end)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Child rows follow their sample on deletion
+        repo.delete_metrics_before(end).await.unwrap();
+        assert!(repo
+            .get_disk_series("/home", start, end)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_migration_moves_json_columns_to_child_tables() {
+        let repo = MetricsRepository::new("sqlite::memory:").await.unwrap();
+
+        // Schema as of migration 8, with one sample in the old JSON layout
+        for (version, migration_sql) in MIGRATIONS.iter().filter(|(v, _)| *v <= 8) {
+            sqlx::query(migration_sql)
+                .execute(&repo.pool)
+                .await
+                .unwrap();
+            sqlx::query(&format!("PRAGMA user_version = {}", version))
+                .execute(&repo.pool)
+                .await
+                .unwrap();
+        }
+        let legacy = sample_with_devices(Utc::now(), 70.0);
+        sqlx::query(
+            r#"
+            INSERT INTO metrics (
+                timestamp, cpu_global, cpu_per_core, load_avg_1, load_avg_5, load_avg_15,
+                memory_total, memory_used, memory_available, memory_percent, swap_total,
+                swap_used, temperatures, disks, usb_devices, gpu, network_rx, network_tx
+            ) VALUES (?, 10.0, '[10.0]', 0.1, 0.1, 0.1, 1000, 500, 500, 50.0, 0, 0, ?, ?, ?, ?, 0, 0)
+            "#,
+        )
+        .bind(legacy.timestamp.to_rfc3339())
+        .bind(serde_json::to_string(&legacy.temperatures).unwrap())
+        .bind(serde_json::to_string(&legacy.disks).unwrap())
+        .bind(serde_json::to_string(&legacy.usb_devices).unwrap())
+        .bind(serde_json::to_string(&legacy.gpu).unwrap())
+        .execute(&repo.pool)
+        .await
+        .unwrap();
+
+        repo.run_migrations().await.unwrap();
+
+        let migrated = repo.get_recent_metrics(1).await.unwrap().remove(0);
+        assert_eq!(
+            serde_json::to_value(&migrated.disks).unwrap(),
+            serde_json::to_value(&legacy.disks).unwrap()
+        );
+        assert_eq!(
+            serde_json::to_value(&migrated.temperatures).unwrap(),
+            serde_json::to_value(&legacy.temperatures).unwrap()
+        );
+        assert_eq!(
+            serde_json::to_value(&migrated.gpu).unwrap(),
+            serde_json::to_value(&legacy.gpu).unwrap()
+        );
+        assert_eq!(migrated.usb_devices[0].id, "046d:c52b");
+    }
+
+    #[tokio::test]
+    async fn test_incident_lifecycle() {
+        let repo = MetricsRepository::new("sqlite::memory:").await.unwrap();
+        repo.run_migrations().await.unwrap();
+
+        let now = Utc::now();
+        let mut incident = Incident {
+            id: uuid::Uuid::new_v4().to_string(),
+            rule: "disk_usage_critical".to_string(),
+            subject: Some("/home".to_string()),
+            category: AnomalyCategory::Disk,
+            severity: AnomalySeverity::Warning,
+            status: IncidentStatus::Open,
+            message: "Disk /home at 91%".to_string(),
+            first_seen: now,
+            last_seen: now,
+            occurrences: 1,
+            acknowledged_at: None,
+            resolved_at: None,
+            last_anomaly_id: "a1".to_string(),
+        };
+        repo.upsert_incident(&incident).await.unwrap();
+
+        let acked = repo
+            .acknowledge_incident(&incident.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(acked.status, IncidentStatus::Acknowledged);
+        assert!(acked.acknowledged_at.is_some());
+        assert!(repo
+            .acknowledge_incident("missing")
+            .await
+            .unwrap()
+            .is_none());
+
+        // Further firings keep the acknowledgement
+        incident.occurrences = 5;
+        incident.last_seen = now + Duration::seconds(10);
+        repo.upsert_incident(&incident).await.unwrap();
+        let stored = repo.get_incident(&incident.id).await.unwrap().unwrap();
+        assert_eq!(stored.status, IncidentStatus::Acknowledged);
+        assert_eq!(stored.occurrences, 5);
+        assert_eq!(stored.subject.as_deref(), Some("/home"));
+        assert_eq!(repo.get_active_incidents().await.unwrap().len(), 1);
+
+        incident.status = IncidentStatus::Resolved;
+        incident.resolved_at = Some(now + Duration::seconds(20));
+        repo.upsert_incident(&incident).await.unwrap();
+        assert!(repo.get_active_incidents().await.unwrap().is_empty());
+
+        // Reopening clears the acknowledgement
+        incident.status = IncidentStatus::Open;
+        incident.resolved_at = None;
+        repo.upsert_incident(&incident).await.unwrap();
+        let reopened = repo.get_incident(&incident.id).await.unwrap().unwrap();
+        assert_eq!(reopened.status, IncidentStatus::Open);
+        assert!(reopened.acknowledged_at.is_none());
+
+        let open = repo
+            .get_incidents(Some(IncidentStatus::Open), 10)
+            .await
+            .unwrap();
+        assert_eq!(open.len(), 1);
+        assert!(repo
+            .get_incidents(Some(IncidentStatus::Resolved), 10)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(repo.get_incidents(None, 10).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rule_mutes() {
+        let repo = MetricsRepository::new("sqlite::memory:").await.unwrap();
+        repo.run_migrations().await.unwrap();
+
+        let now = Utc::now();
+        repo.mute_rule("cpu_spike", now + Duration::hours(1))
+            .await
+            .unwrap();
+        repo.mute_rule("disk_usage_critical", now + Duration::minutes(10))
+            .await
+            .unwrap();
+        repo.mute_rule("memory_spike", now - Duration::minutes(1))
+            .await
+            .unwrap();
+
+        // Expired mutes are left out, the rest soonest to expire first
+        let mutes = repo.get_rule_mutes(now).await.unwrap();
+        let rules: Vec<_> = mutes.iter().map(|m| m.rule.as_str()).collect();
+        assert_eq!(rules, vec!["disk_usage_critical", "cpu_spike"]);
+
+        // Muting again replaces the end time
+        repo.mute_rule("cpu_spike", now + Duration::minutes(5))
+            .await
+            .unwrap();
+        let mutes = repo.get_rule_mutes(now).await.unwrap();
+        assert_eq!(mutes[0].rule, "cpu_spike");
+
+        assert!(repo.unmute_rule("cpu_spike").await.unwrap());
+        assert!(!repo.unmute_rule("cpu_spike").await.unwrap());
+        assert!(!repo.unmute_rule("memory_spike").await.unwrap());
+        assert_eq!(repo.get_rule_mutes(now).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_save_and_load_baselines() {
+        let repo = MetricsRepository::new("sqlite::memory:").await.unwrap();
+        repo.run_migrations().await.unwrap();
+
+        let mut baselines = vec![
+            MetricBaseline {
+                rule: "cpu_unusual".to_string(),
+                subject: None,
+                hour: None,
+                mean: 42.0,
+                variance: 9.0,
+                samples: 500,
+                updated_at: Utc::now(),
+            },
+            MetricBaseline {
+                rule: "disk_io_unusual".to_string(),
+                subject: Some("/home".to_string()),
+                hour: Some(0),
+                mean: 1.5,
+                variance: 0.25,
+                samples: 40,
+                updated_at: Utc::now(),
+            },
+        ];
+        repo.save_baselines(&baselines).await.unwrap();
+
+        baselines[0].samples = 501;
+        repo.save_baselines(&baselines[..1]).await.unwrap();
+
+        let mut loaded = repo.load_baselines().await.unwrap();
+        loaded.sort_by(|a, b| a.rule.cmp(&b.rule));
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].samples, 501);
+        assert_eq!(loaded[0].hour, None);
+        assert_eq!(loaded[0].subject, None);
+        assert_eq!(loaded[1].hour, Some(0));
+        assert_eq!(loaded[1].subject.as_deref(), Some("/home"));
+    }
 }
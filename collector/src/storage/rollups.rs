@@ -0,0 +1,451 @@
+/// Downsampled history for long-range queries
+///
+/// Raw samples are aggregated into 1-minute, 15-minute and 1-hour tiers with
+/// min/avg/max/p95 per scalar metric. Every tier is computed from raw samples
+/// so percentiles stay exact, and each tier has its own retention. The history
+/// endpoint picks a tier from the requested range or step.
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use serde::{Deserialize, Serialize};
+use shared::types::{RollupPoint, SystemMetrics};
+use sqlx::Error as SqlxError;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+use super::repository::MetricsRepository;
+use crate::detector::{self, MetricPath};
+
+/// Scalar metrics kept in every rollup tier, as metric paths
+pub const ROLLUP_METRICS: &[&str] = &[
+    "cpu.global_usage",
+    "cpu.load_avg_1",
+    "cpu.load_avg_5",
+    "cpu.load_avg_15",
+    "memory.usage_percent",
+    "memory.used",
+    "memory.swap_used",
+    "network.rx_bytes",
+    "network.tx_bytes",
+    "network.rx_packets",
+    "network.tx_packets",
+    "max(temperatures[*].value)",
+    "max(disks[*].usage_percent)",
+    "sum(disks[*].io_mb)",
+    "gpu.usage_percent",
+    "gpu.temperature",
+    "zombies.total",
+];
+
+/// Samples newer than this are not rolled up yet, so a bucket is only
+/// aggregated once every sample in it has been written
+const ROLLUP_GRACE_SECS: i64 = 10;
+
+/// Buckets aggregated per tier per run; a long backlog is worked off over
+/// several runs instead of blocking the database
+const MAX_BUCKETS_PER_RUN: usize = 120;
+
+/// Granularity of stored history
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Resolution {
+    /// Every collected sample
+    #[serde(rename = "raw")]
+    Raw,
+    #[serde(rename = "1m")]
+    Minute,
+    #[serde(rename = "15m")]
+    FifteenMinutes,
+    #[serde(rename = "1h")]
+    Hour,
+}
+
+impl Resolution {
+    /// Rollup tiers, finest first
+    pub const TIERS: [Resolution; 3] = [
+        Resolution::Minute,
+        Resolution::FifteenMinutes,
+        Resolution::Hour,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Resolution::Raw => "raw",
+            Resolution::Minute => "1m",
+            Resolution::FifteenMinutes => "15m",
+            Resolution::Hour => "1h",
+        }
+    }
+
+    /// Parses a tier name: raw, 1m, 15m or 1h
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "raw" => Some(Resolution::Raw),
+            "1m" => Some(Resolution::Minute),
+            "15m" => Some(Resolution::FifteenMinutes),
+            "1h" => Some(Resolution::Hour),
+            _ => None,
+        }
+    }
+
+    /// Width of one bucket; None for raw samples
+    pub fn step(self) -> Option<Duration> {
+        match self {
+            Resolution::Raw => None,
+            Resolution::Minute => Some(Duration::minutes(1)),
+            Resolution::FifteenMinutes => Some(Duration::minutes(15)),
+            Resolution::Hour => Some(Duration::hours(1)),
+        }
+    }
+
+    pub(crate) fn table(self) -> Option<&'static str> {
+        match self {
+            Resolution::Raw => None,
+            Resolution::Minute => Some("rollups_1m"),
+            Resolution::FifteenMinutes => Some("rollups_15m"),
+            Resolution::Hour => Some("rollups_1h"),
+        }
+    }
+
+    /// Picks a resolution that keeps a query over `range` to a few
+    /// thousand points per metric
+    pub fn for_range(range: Duration) -> Self {
+        if range <= Duration::hours(2) {
+            Resolution::Raw
+        } else if range <= Duration::days(2) {
+            Resolution::Minute
+        } else if range <= Duration::days(31) {
+            Resolution::FifteenMinutes
+        } else {
+            Resolution::Hour
+        }
+    }
+
+    /// Picks the coarsest resolution whose buckets are no wider than `step`
+    pub fn for_step(step: Duration) -> Self {
+        Self::TIERS
+            .iter()
+            .rev()
+            .copied()
+            .find(|tier| tier.step().is_some_and(|width| width <= step))
+            .unwrap_or(Resolution::Raw)
+    }
+}
+
+/// Retention of each rollup tier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RollupRetention {
+    pub minute_days: i64,
+    pub fifteen_minute_days: i64,
+    pub hour_days: i64,
+}
+
+impl Default for RollupRetention {
+    fn default() -> Self {
+        Self {
+            minute_days: 7,
+            fifteen_minute_days: 90,
+            hour_days: 730,
+        }
+    }
+}
+
+impl RollupRetention {
+    /// Returns how long `resolution` is kept; None for raw samples
+    pub fn for_resolution(&self, resolution: Resolution) -> Option<Duration> {
+        match resolution {
+            Resolution::Raw => None,
+            Resolution::Minute => Some(Duration::days(self.minute_days)),
+            Resolution::FifteenMinutes => Some(Duration::days(self.fifteen_minute_days)),
+            Resolution::Hour => Some(Duration::days(self.hour_days)),
+        }
+    }
+}
+
+/// Aggregates the samples of one bucket
+///
+/// Metrics missing from every sample (e.g. GPU on a machine without one) are
+/// left out.
+pub fn aggregate(
+    bucket_start: DateTime<Utc>,
+    samples: &[SystemMetrics],
+) -> BTreeMap<&'static str, RollupPoint> {
+    let paths: Vec<(&'static str, MetricPath)> = ROLLUP_METRICS
+        .iter()
+        .map(|raw| {
+            (
+                *raw,
+                MetricPath::parse(raw).expect("rollup paths are valid"),
+            )
+        })
+        .collect();
+
+    let mut values: BTreeMap<&'static str, Vec<f64>> = BTreeMap::new();
+    for metrics in samples {
+        let snapshot = detector::snapshot(metrics);
+        for (name, path) in &paths {
+            if let Some(sample) = path.resolve(&snapshot).first() {
+                values.entry(name).or_default().push(sample.value);
+            }
+        }
+    }
+
+    values
+        .into_iter()
+        .filter_map(|(name, values)| Some((name, summarize(bucket_start, values)?)))
+        .collect()
+}
+
+/// Computes min/avg/max/p95 of one bucket's values; None if there are none
+pub(crate) fn summarize(bucket_start: DateTime<Utc>, mut values: Vec<f64>) -> Option<RollupPoint> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let count = values.len();
+
+    Some(RollupPoint {
+        timestamp: bucket_start,
+        min: values[0],
+        avg: values.iter().sum::<f64>() / count as f64,
+        max: values[count - 1],
+        p95: percentile(&values, 0.95),
+        samples: count as u32,
+    })
+}
+
+/// Nearest-rank percentile of sorted, non-empty values
+fn percentile(sorted: &[f64], fraction: f64) -> f64 {
+    let rank = (fraction * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Start of the bucket of width `step` containing `timestamp`
+pub(crate) fn bucket_start(timestamp: DateTime<Utc>, step: Duration) -> DateTime<Utc> {
+    timestamp.duration_trunc(step).unwrap_or(timestamp)
+}
+
+/// Aggregates complete buckets of `resolution` that have not been rolled up
+///
+/// # Returns
+/// Result containing the number of buckets written or SqlxError
+pub async fn roll_up(
+    repo: &MetricsRepository,
+    resolution: Resolution,
+    now: DateTime<Utc>,
+) -> Result<usize, SqlxError> {
+    let Some(step) = resolution.step() else {
+        return Ok(0);
+    };
+    let horizon = now - Duration::seconds(ROLLUP_GRACE_SECS);
+
+    let mut cursor = match repo.last_rollup_bucket(resolution).await? {
+        Some(last) => last + step,
+        None => DateTime::<Utc>::UNIX_EPOCH,
+    };
+    let mut written = 0;
+
+    while written < MAX_BUCKETS_PER_RUN {
+        // Jump over gaps (collector stopped) instead of walking empty buckets
+        let Some(next) = repo.next_metrics_timestamp(cursor).await? else {
+            break;
+        };
+        let start = bucket_start(next, step);
+        let end = start + step;
+        if end > horizon {
+            break;
+        }
+
+        let samples = repo.get_metrics_between(start, end).await?;
+        repo.store_rollups(resolution, &aggregate(start, &samples))
+            .await?;
+        cursor = end;
+        written += 1;
+    }
+
+    Ok(written)
+}
+
+/// Rolls up new samples in the background
+///
+/// Expired buckets are pruned by the maintenance task, using [`RollupRetention`].
+pub struct RollupWorker {
+    repo: Arc<MetricsRepository>,
+}
+
+impl RollupWorker {
+    pub fn new(repo: Arc<MetricsRepository>) -> Self {
+        Self { repo }
+    }
+
+    /// Runs one pass over every tier
+    pub async fn run_once(&self, now: DateTime<Utc>) -> Result<(), SqlxError> {
+        for resolution in Resolution::TIERS {
+            let written = roll_up(&self.repo, resolution, now).await?;
+            if written > 0 {
+                tracing::debug!("Rolled up {} {} buckets", written, resolution.as_str());
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs a pass every `period` until `shutdown` is cancelled
+    pub async fn run(self, period: std::time::Duration, shutdown: CancellationToken) {
+        let mut tick = tokio::time::interval(period);
+
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = tick.tick() => {
+                    if let Err(e) = self.run_once(Utc::now()).await {
+                        tracing::error!("Failed to roll up metrics: {}", e);
+                    }
+                }
+            }
+        }
+
+        tracing::info!("Rollup worker stopped");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::types::{CpuMetrics, MemoryMetrics, NetworkMetrics};
+
+    fn metrics_at(timestamp: DateTime<Utc>, cpu: f32) -> SystemMetrics {
+        SystemMetrics {
+            timestamp,
+            cpu: CpuMetrics {
+                global_usage: cpu,
+                per_core: vec![cpu],
+                load_avg_1: 0.5,
+                load_avg_5: 0.5,
+                load_avg_15: 0.5,
+            },
+            memory: MemoryMetrics {
+                total: 1000,
+                used: 500,
+                available: 500,
+                usage_percent: 50.0,
+                swap_total: 0,
+                swap_used: 0,
+            },
+            temperatures: vec![],
+            disks: vec![],
+            usb_devices: vec![],
+            network: NetworkMetrics {
+                rx_bytes: 0,
+                tx_bytes: 0,
+                rx_packets: 0,
+                tx_packets: 0,
+                interfaces: vec![],
+            },
+            gpu: None,
+            processes: vec![],
+            zombies: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let start = Utc::now().duration_trunc(Duration::minutes(1)).unwrap();
+        let samples: Vec<_> = (1..=20)
+            .map(|i| metrics_at(start + Duration::seconds(i), i as f32))
+            .collect();
+
+        let points = aggregate(start, &samples);
+        let cpu = &points["cpu.global_usage"];
+        assert_eq!(cpu.timestamp, start);
+        assert_eq!(cpu.min, 1.0);
+        assert_eq!(cpu.max, 20.0);
+        assert_eq!(cpu.avg, 10.5);
+        assert_eq!(cpu.p95, 19.0);
+        assert_eq!(cpu.samples, 20);
+        // No GPU and no temperature sensors
+        assert!(!points.contains_key("gpu.usage_percent"));
+        assert!(!points.contains_key("max(temperatures[*].value)"));
+    }
+
+    #[test]
+    fn test_resolution_selection() {
+        assert_eq!(Resolution::for_range(Duration::hours(1)), Resolution::Raw);
+        assert_eq!(
+            Resolution::for_range(Duration::hours(24)),
+            Resolution::Minute
+        );
+        assert_eq!(
+            Resolution::for_range(Duration::days(7)),
+            Resolution::FifteenMinutes
+        );
+        assert_eq!(Resolution::for_range(Duration::days(90)), Resolution::Hour);
+
+        assert_eq!(Resolution::for_step(Duration::seconds(10)), Resolution::Raw);
+        assert_eq!(
+            Resolution::for_step(Duration::minutes(5)),
+            Resolution::Minute
+        );
+        assert_eq!(
+            Resolution::for_step(Duration::minutes(30)),
+            Resolution::FifteenMinutes
+        );
+        assert_eq!(Resolution::for_step(Duration::days(1)), Resolution::Hour);
+    }
+
+    #[tokio::test]
+    async fn test_roll_up_complete_buckets() {
+        let repo = MetricsRepository::new("sqlite::memory:").await.unwrap();
+        repo.run_migrations().await.unwrap();
+
+        // Three minutes of samples every 10s, then a gap of an hour
+        let start = Utc::now().duration_trunc(Duration::hours(1)).unwrap() - Duration::hours(3);
+        for i in 0..18 {
+            let metrics = metrics_at(start + Duration::seconds(10 * i), i as f32);
+            repo.store_metrics(&metrics).await.unwrap();
+        }
+        let later = start + Duration::minutes(63);
+        repo.store_metrics(&metrics_at(later, 99.0)).await.unwrap();
+
+        // Only the first two minutes are complete at this point
+        let now = start + Duration::minutes(2) + Duration::seconds(30);
+        assert_eq!(roll_up(&repo, Resolution::Minute, now).await.unwrap(), 2);
+        assert_eq!(roll_up(&repo, Resolution::Minute, now).await.unwrap(), 0);
+
+        let now = later + Duration::minutes(5);
+        assert_eq!(roll_up(&repo, Resolution::Minute, now).await.unwrap(), 2);
+
+        let series = repo
+            .get_rollups(Resolution::Minute, start, now, &[])
+            .await
+            .unwrap();
+        let cpu = &series["cpu.global_usage"];
+        assert_eq!(cpu.len(), 4);
+        assert_eq!(cpu[0].timestamp, start);
+        assert_eq!(cpu[0].samples, 6);
+        assert_eq!(cpu[0].max, 5.0);
+        assert_eq!(cpu[3].avg, 99.0);
+
+        let worker = RollupWorker::new(Arc::new(repo));
+        worker.run_once(now).await.unwrap();
+        let hourly = worker
+            .repo
+            .get_rollups(
+                Resolution::Hour,
+                start,
+                now,
+                &["cpu.global_usage".to_string()],
+            )
+            .await
+            .unwrap();
+        assert_eq!(hourly.len(), 1);
+        // The second hour is still in progress
+        assert_eq!(hourly["cpu.global_usage"][0].samples, 18);
+
+        let per_bucket = series.len() as u64;
+        let pruned = worker
+            .repo
+            .prune_rollups(Resolution::Minute, now)
+            .await
+            .unwrap();
+        assert_eq!(pruned, 4 * per_bucket);
+    }
+}
@@ -0,0 +1,325 @@
+/// Single-metric series for charting
+///
+/// Resolves metric paths over a time range into compact `[timestamp, value]`
+/// points. Paths kept in the rollup tiers are read from the coarsest tier no
+/// wider than the step and merged into step-wide buckets. Any other path is
+/// resolved against raw samples and aggregated on the fly, so such queries
+/// are limited to [`MAX_RAW_SERIES_RANGE_HOURS`].
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use shared::types::RollupPoint;
+use sqlx::Error as SqlxError;
+use std::collections::BTreeMap;
+
+use super::repository::MetricsRepository;
+use super::rollups::{bucket_start, summarize, Resolution, ROLLUP_METRICS};
+use crate::detector::{self, MetricPath};
+
+/// Longest range that can be served from raw samples
+pub const MAX_RAW_SERIES_RANGE_HOURS: i64 = 48;
+
+/// Statistic reported for each bucket of a series
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeriesAggregate {
+    Min,
+    #[default]
+    Avg,
+    Max,
+    /// 95th percentile; for rolled-up metrics merged from several rollup
+    /// buckets this is the highest bucket p95, an upper bound
+    P95,
+}
+
+impl SeriesAggregate {
+    /// Parses `min`, `avg`, `max` or `p95` (case-insensitive)
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "min" => Some(Self::Min),
+            "avg" => Some(Self::Avg),
+            "max" => Some(Self::Max),
+            "p95" => Some(Self::P95),
+            _ => None,
+        }
+    }
+
+    fn pick(self, point: &RollupPoint) -> f64 {
+        match self {
+            Self::Min => point.min,
+            Self::Avg => point.avg,
+            Self::Max => point.max,
+            Self::P95 => point.p95,
+        }
+    }
+}
+
+/// Points of one metric path
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Series {
+    /// Metric path as requested
+    pub metric: String,
+    /// `[unix_millis, value]` pairs, oldest first; buckets without samples
+    /// are left out
+    pub points: Vec<(i64, f64)>,
+}
+
+/// Returns true if `path` is kept in the rollup tiers
+pub fn is_rolled_up(path: &MetricPath) -> bool {
+    ROLLUP_METRICS.contains(&path.as_str())
+}
+
+/// Returns true if serving `paths` at `step` requires scanning raw samples
+pub fn needs_raw_samples(paths: &[MetricPath], step: Option<Duration>) -> bool {
+    step.is_none() || paths.iter().any(|path| !is_rolled_up(path))
+}
+
+/// Loads one series per path
+///
+/// # Arguments
+/// * `repo` - Repository to read from
+/// * `paths` - Metric paths, each resolving to at most one value per sample
+/// * `start` - Start of time range (inclusive)
+/// * `end` - End of time range (inclusive)
+/// * `step` - Bucket width; None returns every raw sample
+/// * `aggregate` - Statistic reported per bucket; ignored for raw samples
+///
+/// # Returns
+/// Result containing the series in the order of `paths`, or SqlxError
+pub async fn load_series(
+    repo: &MetricsRepository,
+    paths: &[MetricPath],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    step: Option<Duration>,
+    aggregate: SeriesAggregate,
+) -> Result<Vec<Series>, SqlxError> {
+    let mut series: Vec<Series> = paths
+        .iter()
+        .map(|path| Series {
+            metric: path.as_str().to_string(),
+            points: Vec::new(),
+        })
+        .collect();
+
+    // Rolled-up paths come from the rollup tier matching the step
+    let resolution = step.map(Resolution::for_step).unwrap_or(Resolution::Raw);
+    let mut pending = Vec::new();
+    let mut rolled_up = Vec::new();
+    for (index, path) in paths.iter().enumerate() {
+        if resolution != Resolution::Raw && is_rolled_up(path) {
+            rolled_up.push(path.as_str().to_string());
+        } else {
+            pending.push(index);
+        }
+    }
+
+    if let (Some(step), false) = (step, rolled_up.is_empty()) {
+        let stored = repo.get_rollups(resolution, start, end, &rolled_up).await?;
+        for entry in series.iter_mut() {
+            if let Some(points) = stored.get(&entry.metric) {
+                entry.points = merge_rollups(points, step)
+                    .iter()
+                    .map(|point| (point.timestamp.timestamp_millis(), aggregate.pick(point)))
+                    .collect();
+            }
+        }
+    }
+
+    if pending.is_empty() {
+        return Ok(series);
+    }
+
+    // Everything else is resolved against raw samples
+    let samples = repo.get_metrics_range(start, end).await?;
+    let mut buckets: Vec<BTreeMap<DateTime<Utc>, Vec<f64>>> = vec![BTreeMap::new(); paths.len()];
+    for metrics in &samples {
+        let snapshot = detector::snapshot(metrics);
+        for &index in &pending {
+            let Some(sample) = paths[index].resolve(&snapshot).into_iter().next() else {
+                continue;
+            };
+            match step {
+                Some(step) => buckets[index]
+                    .entry(bucket_start(metrics.timestamp, step))
+                    .or_default()
+                    .push(sample.value),
+                None => series[index]
+                    .points
+                    .push((metrics.timestamp.timestamp_millis(), sample.value)),
+            }
+        }
+    }
+
+    for index in pending {
+        for (timestamp, values) in std::mem::take(&mut buckets[index]) {
+            if let Some(point) = summarize(timestamp, values) {
+                series[index]
+                    .points
+                    .push((timestamp.timestamp_millis(), aggregate.pick(&point)));
+            }
+        }
+    }
+
+    Ok(series)
+}
+
+/// Merges time-ordered rollup points into buckets of width `step`
+///
+/// Averages are weighted by sample count; p95 becomes the highest p95 of the
+/// merged points since exact percentiles cannot be recombined.
+fn merge_rollups(points: &[RollupPoint], step: Duration) -> Vec<RollupPoint> {
+    let mut merged: Vec<RollupPoint> = Vec::new();
+
+    for point in points {
+        let start = bucket_start(point.timestamp, step);
+        match merged.last_mut() {
+            Some(last) if last.timestamp == start => {
+                let samples = last.samples + point.samples;
+                if samples > 0 {
+                    last.avg = (last.avg * f64::from(last.samples)
+                        + point.avg * f64::from(point.samples))
+                        / f64::from(samples);
+                }
+                last.min = last.min.min(point.min);
+                last.max = last.max.max(point.max);
+                last.p95 = last.p95.max(point.p95);
+                last.samples = samples;
+            }
+            _ => merged.push(RollupPoint {
+                timestamp: start,
+                ..point.clone()
+            }),
+        }
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::rollups::roll_up;
+    use shared::types::{CpuMetrics, DiskMetrics, MemoryMetrics, NetworkMetrics, SystemMetrics};
+
+    fn metrics_at(timestamp: DateTime<Utc>, usage: f32) -> SystemMetrics {
+        SystemMetrics {
+            timestamp,
+            cpu: CpuMetrics {
+                global_usage: usage,
+                per_core: vec![usage; 4],
+                load_avg_1: 0.5,
+                load_avg_5: 0.5,
+                load_avg_15: 0.5,
+            },
+            memory: MemoryMetrics {
+                total: 16_000_000_000,
+                used: 8_000_000_000,
+                available: 8_000_000_000,
+                usage_percent: 50.0,
+                swap_total: 0,
+                swap_used: 0,
+            },
+            temperatures: vec![],
+            disks: vec![DiskMetrics {
+                name: "sda2".to_string(),
+                mount_point: "/home".to_string(),
+                total: 1000,
+                used: 500,
+                available: 500,
+                usage_percent: usage / 2.0,
+                read_mb: 0.0,
+                write_mb: 0.0,
+            }],
+            usb_devices: vec![],
+            network: NetworkMetrics {
+                rx_bytes: 0,
+                tx_bytes: 0,
+                rx_packets: 0,
+                tx_packets: 0,
+                interfaces: vec![],
+            },
+            gpu: None,
+            processes: vec![],
+            zombies: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_load_series_raw_and_bucketed() {
+        let repo = MetricsRepository::new("sqlite::memory:").await.unwrap();
+        repo.run_migrations().await.unwrap();
+
+        // Four samples per minute over two minutes: 10, 20, 30, 40, 50, ...
+        let start = DateTime::parse_from_rfc3339("2024-01-29T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        for i in 0..8 {
+            let at = start + Duration::seconds(i * 15);
+            repo.store_metrics(&metrics_at(at, 10.0 * (i + 1) as f32))
+                .await
+                .unwrap();
+        }
+        roll_up(&repo, Resolution::Minute, start + Duration::minutes(5))
+            .await
+            .unwrap();
+
+        let paths: Vec<MetricPath> = ["cpu.global_usage", "disks[/home].usage_percent"]
+            .iter()
+            .map(|p| MetricPath::parse(p).unwrap())
+            .collect();
+        let end = start + Duration::minutes(2);
+
+        let raw = load_series(&repo, &paths, start, end, None, SeriesAggregate::Avg)
+            .await
+            .unwrap();
+        assert_eq!(raw[0].metric, "cpu.global_usage");
+        assert_eq!(raw[0].points.len(), 8);
+        assert_eq!(raw[0].points[0], (start.timestamp_millis(), 10.0));
+        assert_eq!(raw[1].points[7].1, 40.0);
+
+        // cpu.global_usage is read from rollups, the disk path from raw samples
+        let step = Some(Duration::minutes(1));
+        let avg = load_series(&repo, &paths, start, end, step, SeriesAggregate::Avg)
+            .await
+            .unwrap();
+        assert_eq!(
+            avg[0].points,
+            vec![
+                (start.timestamp_millis(), 25.0),
+                ((start + Duration::minutes(1)).timestamp_millis(), 65.0)
+            ]
+        );
+        assert_eq!(avg[1].points[0].1, 12.5);
+
+        let max = load_series(&repo, &paths, start, end, step, SeriesAggregate::Max)
+            .await
+            .unwrap();
+        assert_eq!(max[0].points[1].1, 80.0);
+        assert_eq!(max[1].points[1].1, 40.0);
+
+        // Two 1m rollup buckets merge into one 2m bucket
+        let merged = load_series(
+            &repo,
+            &paths[..1],
+            start,
+            end,
+            Some(Duration::minutes(2)),
+            SeriesAggregate::Avg,
+        )
+        .await
+        .unwrap();
+        assert_eq!(merged[0].points, vec![(start.timestamp_millis(), 45.0)]);
+    }
+
+    #[test]
+    fn test_needs_raw_samples() {
+        let rolled = vec![MetricPath::parse("cpu.global_usage").unwrap()];
+        let other = vec![MetricPath::parse("cpu.per_core[3]").unwrap()];
+
+        assert!(!needs_raw_samples(&rolled, Some(Duration::minutes(1))));
+        assert!(needs_raw_samples(&rolled, None));
+        assert!(needs_raw_samples(&other, Some(Duration::hours(1))));
+        assert_eq!(SeriesAggregate::parse("P95"), Some(SeriesAggregate::P95));
+        assert_eq!(SeriesAggregate::parse("median"), None);
+    }
+}
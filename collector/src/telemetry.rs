@@ -0,0 +1,206 @@
+/// Collector self-metrics
+///
+/// The collection loop records how long each collection and database write
+/// took, which anomalies it raised and how each collector fared; the API
+/// exposes the totals on the Prometheus `/metrics` endpoint.
+use shared::types::{Anomaly, AnomalyCategory, AnomalySeverity, CollectorStatus};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Upper bounds (seconds) of the latency histogram buckets
+pub const LATENCY_BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Every anomaly category, in display order
+pub const ANOMALY_CATEGORIES: &[AnomalyCategory] = &[
+    AnomalyCategory::Cpu,
+    AnomalyCategory::Memory,
+    AnomalyCategory::Temperature,
+    AnomalyCategory::Disk,
+    AnomalyCategory::Usb,
+    AnomalyCategory::Network,
+    AnomalyCategory::Gpu,
+    AnomalyCategory::Process,
+    AnomalyCategory::System,
+];
+
+/// Every anomaly severity, in increasing order
+pub const ANOMALY_SEVERITIES: &[AnomalySeverity] = &[
+    AnomalySeverity::Info,
+    AnomalySeverity::Warning,
+    AnomalySeverity::Critical,
+];
+
+/// Cumulative latency histogram with fixed buckets
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    /// Observation count per bucket in `LATENCY_BUCKETS` (not cumulative)
+    pub buckets: Vec<u64>,
+    pub count: u64,
+    pub sum: f64,
+}
+
+impl Histogram {
+    fn new() -> Self {
+        Self {
+            buckets: vec![0; LATENCY_BUCKETS.len()],
+            count: 0,
+            sum: 0.0,
+        }
+    }
+
+    fn observe(&mut self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        if let Some(i) = LATENCY_BUCKETS.iter().position(|b| seconds <= *b) {
+            self.buckets[i] += 1;
+        }
+        self.count += 1;
+        self.sum += seconds;
+    }
+}
+
+/// Point-in-time copy of the collector self-metrics
+#[derive(Debug, Clone)]
+pub struct TelemetrySnapshot {
+    pub collections_total: u64,
+    pub collection_errors_total: u64,
+    pub collection_duration: Histogram,
+    pub db_write_errors_total: u64,
+    pub db_write_duration: Histogram,
+    /// Anomalies raised, keyed by (category, severity) debug names
+    pub anomalies_total: BTreeMap<(String, String), u64>,
+    /// Schedule and last outcome of every collector
+    pub collectors: Vec<CollectorStatus>,
+}
+
+/// Thread-safe recorder for collector self-metrics
+pub struct CollectorTelemetry {
+    inner: Mutex<TelemetrySnapshot>,
+}
+
+impl CollectorTelemetry {
+    /// Creates a recorder with every counter at zero
+    pub fn new() -> Self {
+        let anomalies_total = ANOMALY_CATEGORIES
+            .iter()
+            .flat_map(|category| {
+                ANOMALY_SEVERITIES.iter().map(move |severity| {
+                    ((format!("{:?}", category), format!("{:?}", severity)), 0)
+                })
+            })
+            .collect();
+
+        Self {
+            inner: Mutex::new(TelemetrySnapshot {
+                collections_total: 0,
+                collection_errors_total: 0,
+                collection_duration: Histogram::new(),
+                db_write_errors_total: 0,
+                db_write_duration: Histogram::new(),
+                anomalies_total,
+                collectors: Vec::new(),
+            }),
+        }
+    }
+
+    /// Records a collection and how long it took
+    pub fn record_collection(&self, duration: Duration) {
+        let mut inner = self.lock();
+        inner.collections_total += 1;
+        inner.collection_duration.observe(duration);
+    }
+
+    /// Records a collection in which a collector failed or timed out
+    pub fn record_collection_error(&self) {
+        self.lock().collection_errors_total += 1;
+    }
+
+    /// Records a database write and how long it took
+    pub fn record_db_write(&self, duration: Duration, success: bool) {
+        let mut inner = self.lock();
+        inner.db_write_duration.observe(duration);
+        if !success {
+            inner.db_write_errors_total += 1;
+        }
+    }
+
+    /// Counts raised anomalies by category and severity
+    pub fn record_anomalies(&self, anomalies: &[Anomaly]) {
+        let mut inner = self.lock();
+        for anomaly in anomalies {
+            let key = (
+                format!("{:?}", anomaly.category),
+                format!("{:?}", anomaly.severity),
+            );
+            *inner.anomalies_total.entry(key).or_insert(0) += 1;
+        }
+    }
+
+    /// Replaces the collector statuses with the latest ones
+    pub fn record_collectors(&self, statuses: Vec<CollectorStatus>) {
+        self.lock().collectors = statuses;
+    }
+
+    /// Returns the latest collector statuses
+    pub fn collectors(&self) -> Vec<CollectorStatus> {
+        self.lock().collectors.clone()
+    }
+
+    /// Returns a copy of the current values
+    pub fn snapshot(&self) -> TelemetrySnapshot {
+        self.lock().clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, TelemetrySnapshot> {
+        // A panic while holding the lock cannot leave the counters inconsistent
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for CollectorTelemetry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_record_and_snapshot() {
+        let telemetry = CollectorTelemetry::new();
+        telemetry.record_collection(Duration::from_millis(3));
+        telemetry.record_collection(Duration::from_secs(30));
+        telemetry.record_db_write(Duration::from_millis(20), false);
+
+        let anomaly = Anomaly {
+            id: "a".to_string(),
+            timestamp: Utc::now(),
+            severity: AnomalySeverity::Critical,
+            category: AnomalyCategory::Disk,
+            message: "disk full".to_string(),
+            metrics: serde_json::json!({}),
+        };
+        telemetry.record_anomalies(&[anomaly.clone(), anomaly]);
+
+        let snapshot = telemetry.snapshot();
+        assert_eq!(snapshot.collections_total, 2);
+        assert_eq!(snapshot.collection_duration.count, 2);
+        // 3ms lands in the 5ms bucket; 30s exceeds every bucket
+        assert_eq!(snapshot.collection_duration.buckets[1], 1);
+        assert_eq!(snapshot.collection_duration.buckets.iter().sum::<u64>(), 1);
+        assert_eq!(snapshot.db_write_errors_total, 1);
+        assert_eq!(
+            snapshot.anomalies_total[&("Disk".to_string(), "Critical".to_string())],
+            2
+        );
+        assert_eq!(
+            snapshot.anomalies_total.len(),
+            ANOMALY_CATEGORIES.len() * ANOMALY_SEVERITIES.len()
+        );
+    }
+}
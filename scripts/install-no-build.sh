@@ -132,39 +132,31 @@ install_binary() {
 create_config() {
     print_step "Creating configuration file"
 
-    cat > "$CONFIG_DIR/config.toml" << 'EOF'
-# System Monitor Configuration
-
-# Database settings
-database_url = "/var/lib/system-monitor/metrics.db"
+    # Keep an existing file; check it with: collector check-config
+    if [ -f "$CONFIG_DIR/collector.toml" ]; then
+        print_info "Keeping existing $CONFIG_DIR/collector.toml"
+        return
+    fi
 
-# Collection settings
-collection_interval = 2  # seconds
+    cat > "$CONFIG_DIR/collector.toml" << EOF
+# System Monitor collector configuration
+# All keys: collector/collector.example.toml in the repository
 
-# API settings
-api_host = "127.0.0.1"
-api_port = 8080
+database_url = "sqlite://$DATA_DIR/metrics.db"
+collection_interval_secs = 2
 
-# Retention policy
-retention_days = 30  # Keep data for 30 days
+[server]
+host = "127.0.0.1"
+port = 5253
+cors_origins = ["http://localhost:5252"]
 
-# Logging
-log_level = "info"  # trace, debug, info, warn, error
-
-# Anomaly detection thresholds
-[thresholds]
-cpu_critical = 90.0
-cpu_warning = 70.0
-memory_critical = 95.0
-memory_warning = 80.0
-temperature_critical = 85.0
-temperature_warning = 75.0
-disk_critical = 90.0
-disk_warning = 80.0
+[retention]
+metrics_days = 30
+anomalies_days = 90
 EOF
 
-    chmod 644 "$CONFIG_DIR/config.toml"
-    chown root:root "$CONFIG_DIR/config.toml"
+    chmod 644 "$CONFIG_DIR/collector.toml"
+    chown root:root "$CONFIG_DIR/collector.toml"
 }
 
 # Create systemd service file
@@ -195,13 +187,11 @@ ReadWritePaths=$DATA_DIR $LOG_DIR
 CapabilityBoundingSet=
 AmbientCapabilities=
 
-# Environment
+# Environment (settings live in the config file)
 Environment="RUST_LOG=info"
-Environment="CONFIG_PATH=$CONFIG_DIR/config.toml"
-Environment="DATABASE_PATH=$DATA_DIR/metrics.db"
 
 # Start the service
-ExecStart=$INSTALL_DIR/collector
+ExecStart=$INSTALL_DIR/collector --config $CONFIG_DIR/collector.toml run
 
 # Restart policy
 Restart=on-failure
@@ -283,7 +273,7 @@ print_summary() {
     echo "  User:      $SERVICE_USER"
     echo "  Install:   $INSTALL_DIR"
     echo "  Data:      $DATA_DIR"
-    echo "  Config:    $CONFIG_DIR/config.toml"
+    echo "  Config:    $CONFIG_DIR/collector.toml"
     echo "  Logs:      journalctl -u $SERVICE_NAME"
     echo ""
     echo -e "${YELLOW}Useful Commands:${NC}"
@@ -305,7 +295,7 @@ print_summary() {
     echo "  ws://127.0.0.1:8080/ws"
     echo ""
     echo -e "${YELLOW}Configuration:${NC}"
-    echo "  Edit: $CONFIG_DIR/config.toml"
+    echo "  Edit: $CONFIG_DIR/collector.toml"
     echo "  Then: systemctl restart $SERVICE_NAME"
     echo ""
     echo -e "${GREEN}Service is now running!${NC}"
@@ -2,7 +2,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 /// Main system metrics structure containing all collected data
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SystemMetrics {
     pub timestamp: DateTime<Utc>,
     pub cpu: CpuMetrics,
@@ -12,10 +12,14 @@ pub struct SystemMetrics {
     pub usb_devices: Vec<UsbDevice>,
     pub network: NetworkMetrics,
     pub gpu: Option<GpuMetrics>,
+    #[serde(default)]
+    pub processes: Vec<ProcessInfo>,
+    #[serde(default)]
+    pub zombies: ZombieMetrics,
 }
 
 /// CPU metrics including global usage, per-core usage, and load averages
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CpuMetrics {
     pub global_usage: f32,
     pub per_core: Vec<f32>,
@@ -25,7 +29,7 @@ pub struct CpuMetrics {
 }
 
 /// Memory metrics including RAM and swap usage
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct MemoryMetrics {
     pub total: u64,
     pub used: u64,
@@ -66,12 +70,35 @@ pub struct UsbDevice {
 }
 
 /// Network metrics including bytes and packets transferred
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Totals cover every interface except loopback and are rates per second.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct NetworkMetrics {
     pub rx_bytes: u64,
     pub tx_bytes: u64,
     pub rx_packets: u64,
     pub tx_packets: u64,
+    #[serde(default)]
+    pub interfaces: Vec<NetworkInterface>,
+}
+
+/// Traffic, error counters and link information for one network interface
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkInterface {
+    pub name: String,
+    pub rx_bytes_per_sec: u64,
+    pub tx_bytes_per_sec: u64,
+    pub rx_packets_per_sec: u64,
+    pub tx_packets_per_sec: u64,
+    /// Errors and drops since the previous sample
+    pub rx_errors: u64,
+    pub tx_errors: u64,
+    pub rx_dropped: u64,
+    pub tx_dropped: u64,
+    /// Operational state from /sys/class/net, e.g. "up", "down" or "unknown"
+    pub operstate: String,
+    /// Negotiated link speed, if the driver reports one
+    pub speed_mbps: Option<u32>,
 }
 
 /// GPU metrics including usage, temperature, and memory
@@ -88,6 +115,42 @@ pub struct GpuMetrics {
     pub fan_speed_percent: f32,
 }
 
+/// Per-process resource usage (top consumers only)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    pub cmdline: String,
+    pub user: String,
+    pub state: String,
+    /// CPU usage in percent of one core (may exceed 100 on multi-core systems)
+    pub cpu_usage: f32,
+    /// Resident set size in bytes
+    pub memory_rss: u64,
+    pub disk_read_bytes_per_sec: u64,
+    pub disk_write_bytes_per_sec: u64,
+}
+
+/// Zombie (defunct) processes grouped by parent
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ZombieMetrics {
+    pub total: usize,
+    pub groups: Vec<ZombieGroup>,
+}
+
+/// Zombies sharing the same parent process
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZombieGroup {
+    pub parent_pid: u32,
+    pub parent_command: String,
+    pub count: usize,
+    /// Distinct command names of the zombie children
+    pub commands: Vec<String>,
+    pub pids: Vec<u32>,
+    /// Seconds since the oldest zombie in this group was first seen
+    pub oldest_age_secs: u64,
+}
+
 /// Anomaly detection result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Anomaly {
@@ -99,8 +162,8 @@ pub struct Anomaly {
     pub metrics: serde_json::Value,
 }
 
-/// Severity level of an anomaly
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Severity level of an anomaly, ordered from least to most severe
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum AnomalySeverity {
     Info,
     Warning,
@@ -108,7 +171,7 @@ pub enum AnomalySeverity {
 }
 
 /// Category of system component where anomaly was detected
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum AnomalyCategory {
     Cpu,
     Memory,
@@ -117,5 +180,107 @@ pub enum AnomalyCategory {
     Usb,
     Network,
     Gpu,
+    Process,
     System,
 }
+
+/// Repeated firings of one rule for one subject, grouped together
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Incident {
+    pub id: String,
+    /// Rule that raised the anomalies
+    pub rule: String,
+    /// Element the rule fired for (e.g. a mount point), if any
+    pub subject: Option<String>,
+    pub category: AnomalyCategory,
+    /// Highest severity seen while the incident was open
+    pub severity: AnomalySeverity,
+    pub status: IncidentStatus,
+    /// Message of the latest firing
+    pub message: String,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    /// Number of samples the rule fired on
+    pub occurrences: u64,
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    /// Id of the latest stored anomaly for this incident
+    pub last_anomaly_id: String,
+}
+
+/// Lifecycle state of an incident
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum IncidentStatus {
+    /// The condition holds and nobody has acknowledged it
+    Open,
+    /// The condition holds and someone is looking at it
+    Acknowledged,
+    /// The condition cleared
+    Resolved,
+}
+
+/// Learned EWMA baseline of one metric, used by statistical detection rules
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricBaseline {
+    /// Rule that owns the baseline
+    pub rule: String,
+    /// Element the baseline tracks (e.g. a mount point), if any
+    pub subject: Option<String>,
+    /// Hour of day (UTC, 0-23) for seasonal baselines
+    pub hour: Option<u8>,
+    pub mean: f64,
+    pub variance: f64,
+    /// Number of samples folded into the baseline
+    pub samples: u64,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A detection rule whose anomalies are suppressed until `muted_until`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleMute {
+    /// Rule name, as in an incident's `rule`
+    pub rule: String,
+    pub muted_until: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Schedule and health of one metrics collector (cpu, gpu, usb, ...)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CollectorStatus {
+    pub name: String,
+    pub enabled: bool,
+    /// Seconds between runs; 0 means every sample
+    pub interval_secs: u64,
+    /// Longest a run may take before it is abandoned
+    pub timeout_secs: f64,
+    /// True while the last run outlived its timeout
+    pub timed_out: bool,
+    pub last_run: Option<DateTime<Utc>>,
+    /// How long the last run took, refresh included
+    pub last_duration_ms: Option<f64>,
+    /// Most recent failure; kept after later successful runs
+    pub last_error: Option<String>,
+    pub last_error_at: Option<DateTime<Utc>>,
+    pub runs_total: u64,
+    pub errors_total: u64,
+}
+
+/// Reading of one named disk, sensor or device at one sample time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeriesSample<T> {
+    pub timestamp: DateTime<Utc>,
+    pub value: T,
+}
+
+/// Aggregate of one scalar metric over a time bucket
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RollupPoint {
+    /// Start of the bucket
+    pub timestamp: DateTime<Utc>,
+    pub min: f64,
+    pub avg: f64,
+    pub max: f64,
+    pub p95: f64,
+    /// Number of raw samples in the bucket
+    pub samples: u32,
+}
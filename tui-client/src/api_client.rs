@@ -1,9 +1,198 @@
 use anyhow::{Context, Result};
-use reqwest::Client;
-use shared::types::{Anomaly, SystemMetrics};
+use chrono::{DateTime, Utc};
+use futures_util::{SinkExt, StreamExt};
+use reqwest::{Client, Method};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use shared::types::{Anomaly, Incident, RollupPoint, RuleMute, SystemMetrics};
+use std::collections::BTreeMap;
 use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::{self, Message};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+/// Delay before the first reconnect attempt
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Longest delay between reconnect attempts
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Shortest interval the server accepts for streamed metrics
+const MIN_STREAM_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How the client receives data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    /// Periodic requests to the REST API
+    Polling,
+    /// Pushed messages over the `/ws` endpoint
+    WebSocket,
+}
+
+impl Transport {
+    pub fn label(self) -> &'static str {
+        match self {
+            Transport::Polling => "HTTP polling",
+            Transport::WebSocket => "WebSocket",
+        }
+    }
+}
+
+/// Connection state shown in the dashboard header
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionState {
+    Connecting(Transport),
+    Connected(Transport),
+    /// The stream dropped; the next attempt starts after `retry_in`
+    Reconnecting {
+        attempt: u32,
+        retry_in: Duration,
+    },
+    Error(String),
+}
+
+impl ConnectionState {
+    /// Short description for the header
+    pub fn label(&self) -> String {
+        match self {
+            ConnectionState::Connecting(transport) => {
+                format!("Connecting ({})...", transport.label())
+            }
+            ConnectionState::Connected(transport) => format!("Connected ({})", transport.label()),
+            ConnectionState::Reconnecting { attempt, retry_in } => format!(
+                "Reconnecting in {}s (attempt {})",
+                retry_in.as_secs(),
+                attempt
+            ),
+            ConnectionState::Error(message) => format!("Error: {}", message),
+        }
+    }
+}
+
+/// Returns the delay before reconnect attempt `attempt` (1-based), doubling
+/// from one second up to thirty
+pub fn reconnect_delay(attempt: u32) -> Duration {
+    let exponent = attempt.saturating_sub(1).min(5);
+    (INITIAL_RECONNECT_DELAY * 2u32.pow(exponent)).min(MAX_RECONNECT_DELAY)
+}
+
+/// Response of the history endpoint
+#[derive(Debug, Clone)]
+pub enum History {
+    /// Every sample in the range
+    Raw(Vec<SystemMetrics>),
+    /// Min/avg/max/p95 per metric path and bucket
+    Rollup(BTreeMap<String, Vec<RollupPoint>>),
+}
+
+impl History {
+    /// Parses the `data` field of a history response
+    fn from_data(mut data: Value) -> Result<Self> {
+        let resolution = data.get("resolution").and_then(Value::as_str);
+        if resolution == Some("raw") {
+            let metrics = data.get_mut("metrics").map(Value::take).unwrap_or_default();
+            Ok(History::Raw(
+                serde_json::from_value(metrics).context("Failed to parse history samples")?,
+            ))
+        } else {
+            let series = data.get_mut("series").map(Value::take).unwrap_or_default();
+            Ok(History::Rollup(
+                serde_json::from_value(series).context("Failed to parse history rollups")?,
+            ))
+        }
+    }
+}
+
+/// Message received from the collector's WebSocket stream
+#[derive(Debug, Clone)]
+pub enum StreamMessage {
+    Metrics(Box<SystemMetrics>),
+    Anomaly(Box<Anomaly>),
+    /// Acknowledgements, errors and message types the client does not use
+    Other,
+}
+
+impl StreamMessage {
+    /// Parses a text frame sent by the server
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut message: Value =
+            serde_json::from_str(text).context("Failed to parse WebSocket message")?;
+        let data = message.get_mut("data").map(Value::take);
+
+        let parsed = match (message.get("type").and_then(Value::as_str), data) {
+            (Some("metrics"), Some(data)) => StreamMessage::Metrics(
+                serde_json::from_value(data).context("Failed to parse streamed metrics")?,
+            ),
+            (Some("anomaly"), Some(data)) => StreamMessage::Anomaly(
+                serde_json::from_value(data).context("Failed to parse streamed anomaly")?,
+            ),
+            _ => StreamMessage::Other,
+        };
+        Ok(parsed)
+    }
+}
+
+/// Why a WebSocket connection could not be opened
+#[derive(Debug)]
+pub enum ConnectError {
+    /// The server answered but refused the upgrade (e.g. no `/ws` endpoint);
+    /// retrying will not help
+    Rejected(String),
+    /// The server could not be reached; worth retrying
+    Unreachable(String),
+}
+
+impl std::fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConnectError::Rejected(message) | ConnectError::Unreachable(message) => {
+                f.write_str(message)
+            }
+        }
+    }
+}
+
+/// Open WebSocket connection to the collector
+pub struct MetricsStream {
+    socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+}
+
+impl MetricsStream {
+    /// Asks the server to send metrics every `interval`
+    pub async fn set_interval(&mut self, interval: Duration) -> Result<()> {
+        let interval_ms = interval.max(MIN_STREAM_INTERVAL).as_millis() as u64;
+        let command = json!({ "action": "set_interval", "interval_ms": interval_ms });
+        self.socket
+            .send(Message::Text(command.to_string()))
+            .await
+            .context("Failed to send WebSocket command")
+    }
+
+    /// Waits for the next message; None once the connection is closed and an
+    /// error if it failed
+    pub async fn next_message(&mut self) -> Option<Result<StreamMessage>> {
+        loop {
+            let frame = match self.socket.next().await? {
+                Ok(frame) => frame,
+                Err(e) => return Some(Err(e).context("WebSocket connection failed")),
+            };
+            match frame {
+                // A message the client cannot parse is skipped, not fatal
+                Message::Text(text) => match StreamMessage::parse(&text) {
+                    Ok(message) => return Some(Ok(message)),
+                    Err(_) => continue,
+                },
+                Message::Close(_) => return None,
+                // Pings are answered by tungstenite on the next read or write
+                _ => continue,
+            }
+        }
+    }
+}
 
 /// API client for communicating with the system-monitor server
+#[derive(Clone)]
 pub struct ApiClient {
     client: Client,
     base_url: String,
@@ -20,109 +209,193 @@ impl ApiClient {
         Ok(Self { client, base_url })
     }
 
-    /// Get current system metrics
-    pub async fn get_current_metrics(&self) -> Result<SystemMetrics> {
-        let url = format!("{}/api/metrics/current", self.base_url);
+    /// Sends a GET request and unwraps the `data` field of the response
+    async fn get_data<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T> {
+        self.request_data(Method::GET, path, query).await
+    }
+
+    /// Sends a request and unwraps the `data` field of the response
+    ///
+    /// Error responses are reported with the server's `message`.
+    async fn request_data<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T> {
+        let url = format!("{}{}", self.base_url, path);
 
         let response = self
             .client
-            .get(&url)
+            .request(method, &url)
+            .query(query)
             .send()
             .await
-            .context("Failed to send request to /api/metrics/current")?;
+            .with_context(|| format!("Failed to send request to {}", path))?;
 
-        if !response.status().is_success() {
-            anyhow::bail!(
-                "Server returned error status: {} for /api/metrics/current",
-                response.status()
-            );
+        let status = response.status();
+        if !status.is_success() {
+            let message = response
+                .json::<Value>()
+                .await
+                .ok()
+                .and_then(|body| body.get("message")?.as_str().map(str::to_string));
+            match message {
+                Some(message) => anyhow::bail!("{} ({})", message, status),
+                None => anyhow::bail!("Server returned error status: {} for {}", status, path),
+            }
         }
 
-        let metrics = response
-            .json::<SystemMetrics>()
+        let body = response
+            .json::<Value>()
             .await
-            .context("Failed to parse metrics JSON response")?;
+            .with_context(|| format!("Failed to parse JSON response from {}", path))?;
+        let data = body.get("data").cloned().unwrap_or(Value::Null);
+
+        serde_json::from_value(data)
+            .with_context(|| format!("Unexpected response format from {}", path))
+    }
 
-        Ok(metrics)
+    /// Get current system metrics
+    pub async fn get_current_metrics(&self) -> Result<SystemMetrics> {
+        let metrics: Option<SystemMetrics> = self.get_data("/api/v1/metrics/current", &[]).await?;
+        metrics.context("No metrics collected yet")
     }
 
-    /// Get recent anomalies
+    /// Get recent anomalies, oldest first
     pub async fn get_anomalies(&self, limit: Option<usize>) -> Result<Vec<Anomaly>> {
-        let mut url = format!("{}/api/anomalies", self.base_url);
+        #[derive(Deserialize)]
+        struct Page {
+            anomalies: Vec<Anomaly>,
+        }
 
+        let mut query = Vec::new();
         if let Some(limit) = limit {
-            url = format!("{}?limit={}", url, limit);
+            query.push(("limit", limit.to_string()));
         }
 
-        let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .context("Failed to send request to /api/anomalies")?;
+        // The API returns the newest page first
+        let page: Page = self.get_data("/api/v1/anomalies", &query).await?;
+        let mut anomalies = page.anomalies;
+        anomalies.reverse();
+        Ok(anomalies)
+    }
 
-        if !response.status().is_success() {
-            anyhow::bail!(
-                "Server returned error status: {} for /api/anomalies",
-                response.status()
-            );
+    /// Get the stored sample nearest to an anomaly, if any
+    pub async fn get_anomaly_snapshot(&self, id: &str) -> Result<Option<SystemMetrics>> {
+        #[derive(Deserialize)]
+        struct Detail {
+            snapshot: Option<SystemMetrics>,
         }
 
-        let anomalies = response
-            .json::<Vec<Anomaly>>()
-            .await
-            .context("Failed to parse anomalies JSON response")?;
+        let path = format!("/api/v1/anomalies/{}", encode_path_segment(id));
+        let detail: Detail = self.get_data(&path, &[]).await?;
+        Ok(detail.snapshot)
+    }
 
-        Ok(anomalies)
+    /// Acknowledge an incident, returning it after the update
+    pub async fn acknowledge_incident(&self, id: &str) -> Result<Incident> {
+        let path = format!("/api/v1/incidents/{}/ack", encode_path_segment(id));
+        self.request_data(Method::POST, &path, &[]).await
+    }
+
+    /// Mute a rule's anomalies for `duration` (e.g. `1h`)
+    pub async fn mute_rule(&self, rule: &str, duration: &str) -> Result<RuleMute> {
+        let path = format!("/api/v1/rules/{}/mute", encode_path_segment(rule));
+        self.request_data(Method::POST, &path, &[("duration", duration.to_string())])
+            .await
     }
 
     /// Get historical metrics
-    #[allow(dead_code)]
+    ///
+    /// `step` is a resolution understood by the history endpoint (`raw`,
+    /// `1m`, `15m`, `1h`, ...); the server picks one from the range if None.
     pub async fn get_history(
         &self,
-        start: Option<chrono::DateTime<chrono::Utc>>,
-        end: Option<chrono::DateTime<chrono::Utc>>,
-        limit: Option<usize>,
-    ) -> Result<Vec<SystemMetrics>> {
-        let mut url = format!("{}/api/metrics/history", self.base_url);
-        let mut params = vec![];
-
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+        step: Option<&str>,
+    ) -> Result<History> {
+        let mut query = Vec::new();
         if let Some(start) = start {
-            params.push(format!("start={}", start.to_rfc3339()));
+            query.push(("start", start.to_rfc3339()));
         }
-
         if let Some(end) = end {
-            params.push(format!("end={}", end.to_rfc3339()));
+            query.push(("end", end.to_rfc3339()));
         }
-
-        if let Some(limit) = limit {
-            params.push(format!("limit={}", limit));
+        if let Some(step) = step {
+            query.push(("step", step.to_string()));
         }
 
-        if !params.is_empty() {
-            url = format!("{}?{}", url, params.join("&"));
+        let data: Value = self.get_data("/api/v1/metrics/history", &query).await?;
+        History::from_data(data)
+    }
+
+    /// Get `[unix_millis, value]` series for metric paths, keyed by path
+    pub async fn get_series(
+        &self,
+        metrics: &[String],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        step: &str,
+    ) -> Result<BTreeMap<String, Vec<(i64, f64)>>> {
+        #[derive(Deserialize)]
+        struct Series {
+            metric: String,
+            points: Vec<(i64, f64)>,
+        }
+        #[derive(Deserialize)]
+        struct SeriesData {
+            series: Vec<Series>,
         }
 
-        let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .context("Failed to send request to /api/metrics/history")?;
+        let mut query: Vec<(&str, String)> = metrics
+            .iter()
+            .map(|metric| ("metric", metric.clone()))
+            .collect();
+        query.push(("start", start.to_rfc3339()));
+        query.push(("end", end.to_rfc3339()));
+        query.push(("step", step.to_string()));
 
-        if !response.status().is_success() {
-            anyhow::bail!(
-                "Server returned error status: {} for /api/metrics/history",
-                response.status()
-            );
-        }
+        let data: SeriesData = self.get_data("/api/v1/series", &query).await?;
+        Ok(data
+            .series
+            .into_iter()
+            .map(|series| (series.metric, series.points))
+            .collect())
+    }
 
-        let history = response
-            .json::<Vec<SystemMetrics>>()
-            .await
-            .context("Failed to parse history JSON response")?;
+    /// Returns the WebSocket endpoint for the API base URL
+    pub fn websocket_url(&self) -> String {
+        let base = self.base_url.trim_end_matches('/');
+        let base = if let Some(rest) = base.strip_prefix("https://") {
+            format!("wss://{}", rest)
+        } else if let Some(rest) = base.strip_prefix("http://") {
+            format!("ws://{}", rest)
+        } else {
+            base.to_string()
+        };
+        format!("{}/ws", base)
+    }
 
-        Ok(history)
+    /// Opens a WebSocket connection to the collector's `/ws` endpoint
+    pub async fn connect_stream(&self) -> std::result::Result<MetricsStream, ConnectError> {
+        let url = self.websocket_url();
+        match tokio_tungstenite::connect_async(url.as_str()).await {
+            Ok((socket, _)) => Ok(MetricsStream { socket }),
+            Err(tungstenite::Error::Io(e)) => Err(ConnectError::Unreachable(format!(
+                "Failed to connect to {}: {}",
+                url, e
+            ))),
+            Err(e) => Err(ConnectError::Rejected(format!(
+                "WebSocket upgrade to {} failed: {}",
+                url, e
+            ))),
+        }
     }
 
     /// Check if the server is healthy
@@ -137,6 +410,20 @@ impl ApiClient {
     }
 }
 
+/// Percent-encodes a URL path segment; rule names may contain spaces,
+/// colons and slashes
+fn encode_path_segment(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -146,4 +433,64 @@ mod tests {
         let client = ApiClient::new("http://localhost:8080".to_string());
         assert!(client.is_ok());
     }
+
+    #[test]
+    fn test_parse_history() {
+        let raw = json!({ "resolution": "raw", "metrics": [], "count": 0 });
+        assert!(matches!(History::from_data(raw).unwrap(), History::Raw(m) if m.is_empty()));
+
+        let rollup = json!({
+            "resolution": "1m",
+            "series": { "cpu.global_usage": [{
+                "timestamp": "2024-01-29T12:00:00Z",
+                "min": 1.0, "avg": 2.0, "max": 3.0, "p95": 3.0, "samples": 30
+            }]}
+        });
+        match History::from_data(rollup).unwrap() {
+            History::Rollup(series) => assert_eq!(series["cpu.global_usage"][0].avg, 2.0),
+            other => panic!("unexpected history: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_encode_path_segment() {
+        assert_eq!(encode_path_segment("cpu_spike"), "cpu_spike");
+        assert_eq!(
+            encode_path_segment("Disk: /home at 95%"),
+            "Disk%3A%20%2Fhome%20at%2095%25"
+        );
+    }
+
+    #[test]
+    fn test_websocket_url() {
+        let url = |base: &str| ApiClient::new(base.to_string()).unwrap().websocket_url();
+
+        assert_eq!(url("http://localhost:5253"), "ws://localhost:5253/ws");
+        assert_eq!(url("https://monitor.example/"), "wss://monitor.example/ws");
+    }
+
+    #[test]
+    fn test_reconnect_delay_backs_off() {
+        assert_eq!(reconnect_delay(1), Duration::from_secs(1));
+        assert_eq!(reconnect_delay(3), Duration::from_secs(4));
+        assert_eq!(reconnect_delay(6), Duration::from_secs(30));
+        assert_eq!(reconnect_delay(40), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_parse_stream_message() {
+        let anomaly = r#"{"type":"anomaly","timestamp":"2024-01-29T12:00:00Z","data":{
+            "id":"a1","timestamp":"2024-01-29T12:00:00Z","severity":"Critical",
+            "category":"Cpu","message":"CPU usage high","metrics":{}}}"#;
+        match StreamMessage::parse(anomaly).unwrap() {
+            StreamMessage::Anomaly(anomaly) => assert_eq!(anomaly.id, "a1"),
+            other => panic!("unexpected message: {:?}", other),
+        }
+
+        assert!(matches!(
+            StreamMessage::parse(r#"{"type":"connected","data":{}}"#).unwrap(),
+            StreamMessage::Other
+        ));
+        assert!(StreamMessage::parse("not json").is_err());
+    }
 }
@@ -87,6 +87,16 @@ fn print_help() {
     println!();
     println!("CONTROLS:");
     println!("    q, Ctrl+C                  Quit application");
-    println!("    Up/Down, k/j              Scroll anomalies list");
-    println!("    Page Up/Down              Scroll anomalies page by page");
+    println!("    Tab/Shift+Tab, 1-6         Switch pane");
+    println!("    Up/Down, k/j              Scroll anomalies list or pane table");
+    println!("    Page Up/Down              Scroll page by page");
+    println!("    Enter                      Show the selected anomaly's details");
+    println!("    a, m                       In details: acknowledge incident, mute rule for 1h");
+    println!("    c, v                       Cycle anomaly category and minimum severity filters");
+    println!("    s, r                       Cycle sort column, reverse sort order");
+    println!("    /                          Filter the pane table (Enter to apply, Esc to clear)");
+    println!("    w                          Cycle history window (5m, 1h, 24h)");
+    println!("    g                          Toggle full-screen chart");
+    println!("    Left/Right, h/l            Switch chart metric");
+    println!("    Esc                        Close details or chart, or clear filter");
 }
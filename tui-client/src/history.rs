@@ -0,0 +1,364 @@
+use shared::types::{RollupPoint, SystemMetrics};
+use std::collections::{BTreeMap, VecDeque};
+
+/// Time window shown by sparklines and charts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeWindow {
+    #[default]
+    FiveMinutes,
+    Hour,
+    Day,
+}
+
+impl TimeWindow {
+    /// Window length
+    pub fn duration(self) -> chrono::Duration {
+        match self {
+            TimeWindow::FiveMinutes => chrono::Duration::minutes(5),
+            TimeWindow::Hour => chrono::Duration::hours(1),
+            TimeWindow::Day => chrono::Duration::hours(24),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TimeWindow::FiveMinutes => "5m",
+            TimeWindow::Hour => "1h",
+            TimeWindow::Day => "24h",
+        }
+    }
+
+    /// The next window, wrapping around from 24h to 5m
+    pub fn next(self) -> Self {
+        match self {
+            TimeWindow::FiveMinutes => TimeWindow::Hour,
+            TimeWindow::Hour => TimeWindow::Day,
+            TimeWindow::Day => TimeWindow::FiveMinutes,
+        }
+    }
+
+    /// Resolution requested from the history endpoint
+    pub fn step(self) -> &'static str {
+        match self {
+            TimeWindow::FiveMinutes => "raw",
+            TimeWindow::Hour => "1m",
+            TimeWindow::Day => "15m",
+        }
+    }
+
+    /// Bucket width in seconds; None keeps every sample
+    fn bucket_secs(self) -> Option<i64> {
+        match self {
+            TimeWindow::FiveMinutes => None,
+            TimeWindow::Hour => Some(60),
+            TimeWindow::Day => Some(15 * 60),
+        }
+    }
+}
+
+/// One point of a series
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Point {
+    /// Unix seconds (bucket start for bucketed windows)
+    timestamp: i64,
+    value: f64,
+    /// Samples averaged into `value`
+    samples: u32,
+}
+
+/// Points of one metric, oldest first
+#[derive(Debug, Clone, Default)]
+pub struct Series {
+    points: VecDeque<Point>,
+}
+
+impl Series {
+    /// Adds a sample, averaging it into the last bucket if it falls there
+    fn push(&mut self, timestamp: i64, value: f64, bucket_secs: Option<i64>, samples: u32) {
+        let timestamp = match bucket_secs {
+            Some(width) => timestamp - timestamp.rem_euclid(width),
+            None => timestamp,
+        };
+
+        match self.points.back_mut() {
+            Some(last) if last.timestamp == timestamp && bucket_secs.is_some() => {
+                let total = last.samples + samples;
+                last.value = (last.value * f64::from(last.samples) + value * f64::from(samples))
+                    / f64::from(total.max(1));
+                last.samples = total;
+            }
+            Some(last) if last.timestamp >= timestamp => {}
+            _ => self.points.push_back(Point {
+                timestamp,
+                value,
+                samples,
+            }),
+        }
+    }
+
+    /// Drops points older than `cutoff` (Unix seconds)
+    fn trim(&mut self, cutoff: i64) {
+        while self.points.front().is_some_and(|p| p.timestamp < cutoff) {
+            self.points.pop_front();
+        }
+    }
+
+    /// Returns the largest value, or 0 if empty
+    pub fn max(&self) -> f64 {
+        self.points.iter().map(|p| p.value).fold(0.0, f64::max)
+    }
+
+    /// Returns the smallest value, or 0 if empty
+    pub fn min(&self) -> f64 {
+        self.points
+            .iter()
+            .map(|p| p.value)
+            .reduce(f64::min)
+            .unwrap_or(0.0)
+    }
+
+    /// Chart data: seconds relative to `now` (negative) and values
+    pub fn chart_points(&self, now: i64) -> Vec<(f64, f64)> {
+        self.points
+            .iter()
+            .map(|p| ((p.timestamp - now) as f64, p.value))
+            .collect()
+    }
+
+    /// Sparkline data: the newest `width` values, rounded since sparklines
+    /// only take integers
+    pub fn sparkline(&self, width: usize) -> Vec<u64> {
+        let skip = self.points.len().saturating_sub(width);
+        self.points
+            .iter()
+            .skip(skip)
+            .map(|p| p.value.max(0.0).round() as u64)
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+}
+
+/// Rolling history of the charted metrics over the selected window
+#[derive(Debug, Clone, Default)]
+pub struct MetricHistory {
+    window: TimeWindow,
+    /// Global CPU usage (%)
+    pub cpu: Series,
+    /// Memory usage (%)
+    pub memory: Series,
+    /// Network receive rate (bytes/s)
+    pub rx: Series,
+    /// Network transmit rate (bytes/s)
+    pub tx: Series,
+    /// Temperature (°C) per `sensor:label`
+    pub temperatures: BTreeMap<String, Series>,
+}
+
+impl MetricHistory {
+    /// Creates an empty history for `window`
+    pub fn new(window: TimeWindow) -> Self {
+        Self {
+            window,
+            ..Default::default()
+        }
+    }
+
+    pub fn window(&self) -> TimeWindow {
+        self.window
+    }
+
+    /// Builds a history from raw samples
+    pub fn from_samples(window: TimeWindow, samples: &[SystemMetrics]) -> Self {
+        let mut history = Self::new(window);
+        for metrics in samples {
+            history.push(metrics);
+        }
+        history
+    }
+
+    /// Builds a history from rollup series keyed by metric path, plus
+    /// per-sensor temperature series keyed by `sensor:label` with
+    /// `[unix_millis, value]` points
+    pub fn from_rollups(
+        window: TimeWindow,
+        rollups: &BTreeMap<String, Vec<RollupPoint>>,
+        temperatures: &BTreeMap<String, Vec<(i64, f64)>>,
+    ) -> Self {
+        let mut history = Self::new(window);
+        let bucket = window.bucket_secs();
+        let fill = |series: &mut Series, path: &str| {
+            for point in rollups.get(path).into_iter().flatten() {
+                series.push(
+                    point.timestamp.timestamp(),
+                    point.avg,
+                    bucket,
+                    point.samples,
+                );
+            }
+        };
+
+        fill(&mut history.cpu, "cpu.global_usage");
+        fill(&mut history.memory, "memory.usage_percent");
+        fill(&mut history.rx, "network.rx_bytes");
+        fill(&mut history.tx, "network.tx_bytes");
+
+        for (sensor, points) in temperatures {
+            let series = history.temperatures.entry(sensor.clone()).or_default();
+            for (millis, value) in points {
+                series.push(millis / 1000, *value, bucket, 1);
+            }
+        }
+        history
+    }
+
+    /// Adds a live sample and drops points that left the window
+    pub fn push(&mut self, metrics: &SystemMetrics) {
+        let timestamp = metrics.timestamp.timestamp();
+        let bucket = self.window.bucket_secs();
+
+        self.cpu
+            .push(timestamp, f64::from(metrics.cpu.global_usage), bucket, 1);
+        self.memory.push(
+            timestamp,
+            f64::from(metrics.memory.usage_percent),
+            bucket,
+            1,
+        );
+        self.rx
+            .push(timestamp, metrics.network.rx_bytes as f64, bucket, 1);
+        self.tx
+            .push(timestamp, metrics.network.tx_bytes as f64, bucket, 1);
+        for temp in &metrics.temperatures {
+            self.temperatures
+                .entry(format!("{}:{}", temp.sensor, temp.label))
+                .or_default()
+                .push(timestamp, f64::from(temp.value), bucket, 1);
+        }
+
+        let cutoff = timestamp - self.window.duration().num_seconds();
+        for series in [&mut self.cpu, &mut self.memory, &mut self.rx, &mut self.tx] {
+            series.trim(cutoff);
+        }
+        for series in self.temperatures.values_mut() {
+            series.trim(cutoff);
+        }
+        self.temperatures.retain(|_, series| !series.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Duration, Utc};
+    use shared::types::{CpuMetrics, MemoryMetrics, NetworkMetrics, Temperature};
+
+    fn metrics_at(timestamp: DateTime<Utc>, usage: f32) -> SystemMetrics {
+        SystemMetrics {
+            timestamp,
+            cpu: CpuMetrics {
+                global_usage: usage,
+                per_core: vec![],
+                load_avg_1: 0.0,
+                load_avg_5: 0.0,
+                load_avg_15: 0.0,
+            },
+            memory: MemoryMetrics {
+                total: 100,
+                used: 50,
+                available: 50,
+                usage_percent: 50.0,
+                swap_total: 0,
+                swap_used: 0,
+            },
+            temperatures: vec![Temperature {
+                sensor: "coretemp".to_string(),
+                value: 40.0 + usage,
+                label: "Core 0".to_string(),
+            }],
+            disks: vec![],
+            usb_devices: vec![],
+            network: NetworkMetrics {
+                rx_bytes: 1000,
+                tx_bytes: 500,
+                rx_packets: 0,
+                tx_packets: 0,
+                interfaces: vec![],
+            },
+            gpu: None,
+            processes: vec![],
+            zombies: Default::default(),
+        }
+    }
+
+    fn start() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2024-01-29T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_raw_window_keeps_samples_and_trims() {
+        let samples: Vec<_> = (0..200)
+            .map(|i| metrics_at(start() + Duration::seconds(i * 2), i as f32 % 100.0))
+            .collect();
+        let history = MetricHistory::from_samples(TimeWindow::FiveMinutes, &samples);
+
+        // 5 minutes of 2-second samples, inclusive of the cutoff
+        assert_eq!(history.cpu.chart_points(0).len(), 151);
+        assert_eq!(history.cpu.sparkline(1), vec![99]);
+        assert_eq!(
+            history.temperatures["coretemp:Core 0"].sparkline(1),
+            vec![139]
+        );
+        assert_eq!(history.rx.sparkline(10), vec![1000; 10]);
+    }
+
+    #[test]
+    fn test_bucketed_window_averages_live_samples() {
+        let mut history = MetricHistory::new(TimeWindow::Hour);
+        for (i, usage) in [10.0, 20.0, 30.0, 40.0].into_iter().enumerate() {
+            history.push(&metrics_at(
+                start() + Duration::seconds(i as i64 * 20),
+                usage,
+            ));
+        }
+
+        let points = history.cpu.chart_points(start().timestamp());
+        assert_eq!(points, vec![(0.0, 20.0), (60.0, 40.0)]);
+    }
+
+    #[test]
+    fn test_from_rollups() {
+        let point = |minute: i64, avg: f64| RollupPoint {
+            timestamp: start() + Duration::minutes(minute),
+            min: avg,
+            avg,
+            max: avg,
+            p95: avg,
+            samples: 30,
+        };
+        let rollups = BTreeMap::from([(
+            "cpu.global_usage".to_string(),
+            vec![point(0, 10.0), point(15, 20.0)],
+        )]);
+        let temperatures = BTreeMap::from([(
+            "coretemp:Core 0".to_string(),
+            vec![(start().timestamp_millis(), 55.0)],
+        )]);
+
+        let mut history = MetricHistory::from_rollups(TimeWindow::Day, &rollups, &temperatures);
+        assert_eq!(history.cpu.sparkline(80), vec![10, 20]);
+        assert_eq!(
+            history.temperatures["coretemp:Core 0"].sparkline(1),
+            vec![55]
+        );
+        assert!(history.memory.is_empty());
+
+        // A live sample in the last bucket is averaged into it
+        history.push(&metrics_at(start() + Duration::minutes(16), 50.0));
+        assert_eq!(history.cpu.sparkline(80), vec![10, 21]);
+    }
+}
@@ -1,22 +1,31 @@
 mod api_client;
 mod config;
+mod history;
 mod ui;
 
 use anyhow::{Context, Result};
-use api_client::ApiClient;
+use api_client::{
+    reconnect_delay, ApiClient, ConnectError, ConnectionState, History, StreamMessage, Transport,
+};
+use chrono::Utc;
 use config::Config;
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use history::{MetricHistory, TimeWindow};
 use ratatui::{backend::CrosstermBackend, Terminal};
 use shared::types::{Anomaly, SystemMetrics};
 use std::io;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use tokio::time::interval;
-use ui::dashboard::{render_dashboard, DashboardState};
+use tokio::time::{interval, sleep};
+use ui::anomaly_detail::{ActionStatus, AnomalyDetailState, Snapshot};
+use ui::dashboard::{render_dashboard, DashboardState, Tab};
+
+/// Anomalies kept in memory for the anomalies view
+const MAX_ANOMALIES: usize = 100;
 
 /// Application state
 struct App {
@@ -24,8 +33,10 @@ struct App {
     metrics: Option<SystemMetrics>,
     /// List of anomalies
     anomalies: Vec<Anomaly>,
-    /// Connection status message
-    connection_status: String,
+    /// Connection state shown in the header
+    connection: ConnectionState,
+    /// Recent values for sparklines and charts
+    history: MetricHistory,
     /// Dashboard UI state
     dashboard_state: DashboardState,
     /// Should quit
@@ -37,7 +48,8 @@ impl Default for App {
         Self {
             metrics: None,
             anomalies: Vec::new(),
-            connection_status: "Connecting...".to_string(),
+            connection: ConnectionState::Connecting(Transport::Polling),
+            history: MetricHistory::default(),
             dashboard_state: DashboardState::default(),
             should_quit: false,
         }
@@ -61,17 +73,30 @@ async fn main() -> Result<()> {
     // Create application state
     let app = Arc::new(Mutex::new(App::default()));
 
+    // Pre-fill sparklines from the history endpoint
+    tokio::spawn(load_history(
+        Arc::clone(&app),
+        client.clone(),
+        TimeWindow::default(),
+    ));
+
     // Spawn data fetching task
     let app_clone = Arc::clone(&app);
-    let client_clone = client;
+    let client_clone = client.clone();
     let refresh_rate = config.refresh_rate;
 
+    let use_websocket = config.use_websocket;
+
     tokio::spawn(async move {
-        fetch_data_loop(app_clone, client_clone, refresh_rate).await;
+        if use_websocket {
+            stream_data_loop(app_clone, client_clone, refresh_rate).await;
+        } else {
+            fetch_data_loop(app_clone, client_clone, refresh_rate).await;
+        }
     });
 
     // Run main event loop
-    let result = run_event_loop(&mut terminal, app).await;
+    let result = run_event_loop(&mut terminal, app, client).await;
 
     // Restore terminal
     restore_terminal(&mut terminal).context("Failed to restore terminal")?;
@@ -117,6 +142,7 @@ fn setup_panic_handler() {
 async fn run_event_loop(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     app: Arc<Mutex<App>>,
+    client: ApiClient,
 ) -> Result<()> {
     loop {
         // Draw UI
@@ -126,7 +152,8 @@ async fn run_event_loop(
             // Clone the data we need for rendering
             let metrics = app.metrics.clone();
             let anomalies = app.anomalies.clone();
-            let connection_status = app.connection_status.clone();
+            let connection = app.connection.clone();
+            let history = app.history.clone();
 
             terminal
                 .draw(|frame| {
@@ -134,7 +161,8 @@ async fn run_event_loop(
                         frame,
                         &metrics,
                         &anomalies,
-                        &connection_status,
+                        &connection,
+                        &history,
                         &mut app.dashboard_state,
                     );
                 })
@@ -150,49 +178,190 @@ async fn run_event_loop(
         // Handle events with timeout
         if event::poll(Duration::from_millis(100)).context("Failed to poll events")? {
             if let Event::Key(key) = event::read().context("Failed to read event")? {
+                let app_ref = &app;
                 let mut app = app.lock().unwrap();
-                let anomalies_len = app.anomalies.len();
+                let anomalies_len = app
+                    .dashboard_state
+                    .anomalies_view_state
+                    .visible(&app.anomalies)
+                    .len();
+
+                if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                    app.should_quit = true;
+                    continue;
+                }
+
+                // The detail popup takes every key until it is closed
+                if let Some(detail) = app.dashboard_state.detail.as_mut() {
+                    match key.code {
+                        KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+                            app.dashboard_state.detail = None;
+                        }
+                        KeyCode::Up | KeyCode::Char('k') => detail.scroll_up(1),
+                        KeyCode::Down | KeyCode::Char('j') => detail.scroll_down(1),
+                        KeyCode::PageUp => detail.scroll_up(10),
+                        KeyCode::PageDown => detail.scroll_down(10),
+                        KeyCode::Char('a') => match detail.incident_id() {
+                            Some(incident_id) => {
+                                tokio::spawn(acknowledge_incident(
+                                    Arc::clone(app_ref),
+                                    client.clone(),
+                                    detail.anomaly.id.clone(),
+                                    incident_id.to_string(),
+                                ));
+                                detail.status =
+                                    Some(ActionStatus::Pending("Acknowledging...".to_string()));
+                            }
+                            None => {
+                                detail.status = Some(ActionStatus::Failed(
+                                    "This anomaly has no incident to acknowledge".to_string(),
+                                ));
+                            }
+                        },
+                        KeyCode::Char('m') => match detail.rule() {
+                            Some(rule) => {
+                                tokio::spawn(mute_rule(
+                                    Arc::clone(app_ref),
+                                    client.clone(),
+                                    detail.anomaly.id.clone(),
+                                    rule.to_string(),
+                                ));
+                                detail.status =
+                                    Some(ActionStatus::Pending(format!("Muting {}...", rule)));
+                            }
+                            None => {
+                                detail.status = Some(ActionStatus::Failed(
+                                    "This anomaly was not raised by a rule".to_string(),
+                                ));
+                            }
+                        },
+                        _ => {}
+                    }
+                    continue;
+                }
 
+                // While a filter is being edited every key goes into it
+                if let Some(table) = app
+                    .dashboard_state
+                    .table_state_mut()
+                    .filter(|table| table.editing_filter)
+                {
+                    match key.code {
+                        KeyCode::Char(c) => table.filter.push(c),
+                        KeyCode::Backspace => {
+                            table.filter.pop();
+                        }
+                        KeyCode::Enter => table.editing_filter = false,
+                        KeyCode::Esc => {
+                            table.filter.clear();
+                            table.editing_filter = false;
+                        }
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                let state = &mut app.dashboard_state;
                 match key.code {
                     KeyCode::Char('q') => {
                         app.should_quit = true;
                     }
-                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        app.should_quit = true;
+                    KeyCode::Char('w') => {
+                        // Show the new window right away and fill it in the background
+                        let window = app.history.window().next();
+                        app.history = MetricHistory::new(window);
+                        tokio::spawn(load_history(Arc::clone(app_ref), client.clone(), window));
                     }
-                    KeyCode::Up | KeyCode::Char('k') => {
-                        app.dashboard_state
-                            .anomalies_view_state
-                            .scroll_up(anomalies_len);
+                    KeyCode::Tab => {
+                        state.tab = state.tab.next();
                     }
-                    KeyCode::Down | KeyCode::Char('j') => {
-                        app.dashboard_state
-                            .anomalies_view_state
-                            .scroll_down(anomalies_len);
+                    KeyCode::BackTab => {
+                        state.tab = state.tab.previous();
                     }
-                    KeyCode::PageUp => {
-                        app.dashboard_state
-                            .anomalies_view_state
-                            .scroll_page_up(anomalies_len, 10);
+                    KeyCode::Char(c @ '1'..='9') => {
+                        if let Some(tab) = Tab::ALL.get(c as usize - '1' as usize) {
+                            state.tab = *tab;
+                        }
                     }
-                    KeyCode::PageDown => {
-                        app.dashboard_state
-                            .anomalies_view_state
-                            .scroll_page_down(anomalies_len, 10);
+                    KeyCode::Char('s') => {
+                        let columns = state.tab.columns().map_or(0, |columns| columns.len());
+                        if let Some(table) = state.table_state_mut() {
+                            table.next_sort_column(columns);
+                        }
+                    }
+                    KeyCode::Char('r') => {
+                        if let Some(table) = state.table_state_mut() {
+                            table.toggle_order();
+                        }
+                    }
+                    KeyCode::Char('/') => {
+                        if let Some(table) = state.table_state_mut() {
+                            table.editing_filter = true;
+                        }
                     }
-                    KeyCode::Home => {
-                        app.dashboard_state
+                    KeyCode::Enter if state.tab == Tab::Overview && state.chart.is_none() => {
+                        let selected = app
+                            .dashboard_state
                             .anomalies_view_state
-                            .list_state
-                            .select(Some(0));
-                    }
-                    KeyCode::End => {
-                        if anomalies_len > 0 {
-                            app.dashboard_state
-                                .anomalies_view_state
-                                .scroll_to_newest(anomalies_len);
+                            .selected(&app.anomalies)
+                            .cloned();
+                        if let Some(anomaly) = selected {
+                            let id = anomaly.id.clone();
+                            app.dashboard_state.detail = Some(AnomalyDetailState::new(anomaly));
+                            tokio::spawn(load_snapshot(Arc::clone(app_ref), client.clone(), id));
                         }
                     }
+                    KeyCode::Char('c') => {
+                        state.anomalies_view_state.cycle_category();
+                    }
+                    KeyCode::Char('v') => {
+                        state.anomalies_view_state.cycle_severity();
+                    }
+                    KeyCode::Char('g') => {
+                        state.chart = match state.chart {
+                            Some(_) => None,
+                            None => Some(Default::default()),
+                        };
+                    }
+                    KeyCode::Esc => {
+                        if state.chart.is_some() {
+                            state.chart = None;
+                        } else if let Some(table) = state.table_state_mut() {
+                            table.filter.clear();
+                        }
+                    }
+                    KeyCode::Left | KeyCode::Char('h') if state.chart.is_some() => {
+                        state.chart = state.chart.map(|m| m.previous());
+                    }
+                    KeyCode::Right | KeyCode::Char('l') if state.chart.is_some() => {
+                        state.chart = state.chart.map(|m| m.next());
+                    }
+                    KeyCode::Up | KeyCode::Char('k') => match state.table_state_mut() {
+                        Some(table) => table.scroll_up(1),
+                        None => state.anomalies_view_state.scroll_up(anomalies_len),
+                    },
+                    KeyCode::Down | KeyCode::Char('j') => match state.table_state_mut() {
+                        Some(table) => table.scroll_down(1),
+                        None => state.anomalies_view_state.scroll_down(anomalies_len),
+                    },
+                    KeyCode::PageUp => match state.table_state_mut() {
+                        Some(table) => table.scroll_up(10),
+                        None => state.anomalies_view_state.scroll_page_up(anomalies_len, 10),
+                    },
+                    KeyCode::PageDown => match state.table_state_mut() {
+                        Some(table) => table.scroll_down(10),
+                        None => state
+                            .anomalies_view_state
+                            .scroll_page_down(anomalies_len, 10),
+                    },
+                    KeyCode::Home => match state.table_state_mut() {
+                        Some(table) => table.table_state.select(Some(0)),
+                        None => state.anomalies_view_state.list_state.select(Some(0)),
+                    },
+                    KeyCode::End => match state.table_state_mut() {
+                        Some(table) => table.scroll_down(usize::MAX),
+                        None => state.anomalies_view_state.scroll_to_newest(anomalies_len),
+                    },
                     _ => {}
                 }
             }
@@ -213,17 +382,18 @@ async fn fetch_data_loop(app: Arc<Mutex<App>>, client: ApiClient, refresh_rate:
         match client.get_current_metrics().await {
             Ok(metrics) => {
                 let mut app = app.lock().unwrap();
+                app.history.push(&metrics);
                 app.metrics = Some(metrics);
-                app.connection_status = "Connected".to_string();
+                app.connection = ConnectionState::Connected(Transport::Polling);
             }
             Err(e) => {
                 let mut app = app.lock().unwrap();
-                app.connection_status = format!("Error: {}", e);
+                app.connection = ConnectionState::Error(e.to_string());
             }
         }
 
         // Fetch anomalies (limit to last 100)
-        match client.get_anomalies(Some(100)).await {
+        match client.get_anomalies(Some(MAX_ANOMALIES)).await {
             Ok(anomalies) => {
                 let mut app = app.lock().unwrap();
                 app.anomalies = anomalies;
@@ -234,3 +404,186 @@ async fn fetch_data_loop(app: Arc<Mutex<App>>, client: ApiClient, refresh_rate:
         }
     }
 }
+
+/// Background task streaming data over the collector's WebSocket endpoint
+///
+/// Reconnects with exponential backoff when the connection drops, and falls
+/// back to HTTP polling if the server refuses the WebSocket upgrade.
+async fn stream_data_loop(app: Arc<Mutex<App>>, client: ApiClient, refresh_rate: Duration) {
+    let set_connection = |connection: ConnectionState| {
+        app.lock().unwrap().connection = connection;
+    };
+    let mut attempt = 0;
+
+    loop {
+        if attempt == 0 {
+            set_connection(ConnectionState::Connecting(Transport::WebSocket));
+        }
+
+        match client.connect_stream().await {
+            Ok(mut stream) => {
+                attempt = 0;
+                set_connection(ConnectionState::Connected(Transport::WebSocket));
+
+                // Seed the anomalies list; new ones are pushed afterwards
+                if let Ok(anomalies) = client.get_anomalies(Some(MAX_ANOMALIES)).await {
+                    app.lock().unwrap().anomalies = anomalies;
+                }
+
+                if let Err(e) = stream.set_interval(refresh_rate).await {
+                    set_connection(ConnectionState::Error(e.to_string()));
+                } else {
+                    while let Some(message) = stream.next_message().await {
+                        match message {
+                            Ok(StreamMessage::Metrics(metrics)) => {
+                                let mut app = app.lock().unwrap();
+                                app.history.push(&metrics);
+                                app.metrics = Some(*metrics);
+                            }
+                            Ok(StreamMessage::Anomaly(anomaly)) => {
+                                let mut app = app.lock().unwrap();
+                                app.anomalies.push(*anomaly);
+                                if app.anomalies.len() > MAX_ANOMALIES {
+                                    let excess = app.anomalies.len() - MAX_ANOMALIES;
+                                    app.anomalies.drain(0..excess);
+                                }
+                            }
+                            Ok(StreamMessage::Other) => {}
+                            Err(e) => {
+                                set_connection(ConnectionState::Error(e.to_string()));
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+            Err(ConnectError::Rejected(_)) => {
+                // No WebSocket endpoint on this server; poll instead
+                set_connection(ConnectionState::Connecting(Transport::Polling));
+                fetch_data_loop(app, client, refresh_rate).await;
+                return;
+            }
+            Err(ConnectError::Unreachable(_)) => {}
+        }
+
+        attempt += 1;
+        let retry_in = reconnect_delay(attempt);
+        set_connection(ConnectionState::Reconnecting { attempt, retry_in });
+        sleep(retry_in).await;
+    }
+}
+
+/// How long the detail popup mutes a rule for
+const MUTE_DURATION: &str = "1h";
+
+/// Applies `update` to the detail popup if it still shows `anomaly_id`
+fn update_detail(app: &Mutex<App>, anomaly_id: &str, update: impl FnOnce(&mut AnomalyDetailState)) {
+    let mut app = app.lock().unwrap();
+    if let Some(detail) = app
+        .dashboard_state
+        .detail
+        .as_mut()
+        .filter(|detail| detail.anomaly.id == anomaly_id)
+    {
+        update(detail);
+    }
+}
+
+/// Fetches the system snapshot shown in the detail popup
+async fn load_snapshot(app: Arc<Mutex<App>>, client: ApiClient, anomaly_id: String) {
+    let snapshot = match client.get_anomaly_snapshot(&anomaly_id).await {
+        Ok(Some(metrics)) => Snapshot::Loaded(Box::new(metrics)),
+        Ok(None) => Snapshot::Missing,
+        Err(e) => Snapshot::Failed(e.to_string()),
+    };
+    update_detail(&app, &anomaly_id, |detail| detail.snapshot = snapshot);
+}
+
+/// Acknowledges the incident of the anomaly shown in the detail popup
+async fn acknowledge_incident(
+    app: Arc<Mutex<App>>,
+    client: ApiClient,
+    anomaly_id: String,
+    incident_id: String,
+) {
+    let status = match client.acknowledge_incident(&incident_id).await {
+        Ok(incident) => ActionStatus::Done(format!(
+            "Incident {:?} ({} occurrences)",
+            incident.status, incident.occurrences
+        )),
+        Err(e) => ActionStatus::Failed(format!("Failed to acknowledge: {}", e)),
+    };
+    update_detail(&app, &anomaly_id, |detail| detail.status = Some(status));
+}
+
+/// Mutes the rule of the anomaly shown in the detail popup
+async fn mute_rule(app: Arc<Mutex<App>>, client: ApiClient, anomaly_id: String, rule: String) {
+    let status = match client.mute_rule(&rule, MUTE_DURATION).await {
+        Ok(mute) => ActionStatus::Done(format!(
+            "Muted {} until {}",
+            mute.rule,
+            mute.muted_until
+                .with_timezone(&chrono::Local)
+                .format("%H:%M:%S")
+        )),
+        Err(e) => ActionStatus::Failed(format!("Failed to mute {}: {}", rule, e)),
+    };
+    update_detail(&app, &anomaly_id, |detail| detail.status = Some(status));
+}
+
+/// Fills the sparkline history for `window` from the collector
+///
+/// Short windows are built from raw samples; longer ones from rollups, with
+/// per-sensor temperatures read from the series endpoint since only their
+/// maximum is rolled up. The result is dropped if the user has switched to
+/// another window in the meantime.
+async fn load_history(app: Arc<Mutex<App>>, client: ApiClient, window: TimeWindow) {
+    let end = Utc::now();
+    let start = end - window.duration();
+
+    let history = match client
+        .get_history(Some(start), Some(end), Some(window.step()))
+        .await
+    {
+        Ok(History::Raw(samples)) => MetricHistory::from_samples(window, &samples),
+        Ok(History::Rollup(rollups)) => {
+            let sensors: Vec<String> = app
+                .lock()
+                .unwrap()
+                .metrics
+                .iter()
+                .flat_map(|m| &m.temperatures)
+                .map(|t| format!("temperatures[{}:{}].value", t.sensor, t.label))
+                .collect();
+            let temperatures = if sensors.is_empty() {
+                Default::default()
+            } else {
+                client
+                    .get_series(&sensors, start, end, window.step())
+                    .await
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|(path, points)| {
+                        let key = path
+                            .strip_prefix("temperatures[")?
+                            .strip_suffix("].value")?;
+                        Some((key.to_string(), points))
+                    })
+                    .collect()
+            };
+            MetricHistory::from_rollups(window, &rollups, &temperatures)
+        }
+        // Live samples still fill the window
+        Err(_) => return,
+    };
+
+    let mut app = app.lock().unwrap();
+    if app.history.window() == window {
+        // Keep live samples that arrived while loading
+        let current = app.metrics.clone();
+        app.history = history;
+        if let Some(metrics) = current {
+            app.history.push(&metrics);
+        }
+    }
+}
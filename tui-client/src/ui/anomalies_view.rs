@@ -6,22 +6,103 @@ use ratatui::{
     widgets::{Block, Borders, List, ListItem, ListState},
     Frame,
 };
-use shared::types::{Anomaly, AnomalySeverity};
+use shared::types::{Anomaly, AnomalyCategory, AnomalySeverity};
 
-/// State for the anomalies view (for scrolling)
+/// Categories cycled through by the category filter
+const CATEGORIES: [AnomalyCategory; 9] = [
+    AnomalyCategory::Cpu,
+    AnomalyCategory::Memory,
+    AnomalyCategory::Temperature,
+    AnomalyCategory::Disk,
+    AnomalyCategory::Usb,
+    AnomalyCategory::Network,
+    AnomalyCategory::Gpu,
+    AnomalyCategory::Process,
+    AnomalyCategory::System,
+];
+
+/// State for the anomalies view (for scrolling and filtering)
 pub struct AnomaliesViewState {
     pub list_state: ListState,
+    /// Only show anomalies of this category
+    pub category: Option<AnomalyCategory>,
+    /// Only show anomalies at least this severe
+    pub min_severity: Option<AnomalySeverity>,
 }
 
 impl Default for AnomaliesViewState {
     fn default() -> Self {
         let mut list_state = ListState::default();
         list_state.select(Some(0));
-        Self { list_state }
+        Self {
+            list_state,
+            category: None,
+            min_severity: None,
+        }
     }
 }
 
 impl AnomaliesViewState {
+    /// Anomalies passing the filters, newest first as listed
+    pub fn visible<'a>(&self, anomalies: &'a [Anomaly]) -> Vec<&'a Anomaly> {
+        anomalies
+            .iter()
+            .rev()
+            .filter(|anomaly| {
+                self.category
+                    .as_ref()
+                    .is_none_or(|c| anomaly.category == *c)
+            })
+            .filter(|anomaly| {
+                self.min_severity
+                    .as_ref()
+                    .is_none_or(|s| anomaly.severity >= *s)
+            })
+            .collect()
+    }
+
+    /// The selected anomaly, if any passes the filters
+    pub fn selected<'a>(&self, anomalies: &'a [Anomaly]) -> Option<&'a Anomaly> {
+        let visible = self.visible(anomalies);
+        visible.get(self.list_state.selected()?).copied()
+    }
+
+    /// Shows the next category only, wrapping around to all categories
+    pub fn cycle_category(&mut self) {
+        self.category = match &self.category {
+            None => Some(CATEGORIES[0].clone()),
+            Some(category) => {
+                let index = CATEGORIES.iter().position(|c| c == category).unwrap_or(0);
+                CATEGORIES.get(index + 1).cloned()
+            }
+        };
+        self.list_state.select(Some(0));
+    }
+
+    /// Cycles the minimum severity: all, warning and above, critical only
+    pub fn cycle_severity(&mut self) {
+        self.min_severity = match self.min_severity {
+            None => Some(AnomalySeverity::Warning),
+            Some(AnomalySeverity::Warning) => Some(AnomalySeverity::Critical),
+            Some(_) => None,
+        };
+        self.list_state.select(Some(0));
+    }
+
+    /// Describes the active filters, e.g. `Cpu, ≥Warning`
+    fn filter_label(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if let Some(category) = &self.category {
+            parts.push(format!("{:?}", category));
+        }
+        match self.min_severity {
+            Some(AnomalySeverity::Critical) => parts.push("Critical".to_string()),
+            Some(ref severity) => parts.push(format!("≥{:?}", severity)),
+            None => {}
+        }
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+
     pub fn scroll_up(&mut self, anomalies_count: usize) {
         if anomalies_count == 0 {
             return;
@@ -96,14 +177,30 @@ pub fn render_anomalies_view(
     anomalies: &[Anomaly],
     state: &mut AnomaliesViewState,
 ) {
+    let visible = state.visible(anomalies);
+    let title = match state.filter_label() {
+        Some(filters) => format!(
+            " Anomalies ({}/{}) [{}] ",
+            visible.len(),
+            anomalies.len(),
+            filters
+        ),
+        None => format!(" Anomalies ({}) ", anomalies.len()),
+    };
     let block = Block::default()
-        .title(format!(" Anomalies ({}) ", anomalies.len()))
+        .title(title)
+        .title_bottom(" Enter details  c category  v severity ")
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::Cyan));
 
-    if anomalies.is_empty() {
+    if visible.is_empty() {
+        let message = if anomalies.is_empty() {
+            "No anomalies detected"
+        } else {
+            "No anomalies match the filters"
+        };
         let list = List::new(vec![ListItem::new(Line::from(Span::styled(
-            "No anomalies detected",
+            message,
             Style::default().fg(Color::Gray),
         )))])
         .block(block);
@@ -113,9 +210,8 @@ pub fn render_anomalies_view(
     }
 
     // Create list items from anomalies (newest first)
-    let items: Vec<ListItem> = anomalies
-        .iter()
-        .rev() // Reverse to show newest first
+    let items: Vec<ListItem> = visible
+        .into_iter()
         .map(|anomaly| {
             let severity_color = get_severity_color(&anomaly.severity);
             let severity_symbol = get_severity_symbol(&anomaly.severity);
@@ -159,7 +255,7 @@ pub fn render_anomalies_view(
     frame.render_stateful_widget(list, area, &mut state.list_state);
 }
 
-fn get_severity_color(severity: &AnomalySeverity) -> Color {
+pub fn get_severity_color(severity: &AnomalySeverity) -> Color {
     match severity {
         AnomalySeverity::Info => Color::Blue,
         AnomalySeverity::Warning => Color::Yellow,
@@ -167,10 +263,58 @@ fn get_severity_color(severity: &AnomalySeverity) -> Color {
     }
 }
 
-fn get_severity_symbol(severity: &AnomalySeverity) -> &'static str {
+pub fn get_severity_symbol(severity: &AnomalySeverity) -> &'static str {
     match severity {
         AnomalySeverity::Info => "ℹ",
         AnomalySeverity::Warning => "⚠",
         AnomalySeverity::Critical => "✖",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn anomaly(category: AnomalyCategory, severity: AnomalySeverity) -> Anomaly {
+        Anomaly {
+            id: format!("{:?}-{:?}", category, severity),
+            timestamp: Utc::now(),
+            severity,
+            category,
+            message: "test".to_string(),
+            metrics: serde_json::Value::Null,
+        }
+    }
+
+    #[test]
+    fn test_filters() {
+        let anomalies = vec![
+            anomaly(AnomalyCategory::Cpu, AnomalySeverity::Info),
+            anomaly(AnomalyCategory::Disk, AnomalySeverity::Critical),
+            anomaly(AnomalyCategory::Cpu, AnomalySeverity::Warning),
+        ];
+        let mut state = AnomaliesViewState::default();
+        assert_eq!(state.visible(&anomalies).len(), 3);
+        assert_eq!(state.selected(&anomalies).unwrap().id, "Cpu-Warning");
+
+        state.cycle_category();
+        assert_eq!(state.category, Some(AnomalyCategory::Cpu));
+        assert_eq!(state.visible(&anomalies).len(), 2);
+
+        state.cycle_severity();
+        let ids: Vec<_> = state.visible(&anomalies).iter().map(|a| &a.id).collect();
+        assert_eq!(ids, vec!["Cpu-Warning"]);
+        assert_eq!(state.filter_label().unwrap(), "Cpu, ≥Warning");
+
+        // Past the last category the filter is cleared
+        for _ in 0..CATEGORIES.len() {
+            state.cycle_category();
+        }
+        assert_eq!(state.category, None);
+        state.cycle_severity();
+        assert_eq!(state.visible(&anomalies).len(), 1);
+        state.cycle_severity();
+        assert!(state.filter_label().is_none());
+    }
+}
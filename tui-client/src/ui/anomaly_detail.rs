@@ -0,0 +1,307 @@
+use chrono::Local;
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+use shared::types::{Anomaly, SystemMetrics};
+
+use super::anomalies_view::{get_severity_color, get_severity_symbol};
+use super::network_view::format_rate;
+
+/// Processes listed in the snapshot section
+const SNAPSHOT_PROCESSES: usize = 5;
+
+/// System snapshot of the popup, fetched when it opens
+#[derive(Debug, Clone)]
+pub enum Snapshot {
+    Loading,
+    Loaded(Box<SystemMetrics>),
+    /// No sample was stored near the anomaly
+    Missing,
+    Failed(String),
+}
+
+/// Outcome of an acknowledge or mute request
+#[derive(Debug, Clone, PartialEq)]
+pub enum ActionStatus {
+    Pending(String),
+    Done(String),
+    Failed(String),
+}
+
+/// State of the anomaly detail popup
+#[derive(Debug, Clone)]
+pub struct AnomalyDetailState {
+    pub anomaly: Anomaly,
+    pub snapshot: Snapshot,
+    /// Result of the last action, shown above the details
+    pub status: Option<ActionStatus>,
+    /// Lines scrolled past
+    pub scroll: u16,
+}
+
+impl AnomalyDetailState {
+    pub fn new(anomaly: Anomaly) -> Self {
+        Self {
+            anomaly,
+            snapshot: Snapshot::Loading,
+            status: None,
+            scroll: 0,
+        }
+    }
+
+    /// Rule that raised the anomaly, if it came from the rules engine
+    pub fn rule(&self) -> Option<&str> {
+        self.anomaly.metrics["rule"].as_str()
+    }
+
+    /// Incident the anomaly opened or escalated
+    pub fn incident_id(&self) -> Option<&str> {
+        self.anomaly.metrics["incident_id"].as_str()
+    }
+
+    pub fn scroll_up(&mut self, lines: u16) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+
+    pub fn scroll_down(&mut self, lines: u16) {
+        self.scroll = self.scroll.saturating_add(lines);
+    }
+}
+
+/// Render the anomaly detail popup over the dashboard
+pub fn render_anomaly_detail(frame: &mut Frame, area: Rect, state: &AnomalyDetailState) {
+    let area = centered(area, 80, 80);
+    let color = get_severity_color(&state.anomaly.severity);
+
+    let block = Block::default()
+        .title(Span::styled(
+            format!(
+                " {} {:?} anomaly ",
+                get_severity_symbol(&state.anomaly.severity),
+                state.anomaly.severity
+            ),
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        ))
+        .title_bottom(" a acknowledge  m mute rule 1h  ↑/↓ scroll  Esc close ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(color));
+
+    let lines = detail_lines(state);
+    // Keep the last line reachable but stop scrolling past it
+    let max_scroll = (lines.len() as u16).saturating_sub(1);
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false })
+        .scroll((state.scroll.min(max_scroll), 0));
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(paragraph, area);
+}
+
+/// Lines of the popup: action status, summary, message, metrics context and
+/// the system snapshot
+pub fn detail_lines(state: &AnomalyDetailState) -> Vec<Line<'static>> {
+    let anomaly = &state.anomaly;
+    let label = Style::default().fg(Color::Gray);
+    let heading = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let mut lines = Vec::new();
+
+    if let Some(status) = &state.status {
+        let (text, color) = match status {
+            ActionStatus::Pending(text) => (text, Color::Yellow),
+            ActionStatus::Done(text) => (text, Color::Green),
+            ActionStatus::Failed(text) => (text, Color::Red),
+        };
+        lines.push(Line::styled(text.clone(), Style::default().fg(color)));
+        lines.push(Line::from(""));
+    }
+
+    let time = anomaly.timestamp.with_timezone(&Local);
+    lines.push(Line::from(vec![
+        Span::styled("Time:     ", label),
+        Span::raw(time.format("%Y-%m-%d %H:%M:%S").to_string()),
+        Span::styled("   Category: ", label),
+        Span::raw(format!("{:?}", anomaly.category)),
+    ]));
+    lines.push(Line::from(vec![
+        Span::styled("Rule:     ", label),
+        Span::raw(state.rule().unwrap_or("-").to_string()),
+        Span::styled("   Subject: ", label),
+        Span::raw(
+            anomaly.metrics["subject"]
+                .as_str()
+                .unwrap_or("-")
+                .to_string(),
+        ),
+    ]));
+    lines.push(Line::from(vec![
+        Span::styled("Incident: ", label),
+        Span::raw(state.incident_id().unwrap_or("-").to_string()),
+    ]));
+
+    lines.push(Line::from(""));
+    lines.push(Line::styled("Message", heading));
+    lines.push(Line::from(anomaly.message.clone()));
+
+    lines.push(Line::from(""));
+    lines.push(Line::styled("Metrics", heading));
+    let context = serde_json::to_string_pretty(&anomaly.metrics).unwrap_or_default();
+    lines.extend(context.lines().map(|line| Line::from(line.to_string())));
+
+    lines.push(Line::from(""));
+    match &state.snapshot {
+        Snapshot::Loading => lines.push(Line::styled("Loading system snapshot...", label)),
+        Snapshot::Missing => lines.push(Line::styled("No system snapshot stored", label)),
+        Snapshot::Failed(error) => lines.push(Line::styled(
+            format!("Failed to load system snapshot: {}", error),
+            Style::default().fg(Color::Red),
+        )),
+        Snapshot::Loaded(metrics) => {
+            let time = metrics.timestamp.with_timezone(&Local);
+            lines.push(Line::styled(
+                format!("System snapshot at {}", time.format("%H:%M:%S")),
+                heading,
+            ));
+            lines.extend(snapshot_lines(metrics));
+        }
+    }
+
+    lines
+}
+
+/// Summary of one sample: usage, temperatures, disks and the top processes
+fn snapshot_lines(metrics: &SystemMetrics) -> Vec<Line<'static>> {
+    let label = Style::default().fg(Color::Gray);
+    let gib = |bytes: u64| bytes as f64 / (1024.0 * 1024.0 * 1024.0);
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("CPU:      ", label),
+            Span::raw(format!(
+                "{:.1}%  load {:.2} {:.2} {:.2}",
+                metrics.cpu.global_usage,
+                metrics.cpu.load_avg_1,
+                metrics.cpu.load_avg_5,
+                metrics.cpu.load_avg_15
+            )),
+        ]),
+        Line::from(vec![
+            Span::styled("Memory:   ", label),
+            Span::raw(format!(
+                "{:.1}%  {:.2} / {:.2} GB",
+                metrics.memory.usage_percent,
+                gib(metrics.memory.used),
+                gib(metrics.memory.total)
+            )),
+        ]),
+        Line::from(vec![
+            Span::styled("Network:  ", label),
+            Span::raw(format!(
+                "RX {}  TX {}",
+                format_rate(metrics.network.rx_bytes as f64),
+                format_rate(metrics.network.tx_bytes as f64)
+            )),
+        ]),
+    ];
+
+    for temp in &metrics.temperatures {
+        lines.push(Line::from(vec![
+            Span::styled("Temp:     ", label),
+            Span::raw(format!("{} {:.1}°C", temp.label, temp.value)),
+        ]));
+    }
+    for disk in &metrics.disks {
+        lines.push(Line::from(vec![
+            Span::styled("Disk:     ", label),
+            Span::raw(format!("{} {:.1}%", disk.mount_point, disk.usage_percent)),
+        ]));
+    }
+    if let Some(gpu) = &metrics.gpu {
+        lines.push(Line::from(vec![
+            Span::styled("GPU:      ", label),
+            Span::raw(format!(
+                "{:.1}%  VRAM {:.1}%  {:.1}°C",
+                gpu.usage_percent, gpu.memory_usage_percent, gpu.temperature
+            )),
+        ]));
+    }
+
+    let mut processes: Vec<_> = metrics.processes.iter().collect();
+    processes.sort_by(|a, b| b.cpu_usage.total_cmp(&a.cpu_usage));
+    for process in processes.into_iter().take(SNAPSHOT_PROCESSES) {
+        lines.push(Line::from(vec![
+            Span::styled("Process:  ", label),
+            Span::raw(format!(
+                "{} {} {:.1}% CPU {:.1} MB",
+                process.pid,
+                process.name,
+                process.cpu_usage,
+                process.memory_rss as f64 / (1024.0 * 1024.0)
+            )),
+        ]));
+    }
+
+    lines
+}
+
+/// A rectangle of `percent_x` by `percent_y` of `area`, centered in it
+fn centered(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
+    let vertical = Layout::default()
+        .direction(ratatui::layout::Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(area);
+    Layout::default()
+        .direction(ratatui::layout::Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(vertical[1])[1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use serde_json::json;
+    use shared::types::{AnomalyCategory, AnomalySeverity};
+
+    #[test]
+    fn test_detail_lines() {
+        let mut state = AnomalyDetailState::new(Anomaly {
+            id: "a1".to_string(),
+            timestamp: Utc::now(),
+            severity: AnomalySeverity::Critical,
+            category: AnomalyCategory::Disk,
+            message: "Disk /home at 96%".to_string(),
+            metrics: json!({
+                "rule": "disk_usage_critical",
+                "subject": "/home",
+                "incident_id": "i1",
+                "value": 96.0,
+            }),
+        });
+        assert_eq!(state.rule(), Some("disk_usage_critical"));
+        assert_eq!(state.incident_id(), Some("i1"));
+
+        state.status = Some(ActionStatus::Done("Incident acknowledged".to_string()));
+        let text: Vec<String> = detail_lines(&state)
+            .iter()
+            .map(|line| line.to_string())
+            .collect();
+        assert_eq!(text[0], "Incident acknowledged");
+        assert!(text.contains(&"Disk /home at 96%".to_string()));
+        assert!(text.contains(&"  \"value\": 96.0".to_string()));
+        assert_eq!(text.last().unwrap(), "Loading system snapshot...");
+    }
+}
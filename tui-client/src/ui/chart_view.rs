@@ -0,0 +1,175 @@
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    symbols::Marker,
+    text::Span,
+    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType},
+    Frame,
+};
+
+use super::network_view::format_rate;
+use crate::history::MetricHistory;
+
+/// Colors cycled through for multi-line charts
+const LINE_COLORS: [Color; 6] = [
+    Color::Cyan,
+    Color::Yellow,
+    Color::Magenta,
+    Color::Green,
+    Color::LightRed,
+    Color::LightBlue,
+];
+
+/// Metric shown by the full-screen chart
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChartMetric {
+    #[default]
+    Cpu,
+    Memory,
+    Network,
+    Temperatures,
+}
+
+impl ChartMetric {
+    const ALL: [ChartMetric; 4] = [
+        ChartMetric::Cpu,
+        ChartMetric::Memory,
+        ChartMetric::Network,
+        ChartMetric::Temperatures,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            ChartMetric::Cpu => "CPU Usage",
+            ChartMetric::Memory => "Memory Usage",
+            ChartMetric::Network => "Network Throughput",
+            ChartMetric::Temperatures => "Temperatures",
+        }
+    }
+
+    /// The next metric, wrapping around
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|m| *m == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// The previous metric, wrapping around
+    pub fn previous(self) -> Self {
+        let index = Self::ALL.iter().position(|m| *m == self).unwrap_or(0);
+        Self::ALL[(index + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+/// Render a full-screen chart of one metric over the selected window
+pub fn render_chart_view(
+    frame: &mut Frame,
+    area: Rect,
+    history: &MetricHistory,
+    metric: ChartMetric,
+    now: i64,
+) {
+    let window = history.window();
+    let window_secs = window.duration().num_seconds() as f64;
+
+    // (name, points) per line; datasets borrow the points
+    let lines: Vec<(String, Vec<(f64, f64)>)> = match metric {
+        ChartMetric::Cpu => vec![("CPU %".to_string(), history.cpu.chart_points(now))],
+        ChartMetric::Memory => vec![("RAM %".to_string(), history.memory.chart_points(now))],
+        ChartMetric::Network => vec![
+            ("RX".to_string(), history.rx.chart_points(now)),
+            ("TX".to_string(), history.tx.chart_points(now)),
+        ],
+        ChartMetric::Temperatures => history
+            .temperatures
+            .iter()
+            .map(|(sensor, series)| (sensor.clone(), series.chart_points(now)))
+            .collect(),
+    };
+
+    let (y_min, y_max) = match metric {
+        ChartMetric::Cpu | ChartMetric::Memory => (0.0, 100.0),
+        ChartMetric::Network => {
+            let max = history.rx.max().max(history.tx.max());
+            (0.0, (max * 1.1).max(1.0))
+        }
+        ChartMetric::Temperatures => {
+            let series = history.temperatures.values();
+            let min = series.clone().map(|s| s.min()).fold(f64::MAX, f64::min);
+            let max = series.map(|s| s.max()).fold(f64::MIN, f64::max);
+            if min <= max {
+                ((min - 5.0).floor().max(0.0), (max + 5.0).ceil())
+            } else {
+                (0.0, 100.0)
+            }
+        }
+    };
+    let y_label = |value: f64| match metric {
+        ChartMetric::Cpu | ChartMetric::Memory => format!("{:.0}%", value),
+        ChartMetric::Network => format_rate(value),
+        ChartMetric::Temperatures => format!("{:.0}°C", value),
+    };
+
+    let datasets: Vec<Dataset> = lines
+        .iter()
+        .enumerate()
+        .map(|(i, (name, points))| {
+            Dataset::default()
+                .name(name.clone())
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(LINE_COLORS[i % LINE_COLORS.len()]))
+                .data(points)
+        })
+        .collect();
+
+    let label_style = Style::default().fg(Color::Gray);
+    let x_axis = Axis::default()
+        .style(label_style)
+        .bounds([-window_secs, 0.0])
+        .labels(vec![
+            Span::styled(format!("-{}", window.label()), label_style),
+            Span::styled("now", label_style),
+        ]);
+    let y_axis = Axis::default()
+        .style(label_style)
+        .bounds([y_min, y_max])
+        .labels(vec![
+            Span::styled(y_label(y_min), label_style),
+            Span::styled(y_label((y_min + y_max) / 2.0), label_style),
+            Span::styled(y_label(y_max), label_style),
+        ]);
+
+    let title = if lines.iter().all(|(_, points)| points.is_empty()) {
+        format!(" {} ({}) - no data yet ", metric.title(), window.label())
+    } else {
+        format!(" {} ({}) ", metric.title(), window.label())
+    };
+    let block = Block::default()
+        .title(Span::styled(
+            title,
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ))
+        .title_bottom(" ←/→ metric  w window  Esc/g back ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let chart = Chart::new(datasets)
+        .block(block)
+        .x_axis(x_axis)
+        .y_axis(y_axis);
+    frame.render_widget(chart, area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chart_metric_cycles() {
+        assert_eq!(ChartMetric::Cpu.next(), ChartMetric::Memory);
+        assert_eq!(ChartMetric::Temperatures.next(), ChartMetric::Cpu);
+        assert_eq!(ChartMetric::Cpu.previous(), ChartMetric::Temperatures);
+    }
+}
@@ -2,13 +2,15 @@ use ratatui::{
     layout::{Constraint, Layout, Rect},
     style::{Color, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Gauge, Paragraph},
+    widgets::{Block, Borders, Gauge, Paragraph, Sparkline},
     Frame,
 };
 use shared::types::CpuMetrics;
 
+use crate::history::Series;
+
 /// Render CPU metrics view
-pub fn render_cpu_view(frame: &mut Frame, area: Rect, cpu: &CpuMetrics) {
+pub fn render_cpu_view(frame: &mut Frame, area: Rect, cpu: &CpuMetrics, history: &Series) {
     let block = Block::default()
         .title(" CPU ")
         .borders(Borders::ALL)
@@ -17,11 +19,12 @@ pub fn render_cpu_view(frame: &mut Frame, area: Rect, cpu: &CpuMetrics) {
     let inner_area = block.inner(area);
     frame.render_widget(block, area);
 
-    // Calculate layout: global gauge + per-core bars + load averages
+    // Calculate layout: global gauge + history + per-core bars + load averages
     let chunks = Layout::default()
         .direction(ratatui::layout::Direction::Vertical)
         .constraints([
             Constraint::Length(2), // Global usage
+            Constraint::Length(2), // Usage history
             Constraint::Min(1),    // Per-core bars
             Constraint::Length(3), // Load averages
         ])
@@ -30,11 +33,24 @@ pub fn render_cpu_view(frame: &mut Frame, area: Rect, cpu: &CpuMetrics) {
     // Render global CPU usage
     render_global_usage(frame, chunks[0], cpu.global_usage);
 
+    // Render usage over the selected window
+    render_usage_history(frame, chunks[1], history, cpu.global_usage);
+
     // Render per-core usage (show first 8 cores if more than 8)
-    render_per_core_usage(frame, chunks[1], &cpu.per_core);
+    render_per_core_usage(frame, chunks[2], &cpu.per_core);
 
     // Render load averages
-    render_load_averages(frame, chunks[2], cpu);
+    render_load_averages(frame, chunks[3], cpu);
+}
+
+fn render_usage_history(frame: &mut Frame, area: Rect, history: &Series, usage: f32) {
+    let data = history.sparkline(area.width as usize);
+    let sparkline = Sparkline::default()
+        .data(&data)
+        .max(100)
+        .style(Style::default().fg(get_usage_color(usage)));
+
+    frame.render_widget(sparkline, area);
 }
 
 fn render_global_usage(frame: &mut Frame, area: Rect, usage: f32) {
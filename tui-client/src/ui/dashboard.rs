@@ -3,22 +3,133 @@ use ratatui::{
     layout::{Constraint, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Block, Borders, Paragraph, Sparkline, Tabs},
     Frame,
 };
 use shared::types::{Anomaly, SystemMetrics};
 
+use crate::api_client::ConnectionState;
+use crate::history::MetricHistory;
+
 use super::{
     anomalies_view::{render_anomalies_view, AnomaliesViewState},
+    anomaly_detail::{render_anomaly_detail, AnomalyDetailState},
+    chart_view::{render_chart_view, ChartMetric},
     cpu_view::render_cpu_view,
     disk_view::render_disk_view,
+    gpu_view::{self, render_gpu_pane},
     memory_view::render_memory_view,
+    network_view::{self, render_network_pane, render_network_view},
+    process_view::{self, render_process_pane},
+    table::{Column, TableViewState},
+    temperatures_view::{self, render_temperatures_pane, sensor_key, TemperatureBand},
+    usb_view::{self, render_usb_pane},
 };
 
+/// Dashboard tab
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tab {
+    #[default]
+    Overview,
+    Network,
+    Temperatures,
+    Gpu,
+    Usb,
+    Processes,
+}
+
+impl Tab {
+    pub const ALL: [Tab; 6] = [
+        Tab::Overview,
+        Tab::Network,
+        Tab::Temperatures,
+        Tab::Gpu,
+        Tab::Usb,
+        Tab::Processes,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Tab::Overview => "Overview",
+            Tab::Network => "Network",
+            Tab::Temperatures => "Temperatures",
+            Tab::Gpu => "GPU",
+            Tab::Usb => "USB",
+            Tab::Processes => "Processes",
+        }
+    }
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|tab| *tab == self).unwrap_or(0)
+    }
+
+    /// The next tab, wrapping around
+    pub fn next(self) -> Self {
+        Self::ALL[(self.index() + 1) % Self::ALL.len()]
+    }
+
+    /// The previous tab, wrapping around
+    pub fn previous(self) -> Self {
+        Self::ALL[(self.index() + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    /// Columns of the tab's table; None for the overview
+    pub fn columns(self) -> Option<&'static [Column]> {
+        match self {
+            Tab::Overview => None,
+            Tab::Network => Some(&network_view::COLUMNS),
+            Tab::Temperatures => Some(&temperatures_view::COLUMNS),
+            Tab::Gpu => Some(&gpu_view::COLUMNS),
+            Tab::Usb => Some(&usb_view::COLUMNS),
+            Tab::Processes => Some(&process_view::COLUMNS),
+        }
+    }
+}
+
 /// Main dashboard state
-#[derive(Default)]
 pub struct DashboardState {
     pub anomalies_view_state: AnomaliesViewState,
+    /// Metric shown full-screen; None shows the dashboard
+    pub chart: Option<ChartMetric>,
+    /// Anomaly shown in the detail popup
+    pub detail: Option<AnomalyDetailState>,
+    pub tab: Tab,
+    pub network: TableViewState,
+    pub temperatures: TableViewState,
+    pub gpu: TableViewState,
+    pub usb: TableViewState,
+    pub processes: TableViewState,
+}
+
+impl Default for DashboardState {
+    fn default() -> Self {
+        Self {
+            anomalies_view_state: AnomaliesViewState::default(),
+            chart: None,
+            detail: None,
+            tab: Tab::default(),
+            network: TableViewState::default(),
+            temperatures: TableViewState::default(),
+            gpu: TableViewState::default(),
+            usb: TableViewState::default(),
+            // Busiest processes first
+            processes: TableViewState::new(process_view::DEFAULT_SORT_COLUMN, true),
+        }
+    }
+}
+
+impl DashboardState {
+    /// Table state of the current tab; None for the overview
+    pub fn table_state_mut(&mut self) -> Option<&mut TableViewState> {
+        match self.tab {
+            Tab::Overview => None,
+            Tab::Network => Some(&mut self.network),
+            Tab::Temperatures => Some(&mut self.temperatures),
+            Tab::Gpu => Some(&mut self.gpu),
+            Tab::Usb => Some(&mut self.usb),
+            Tab::Processes => Some(&mut self.processes),
+        }
+    }
 }
 
 /// Render the main dashboard
@@ -26,7 +137,8 @@ pub fn render_dashboard(
     frame: &mut Frame,
     metrics: &Option<SystemMetrics>,
     anomalies: &[Anomaly],
-    connection_status: &str,
+    connection: &ConnectionState,
+    history: &MetricHistory,
     state: &mut DashboardState,
 ) {
     let area = frame.size();
@@ -34,12 +146,49 @@ pub fn render_dashboard(
         .direction(ratatui::layout::Direction::Vertical)
         .constraints([
             Constraint::Length(3), // Header
+            Constraint::Length(1), // Tabs
             Constraint::Min(10),   // Main content
         ])
         .split(area);
 
     // Render header
-    render_header(frame, chunks[0], metrics, connection_status);
+    render_header(frame, chunks[0], metrics, connection, history);
+    render_tabs(frame, chunks[1], state.tab);
+    render_main(frame, chunks[2], metrics, anomalies, history, state);
+
+    // The detail popup is drawn over whatever is shown
+    if let Some(detail) = &state.detail {
+        render_anomaly_detail(frame, area, detail);
+    }
+}
+
+/// Render the chart, the selected pane or the overview panels
+fn render_main(
+    frame: &mut Frame,
+    area: Rect,
+    metrics: &Option<SystemMetrics>,
+    anomalies: &[Anomaly],
+    history: &MetricHistory,
+    state: &mut DashboardState,
+) {
+    // Full-screen chart replaces the dashboard panels
+    if let Some(metric) = state.chart {
+        let now = metrics
+            .as_ref()
+            .map(|m| m.timestamp)
+            .unwrap_or_else(chrono::Utc::now)
+            .timestamp();
+        render_chart_view(frame, area, history, metric, now);
+        return;
+    }
+
+    if state.tab != Tab::Overview {
+        match metrics {
+            Some(metrics) => render_pane(frame, area, metrics, history, state),
+            None => render_no_data(frame, area),
+        }
+        return;
+    }
 
     // Main content area
     let main_chunks = Layout::default()
@@ -48,15 +197,16 @@ pub fn render_dashboard(
             Constraint::Percentage(60), // Left side (CPU, Memory, Disks)
             Constraint::Percentage(40), // Right side (Anomalies, Temps)
         ])
-        .split(chunks[1]);
+        .split(area);
 
     // Left side layout
     let left_chunks = Layout::default()
         .direction(ratatui::layout::Direction::Vertical)
         .constraints([
-            Constraint::Length(12), // CPU
-            Constraint::Length(8),  // Memory
-            Constraint::Min(10),    // Disks
+            Constraint::Length(14), // CPU
+            Constraint::Length(10), // Memory
+            Constraint::Length(5),  // Network
+            Constraint::Min(6),     // Disks
         ])
         .split(main_chunks[0]);
 
@@ -71,16 +221,19 @@ pub fn render_dashboard(
 
     if let Some(metrics) = metrics {
         // Render CPU view
-        render_cpu_view(frame, left_chunks[0], &metrics.cpu);
+        render_cpu_view(frame, left_chunks[0], &metrics.cpu, &history.cpu);
 
         // Render Memory view
-        render_memory_view(frame, left_chunks[1], &metrics.memory);
+        render_memory_view(frame, left_chunks[1], &metrics.memory, &history.memory);
+
+        // Render Network view
+        render_network_view(frame, left_chunks[2], &metrics.network, history);
 
         // Render Disks view
-        render_disk_view(frame, left_chunks[2], &metrics.disks);
+        render_disk_view(frame, left_chunks[3], &metrics.disks);
 
         // Render Temperatures view
-        render_temperatures_view(frame, right_chunks[0], metrics);
+        render_temperatures_view(frame, right_chunks[0], metrics, history);
     } else {
         // Show loading/error state
         render_no_data(frame, main_chunks[0]);
@@ -95,21 +248,69 @@ pub fn render_dashboard(
     );
 }
 
+/// Render the pane of a subsystem tab
+fn render_pane(
+    frame: &mut Frame,
+    area: Rect,
+    metrics: &SystemMetrics,
+    history: &MetricHistory,
+    state: &mut DashboardState,
+) {
+    match state.tab {
+        Tab::Overview => {}
+        Tab::Network => {
+            render_network_pane(frame, area, &metrics.network, history, &mut state.network)
+        }
+        Tab::Temperatures => render_temperatures_pane(
+            frame,
+            area,
+            &metrics.temperatures,
+            history,
+            &mut state.temperatures,
+        ),
+        Tab::Gpu => render_gpu_pane(frame, area, metrics.gpu.as_ref(), &mut state.gpu),
+        Tab::Usb => render_usb_pane(frame, area, &metrics.usb_devices, &mut state.usb),
+        Tab::Processes => {
+            render_process_pane(frame, area, &metrics.processes, &mut state.processes)
+        }
+    }
+}
+
+fn render_tabs(frame: &mut Frame, area: Rect, selected: Tab) {
+    let titles: Vec<Line> = Tab::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, tab)| Line::from(format!("{} {}", i + 1, tab.title())))
+        .collect();
+
+    let tabs = Tabs::new(titles)
+        .select(selected.index())
+        .style(Style::default().fg(Color::Gray))
+        .highlight_style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD | Modifier::REVERSED),
+        );
+    frame.render_widget(tabs, area);
+}
+
 fn render_header(
     frame: &mut Frame,
     area: Rect,
     metrics: &Option<SystemMetrics>,
-    connection_status: &str,
+    connection: &ConnectionState,
+    history: &MetricHistory,
 ) {
     let block = Block::default()
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::Cyan));
 
-    let (status_color, status_text) = if connection_status == "Connected" {
-        (Color::Green, connection_status)
-    } else {
-        (Color::Red, connection_status)
+    let status_color = match connection {
+        ConnectionState::Connected(_) => Color::Green,
+        ConnectionState::Connecting(_) | ConnectionState::Reconnecting { .. } => Color::Yellow,
+        ConnectionState::Error(_) => Color::Red,
     };
+    let status_text = connection.label();
 
     let timestamp = if let Some(metrics) = metrics {
         let local_time = metrics.timestamp.with_timezone(&Local);
@@ -137,14 +338,25 @@ fn render_header(
         Span::styled("Updated: ", Style::default().fg(Color::Gray)),
         Span::styled(timestamp, Style::default().fg(Color::White)),
         Span::raw("  |  "),
-        Span::styled("Press 'q' to quit", Style::default().fg(Color::DarkGray)),
+        Span::styled("Window: ", Style::default().fg(Color::Gray)),
+        Span::styled(history.window().label(), Style::default().fg(Color::White)),
+        Span::raw("  |  "),
+        Span::styled(
+            "Tab: pane  w: window  g: chart  q: quit",
+            Style::default().fg(Color::DarkGray),
+        ),
     ])];
 
     let paragraph = Paragraph::new(header_text).block(block);
     frame.render_widget(paragraph, area);
 }
 
-fn render_temperatures_view(frame: &mut Frame, area: Rect, metrics: &SystemMetrics) {
+fn render_temperatures_view(
+    frame: &mut Frame,
+    area: Rect,
+    metrics: &SystemMetrics,
+    history: &MetricHistory,
+) {
     let block = Block::default()
         .title(" Temperatures ")
         .borders(Borders::ALL)
@@ -163,29 +375,42 @@ fn render_temperatures_view(frame: &mut Frame, area: Rect, metrics: &SystemMetri
         return;
     }
 
-    let max_temps_to_show = inner_area.height as usize;
-    let temps: Vec<Line> = metrics
-        .temperatures
-        .iter()
-        .take(max_temps_to_show)
-        .map(|temp| {
-            let color = get_temperature_color(temp.value);
-            Line::from(vec![
-                Span::styled(
-                    format!("{:20}", temp.label),
-                    Style::default().fg(Color::Gray),
-                ),
-                Span::raw(" "),
-                Span::styled(
-                    format!("{:.1}°C", temp.value),
-                    Style::default().fg(color).add_modifier(Modifier::BOLD),
-                ),
-            ])
-        })
-        .collect();
+    let max_temps_to_show = (inner_area.height as usize).min(metrics.temperatures.len());
+    let rows = Layout::default()
+        .direction(ratatui::layout::Direction::Vertical)
+        .constraints(vec![Constraint::Length(1); max_temps_to_show])
+        .split(inner_area);
+
+    for (temp, row) in metrics.temperatures.iter().zip(rows.iter()) {
+        let color = TemperatureBand::for_value(temp.value).color();
+        let columns = Layout::default()
+            .direction(ratatui::layout::Direction::Horizontal)
+            .constraints([Constraint::Length(30), Constraint::Min(0)])
+            .split(*row);
+
+        let line = Line::from(vec![
+            Span::styled(
+                format!("{:20}", temp.label),
+                Style::default().fg(Color::Gray),
+            ),
+            Span::raw(" "),
+            Span::styled(
+                format!("{:.1}°C", temp.value),
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            ),
+        ]);
+        frame.render_widget(Paragraph::new(line), columns[0]);
 
-    let paragraph = Paragraph::new(temps);
-    frame.render_widget(paragraph, inner_area);
+        // Per-sensor history, scaled to the sensor's own peak
+        if let Some(series) = history.temperatures.get(&sensor_key(temp)) {
+            let data = series.sparkline(columns[1].width as usize);
+            let sparkline = Sparkline::default()
+                .data(&data)
+                .max(series.max().max(1.0).round() as u64)
+                .style(Style::default().fg(color));
+            frame.render_widget(sparkline, columns[1]);
+        }
+    }
 }
 
 fn render_no_data(frame: &mut Frame, area: Rect) {
@@ -211,14 +436,118 @@ fn render_no_data(frame: &mut Frame, area: Rect) {
     frame.render_widget(paragraph, area);
 }
 
-fn get_temperature_color(temp: f32) -> Color {
-    if temp < 50.0 {
-        Color::Green
-    } else if temp < 70.0 {
-        Color::Yellow
-    } else if temp < 85.0 {
-        Color::LightRed
-    } else {
-        Color::Red
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+    use shared::types::{
+        CpuMetrics, GpuMetrics, MemoryMetrics, NetworkMetrics, Temperature, UsbDevice,
+    };
+
+    fn sample_metrics() -> SystemMetrics {
+        SystemMetrics {
+            timestamp: chrono::Utc::now(),
+            cpu: CpuMetrics {
+                global_usage: 25.0,
+                per_core: vec![20.0, 30.0],
+                load_avg_1: 0.5,
+                load_avg_5: 0.4,
+                load_avg_15: 0.3,
+            },
+            memory: MemoryMetrics {
+                total: 8 << 30,
+                used: 4 << 30,
+                available: 4 << 30,
+                usage_percent: 50.0,
+                swap_total: 0,
+                swap_used: 0,
+            },
+            temperatures: vec![Temperature {
+                sensor: "coretemp".to_string(),
+                value: 72.0,
+                label: "Package id 0".to_string(),
+            }],
+            disks: vec![],
+            usb_devices: vec![UsbDevice {
+                id: "0bda:8153".to_string(),
+                manufacturer: "Realtek".to_string(),
+                product: "USB 10/100/1000 LAN".to_string(),
+                has_timeout: true,
+            }],
+            network: NetworkMetrics {
+                rx_bytes: 2048,
+                tx_bytes: 1024,
+                rx_packets: 10,
+                tx_packets: 5,
+                interfaces: vec![],
+            },
+            gpu: Some(GpuMetrics {
+                name: "GeForce RTX 3060".to_string(),
+                temperature: 61.0,
+                usage_percent: 40.0,
+                memory_usage_percent: 25.0,
+                memory_total_mb: 12288,
+                memory_used_mb: 3072,
+                memory_free_mb: 9216,
+                power_draw_watts: 95.5,
+                fan_speed_percent: 35.0,
+            }),
+            processes: vec![],
+            zombies: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_render_every_tab() {
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        let metrics = Some(sample_metrics());
+        let history = MetricHistory::default();
+        let mut state = DashboardState::default();
+
+        for tab in Tab::ALL {
+            state.tab = tab;
+            if let Some(table) = state.table_state_mut() {
+                // Selection past the end is clamped when drawn
+                table.scroll_down(usize::MAX);
+            }
+            terminal
+                .draw(|frame| {
+                    render_dashboard(
+                        frame,
+                        &metrics,
+                        &[],
+                        &ConnectionState::Error("offline".to_string()),
+                        &history,
+                        &mut state,
+                    )
+                })
+                .unwrap();
+
+            let screen: String = terminal
+                .backend()
+                .buffer()
+                .content()
+                .iter()
+                .map(|cell| cell.symbol())
+                .collect();
+            assert!(screen.contains(tab.title()), "{:?} not drawn", tab);
+        }
+        assert_eq!(state.usb.table_state.selected(), Some(0));
+    }
+
+    #[test]
+    fn test_tabs_cycle() {
+        assert_eq!(Tab::Overview.next(), Tab::Network);
+        assert_eq!(Tab::Processes.next(), Tab::Overview);
+        assert_eq!(Tab::Overview.previous(), Tab::Processes);
+        assert!(Tab::Overview.columns().is_none());
+
+        let mut state = DashboardState {
+            tab: Tab::Processes,
+            ..Default::default()
+        };
+        let table = state.table_state_mut().unwrap();
+        assert_eq!(table.sort_column, process_view::DEFAULT_SORT_COLUMN);
+        assert!(table.descending);
     }
 }
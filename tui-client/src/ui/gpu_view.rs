@@ -0,0 +1,161 @@
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Paragraph, Row},
+    Frame,
+};
+use shared::types::GpuMetrics;
+
+use super::table::{filter_sort, level_bar, render_table, Column, TableViewState};
+use super::temperatures_view::TemperatureBand;
+
+pub const COLUMNS: [Column; 3] = [
+    Column::new("Reading", Constraint::Length(14)),
+    Column::new("Value", Constraint::Length(22)),
+    Column::new("Level", Constraint::Min(20)),
+];
+
+/// Width of the level bars
+const BAR_WIDTH: usize = 30;
+
+/// One reading of the GPU pane
+#[derive(Debug, Clone, PartialEq)]
+pub struct GpuReading {
+    pub name: &'static str,
+    pub value: String,
+    /// Fill of the level bar; None for readings without a known range
+    pub percent: Option<f64>,
+    pub color: Color,
+}
+
+/// Splits the GPU metrics into the readings shown as table rows
+pub fn gpu_readings(gpu: &GpuMetrics) -> Vec<GpuReading> {
+    let usage_color = |percent: f32| {
+        if percent < 50.0 {
+            Color::Green
+        } else if percent < 80.0 {
+            Color::Yellow
+        } else {
+            Color::Red
+        }
+    };
+
+    vec![
+        GpuReading {
+            name: "Utilization",
+            value: format!("{:.1}%", gpu.usage_percent),
+            percent: Some(f64::from(gpu.usage_percent)),
+            color: usage_color(gpu.usage_percent),
+        },
+        GpuReading {
+            name: "VRAM",
+            value: format!(
+                "{} / {} MB ({:.1}%)",
+                gpu.memory_used_mb, gpu.memory_total_mb, gpu.memory_usage_percent
+            ),
+            percent: Some(f64::from(gpu.memory_usage_percent)),
+            color: usage_color(gpu.memory_usage_percent),
+        },
+        GpuReading {
+            name: "Temperature",
+            value: format!("{:.1}°C", gpu.temperature),
+            percent: Some(f64::from(gpu.temperature)),
+            color: TemperatureBand::for_value(gpu.temperature).color(),
+        },
+        GpuReading {
+            name: "Power",
+            value: format!("{:.1} W", gpu.power_draw_watts),
+            percent: None,
+            color: Color::White,
+        },
+        GpuReading {
+            name: "Fan",
+            value: format!("{:.0}%", gpu.fan_speed_percent),
+            percent: Some(f64::from(gpu.fan_speed_percent)),
+            color: Color::Cyan,
+        },
+    ]
+}
+
+/// Render the GPU pane
+pub fn render_gpu_pane(
+    frame: &mut Frame,
+    area: Rect,
+    gpu: Option<&GpuMetrics>,
+    state: &mut TableViewState,
+) {
+    let Some(gpu) = gpu else {
+        let block = Block::default()
+            .title(" GPU ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan));
+        let text = Line::from(Span::styled(
+            "No GPU detected",
+            Style::default().fg(Color::Gray),
+        ));
+        frame.render_widget(Paragraph::new(text).block(block), area);
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(ratatui::layout::Direction::Vertical)
+        .constraints([
+            Constraint::Length(1), // Name
+            Constraint::Min(5),    // Readings
+        ])
+        .split(area);
+
+    let name = Line::from(vec![
+        Span::styled(" GPU: ", Style::default().fg(Color::Gray)),
+        Span::styled(
+            &gpu.name,
+            Style::default()
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD),
+        ),
+    ]);
+    frame.render_widget(Paragraph::new(name), chunks[0]);
+
+    let readings = gpu_readings(gpu);
+    let rows: Vec<Row> = filter_sort(
+        &readings,
+        state,
+        |reading| reading.name.to_string(),
+        |a, b, column| match column {
+            0 => a.name.cmp(b.name),
+            // Readings without a range sort below the others
+            _ => a
+                .percent
+                .unwrap_or(f64::MIN)
+                .total_cmp(&b.percent.unwrap_or(f64::MIN)),
+        },
+    )
+    .into_iter()
+    .map(|reading| {
+        let bar = reading
+            .percent
+            .map(|percent| level_bar(percent, BAR_WIDTH))
+            .unwrap_or_default();
+        Row::new(vec![
+            Cell::from(reading.name),
+            Cell::from(reading.value.clone()).style(
+                Style::default()
+                    .fg(reading.color)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Cell::from(bar).style(Style::default().fg(reading.color)),
+        ])
+    })
+    .collect();
+
+    render_table(
+        frame,
+        chunks[1],
+        "Readings",
+        &COLUMNS,
+        rows,
+        readings.len(),
+        state,
+    );
+}
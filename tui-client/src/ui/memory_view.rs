@@ -2,13 +2,15 @@ use ratatui::{
     layout::{Constraint, Layout, Rect},
     style::{Color, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Gauge, Paragraph},
+    widgets::{Block, Borders, Gauge, Paragraph, Sparkline},
     Frame,
 };
 use shared::types::MemoryMetrics;
 
+use crate::history::Series;
+
 /// Render memory metrics view
-pub fn render_memory_view(frame: &mut Frame, area: Rect, memory: &MemoryMetrics) {
+pub fn render_memory_view(frame: &mut Frame, area: Rect, memory: &MemoryMetrics, history: &Series) {
     let block = Block::default()
         .title(" Memory ")
         .borders(Borders::ALL)
@@ -17,11 +19,12 @@ pub fn render_memory_view(frame: &mut Frame, area: Rect, memory: &MemoryMetrics)
     let inner_area = block.inner(area);
     frame.render_widget(block, area);
 
-    // Layout: RAM gauge + SWAP gauge + details
+    // Layout: RAM gauge + RAM history + SWAP gauge + details
     let chunks = Layout::default()
         .direction(ratatui::layout::Direction::Vertical)
         .constraints([
             Constraint::Length(2), // RAM gauge
+            Constraint::Length(2), // RAM history
             Constraint::Length(2), // SWAP gauge
             Constraint::Min(1),    // Details
         ])
@@ -30,11 +33,19 @@ pub fn render_memory_view(frame: &mut Frame, area: Rect, memory: &MemoryMetrics)
     // Render RAM usage
     render_ram_usage(frame, chunks[0], memory);
 
+    // Render RAM usage over the selected window
+    let data = history.sparkline(chunks[1].width as usize);
+    let sparkline = Sparkline::default()
+        .data(&data)
+        .max(100)
+        .style(Style::default().fg(get_usage_color(memory.usage_percent)));
+    frame.render_widget(sparkline, chunks[1]);
+
     // Render SWAP usage
-    render_swap_usage(frame, chunks[1], memory);
+    render_swap_usage(frame, chunks[2], memory);
 
     // Render details
-    render_memory_details(frame, chunks[2], memory);
+    render_memory_details(frame, chunks[3], memory);
 }
 
 fn render_ram_usage(frame: &mut Frame, area: Rect, memory: &MemoryMetrics) {
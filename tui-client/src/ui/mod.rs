@@ -1,5 +1,13 @@
 pub mod anomalies_view;
+pub mod anomaly_detail;
+pub mod chart_view;
 pub mod cpu_view;
 pub mod dashboard;
 pub mod disk_view;
+pub mod gpu_view;
 pub mod memory_view;
+pub mod network_view;
+pub mod process_view;
+pub mod table;
+pub mod temperatures_view;
+pub mod usb_view;
@@ -0,0 +1,237 @@
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Sparkline},
+    Frame,
+};
+use shared::types::{NetworkInterface, NetworkMetrics};
+
+use super::table::{filter_sort, render_table, Column, TableViewState};
+use crate::history::MetricHistory;
+
+pub const COLUMNS: [Column; 9] = [
+    Column::new("Interface", Constraint::Min(10)),
+    Column::new("State", Constraint::Length(8)),
+    Column::new("RX", Constraint::Length(12)),
+    Column::new("TX", Constraint::Length(12)),
+    Column::new("RX pkt/s", Constraint::Length(10)),
+    Column::new("TX pkt/s", Constraint::Length(10)),
+    Column::new("Errors", Constraint::Length(8)),
+    Column::new("Dropped", Constraint::Length(9)),
+    Column::new("Speed", Constraint::Length(11)),
+];
+
+/// Render network throughput view
+pub fn render_network_view(
+    frame: &mut Frame,
+    area: Rect,
+    network: &NetworkMetrics,
+    history: &MetricHistory,
+) {
+    let block = Block::default()
+        .title(" Network ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let inner_area = block.inner(area);
+    frame.render_widget(block, area);
+
+    // Layout: current rates + RX/TX history side by side
+    let chunks = Layout::default()
+        .direction(ratatui::layout::Direction::Vertical)
+        .constraints([
+            Constraint::Length(1), // Rates
+            Constraint::Min(1),    // History
+        ])
+        .split(inner_area);
+
+    let text = vec![Line::from(vec![
+        Span::styled("RX: ", Style::default().fg(Color::Gray)),
+        Span::styled(
+            format_rate(network.rx_bytes as f64),
+            Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::raw("  "),
+        Span::styled("TX: ", Style::default().fg(Color::Gray)),
+        Span::styled(
+            format_rate(network.tx_bytes as f64),
+            Style::default()
+                .fg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+        ),
+    ])];
+    frame.render_widget(Paragraph::new(text), chunks[0]);
+
+    let halves = Layout::default()
+        .direction(ratatui::layout::Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[1]);
+
+    // Both directions share a scale so their heights are comparable
+    let max = history.rx.max().max(history.tx.max()).max(1.0).round() as u64;
+    for (series, color, area) in [
+        (&history.rx, Color::Green, halves[0]),
+        (&history.tx, Color::Blue, halves[1]),
+    ] {
+        let data = series.sparkline(area.width as usize);
+        let sparkline = Sparkline::default()
+            .data(&data)
+            .max(max)
+            .style(Style::default().fg(color));
+        frame.render_widget(sparkline, area);
+    }
+}
+
+/// Returns the interfaces matching the filter, sorted by the selected column
+pub fn interface_rows<'a>(
+    interfaces: &'a [NetworkInterface],
+    state: &TableViewState,
+) -> Vec<&'a NetworkInterface> {
+    filter_sort(
+        interfaces,
+        state,
+        |iface| format!("{} {}", iface.name, iface.operstate),
+        |a, b, column| match column {
+            1 => a.operstate.cmp(&b.operstate),
+            2 => a.rx_bytes_per_sec.cmp(&b.rx_bytes_per_sec),
+            3 => a.tx_bytes_per_sec.cmp(&b.tx_bytes_per_sec),
+            4 => a.rx_packets_per_sec.cmp(&b.rx_packets_per_sec),
+            5 => a.tx_packets_per_sec.cmp(&b.tx_packets_per_sec),
+            6 => (a.rx_errors + a.tx_errors).cmp(&(b.rx_errors + b.tx_errors)),
+            7 => (a.rx_dropped + a.tx_dropped).cmp(&(b.rx_dropped + b.tx_dropped)),
+            8 => a.speed_mbps.cmp(&b.speed_mbps),
+            _ => a.name.cmp(&b.name),
+        },
+    )
+}
+
+/// Render the network pane: total throughput history above a table of
+/// per-interface rates and error counters
+pub fn render_network_pane(
+    frame: &mut Frame,
+    area: Rect,
+    network: &NetworkMetrics,
+    history: &MetricHistory,
+    state: &mut TableViewState,
+) {
+    let chunks = Layout::default()
+        .direction(ratatui::layout::Direction::Vertical)
+        .constraints([
+            Constraint::Length(8), // Throughput
+            Constraint::Min(5),    // Interfaces
+        ])
+        .split(area);
+
+    render_network_view(frame, chunks[0], network, history);
+
+    let rows: Vec<Row> = interface_rows(&network.interfaces, state)
+        .into_iter()
+        .map(|iface| {
+            let state_color = if iface.operstate == "up" {
+                Color::Green
+            } else {
+                Color::Gray
+            };
+            let errors = iface.rx_errors + iface.tx_errors;
+            let dropped = iface.rx_dropped + iface.tx_dropped;
+            let counter_style = |count: u64| {
+                if count > 0 {
+                    Style::default().fg(Color::Red)
+                } else {
+                    Style::default()
+                }
+            };
+            Row::new(vec![
+                Cell::from(iface.name.as_str()),
+                Cell::from(iface.operstate.as_str()).style(Style::default().fg(state_color)),
+                Cell::from(format_rate(iface.rx_bytes_per_sec as f64))
+                    .style(Style::default().fg(Color::Green)),
+                Cell::from(format_rate(iface.tx_bytes_per_sec as f64))
+                    .style(Style::default().fg(Color::Blue)),
+                Cell::from(iface.rx_packets_per_sec.to_string()),
+                Cell::from(iface.tx_packets_per_sec.to_string()),
+                Cell::from(errors.to_string()).style(counter_style(errors)),
+                Cell::from(dropped.to_string()).style(counter_style(dropped)),
+                Cell::from(
+                    iface
+                        .speed_mbps
+                        .map_or_else(|| "-".to_string(), |speed| format!("{} Mb/s", speed)),
+                ),
+            ])
+        })
+        .collect();
+
+    render_table(
+        frame,
+        chunks[1],
+        "Interfaces",
+        &COLUMNS,
+        rows,
+        network.interfaces.len(),
+        state,
+    );
+}
+
+/// Formats a byte rate with a binary unit, e.g. `1.5 MB/s`
+pub fn format_rate(bytes_per_sec: f64) -> String {
+    const UNITS: [&str; 4] = ["B/s", "KB/s", "MB/s", "GB/s"];
+    let mut value = bytes_per_sec;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_rate() {
+        assert_eq!(format_rate(512.0), "512.0 B/s");
+        assert_eq!(format_rate(1536.0), "1.5 KB/s");
+        assert_eq!(format_rate(3.0 * 1024.0 * 1024.0), "3.0 MB/s");
+    }
+
+    #[test]
+    fn test_interface_rows() {
+        let iface = |name: &str, rx: u64, errors: u64| NetworkInterface {
+            name: name.to_string(),
+            rx_bytes_per_sec: rx,
+            tx_bytes_per_sec: 0,
+            rx_packets_per_sec: 0,
+            tx_packets_per_sec: 0,
+            rx_errors: errors,
+            tx_errors: 0,
+            rx_dropped: 0,
+            tx_dropped: 0,
+            operstate: "up".to_string(),
+            speed_mbps: None,
+        };
+        let interfaces = vec![
+            iface("eth0", 500, 0),
+            iface("wlan0", 900, 3),
+            iface("eth1", 100, 1),
+        ];
+
+        let state = TableViewState::new(2, true);
+        let names: Vec<_> = interface_rows(&interfaces, &state)
+            .iter()
+            .map(|i| i.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["wlan0", "eth0", "eth1"]);
+
+        let mut state = TableViewState::new(6, false);
+        state.filter = "eth".to_string();
+        let names: Vec<_> = interface_rows(&interfaces, &state)
+            .iter()
+            .map(|i| i.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["eth0", "eth1"]);
+    }
+}
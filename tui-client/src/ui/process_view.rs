@@ -0,0 +1,141 @@
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::{Color, Style},
+    widgets::{Cell, Row},
+    Frame,
+};
+use shared::types::ProcessInfo;
+
+use super::network_view::format_rate;
+use super::table::{filter_sort, render_table, Column, TableViewState};
+
+pub const COLUMNS: [Column; 9] = [
+    Column::new("PID", Constraint::Length(8)),
+    Column::new("Name", Constraint::Length(16)),
+    Column::new("User", Constraint::Length(10)),
+    Column::new("State", Constraint::Length(7)),
+    Column::new("CPU%", Constraint::Length(8)),
+    Column::new("Memory", Constraint::Length(10)),
+    Column::new("Read", Constraint::Length(11)),
+    Column::new("Write", Constraint::Length(11)),
+    Column::new("Command", Constraint::Min(10)),
+];
+
+/// Column the process pane is sorted by initially
+pub const DEFAULT_SORT_COLUMN: usize = 4;
+
+/// Returns the processes matching the filter, sorted by the selected column
+pub fn process_rows<'a>(
+    processes: &'a [ProcessInfo],
+    state: &TableViewState,
+) -> Vec<&'a ProcessInfo> {
+    filter_sort(
+        processes,
+        state,
+        |process| {
+            format!(
+                "{} {} {} {}",
+                process.pid, process.name, process.user, process.cmdline
+            )
+        },
+        |a, b, column| match column {
+            1 => a.name.cmp(&b.name),
+            2 => a.user.cmp(&b.user),
+            3 => a.state.cmp(&b.state),
+            4 => a.cpu_usage.total_cmp(&b.cpu_usage),
+            5 => a.memory_rss.cmp(&b.memory_rss),
+            6 => a.disk_read_bytes_per_sec.cmp(&b.disk_read_bytes_per_sec),
+            7 => a.disk_write_bytes_per_sec.cmp(&b.disk_write_bytes_per_sec),
+            8 => a.cmdline.cmp(&b.cmdline),
+            _ => a.pid.cmp(&b.pid),
+        },
+    )
+}
+
+/// Render the process pane with the collector's top consumers
+pub fn render_process_pane(
+    frame: &mut Frame,
+    area: Rect,
+    processes: &[ProcessInfo],
+    state: &mut TableViewState,
+) {
+    let rows: Vec<Row> = process_rows(processes, state)
+        .into_iter()
+        .map(|process| {
+            let cpu_color = if process.cpu_usage < 50.0 {
+                Color::Green
+            } else if process.cpu_usage < 90.0 {
+                Color::Yellow
+            } else {
+                Color::Red
+            };
+            let memory_mb = process.memory_rss as f64 / (1024.0 * 1024.0);
+            Row::new(vec![
+                Cell::from(process.pid.to_string()),
+                Cell::from(process.name.as_str()),
+                Cell::from(process.user.as_str()),
+                Cell::from(process.state.as_str()),
+                Cell::from(format!("{:.1}", process.cpu_usage))
+                    .style(Style::default().fg(cpu_color)),
+                Cell::from(format!("{:.1} MB", memory_mb)),
+                Cell::from(format_rate(process.disk_read_bytes_per_sec as f64)),
+                Cell::from(format_rate(process.disk_write_bytes_per_sec as f64)),
+                Cell::from(process.cmdline.as_str()).style(Style::default().fg(Color::Gray)),
+            ])
+        })
+        .collect();
+
+    render_table(
+        frame,
+        area,
+        "Processes",
+        &COLUMNS,
+        rows,
+        processes.len(),
+        state,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, name: &str, cpu_usage: f32, memory_rss: u64) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: name.to_string(),
+            cmdline: format!("/usr/bin/{}", name),
+            user: "root".to_string(),
+            state: "S".to_string(),
+            cpu_usage,
+            memory_rss,
+            disk_read_bytes_per_sec: 0,
+            disk_write_bytes_per_sec: 0,
+        }
+    }
+
+    #[test]
+    fn test_process_rows() {
+        let processes = vec![
+            process(10, "postgres", 12.5, 300),
+            process(20, "firefox", 80.0, 900),
+            process(30, "sshd", 0.1, 10),
+        ];
+
+        let state = TableViewState::new(DEFAULT_SORT_COLUMN, true);
+        let pids: Vec<_> = process_rows(&processes, &state)
+            .iter()
+            .map(|p| p.pid)
+            .collect();
+        assert_eq!(pids, vec![20, 10, 30]);
+
+        // The filter also matches PIDs and command lines
+        let mut state = TableViewState::new(5, false);
+        state.filter = "/usr/bin/s".to_string();
+        let pids: Vec<_> = process_rows(&processes, &state)
+            .iter()
+            .map(|p| p.pid)
+            .collect();
+        assert_eq!(pids, vec![30]);
+    }
+}
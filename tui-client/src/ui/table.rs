@@ -0,0 +1,220 @@
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    text::Span,
+    widgets::{Block, Borders, Cell, Row, Table, TableState},
+    Frame,
+};
+use std::cmp::Ordering;
+
+/// A column of a table pane
+pub struct Column {
+    pub title: &'static str,
+    pub width: Constraint,
+}
+
+impl Column {
+    pub const fn new(title: &'static str, width: Constraint) -> Self {
+        Self { title, width }
+    }
+}
+
+/// Selection, sort order and filter of a table pane
+pub struct TableViewState {
+    pub table_state: TableState,
+    /// Index of the column rows are sorted by
+    pub sort_column: usize,
+    pub descending: bool,
+    /// Case-insensitive text rows must contain
+    pub filter: String,
+    /// Whether key presses are typed into the filter
+    pub editing_filter: bool,
+}
+
+impl Default for TableViewState {
+    fn default() -> Self {
+        Self::new(0, false)
+    }
+}
+
+impl TableViewState {
+    /// Creates a state sorted by `sort_column`
+    pub fn new(sort_column: usize, descending: bool) -> Self {
+        let mut table_state = TableState::default();
+        table_state.select(Some(0));
+        Self {
+            table_state,
+            sort_column,
+            descending,
+            filter: String::new(),
+            editing_filter: false,
+        }
+    }
+
+    /// Sorts by the next of `columns` columns, wrapping around
+    pub fn next_sort_column(&mut self, columns: usize) {
+        if columns > 0 {
+            self.sort_column = (self.sort_column + 1) % columns;
+        }
+    }
+
+    pub fn toggle_order(&mut self) {
+        self.descending = !self.descending;
+    }
+
+    pub fn scroll_up(&mut self, rows: usize) {
+        let i = self.table_state.selected().unwrap_or(0);
+        self.table_state.select(Some(i.saturating_sub(rows)));
+    }
+
+    /// Moves the selection down; it is clamped to the rows when rendered
+    pub fn scroll_down(&mut self, rows: usize) {
+        let i = self.table_state.selected().unwrap_or(0);
+        self.table_state.select(Some(i.saturating_add(rows)));
+    }
+
+    /// Whether `text` contains the filter, ignoring case
+    pub fn matches(&self, text: &str) -> bool {
+        self.filter.is_empty() || text.to_lowercase().contains(&self.filter.to_lowercase())
+    }
+
+    /// Applies the sort direction to an ascending ordering
+    pub fn order(&self, ordering: Ordering) -> Ordering {
+        if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+/// Returns the items matching the filter, sorted by the selected column
+///
+/// # Arguments
+/// * `text` - Text of an item the filter is matched against
+/// * `compare` - Ascending ordering of two items by a column index
+pub fn filter_sort<'a, T>(
+    items: &'a [T],
+    state: &TableViewState,
+    text: impl Fn(&T) -> String,
+    compare: impl Fn(&T, &T, usize) -> Ordering,
+) -> Vec<&'a T> {
+    let mut rows: Vec<&T> = items
+        .iter()
+        .filter(|item| state.matches(&text(item)))
+        .collect();
+    rows.sort_by(|a, b| state.order(compare(a, b, state.sort_column)));
+    rows
+}
+
+/// Render a sortable, filterable table
+///
+/// # Arguments
+/// * `name` - Pane title, shown with the shown/total row counts
+/// * `rows` - Rows left after filtering, already sorted
+/// * `total` - Number of rows before filtering
+pub fn render_table(
+    frame: &mut Frame,
+    area: Rect,
+    name: &str,
+    columns: &[Column],
+    rows: Vec<Row>,
+    total: usize,
+    state: &mut TableViewState,
+) {
+    let selected = state.table_state.selected().unwrap_or(0);
+    state
+        .table_state
+        .select(Some(selected.min(rows.len().saturating_sub(1))));
+
+    let header = Row::new(columns.iter().enumerate().map(|(i, column)| {
+        if i == state.sort_column {
+            let arrow = if state.descending { "▼" } else { "▲" };
+            Cell::from(format!("{} {}", column.title, arrow)).style(
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            Cell::from(column.title).style(Style::default().fg(Color::Gray))
+        }
+    }));
+
+    let hint = if state.editing_filter {
+        Span::styled(
+            format!(" filter: {}▏ (Enter done, Esc clear) ", state.filter),
+            Style::default().fg(Color::Yellow),
+        )
+    } else if !state.filter.is_empty() {
+        Span::styled(
+            format!(" filter: {}  (/ edit, Esc clear) ", state.filter),
+            Style::default().fg(Color::Yellow),
+        )
+    } else {
+        Span::raw(" s sort  r reverse  / filter ")
+    };
+
+    let block = Block::default()
+        .title(format!(" {} ({}/{}) ", name, rows.len(), total))
+        .title_bottom(hint)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let table = Table::new(rows, columns.iter().map(|column| column.width))
+        .header(header)
+        .block(block)
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("> ");
+
+    frame.render_stateful_widget(table, area, &mut state.table_state);
+}
+
+/// A text bar filled to `percent`, e.g. `████░░░░`
+pub fn level_bar(percent: f64, width: usize) -> String {
+    let filled = ((percent.clamp(0.0, 100.0) / 100.0) * width as f64).round() as usize;
+    format!("{}{}", "█".repeat(filled), "░".repeat(width - filled))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_sort() {
+        let items = ["eth0", "lo", "wlan0", "eth1"];
+        let mut state = TableViewState::default();
+        let text = |item: &&str| item.to_string();
+        let compare = |a: &&str, b: &&str, _: usize| a.cmp(b);
+
+        assert_eq!(
+            filter_sort(&items, &state, text, compare),
+            vec![&"eth0", &"eth1", &"lo", &"wlan0"]
+        );
+
+        state.filter = "ETH".to_string();
+        state.toggle_order();
+        assert_eq!(
+            filter_sort(&items, &state, text, compare),
+            vec![&"eth1", &"eth0"]
+        );
+    }
+
+    #[test]
+    fn test_sort_column_wraps() {
+        let mut state = TableViewState::new(2, false);
+        state.next_sort_column(3);
+        assert_eq!(state.sort_column, 0);
+        state.scroll_up(5);
+        assert_eq!(state.table_state.selected(), Some(0));
+    }
+
+    #[test]
+    fn test_level_bar() {
+        assert_eq!(level_bar(50.0, 4), "██░░");
+        assert_eq!(level_bar(150.0, 2), "██");
+    }
+}
@@ -0,0 +1,245 @@
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Sparkline},
+    Frame,
+};
+use shared::types::Temperature;
+
+use super::table::{filter_sort, render_table, Column, TableViewState};
+use crate::history::{MetricHistory, Series};
+
+pub const COLUMNS: [Column; 6] = [
+    Column::new("Sensor", Constraint::Length(16)),
+    Column::new("Label", Constraint::Min(16)),
+    Column::new("Current", Constraint::Length(10)),
+    Column::new("Min", Constraint::Length(9)),
+    Column::new("Max", Constraint::Length(9)),
+    Column::new("Band", Constraint::Length(10)),
+];
+
+/// Color band of a temperature reading
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemperatureBand {
+    Normal,
+    Warm,
+    Hot,
+    Critical,
+}
+
+impl TemperatureBand {
+    const ALL: [TemperatureBand; 4] = [
+        TemperatureBand::Normal,
+        TemperatureBand::Warm,
+        TemperatureBand::Hot,
+        TemperatureBand::Critical,
+    ];
+
+    /// Band of a reading in °C
+    pub fn for_value(celsius: f32) -> Self {
+        if celsius < 50.0 {
+            TemperatureBand::Normal
+        } else if celsius < 70.0 {
+            TemperatureBand::Warm
+        } else if celsius < 85.0 {
+            TemperatureBand::Hot
+        } else {
+            TemperatureBand::Critical
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TemperatureBand::Normal => "Normal",
+            TemperatureBand::Warm => "Warm",
+            TemperatureBand::Hot => "Hot",
+            TemperatureBand::Critical => "Critical",
+        }
+    }
+
+    /// Range covered by the band, for the legend
+    fn range(self) -> &'static str {
+        match self {
+            TemperatureBand::Normal => "<50°C",
+            TemperatureBand::Warm => "50-70°C",
+            TemperatureBand::Hot => "70-85°C",
+            TemperatureBand::Critical => "≥85°C",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            TemperatureBand::Normal => Color::Green,
+            TemperatureBand::Warm => Color::Yellow,
+            TemperatureBand::Hot => Color::LightRed,
+            TemperatureBand::Critical => Color::Red,
+        }
+    }
+}
+
+/// History key of a sensor, as used by `MetricHistory::temperatures`
+pub fn sensor_key(temp: &Temperature) -> String {
+    format!("{}:{}", temp.sensor, temp.label)
+}
+
+/// Returns the sensors matching the filter, sorted by the selected column
+pub fn temperature_rows<'a>(
+    temperatures: &'a [Temperature],
+    history: &MetricHistory,
+    state: &TableViewState,
+) -> Vec<&'a Temperature> {
+    let series = |temp: &Temperature| history.temperatures.get(&sensor_key(temp));
+    let min = |temp: &Temperature| series(temp).map_or(f64::from(temp.value), Series::min);
+    let max = |temp: &Temperature| series(temp).map_or(f64::from(temp.value), Series::max);
+
+    filter_sort(
+        temperatures,
+        state,
+        sensor_key,
+        |a, b, column| match column {
+            0 => a.sensor.cmp(&b.sensor).then_with(|| a.label.cmp(&b.label)),
+            1 => a.label.cmp(&b.label),
+            3 => min(a).total_cmp(&min(b)),
+            4 => max(a).total_cmp(&max(b)),
+            _ => a.value.total_cmp(&b.value),
+        },
+    )
+}
+
+/// Render the temperatures pane: every sensor with its band and the range
+/// seen over the history window, plus the selected sensor's history
+pub fn render_temperatures_pane(
+    frame: &mut Frame,
+    area: Rect,
+    temperatures: &[Temperature],
+    history: &MetricHistory,
+    state: &mut TableViewState,
+) {
+    let chunks = Layout::default()
+        .direction(ratatui::layout::Direction::Vertical)
+        .constraints([
+            Constraint::Min(5),    // Sensors
+            Constraint::Length(1), // Legend
+            Constraint::Length(6), // Selected sensor history
+        ])
+        .split(area);
+
+    let sensors = temperature_rows(temperatures, history, state);
+    let rows: Vec<Row> = sensors
+        .iter()
+        .map(|temp| {
+            let band = TemperatureBand::for_value(temp.value);
+            let range = history.temperatures.get(&sensor_key(temp));
+            let format_range = |value: Option<f64>| {
+                value.map_or_else(|| "-".to_string(), |v| format!("{:.1}°C", v))
+            };
+            Row::new(vec![
+                Cell::from(temp.sensor.as_str()),
+                Cell::from(temp.label.as_str()),
+                Cell::from(format!("{:.1}°C", temp.value)).style(
+                    Style::default()
+                        .fg(band.color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Cell::from(format_range(range.map(Series::min))),
+                Cell::from(format_range(range.map(Series::max))),
+                Cell::from(band.label()).style(Style::default().fg(band.color())),
+            ])
+        })
+        .collect();
+
+    let selected = state
+        .table_state
+        .selected()
+        .and_then(|i| sensors.get(i.min(sensors.len().saturating_sub(1))))
+        .copied();
+
+    render_table(
+        frame,
+        chunks[0],
+        "Temperatures",
+        &COLUMNS,
+        rows,
+        temperatures.len(),
+        state,
+    );
+
+    let mut legend = vec![Span::styled(" Bands: ", Style::default().fg(Color::Gray))];
+    for band in TemperatureBand::ALL {
+        legend.push(Span::styled(
+            format!("■ {} {}  ", band.label(), band.range()),
+            Style::default().fg(band.color()),
+        ));
+    }
+    frame.render_widget(Paragraph::new(Line::from(legend)), chunks[1]);
+
+    let title = match selected {
+        Some(temp) => format!(" {} ({}) ", sensor_key(temp), history.window().label()),
+        None => " History ".to_string(),
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner_area = block.inner(chunks[2]);
+    frame.render_widget(block, chunks[2]);
+
+    if let Some((temp, series)) = selected.and_then(|temp| {
+        history
+            .temperatures
+            .get(&sensor_key(temp))
+            .map(|series| (temp, series))
+    }) {
+        let data = series.sparkline(inner_area.width as usize);
+        let sparkline = Sparkline::default()
+            .data(&data)
+            .max(series.max().max(1.0).round() as u64)
+            .style(Style::default().fg(TemperatureBand::for_value(temp.value).color()));
+        frame.render_widget(sparkline, inner_area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp(sensor: &str, label: &str, value: f32) -> Temperature {
+        Temperature {
+            sensor: sensor.to_string(),
+            value,
+            label: label.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_bands() {
+        assert_eq!(TemperatureBand::for_value(35.0), TemperatureBand::Normal);
+        assert_eq!(TemperatureBand::for_value(50.0), TemperatureBand::Warm);
+        assert_eq!(TemperatureBand::for_value(84.9), TemperatureBand::Hot);
+        assert_eq!(TemperatureBand::for_value(95.0), TemperatureBand::Critical);
+    }
+
+    #[test]
+    fn test_temperature_rows() {
+        let temps = vec![
+            temp("coretemp", "Core 0", 55.0),
+            temp("nvme", "Composite", 40.0),
+            temp("coretemp", "Core 1", 72.0),
+        ];
+        let history = MetricHistory::default();
+
+        let state = TableViewState::new(2, true);
+        let rows = temperature_rows(&temps, &history, &state);
+        let labels: Vec<_> = rows.iter().map(|t| t.label.as_str()).collect();
+        assert_eq!(labels, vec!["Core 1", "Core 0", "Composite"]);
+
+        let state = TableViewState {
+            filter: "core".to_string(),
+            ..Default::default()
+        };
+        let rows = temperature_rows(&temps, &history, &state);
+        let labels: Vec<_> = rows.iter().map(|t| t.label.as_str()).collect();
+        assert_eq!(labels, vec!["Core 0", "Core 1"]);
+    }
+}
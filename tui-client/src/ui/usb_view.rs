@@ -0,0 +1,107 @@
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Cell, Row},
+    Frame,
+};
+use shared::types::UsbDevice;
+
+use super::table::{filter_sort, render_table, Column, TableViewState};
+
+pub const COLUMNS: [Column; 4] = [
+    Column::new("ID", Constraint::Length(12)),
+    Column::new("Manufacturer", Constraint::Min(16)),
+    Column::new("Product", Constraint::Min(20)),
+    Column::new("Status", Constraint::Length(9)),
+];
+
+/// Returns the devices matching the filter, sorted by the selected column
+///
+/// Sorting by status puts devices with timeouts first.
+pub fn usb_rows<'a>(devices: &'a [UsbDevice], state: &TableViewState) -> Vec<&'a UsbDevice> {
+    filter_sort(
+        devices,
+        state,
+        |device| format!("{} {} {}", device.id, device.manufacturer, device.product),
+        |a, b, column| match column {
+            1 => a.manufacturer.cmp(&b.manufacturer),
+            2 => a.product.cmp(&b.product),
+            3 => b
+                .has_timeout
+                .cmp(&a.has_timeout)
+                .then_with(|| a.id.cmp(&b.id)),
+            _ => a.id.cmp(&b.id),
+        },
+    )
+}
+
+/// Render the USB device pane, flagging devices that timed out
+pub fn render_usb_pane(
+    frame: &mut Frame,
+    area: Rect,
+    devices: &[UsbDevice],
+    state: &mut TableViewState,
+) {
+    let rows: Vec<Row> = usb_rows(devices, state)
+        .into_iter()
+        .map(|device| {
+            let status = if device.has_timeout {
+                Cell::from("TIMEOUT")
+                    .style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+            } else {
+                Cell::from("OK").style(Style::default().fg(Color::Green))
+            };
+            Row::new(vec![
+                Cell::from(device.id.as_str()),
+                Cell::from(device.manufacturer.as_str()),
+                Cell::from(device.product.as_str()),
+                status,
+            ])
+        })
+        .collect();
+
+    let timeouts = devices.iter().filter(|device| device.has_timeout).count();
+    let name = if timeouts > 0 {
+        format!("USB Devices, {} timed out", timeouts)
+    } else {
+        "USB Devices".to_string()
+    };
+
+    render_table(frame, area, &name, &COLUMNS, rows, devices.len(), state);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(id: &str, product: &str, has_timeout: bool) -> UsbDevice {
+        UsbDevice {
+            id: id.to_string(),
+            manufacturer: "Logitech".to_string(),
+            product: product.to_string(),
+            has_timeout,
+        }
+    }
+
+    #[test]
+    fn test_usb_rows_status_sort_puts_timeouts_first() {
+        let devices = vec![
+            device("046d:c52b", "Receiver", false),
+            device("0bda:8153", "Ethernet", true),
+            device("046d:0825", "Webcam", false),
+        ];
+
+        let state = TableViewState::new(3, false);
+        let ids: Vec<_> = usb_rows(&devices, &state)
+            .iter()
+            .map(|d| d.id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["0bda:8153", "046d:0825", "046d:c52b"]);
+
+        let state = TableViewState {
+            filter: "webcam".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(usb_rows(&devices, &state).len(), 1);
+    }
+}
@@ -78,19 +78,19 @@ export const SystemInfo: React.FC<SystemInfoProps> = ({ metrics }) => {
           <div className="space-y-1">
             <div className="text-sm">
               <span className="text-gray-600 dark:text-gray-300">RX:</span>{' '}
-              <span className="font-medium text-gray-800 dark:text-white">{formatBytes(metrics.network.rx_bytes)}</span>
+              <span className="font-medium text-gray-800 dark:text-white">{formatBytes(metrics.network.rx_bytes)}/s</span>
             </div>
             <div className="text-sm">
               <span className="text-gray-600 dark:text-gray-300">TX:</span>{' '}
-              <span className="font-medium text-gray-800 dark:text-white">{formatBytes(metrics.network.tx_bytes)}</span>
+              <span className="font-medium text-gray-800 dark:text-white">{formatBytes(metrics.network.tx_bytes)}/s</span>
             </div>
             <div className="text-sm">
               <span className="text-gray-600 dark:text-gray-300">RX Packets:</span>{' '}
-              <span className="font-medium text-gray-800 dark:text-white">{metrics.network.rx_packets.toLocaleString()}</span>
+              <span className="font-medium text-gray-800 dark:text-white">{metrics.network.rx_packets.toLocaleString()}/s</span>
             </div>
             <div className="text-sm">
               <span className="text-gray-600 dark:text-gray-300">TX Packets:</span>{' '}
-              <span className="font-medium text-gray-800 dark:text-white">{metrics.network.tx_packets.toLocaleString()}</span>
+              <span className="font-medium text-gray-800 dark:text-white">{metrics.network.tx_packets.toLocaleString()}/s</span>
             </div>
           </div>
         </div>
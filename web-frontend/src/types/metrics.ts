@@ -57,6 +57,21 @@ export interface NetworkMetrics {
   tx_bytes: number;
   rx_packets: number;
   tx_packets: number;
+  interfaces: NetworkInterface[];
+}
+
+export interface NetworkInterface {
+  name: string;
+  rx_bytes_per_sec: number;
+  tx_bytes_per_sec: number;
+  rx_packets_per_sec: number;
+  tx_packets_per_sec: number;
+  rx_errors: number;
+  tx_errors: number;
+  rx_dropped: number;
+  tx_dropped: number;
+  operstate: string;
+  speed_mbps: number | null;
 }
 
 export interface GpuMetrics {
@@ -94,6 +109,7 @@ export enum AnomalyCategory {
   Usb = 'Usb',
   Network = 'Network',
   Gpu = 'Gpu',
+  Process = 'Process',
   System = 'System',
 }
 